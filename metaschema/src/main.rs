@@ -1,8 +1,21 @@
-use near_abi::AbiRoot;
+use near_abi::draft::{root_schema_to_value, SchemaDraft};
 
 fn main() -> anyhow::Result<()> {
-    let mut gen = schemars::gen::SchemaGenerator::default();
-    let schema = gen.root_schema_for::<AbiRoot>();
-    println!("{}", serde_json::to_string_pretty(&schema)?);
+    let draft = match std::env::args().nth(1).as_deref() {
+        None | Some("--draft-07") => SchemaDraft::Draft7,
+        Some("--draft-2020-12") => SchemaDraft::Draft2020_12,
+        Some(arg) => anyhow::bail!(
+            "unexpected argument `{}`, expected `--draft-07` or `--draft-2020-12`",
+            arg
+        ),
+    };
+
+    let schema = near_abi::metaschema();
+    // Draft-07 is printed directly to preserve the field order of the published metaschemas.
+    let output = match draft {
+        SchemaDraft::Draft7 => serde_json::to_string_pretty(&schema)?,
+        _ => serde_json::to_string_pretty(&root_schema_to_value(&schema, draft))?,
+    };
+    println!("{}", output);
     Ok(())
 }
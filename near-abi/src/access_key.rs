@@ -0,0 +1,132 @@
+//! Function-call access keys.
+//!
+//! A function-call access key only allows calling the methods listed in its `method_names`, and
+//! never with an attached deposit. [`method_names`] derives the narrowest such list from the ABI,
+//! so that dApps can request least-privilege keys for their users.
+
+use crate::{AbiFunction, AbiFunctionKind, AbiFunctionModifier, AbiRoot};
+use std::fmt;
+
+/// Selection of the functions a key is created for. The default selection covers all the call
+/// functions of the contract.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MethodFilter {
+    /// Namespaces of the selected functions, `None` standing for functions without a namespace.
+    /// All namespaces are selected when empty.
+    pub namespaces: Vec<Option<String>>,
+    /// Interfaces implemented by the selected functions. All functions are selected when empty.
+    pub interfaces: Vec<String>,
+    /// Whether view functions are selected as well, for clients calling them in transactions.
+    pub include_views: bool,
+}
+
+impl MethodFilter {
+    fn matches(&self, function: &AbiFunction) -> bool {
+        (self.namespaces.is_empty() || self.namespaces.contains(&function.namespace))
+            && (self.interfaces.is_empty()
+                || function
+                    .interface
+                    .as_ref()
+                    .map_or(false, |interface| self.interfaces.contains(interface)))
+            && (self.include_views || function.kind == AbiFunctionKind::Call)
+    }
+}
+
+/// The `method_names` of a function-call access key to the contract, for the functions selected
+/// by `filter`, in declaration order.
+///
+/// Functions a function-call access key can never call successfully are left out: payable
+/// functions, since such keys can not attach a deposit, private functions, init functions and
+/// upgrade entrypoints. Fails if no function is left, as an empty `method_names` list would allow
+/// every method of the contract.
+pub fn method_names(abi: &AbiRoot, filter: &MethodFilter) -> Result<Vec<String>, AccessKeyError> {
+    let mut method_names = Vec::<String>::new();
+    for function in &abi.body.functions {
+        let has = |modifier| function.modifiers.contains(&modifier);
+        if !filter.matches(function)
+            || has(AbiFunctionModifier::Payable)
+            || has(AbiFunctionModifier::Private)
+            || has(AbiFunctionModifier::Init)
+            || function.is_upgrade_entrypoint()
+            || method_names.contains(&function.name)
+        {
+            continue;
+        }
+        method_names.push(function.name.clone());
+    }
+    if method_names.is_empty() {
+        return Err(AccessKeyError::NoMethods);
+    }
+    Ok(method_names)
+}
+
+/// Error returned when an access key allowlist can not be derived from an ABI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AccessKeyError {
+    /// No function callable with a function-call access key matches the filter.
+    NoMethods,
+}
+
+impl std::error::Error for AccessKeyError {}
+impl fmt::Display for AccessKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoMethods => write!(
+                f,
+                "no function callable with a function-call access key matches the filter"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_method_names() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    { "name": "new", "kind": "call", "modifiers": ["init"] },
+                    { "name": "get_messages", "kind": "view" },
+                    { "name": "add_message", "kind": "call", "interface": "Guestbook" },
+                    { "name": "add_premium_message", "kind": "call", "modifiers": ["payable"] },
+                    { "name": "on_message", "kind": "call", "modifiers": ["private"] },
+                    { "name": "set_owner", "kind": "call", "namespace": "admin" },
+                    { "name": "upgrade", "kind": "call", "modifiers": ["upgrade"] }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            method_names(&abi, &MethodFilter::default()).unwrap(),
+            ["add_message", "set_owner"]
+        );
+        let filter = MethodFilter {
+            namespaces: vec![None],
+            include_views: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            method_names(&abi, &filter).unwrap(),
+            ["get_messages", "add_message"]
+        );
+        let filter = MethodFilter {
+            interfaces: vec!["Guestbook".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(method_names(&abi, &filter).unwrap(), ["add_message"]);
+        let filter = MethodFilter {
+            namespaces: vec![Some("internal".to_string())],
+            ..Default::default()
+        };
+        assert_eq!(method_names(&abi, &filter), Err(AccessKeyError::NoMethods));
+    }
+}
@@ -0,0 +1,439 @@
+use borsh::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Rules deciding which changes of a Borsh schema keep values serialized with the old schema
+/// decodable with the new one.
+///
+/// The default rules are [`BorshCompatRules::lenient`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BorshCompatRules {
+    /// Variants appended after the existing variants of an enum are accepted.
+    pub allow_appended_variants: bool,
+    /// Sequences accepting a wider range of lengths are accepted.
+    pub allow_widened_lengths: bool,
+}
+
+impl BorshCompatRules {
+    /// Only identical layouts are accepted.
+    pub fn strict() -> Self {
+        Self {
+            allow_appended_variants: false,
+            allow_widened_lengths: false,
+        }
+    }
+
+    /// Appended enum variants and widened sequence lengths are accepted, as is common practice
+    /// when upgrading contracts.
+    pub fn lenient() -> Self {
+        Self {
+            allow_appended_variants: true,
+            allow_widened_lengths: true,
+        }
+    }
+}
+
+impl Default for BorshCompatRules {
+    fn default() -> Self {
+        Self::lenient()
+    }
+}
+
+/// Checks that values serialized according to the `old` Borsh schema can be decoded according to
+/// the `new` one.
+///
+/// Definitions are compared structurally, starting from the declarations of both containers, so
+/// renaming a type is not a change in itself. Names of struct fields and enum variants are part
+/// of the comparison.
+pub fn borsh_compat_errors(
+    old: &BorshSchemaContainer,
+    new: &BorshSchemaContainer,
+    rules: &BorshCompatRules,
+) -> Vec<BorshCompatError> {
+    let mut checker = Checker {
+        old,
+        new,
+        rules,
+        visited: BTreeSet::new(),
+        errors: Vec::new(),
+    };
+    checker.check(old.declaration(), new.declaration());
+    checker.errors
+}
+
+struct Checker<'a> {
+    old: &'a BorshSchemaContainer,
+    new: &'a BorshSchemaContainer,
+    rules: &'a BorshCompatRules,
+    visited: BTreeSet<(&'a str, &'a str)>,
+    errors: Vec<BorshCompatError>,
+}
+
+impl<'a> Checker<'a> {
+    fn check(&mut self, old: &'a Declaration, new: &'a Declaration) {
+        // Recursive types are compared once per pair of declarations.
+        if !self.visited.insert((old.as_str(), new.as_str())) {
+            return;
+        }
+        let mut error = |kind| {
+            self.errors.push(BorshCompatError {
+                old: old.clone(),
+                new: new.clone(),
+                kind,
+            })
+        };
+        let (old_definition, new_definition) =
+            match (self.old.get_definition(old), self.new.get_definition(new)) {
+                (Some(old_definition), Some(new_definition)) => (old_definition, new_definition),
+                _ => return error(BorshCompatErrorKind::MissingDefinition),
+            };
+        match (old_definition, new_definition) {
+            (Definition::Primitive(old_size), Definition::Primitive(new_size)) => {
+                if old_size != new_size || old != new {
+                    error(BorshCompatErrorKind::PrimitiveMismatch);
+                }
+            }
+            (
+                Definition::Sequence {
+                    length_width: old_width,
+                    length_range: old_range,
+                    elements: old_elements,
+                },
+                Definition::Sequence {
+                    length_width: new_width,
+                    length_range: new_range,
+                    elements: new_elements,
+                },
+            ) => {
+                if old_width != new_width {
+                    error(BorshCompatErrorKind::LengthWidthMismatch {
+                        old: *old_width,
+                        new: *new_width,
+                    });
+                } else {
+                    let widened = new_range.start() <= old_range.start()
+                        && new_range.end() >= old_range.end();
+                    if old_range != new_range && !(self.rules.allow_widened_lengths && widened) {
+                        error(BorshCompatErrorKind::LengthRangeMismatch);
+                    }
+                }
+                self.check(old_elements, new_elements);
+            }
+            (
+                Definition::Tuple {
+                    elements: old_elements,
+                },
+                Definition::Tuple {
+                    elements: new_elements,
+                },
+            ) => {
+                if old_elements.len() != new_elements.len() {
+                    return error(BorshCompatErrorKind::ArityMismatch {
+                        old: old_elements.len(),
+                        new: new_elements.len(),
+                    });
+                }
+                for (old_element, new_element) in old_elements.iter().zip(new_elements) {
+                    self.check(old_element, new_element);
+                }
+            }
+            (
+                Definition::Struct { fields: old_fields },
+                Definition::Struct { fields: new_fields },
+            ) => match (old_fields, new_fields) {
+                (Fields::Empty, Fields::Empty) => {}
+                (Fields::UnnamedFields(old_fields), Fields::UnnamedFields(new_fields)) => {
+                    if old_fields.len() != new_fields.len() {
+                        return error(BorshCompatErrorKind::ArityMismatch {
+                            old: old_fields.len(),
+                            new: new_fields.len(),
+                        });
+                    }
+                    for (old_field, new_field) in old_fields.iter().zip(new_fields) {
+                        self.check(old_field, new_field);
+                    }
+                }
+                (Fields::NamedFields(old_fields), Fields::NamedFields(new_fields)) => {
+                    if old_fields.len() != new_fields.len() {
+                        return error(BorshCompatErrorKind::ArityMismatch {
+                            old: old_fields.len(),
+                            new: new_fields.len(),
+                        });
+                    }
+                    let renamed = old_fields
+                        .iter()
+                        .zip(new_fields)
+                        .find(|((old_name, _), (new_name, _))| old_name != new_name)
+                        .map(|((old_name, _), (new_name, _))| (old_name.clone(), new_name.clone()));
+                    if let Some((old, new)) = renamed {
+                        error(BorshCompatErrorKind::FieldMismatch { old, new });
+                    }
+                    for ((_, old_field), (_, new_field)) in old_fields.iter().zip(new_fields) {
+                        self.check(old_field, new_field);
+                    }
+                }
+                _ => error(BorshCompatErrorKind::KindMismatch),
+            },
+            (
+                Definition::Enum {
+                    tag_width: old_width,
+                    variants: old_variants,
+                },
+                Definition::Enum {
+                    tag_width: new_width,
+                    variants: new_variants,
+                },
+            ) => {
+                if old_width != new_width {
+                    return error(BorshCompatErrorKind::TagWidthMismatch {
+                        old: *old_width,
+                        new: *new_width,
+                    });
+                }
+                let mut pairs = Vec::new();
+                for (discriminant, old_name, old_variant) in old_variants {
+                    match new_variants.iter().find(|(d, _, _)| d == discriminant) {
+                        None => error(BorshCompatErrorKind::MissingVariant(old_name.clone())),
+                        Some((_, new_name, _)) if new_name != old_name => {
+                            error(BorshCompatErrorKind::VariantMismatch {
+                                discriminant: *discriminant,
+                                old: old_name.clone(),
+                                new: new_name.clone(),
+                            })
+                        }
+                        Some((_, _, new_variant)) => pairs.push((old_variant, new_variant)),
+                    }
+                }
+                let last = old_variants.iter().map(|(d, _, _)| *d).max();
+                for (discriminant, name, _) in new_variants {
+                    if old_variants.iter().any(|(d, _, _)| d == discriminant) {
+                        continue;
+                    }
+                    let appended = last.map_or(true, |last| *discriminant > last);
+                    if !(self.rules.allow_appended_variants && appended) {
+                        error(BorshCompatErrorKind::AddedVariant(name.clone()));
+                    }
+                }
+                for (old_variant, new_variant) in pairs {
+                    self.check(old_variant, new_variant);
+                }
+            }
+            _ => error(BorshCompatErrorKind::KindMismatch),
+        }
+    }
+}
+
+/// Change of a Borsh definition breaking the decoding of values serialized with the old schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BorshCompatError {
+    old: Declaration,
+    new: Declaration,
+    kind: BorshCompatErrorKind,
+}
+
+impl BorshCompatError {
+    /// Declaration of the definition in the old schema.
+    pub fn old_declaration(&self) -> &Declaration {
+        &self.old
+    }
+
+    /// Declaration of the definition in the new schema.
+    pub fn new_declaration(&self) -> &Declaration {
+        &self.new
+    }
+
+    pub fn kind(&self) -> &BorshCompatErrorKind {
+        &self.kind
+    }
+}
+
+impl std::error::Error for BorshCompatError {}
+impl fmt::Display for BorshCompatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.old == self.new {
+            write!(f, "`{}`: {}", self.old, self.kind)
+        } else {
+            write!(f, "`{}` -> `{}`: {}", self.old, self.new, self.kind)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BorshCompatErrorKind {
+    /// One of the schemas lacks the definition.
+    MissingDefinition,
+    /// The definitions are of different kinds, e.g. a struct and an enum.
+    KindMismatch,
+    /// The primitive types differ.
+    PrimitiveMismatch,
+    /// The sequence length prefix width changed.
+    LengthWidthMismatch { old: u8, new: u8 },
+    /// The range of sequence lengths changed.
+    LengthRangeMismatch,
+    /// The number of tuple elements or struct fields changed.
+    ArityMismatch { old: usize, new: usize },
+    /// A struct field was renamed or moved.
+    FieldMismatch { old: String, new: String },
+    /// The enum tag width changed.
+    TagWidthMismatch { old: u8, new: u8 },
+    /// An enum variant was removed.
+    MissingVariant(String),
+    /// An enum variant discriminant is now used by a differently named variant.
+    VariantMismatch {
+        discriminant: i64,
+        old: String,
+        new: String,
+    },
+    /// An enum variant was added.
+    AddedVariant(String),
+}
+
+impl fmt::Display for BorshCompatErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingDefinition => write!(f, "definition is missing"),
+            Self::KindMismatch => write!(f, "definition kind changed"),
+            Self::PrimitiveMismatch => write!(f, "primitive type changed"),
+            Self::LengthWidthMismatch { old, new } => write!(
+                f,
+                "length prefix width changed from {} to {} bytes",
+                old, new
+            ),
+            Self::LengthRangeMismatch => write!(f, "sequence length range changed"),
+            Self::ArityMismatch { old, new } => {
+                write!(f, "number of fields changed from {} to {}", old, new)
+            }
+            Self::FieldMismatch { old, new } => {
+                write!(f, "field `{}` was replaced with `{}`", old, new)
+            }
+            Self::TagWidthMismatch { old, new } => {
+                write!(f, "tag width changed from {} to {} bytes", old, new)
+            }
+            Self::MissingVariant(variant) => write!(f, "variant `{}` was removed", variant),
+            Self::VariantMismatch {
+                discriminant,
+                old,
+                new,
+            } => write!(
+                f,
+                "discriminant {} changed from variant `{}` to `{}`",
+                discriminant, old, new
+            ),
+            Self::AddedVariant(variant) => write!(f, "variant `{}` was added", variant),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSchema;
+
+    mod v1 {
+        use borsh::BorshSchema;
+
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        pub enum Status {
+            Active,
+            Paused { reason: String },
+        }
+
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        pub struct Account {
+            pub balance: u64,
+            pub status: Status,
+        }
+    }
+
+    mod v2 {
+        use borsh::BorshSchema;
+
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        pub enum Status {
+            Active,
+            Paused { reason: String },
+            Frozen,
+        }
+
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        pub struct Account {
+            pub balance: u64,
+            pub status: Status,
+        }
+    }
+
+    #[test]
+    fn test_appended_variants() {
+        let old = borsh::schema_container_of::<v1::Account>();
+        let new = borsh::schema_container_of::<v2::Account>();
+        assert!(borsh_compat_errors(&old, &new, &BorshCompatRules::lenient()).is_empty());
+
+        let errors = borsh_compat_errors(&old, &new, &BorshCompatRules::strict());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind(),
+            &BorshCompatErrorKind::AddedVariant("Frozen".to_string())
+        );
+        // Removing the variant again is never compatible.
+        let errors = borsh_compat_errors(&new, &old, &BorshCompatRules::lenient());
+        assert_eq!(
+            errors[0].to_string(),
+            "`Status`: variant `Frozen` was removed"
+        );
+    }
+
+    #[test]
+    fn test_widened_lengths() {
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        struct Fixed([u8; 4]);
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        struct Other {
+            bytes: Vec<u8>,
+        }
+
+        let fixed = borsh::schema_container_of::<[u8; 4]>();
+        let vec = borsh::schema_container_of::<Vec<u8>>();
+        let errors = borsh_compat_errors(&fixed, &vec, &BorshCompatRules::lenient());
+        assert_eq!(
+            errors[0].kind(),
+            &BorshCompatErrorKind::LengthWidthMismatch { old: 0, new: 4 }
+        );
+
+        let mut narrow = vec.clone();
+        let mut definitions: Vec<_> = narrow
+            .definitions()
+            .map(|(declaration, definition)| (declaration.clone(), definition.clone()))
+            .collect();
+        for (_, definition) in &mut definitions {
+            if let Definition::Sequence { length_range, .. } = definition {
+                *length_range = 0..=32;
+            }
+        }
+        narrow = BorshSchemaContainer::new(
+            narrow.declaration().clone(),
+            definitions.into_iter().collect(),
+        );
+        assert!(borsh_compat_errors(&narrow, &vec, &BorshCompatRules::lenient()).is_empty());
+        assert_eq!(
+            borsh_compat_errors(&narrow, &vec, &BorshCompatRules::strict())[0].kind(),
+            &BorshCompatErrorKind::LengthRangeMismatch
+        );
+        assert_eq!(
+            borsh_compat_errors(&vec, &narrow, &BorshCompatRules::lenient())[0].kind(),
+            &BorshCompatErrorKind::LengthRangeMismatch
+        );
+
+        let errors = borsh_compat_errors(
+            &borsh::schema_container_of::<Fixed>(),
+            &borsh::schema_container_of::<Other>(),
+            &BorshCompatRules::strict(),
+        );
+        assert_eq!(errors[0].kind(), &BorshCompatErrorKind::KindMismatch);
+    }
+}
@@ -0,0 +1,208 @@
+use crate::walk::borsh_type_schemas;
+use crate::AbiBody;
+use borsh::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Primitive declarations that decoders know the size of even without a definition.
+const PRIMITIVES: [&str; 14] = [
+    "bool", "u8", "u16", "u32", "u64", "u128", "i8", "i16", "i32", "i64", "i128", "f32", "f64",
+    "()",
+];
+
+/// Checks that a Borsh schema can describe actual values: every declaration referenced by the
+/// container is either defined or a known primitive, and every definition has finite values.
+///
+/// Recursion through sequences that may be empty or through enums (e.g. `Vec<Node>` or
+/// `Option<Box<Node>>`) is fine, while a struct containing itself can not be encoded at all.
+pub fn borsh_schema_errors(container: &BorshSchemaContainer) -> Vec<BorshSchemaError> {
+    let mut errors = Vec::new();
+    let is_declared = |declaration: &Declaration| {
+        container.get_definition(declaration).is_some()
+            || PRIMITIVES.contains(&declaration.as_str())
+    };
+    if !is_declared(container.declaration()) {
+        errors.push(BorshSchemaError {
+            declaration: container.declaration().clone(),
+            kind: BorshSchemaErrorKind::MissingDefinition(container.declaration().clone()),
+        });
+    }
+    for (declaration, definition) in container.definitions() {
+        for referenced in references(definition) {
+            if !is_declared(referenced) {
+                errors.push(BorshSchemaError {
+                    declaration: declaration.clone(),
+                    kind: BorshSchemaErrorKind::MissingDefinition(referenced.clone()),
+                });
+            }
+        }
+    }
+
+    // Finds the declarations with a finite value, the others recurse infinitely. Missing
+    // declarations are already reported and assumed finite.
+    let mut finite = BTreeSet::new();
+    loop {
+        let before = finite.len();
+        for (declaration, definition) in container.definitions() {
+            let is_finite = |declaration: &Declaration| {
+                finite.contains(declaration) || container.get_definition(declaration).is_none()
+            };
+            let has_finite_value = match definition {
+                Definition::Primitive(_) => true,
+                Definition::Sequence {
+                    length_range,
+                    elements,
+                    ..
+                } => *length_range.start() == 0 || is_finite(elements),
+                Definition::Enum { variants, .. } => variants
+                    .iter()
+                    .any(|(_, _, declaration)| is_finite(declaration)),
+                Definition::Tuple { .. } | Definition::Struct { .. } => {
+                    references(definition).into_iter().all(is_finite)
+                }
+            };
+            if has_finite_value && !finite.contains(declaration) {
+                finite.insert(declaration.clone());
+            }
+        }
+        if finite.len() == before {
+            break;
+        }
+    }
+    for (declaration, _) in container.definitions() {
+        if !finite.contains(declaration) {
+            errors.push(BorshSchemaError {
+                declaration: declaration.clone(),
+                kind: BorshSchemaErrorKind::InfiniteRecursion,
+            });
+        }
+    }
+    errors
+}
+
+/// Runs [`borsh_schema_errors`] on every Borsh type schema of an ABI, reporting the errors along
+/// with the name of the function they were found in.
+pub fn check_borsh_schemas(body: &AbiBody) -> Vec<(String, BorshSchemaError)> {
+    borsh_type_schemas(body)
+        .into_iter()
+        .flat_map(|(function, _, container)| {
+            borsh_schema_errors(container)
+                .into_iter()
+                .map(move |error| (function.to_string(), error))
+        })
+        .collect()
+}
+
+fn references(definition: &Definition) -> Vec<&Declaration> {
+    match definition {
+        Definition::Primitive(_) => Vec::new(),
+        Definition::Sequence { elements, .. } => vec![elements],
+        Definition::Tuple { elements } => elements.iter().collect(),
+        Definition::Enum { variants, .. } => variants
+            .iter()
+            .map(|(_, _, declaration)| declaration)
+            .collect(),
+        Definition::Struct { fields } => match fields {
+            Fields::NamedFields(fields) => {
+                fields.iter().map(|(_, declaration)| declaration).collect()
+            }
+            Fields::UnnamedFields(fields) => fields.iter().collect(),
+            Fields::Empty => Vec::new(),
+        },
+    }
+}
+
+/// A Borsh definition that can not describe actual values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BorshSchemaError {
+    declaration: Declaration,
+    kind: BorshSchemaErrorKind,
+}
+
+impl BorshSchemaError {
+    /// Declaration of the broken definition.
+    pub fn declaration(&self) -> &Declaration {
+        &self.declaration
+    }
+
+    pub fn kind(&self) -> &BorshSchemaErrorKind {
+        &self.kind
+    }
+}
+
+impl std::error::Error for BorshSchemaError {}
+impl fmt::Display for BorshSchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}`: {}", self.declaration, self.kind)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BorshSchemaErrorKind {
+    /// The declaration references another declaration that has no definition and is not a
+    /// known primitive.
+    MissingDefinition(Declaration),
+    /// Every value of the definition contains another value of the same definition, so no
+    /// finite value exists.
+    InfiniteRecursion,
+}
+
+impl fmt::Display for BorshSchemaErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingDefinition(declaration) => {
+                write!(f, "missing definition for `{}`", declaration)
+            }
+            Self::InfiniteRecursion => write!(f, "recursion without a finite value"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSchema;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_borsh_schema_errors() {
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        struct Node {
+            value: u32,
+            children: Vec<Node>,
+            parent: Option<Box<Node>>,
+        }
+        assert_eq!(
+            borsh_schema_errors(&borsh::schema_container_of::<Node>()),
+            []
+        );
+
+        let definitions = BTreeMap::from([
+            (
+                "Loop".to_string(),
+                Definition::Struct {
+                    fields: Fields::UnnamedFields(vec!["Loop".to_string(), "u8".to_string()]),
+                },
+            ),
+            (
+                "Holder".to_string(),
+                Definition::Tuple {
+                    elements: vec!["Loop".to_string(), "Unknown".to_string()],
+                },
+            ),
+        ]);
+        let container = BorshSchemaContainer::new("Holder".to_string(), definitions);
+        assert_eq!(
+            borsh_schema_errors(&container)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "`Holder`: missing definition for `Unknown`",
+                "`Holder`: recursion without a finite value",
+                "`Loop`: recursion without a finite value",
+            ]
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use crate::path::AbiPath;
+use crate::walk::{function_borsh_schemas, function_json_schemas, referenced_definitions};
+use crate::AbiBody;
+use std::collections::BTreeSet;
+
+/// Elements of the ABI whose wire format changes if the type named `type_name` changes: the
+/// function parameters, callbacks and results using it, the root schema definitions and the
+/// constants depending on it.
+///
+/// JSON types are matched by root schema definition name and followed through the definitions
+/// referencing them, Borsh types by declaration. Function sites come first, in declaration order,
+/// followed by the dependent definitions sorted by name and the constants. The type itself is
+/// not reported.
+pub fn type_impact(body: &AbiBody, type_name: &str) -> Vec<AbiPath> {
+    let affected = dependent_definitions(body, type_name);
+    let uses_affected = |schema| !referenced_definitions(schema).is_disjoint(&affected);
+
+    let mut impact = Vec::new();
+    for function in &body.functions {
+        for (site, schema) in function_json_schemas(function) {
+            if uses_affected(schema) {
+                impact.push(AbiPath::from_site(function, &site));
+            }
+        }
+        for (site, container) in function_borsh_schemas(function) {
+            if container.get_definition(type_name).is_some() {
+                impact.push(AbiPath::from_site(function, &site));
+            }
+        }
+    }
+    impact.extend(
+        affected
+            .iter()
+            .filter(|name| *name != type_name && body.definition(name).is_some())
+            .map(|name| AbiPath::Definition(name.clone())),
+    );
+    impact.extend(
+        body.constants
+            .iter()
+            .filter(|constant| uses_affected(&constant.type_schema))
+            .map(|constant| AbiPath::Constant(constant.name.clone())),
+    );
+    impact
+}
+
+/// `type_name` and the root schema definitions referencing it, directly or transitively.
+fn dependent_definitions(body: &AbiBody, type_name: &str) -> BTreeSet<String> {
+    let references: Vec<(&str, BTreeSet<String>)> = body
+        .definitions()
+        .map(|(name, schema)| (name, referenced_definitions(schema)))
+        .collect();
+    let mut affected = BTreeSet::from([type_name.to_string()]);
+    loop {
+        let dependents: Vec<String> = references
+            .iter()
+            .filter(|(name, referenced)| {
+                !affected.contains(*name) && !referenced.is_disjoint(&affected)
+            })
+            .map(|(name, _)| name.to_string())
+            .collect();
+        if dependents.is_empty() {
+            return affected;
+        }
+        affected.extend(dependents);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiRoot, SCHEMA_VERSION};
+    use serde_json::json;
+
+    #[test]
+    fn test_type_impact() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    {
+                        "name": "ft_transfer",
+                        "kind": "call",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [
+                                { "name": "receiver_id", "type_schema": { "$ref": "#/definitions/AccountId" } },
+                                { "name": "amount", "type_schema": { "$ref": "#/definitions/U128" } }
+                            ]
+                        }
+                    },
+                    {
+                        "name": "storage_balance_of",
+                        "kind": "view",
+                        "result": {
+                            "serialization_type": "json",
+                            "type_schema": {
+                                "anyOf": [{ "$ref": "#/definitions/StorageBalance" }, { "type": "null" }]
+                            }
+                        }
+                    },
+                    {
+                        "name": "on_transfer",
+                        "kind": "call",
+                        "callbacks": [{
+                            "name": "amount",
+                            "serialization_type": "borsh",
+                            "type_schema": {
+                                "declaration": "U128",
+                                "definitions": { "U128": { "Struct": ["u128"] }, "u128": { "Primitive": 16 } }
+                            }
+                        }]
+                    }
+                ],
+                "root_schema": {
+                    "definitions": {
+                        "AccountId": { "type": "string" },
+                        "U128": { "type": "string" },
+                        "StorageBalance": {
+                            "type": "object",
+                            "properties": { "total": { "$ref": "#/definitions/U128" } }
+                        }
+                    }
+                },
+                "constants": [
+                    { "name": "MIN_BALANCE", "type_schema": { "$ref": "#/definitions/U128" }, "value": "0" }
+                ]
+            }
+        }))
+        .unwrap();
+
+        let impact: Vec<String> = abi
+            .impact_of_type_change("U128")
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            impact,
+            [
+                "functions/ft_transfer/params/amount",
+                "functions/storage_balance_of/result",
+                "functions/on_transfer/callbacks/amount",
+                "definitions/StorageBalance",
+                "constants/MIN_BALANCE",
+            ]
+        );
+        assert_eq!(
+            abi.impact_of_type_change("AccountId"),
+            [AbiPath::Param {
+                function: "ft_transfer".to_string(),
+                param: "receiver_id".to_string(),
+            }]
+        );
+        assert!(abi.impact_of_type_change("Unknown").is_empty());
+    }
+}
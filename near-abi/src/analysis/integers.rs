@@ -0,0 +1,196 @@
+use crate::path::AbiPath;
+use crate::walk::{function_json_schemas, subschemas};
+use crate::AbiBody;
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use std::collections::BTreeSet;
+
+/// JSON representation of a 64-bit or 128-bit integer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IntegerEncoding {
+    /// A JSON number, which many JSON parsers can not represent exactly above 2^53.
+    Number,
+    /// A decimal string, as with the `U64` and `U128` types of `near-sdk`.
+    String,
+}
+
+/// A 64-bit or 128-bit integer used in the ABI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntegerSite {
+    /// Where the integer is used, e.g. `functions/ft_transfer/params/amount` or
+    /// `definitions/StorageBalance`.
+    pub location: AbiPath,
+    /// Rust type of the integer, e.g. `u128`.
+    pub width: &'static str,
+    pub encoding: IntegerEncoding,
+}
+
+/// Names of the `near-sdk` string wrappers of wide integers, along with the Rust type and the
+/// schemars format of the integer they wrap.
+pub(crate) const STRING_WRAPPERS: [(&str, &str, &str); 4] = [
+    ("U64", "u64", "uint64"),
+    ("U128", "u128", "uint128"),
+    ("I64", "i64", "int64"),
+    ("I128", "i128", "int128"),
+];
+
+/// Lists the 64-bit and 128-bit integers used in function signatures and root schema
+/// definitions, along with their JSON encoding.
+///
+/// Integers are encoded as strings when they reference a `U64`, `U128`, `I64` or `I128`
+/// definition, or when they are strings with an integer `format` (see
+/// [`annotate_integer_encodings`](crate::transform::annotate_integer_encodings)). Integers with
+/// an integer `format` are encoded as numbers.
+pub fn integer_encodings(body: &AbiBody) -> Vec<IntegerSite> {
+    let mut sites = Vec::new();
+    for function in &body.functions {
+        for (site, schema) in function_json_schemas(function) {
+            collect(schema, &AbiPath::from_site(function, &site), &mut sites);
+        }
+    }
+    for (name, schema) in body.definitions() {
+        if !STRING_WRAPPERS
+            .iter()
+            .any(|(wrapper, _, _)| *wrapper == name)
+        {
+            collect(schema, &AbiPath::Definition(name.to_string()), &mut sites);
+        }
+    }
+    sites
+}
+
+/// Integer types that are encoded as numbers in some places of the ABI and as strings in
+/// others, a frequent source of integration bugs.
+pub fn mixed_integer_encodings(body: &AbiBody) -> Vec<&'static str> {
+    let sites = integer_encodings(body);
+    let encoded_as = |encoding| {
+        sites
+            .iter()
+            .filter(|site| site.encoding == encoding)
+            .map(|site| site.width)
+            .collect::<BTreeSet<_>>()
+    };
+    encoded_as(IntegerEncoding::Number)
+        .intersection(&encoded_as(IntegerEncoding::String))
+        .copied()
+        .collect()
+}
+
+/// Rust type and encoding of the wide integer described by `schema`, if any.
+fn integer_encoding(schema: &SchemaObject) -> Option<(&'static str, IntegerEncoding)> {
+    if let Some(reference) = &schema.reference {
+        let name = reference.rsplit('/').next().unwrap_or(reference);
+        return STRING_WRAPPERS
+            .iter()
+            .find(|(wrapper, _, _)| *wrapper == name)
+            .map(|(_, width, _)| (*width, IntegerEncoding::String));
+    }
+    let width = STRING_WRAPPERS
+        .iter()
+        .find(|(_, _, format)| schema.format.as_deref() == Some(*format))
+        .map(|(_, width, _)| *width)?;
+    match &schema.instance_type {
+        Some(SingleOrVec::Single(ty)) if **ty == InstanceType::Integer => {
+            Some((width, IntegerEncoding::Number))
+        }
+        Some(SingleOrVec::Single(ty)) if **ty == InstanceType::String => {
+            Some((width, IntegerEncoding::String))
+        }
+        _ => None,
+    }
+}
+
+fn collect(schema: &Schema, location: &AbiPath, sites: &mut Vec<IntegerSite>) {
+    let object = match schema {
+        Schema::Object(object) => object,
+        Schema::Bool(_) => return,
+    };
+    if let Some((width, encoding)) = integer_encoding(object) {
+        sites.push(IntegerSite {
+            location: location.clone(),
+            width,
+            encoding,
+        });
+    }
+    for child in subschemas(object) {
+        collect(child, location, sites);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiRoot, SCHEMA_VERSION};
+    use serde_json::json;
+
+    #[test]
+    fn test_mixed_integer_encodings() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    {
+                        "name": "ft_transfer",
+                        "kind": "call",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [{ "name": "amount", "type_schema": { "$ref": "#/definitions/U128" } }]
+                        }
+                    },
+                    {
+                        "name": "get_fee",
+                        "kind": "view",
+                        "result": {
+                            "serialization_type": "json",
+                            "type_schema": { "type": "integer", "format": "uint128", "minimum": 0.0 }
+                        }
+                    }
+                ],
+                "root_schema": {
+                    "definitions": {
+                        "U128": { "type": "string" },
+                        "Balance": {
+                            "type": "object",
+                            "properties": {
+                                "total": { "$ref": "#/definitions/U128" },
+                                "nonce": { "type": "integer", "format": "uint64", "minimum": 0.0 }
+                            }
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let sites = integer_encodings(&abi.body);
+        assert_eq!(
+            sites
+                .iter()
+                .map(|site| (site.location.to_string(), site.width, site.encoding))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "functions/ft_transfer/params/amount".to_string(),
+                    "u128",
+                    IntegerEncoding::String
+                ),
+                (
+                    "functions/get_fee/result".to_string(),
+                    "u128",
+                    IntegerEncoding::Number
+                ),
+                (
+                    "definitions/Balance".to_string(),
+                    "u64",
+                    IntegerEncoding::Number
+                ),
+                (
+                    "definitions/Balance".to_string(),
+                    "u128",
+                    IntegerEncoding::String
+                ),
+            ]
+        );
+        assert_eq!(mixed_integer_encodings(&abi.body), vec!["u128"]);
+    }
+}
@@ -0,0 +1,718 @@
+use crate::value::{resolve_reference, validate_json};
+use schemars::schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+use std::fmt;
+
+/// Checks that every JSON value accepted by the `old` schema is accepted by the `new` one, i.e.
+/// that `old` is a subschema of `new`. References are resolved against `old_root` and `new_root`
+/// respectively.
+///
+/// Schemas are compared structurally: the order of object keys, types, enumerated values and
+/// union variants does not matter, and annotations such as `description` are ignored. The
+/// relation is conservative, an incompatibility is reported for every keyword whose effect can
+/// not be compared, e.g. differing `pattern`s or `not` schemas. Integer `format`s are compared
+/// by the range of values they allow.
+pub fn json_compat_errors(
+    old_root: &RootSchema,
+    old: &Schema,
+    new_root: &RootSchema,
+    new: &Schema,
+) -> Vec<JsonCompatError> {
+    let mut checker = Checker {
+        old_root,
+        new_root,
+        in_progress: Vec::new(),
+        errors: Vec::new(),
+    };
+    checker.check(old, new, String::new());
+    checker.errors
+}
+
+struct Checker<'a> {
+    old_root: &'a RootSchema,
+    new_root: &'a RootSchema,
+    /// Pairs of schemas being compared, from the outermost one.
+    in_progress: Vec<(&'a Schema, &'a Schema)>,
+    errors: Vec<JsonCompatError>,
+}
+
+impl<'a> Checker<'a> {
+    fn error(&mut self, path: &str, kind: JsonCompatErrorKind) {
+        self.errors.push(JsonCompatError {
+            path: path.to_string(),
+            kind,
+        });
+    }
+
+    /// Whether `old` is a subschema of `new`, without reporting errors.
+    fn is_compatible<'b>(&self, old: &'b Schema, new: &'b Schema) -> bool
+    where
+        'a: 'b,
+    {
+        let mut checker = Checker {
+            old_root: self.old_root,
+            new_root: self.new_root,
+            in_progress: self.in_progress.clone(),
+            errors: Vec::new(),
+        };
+        checker.check(old, new, String::new());
+        checker.errors.is_empty()
+    }
+
+    /// Follows the references of `schema`, returning `None` for missing definitions.
+    fn resolve(root: &'a RootSchema, mut schema: &'a Schema) -> Result<&'a Schema, String> {
+        // Bounded to stay clear of reference cycles such as `A = $ref B`, `B = $ref A`.
+        for _ in 0..32 {
+            match schema {
+                Schema::Object(SchemaObject {
+                    reference: Some(reference),
+                    ..
+                }) => {
+                    schema = resolve_reference(root, reference).ok_or_else(|| reference.clone())?
+                }
+                _ => return Ok(schema),
+            }
+        }
+        Ok(schema)
+    }
+
+    fn check(&mut self, old: &'a Schema, new: &'a Schema, path: String) {
+        let (old, new) = match (
+            Self::resolve(self.old_root, old),
+            Self::resolve(self.new_root, new),
+        ) {
+            (Ok(old), Ok(new)) => (old, new),
+            (Err(reference), _) | (_, Err(reference)) => {
+                return self.error(&path, JsonCompatErrorKind::MissingDefinition(reference))
+            }
+        };
+        // A pair of recursive types already being compared is assumed compatible, any
+        // incompatibility is reported by the outer comparison.
+        if self.in_progress.contains(&(old, new)) {
+            return;
+        }
+        self.in_progress.push((old, new));
+        self.check_resolved(old, new, path);
+        self.in_progress.pop();
+    }
+
+    fn check_resolved(&mut self, old: &'a Schema, new: &'a Schema, path: String) {
+        match (old, new) {
+            (_, Schema::Bool(true)) | (Schema::Bool(false), _) => {}
+            (_, Schema::Bool(false)) => self.error(&path, JsonCompatErrorKind::Rejected),
+            (Schema::Bool(true), Schema::Object(new_object)) => {
+                if !accepts_anything(new_object) {
+                    self.error(&path, JsonCompatErrorKind::Rejected);
+                }
+            }
+            (Schema::Object(old_object), Schema::Object(new_object)) => {
+                self.check_objects(old_object, new, new_object, path)
+            }
+        }
+    }
+
+    fn check_objects(
+        &mut self,
+        old: &'a SchemaObject,
+        new_schema: &'a Schema,
+        new: &'a SchemaObject,
+        path: String,
+    ) {
+        // Every variant of an old union must be accepted. Other keywords next to the union only
+        // restrict it further, so they can be ignored.
+        if let Some(subschemas) = &old.subschemas {
+            for (keyword, variants) in
+                [("anyOf", &subschemas.any_of), ("oneOf", &subschemas.one_of)]
+            {
+                if let Some(variants) = variants {
+                    for (i, variant) in variants.iter().enumerate() {
+                        self.check(variant, new_schema, format!("{}/{}/{}", path, keyword, i));
+                    }
+                    return;
+                }
+            }
+            if let Some(all_of) = &subschemas.all_of {
+                if !all_of
+                    .iter()
+                    .any(|member| self.is_compatible(member, new_schema))
+                {
+                    self.error(&path, JsonCompatErrorKind::Unsupported("allOf".to_string()));
+                }
+                return;
+            }
+        }
+
+        if let Some(subschemas) = &new.subschemas {
+            let unchanged = old.subschemas.as_ref().map_or(false, |old_subschemas| {
+                old_subschemas.not == subschemas.not
+                    && old_subschemas.if_schema == subschemas.if_schema
+                    && old_subschemas.then_schema == subschemas.then_schema
+                    && old_subschemas.else_schema == subschemas.else_schema
+            });
+            let conditional = subschemas.not.is_some() || subschemas.if_schema.is_some();
+            if conditional && !unchanged {
+                return self.error(&path, JsonCompatErrorKind::Unsupported("not".to_string()));
+            }
+            let old_schema = Schema::Object(old.clone());
+            for variants in [&subschemas.any_of, &subschemas.one_of]
+                .into_iter()
+                .flatten()
+            {
+                if !variants
+                    .iter()
+                    .any(|variant| self.is_compatible(&old_schema, variant))
+                {
+                    self.error(&path, JsonCompatErrorKind::VariantRejected);
+                }
+            }
+            if let Some(all_of) = &subschemas.all_of {
+                for member in all_of {
+                    if !self.is_compatible(&old_schema, member) {
+                        self.error(&path, JsonCompatErrorKind::Unsupported("allOf".to_string()));
+                    }
+                }
+            }
+        }
+
+        // Enumerations are finite, each old value can be checked against the new schema.
+        let values = match (&old.enum_values, &old.const_value) {
+            (Some(values), _) => Some(values.clone()),
+            (None, Some(value)) => Some(vec![value.clone()]),
+            (None, None) => None,
+        };
+        if let Some(values) = values {
+            for value in values {
+                if validate_json(self.new_root, new_schema, &value).is_err() {
+                    self.error(&path, JsonCompatErrorKind::ValueRejected(value));
+                }
+            }
+            return;
+        }
+        if new.enum_values.is_some() {
+            self.error(
+                &path,
+                JsonCompatErrorKind::ConstraintAdded("enum".to_string()),
+            );
+        }
+        if new.const_value.is_some() {
+            self.error(
+                &path,
+                JsonCompatErrorKind::ConstraintAdded("const".to_string()),
+            );
+        }
+
+        self.check_types(old, new, &path);
+        self.check_format(old, new, &path);
+        self.check_number(old, new, &path);
+        self.check_string(old, new, &path);
+        self.check_array(old, new, &path);
+        self.check_object(old, new, &path);
+    }
+
+    fn check_types(&mut self, old: &SchemaObject, new: &SchemaObject, path: &str) {
+        let new_types = match &new.instance_type {
+            Some(types) => instance_types(types),
+            None => return,
+        };
+        let old_types = match &old.instance_type {
+            Some(types) => instance_types(types),
+            None => {
+                return self.error(
+                    path,
+                    JsonCompatErrorKind::ConstraintAdded("type".to_string()),
+                )
+            }
+        };
+        for ty in old_types {
+            let accepted = new_types.contains(&ty)
+                || (ty == InstanceType::Integer && new_types.contains(&InstanceType::Number));
+            if !accepted {
+                self.error(path, JsonCompatErrorKind::TypeRemoved(ty));
+            }
+        }
+    }
+
+    fn check_format(&mut self, old: &SchemaObject, new: &SchemaObject, path: &str) {
+        let new_format = match &new.format {
+            Some(format) => format,
+            None => return,
+        };
+        let compatible = match old.format.as_deref() {
+            Some(old_format) if old_format == new_format => true,
+            Some(old_format) => match (integer_range(old_format), integer_range(new_format)) {
+                (Some((old_min, old_max)), Some((new_min, new_max))) => {
+                    new_min <= old_min && old_max <= new_max
+                }
+                _ => false,
+            },
+            None => false,
+        };
+        if !compatible {
+            self.error(
+                path,
+                JsonCompatErrorKind::ConstraintAdded("format".to_string()),
+            );
+        }
+    }
+
+    fn check_number(&mut self, old: &SchemaObject, new: &SchemaObject, path: &str) {
+        let (old, new) = match (&old.number, &new.number) {
+            (old, Some(new)) => (old.as_deref().cloned().unwrap_or_default(), new),
+            (_, None) => return,
+        };
+        let checks = [
+            ("minimum", lower_bound_kept(old.minimum, new.minimum)),
+            ("maximum", upper_bound_kept(old.maximum, new.maximum)),
+            (
+                "exclusiveMinimum",
+                lower_bound_kept(old.exclusive_minimum, new.exclusive_minimum),
+            ),
+            (
+                "exclusiveMaximum",
+                upper_bound_kept(old.exclusive_maximum, new.exclusive_maximum),
+            ),
+            (
+                "multipleOf",
+                match (old.multiple_of, new.multiple_of) {
+                    (_, None) => true,
+                    (Some(old), Some(new)) => (old / new).fract() == 0.0,
+                    (None, Some(_)) => false,
+                },
+            ),
+        ];
+        self.check_keywords(path, &checks);
+    }
+
+    fn check_string(&mut self, old: &SchemaObject, new: &SchemaObject, path: &str) {
+        let (old, new) = match (&old.string, &new.string) {
+            (old, Some(new)) => (old.as_deref().cloned().unwrap_or_default(), new),
+            (_, None) => return,
+        };
+        let checks = [
+            (
+                "minLength",
+                lower_bound_kept(old.min_length, new.min_length),
+            ),
+            (
+                "maxLength",
+                upper_bound_kept(old.max_length, new.max_length),
+            ),
+            (
+                "pattern",
+                new.pattern.is_none() || old.pattern == new.pattern,
+            ),
+        ];
+        self.check_keywords(path, &checks);
+    }
+
+    fn check_array(&mut self, old: &'a SchemaObject, new: &'a SchemaObject, path: &str) {
+        let new = match &new.array {
+            Some(new) => new,
+            None => return,
+        };
+        let old = old.array.as_deref();
+        let old_items = old.and_then(|old| old.items.as_ref());
+        let old_additional = old.and_then(|old| old.additional_items.as_deref());
+        let checks = [
+            (
+                "minItems",
+                lower_bound_kept(old.and_then(|old| old.min_items), new.min_items),
+            ),
+            (
+                "maxItems",
+                upper_bound_kept(old.and_then(|old| old.max_items), new.max_items),
+            ),
+            (
+                "uniqueItems",
+                new.unique_items != Some(true)
+                    || old.and_then(|old| old.unique_items) == Some(true),
+            ),
+            (
+                "contains",
+                new.contains.is_none()
+                    || old.and_then(|old| old.contains.as_ref()) == new.contains.as_ref(),
+            ),
+        ];
+        self.check_keywords(path, &checks);
+
+        let items_path = format!("{}/items", path);
+        match (old_items, &new.items) {
+            (_, None) => {}
+            (None, Some(SingleOrVec::Single(new_items))) => {
+                self.check(&Schema::Bool(true), new_items, items_path)
+            }
+            (Some(SingleOrVec::Single(old_items)), Some(SingleOrVec::Single(new_items))) => {
+                self.check(old_items, new_items, items_path)
+            }
+            (Some(SingleOrVec::Vec(old_items)), Some(SingleOrVec::Single(new_items))) => {
+                for (i, old_item) in old_items.iter().enumerate() {
+                    self.check(old_item, new_items, format!("{}/{}", items_path, i));
+                }
+                if let Some(old_additional) = old_additional {
+                    self.check(
+                        old_additional,
+                        new_items,
+                        format!("{}/additionalItems", path),
+                    );
+                }
+            }
+            (Some(SingleOrVec::Vec(old_items)), Some(SingleOrVec::Vec(new_items)))
+                if old_items.len() == new_items.len() =>
+            {
+                for (i, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+                    self.check(old_item, new_item, format!("{}/{}", items_path, i));
+                }
+            }
+            _ => self.error(
+                path,
+                JsonCompatErrorKind::ConstraintAdded("items".to_string()),
+            ),
+        }
+    }
+
+    fn check_object(&mut self, old: &'a SchemaObject, new: &'a SchemaObject, path: &str) {
+        let new = match &new.object {
+            Some(new) => new,
+            None => return,
+        };
+        let old = old.object.as_deref();
+        let checks = [
+            (
+                "minProperties",
+                lower_bound_kept(old.and_then(|old| old.min_properties), new.min_properties),
+            ),
+            (
+                "maxProperties",
+                upper_bound_kept(old.and_then(|old| old.max_properties), new.max_properties),
+            ),
+            (
+                "patternProperties",
+                new.pattern_properties.is_empty()
+                    || old.map(|old| &old.pattern_properties) == Some(&new.pattern_properties),
+            ),
+            (
+                "propertyNames",
+                new.property_names.is_none()
+                    || old.and_then(|old| old.property_names.as_ref())
+                        == new.property_names.as_ref(),
+            ),
+        ];
+        self.check_keywords(path, &checks);
+
+        let any = &Schema::Bool(true);
+        let old_required = old.map(|old| &old.required);
+        let old_additional = old
+            .and_then(|old| old.additional_properties.as_deref())
+            .unwrap_or(any);
+        let old_property = |name: &str| {
+            old.and_then(|old| old.properties.get(name))
+                .unwrap_or(old_additional)
+        };
+        for name in &new.required {
+            if !old_required.map_or(false, |required| required.contains(name)) {
+                self.error(path, JsonCompatErrorKind::PropertyRequired(name.clone()));
+            }
+        }
+        for (name, new_property) in &new.properties {
+            self.check(
+                old_property(name),
+                new_property,
+                format!("{}/properties/{}", path, name),
+            );
+        }
+        if let Some(new_additional) = &new.additional_properties {
+            if let Some(old) = old {
+                for (name, old_property) in &old.properties {
+                    if new.properties.contains_key(name) {
+                        continue;
+                    }
+                    if matches!(**new_additional, Schema::Bool(false)) {
+                        self.error(path, JsonCompatErrorKind::PropertyRejected(name.clone()));
+                    } else {
+                        let path = format!("{}/properties/{}", path, name);
+                        self.check(old_property, new_additional, path);
+                    }
+                }
+            }
+            self.check(
+                old_additional,
+                new_additional,
+                format!("{}/additionalProperties", path),
+            );
+        }
+    }
+
+    fn check_keywords(&mut self, path: &str, checks: &[(&str, bool)]) {
+        for (keyword, kept) in checks {
+            if !kept {
+                self.error(
+                    path,
+                    JsonCompatErrorKind::ConstraintAdded(keyword.to_string()),
+                );
+            }
+        }
+    }
+}
+
+/// Whether a schema without references accepts every value, ignoring annotations.
+fn accepts_anything(schema: &SchemaObject) -> bool {
+    let constraints = SchemaObject {
+        metadata: None,
+        extensions: Default::default(),
+        ..schema.clone()
+    };
+    constraints == SchemaObject::default()
+}
+
+fn instance_types(types: &SingleOrVec<InstanceType>) -> Vec<InstanceType> {
+    match types {
+        SingleOrVec::Single(ty) => vec![**ty],
+        SingleOrVec::Vec(types) => types.clone(),
+    }
+}
+
+fn lower_bound_kept<T: PartialOrd>(old: Option<T>, new: Option<T>) -> bool {
+    match (old, new) {
+        (_, None) => true,
+        (Some(old), Some(new)) => new <= old,
+        (None, Some(_)) => false,
+    }
+}
+
+fn upper_bound_kept<T: PartialOrd>(old: Option<T>, new: Option<T>) -> bool {
+    match (old, new) {
+        (_, None) => true,
+        (Some(old), Some(new)) => new >= old,
+        (None, Some(_)) => false,
+    }
+}
+
+/// Range of values of the integer `format`s emitted by `schemars`, e.g. `uint32`.
+fn integer_range(format: &str) -> Option<(i128, u128)> {
+    let (signed, bits) = match format.strip_prefix("uint") {
+        Some(bits) => (false, bits),
+        None => (true, format.strip_prefix("int")?),
+    };
+    let bits: u32 = bits
+        .parse()
+        .ok()
+        .filter(|bits| [8, 16, 32, 64, 128].contains(bits))?;
+    Some(if signed {
+        (i128::MIN >> (128 - bits), (u128::MAX >> (129 - bits)))
+    } else {
+        (0, u128::MAX >> (128 - bits))
+    })
+}
+
+/// Part of the old schema accepting values the new schema rejects.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonCompatError {
+    path: String,
+    kind: JsonCompatErrorKind,
+}
+
+impl JsonCompatError {
+    /// Location of the incompatibility, as a path of keywords from the compared schemas, e.g.
+    /// `/properties/amount`. References are followed transparently.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn kind(&self) -> &JsonCompatErrorKind {
+        &self.kind
+    }
+}
+
+impl std::error::Error for JsonCompatError {}
+impl fmt::Display for JsonCompatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "`{}`: {}", self.path, self.kind)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonCompatErrorKind {
+    /// A reference points to a missing definition.
+    MissingDefinition(String),
+    /// The new schema rejects every value.
+    Rejected,
+    /// Values of the type are no longer accepted.
+    TypeRemoved(InstanceType),
+    /// The keyword rejects values that used to be accepted.
+    ConstraintAdded(String),
+    /// The property is now required.
+    PropertyRequired(String),
+    /// The property is no longer accepted.
+    PropertyRejected(String),
+    /// A value of the old enumeration is no longer accepted.
+    ValueRejected(Value),
+    /// The old schema matches none of the variants of the new union.
+    VariantRejected,
+    /// The keyword changed in a way that can not be compared.
+    Unsupported(String),
+}
+
+impl fmt::Display for JsonCompatErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingDefinition(reference) => {
+                write!(f, "reference `{}` has no definition", reference)
+            }
+            Self::Rejected => write!(f, "values are rejected"),
+            Self::TypeRemoved(ty) => {
+                write!(
+                    f,
+                    "type `{}` is no longer accepted",
+                    format!("{:?}", ty).to_lowercase()
+                )
+            }
+            Self::ConstraintAdded(keyword) => write!(f, "`{}` was restricted", keyword),
+            Self::PropertyRequired(property) => write!(f, "property `{}` is required", property),
+            Self::PropertyRejected(property) => {
+                write!(f, "property `{}` is no longer accepted", property)
+            }
+            Self::ValueRejected(value) => write!(f, "value `{}` is no longer accepted", value),
+            Self::VariantRejected => write!(f, "no variant matches the old schema"),
+            Self::Unsupported(keyword) => write!(f, "`{}` changed", keyword),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn root(definitions: Value) -> RootSchema {
+        serde_json::from_value(json!({ "definitions": definitions })).unwrap()
+    }
+
+    fn errors(old: Value, new: Value) -> Vec<String> {
+        let old: Schema = serde_json::from_value(old).unwrap();
+        let new: Schema = serde_json::from_value(new).unwrap();
+        let root = root(json!({}));
+        json_compat_errors(&root, &old, &root, &new)
+            .iter()
+            .map(|error| error.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_compat() {
+        // Reordered keys, types and values are not changes.
+        assert!(errors(
+            json!({ "type": ["string", "null"], "enum": ["a", "b", null], "description": "Old." }),
+            json!({ "enum": [null, "b", "a"], "type": ["null", "string"] })
+        )
+        .is_empty());
+        assert!(errors(
+            json!({ "type": "integer", "format": "uint32", "minimum": 0 }),
+            json!({ "type": "number", "format": "uint64" })
+        )
+        .is_empty());
+        assert_eq!(
+            errors(
+                json!({ "type": "integer", "format": "uint64" }),
+                json!({ "type": "integer", "format": "uint32", "minimum": 1 })
+            ),
+            ["`format` was restricted", "`minimum` was restricted"]
+        );
+        assert!(errors(
+            json!({ "type": "string" }),
+            json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] })
+        )
+        .is_empty());
+        assert_eq!(
+            errors(
+                json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] }),
+                json!({ "type": "string" })
+            ),
+            ["`/anyOf/1`: type `null` is no longer accepted"]
+        );
+
+        let object = |required: Value, additional: bool| {
+            json!({
+                "type": "object",
+                "properties": { "id": { "type": "string" }, "memo": { "type": "string" } },
+                "required": required,
+                "additionalProperties": additional
+            })
+        };
+        assert!(errors(
+            object(json!(["id", "memo"]), false),
+            object(json!(["id"]), true)
+        )
+        .is_empty());
+        assert_eq!(
+            errors(
+                object(json!(["id"]), true),
+                object(json!(["id", "memo"]), false)
+            ),
+            [
+                "property `memo` is required",
+                "`/additionalProperties`: values are rejected"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_compat_references() {
+        // Renamed and recursive definitions are compared structurally.
+        let old_root = root(json!({
+            "Node": {
+                "type": "object",
+                "properties": { "children": { "type": "array", "items": { "$ref": "#/definitions/Node" } } }
+            }
+        }));
+        let new_root = root(json!({
+            "Tree": {
+                "type": "object",
+                "properties": { "children": { "type": "array", "items": { "$ref": "#/definitions/Tree" } } }
+            }
+        }));
+        let old: Schema = serde_json::from_value(json!({ "$ref": "#/definitions/Node" })).unwrap();
+        let new: Schema = serde_json::from_value(json!({ "$ref": "#/definitions/Tree" })).unwrap();
+        assert!(json_compat_errors(&old_root, &old, &new_root, &new).is_empty());
+        assert_eq!(
+            json_compat_errors(&old_root, &old, &old_root, &new)[0].kind(),
+            &JsonCompatErrorKind::MissingDefinition("#/definitions/Tree".to_string())
+        );
+    }
+
+    #[test]
+    fn test_json_compat_repeated_references() {
+        // A pair of schemas that is incompatible in one place is reported wherever it appears.
+        let root = root(json!({ "A": { "type": "string" }, "B": { "type": "integer" } }));
+        let object = |property: Value| json!({ "type": "object", "properties": { "z": property } });
+        let old: Schema = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "x": { "$ref": "#/definitions/A" },
+                "y": object(json!({ "$ref": "#/definitions/A" }))
+            }
+        }))
+        .unwrap();
+        let new: Schema = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "x": { "$ref": "#/definitions/B" },
+                "y": { "anyOf": [object(json!({ "$ref": "#/definitions/B" }))] }
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            json_compat_errors(&root, &old, &root, &new)
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "`/properties/x`: type `string` is no longer accepted",
+                "`/properties/y`: no variant matches the old schema"
+            ]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Static analyses over ABI type schemas.
+
+mod compat;
+mod completeness;
+mod impact;
+mod integers;
+mod json_compat;
+mod recursion;
+mod widths;
+
+pub use compat::{borsh_compat_errors, BorshCompatError, BorshCompatErrorKind, BorshCompatRules};
+pub use completeness::{
+    borsh_schema_errors, check_borsh_schemas, BorshSchemaError, BorshSchemaErrorKind,
+};
+pub use impact::type_impact;
+pub(crate) use integers::STRING_WRAPPERS;
+pub use integers::{integer_encodings, mixed_integer_encodings, IntegerEncoding, IntegerSite};
+pub use json_compat::{json_compat_errors, JsonCompatError, JsonCompatErrorKind};
+pub use recursion::{borsh_cycles, json_cycles, recursive_types, RecursionReport};
+pub use widths::{borsh_width_errors, check_borsh_widths, BorshWidthError, BorshWidthErrorKind};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Directed graph between named types, e.g. root schema definitions and the definitions they
+/// reference.
+pub(crate) type TypeGraph = BTreeMap<String, BTreeSet<String>>;
+
+/// Strongly connected components of `graph` that contain a cycle, i.e. groups of mutually
+/// recursive types and types referencing themselves. Members of every group are sorted by name.
+pub(crate) fn cyclic_components(graph: &TypeGraph) -> Vec<Vec<String>> {
+    // Tarjan's algorithm, iterative to stay within stack limits on deep graphs.
+    let mut index = BTreeMap::<&str, usize>::new();
+    let mut lowlink = BTreeMap::<&str, usize>::new();
+    let mut on_stack = BTreeSet::<&str>::new();
+    let mut stack = Vec::<&str>::new();
+    let mut components = Vec::new();
+    let empty = BTreeSet::new();
+
+    for start in graph.keys() {
+        if index.contains_key(start.as_str()) {
+            continue;
+        }
+        let mut work: Vec<(&str, Vec<&str>)> = Vec::new();
+        let visit = |node: &'_ str| -> Vec<&'_ str> {
+            graph
+                .get(node)
+                .unwrap_or(&empty)
+                .iter()
+                .map(String::as_str)
+                .collect()
+        };
+        let mut next_index = index.len();
+        index.insert(start, next_index);
+        lowlink.insert(start, next_index);
+        next_index += 1;
+        stack.push(start);
+        on_stack.insert(start);
+        work.push((start, visit(start)));
+
+        while let Some((node, successors)) = work.last_mut() {
+            let node = *node;
+            if let Some(successor) = successors.pop() {
+                if !index.contains_key(successor) {
+                    index.insert(successor, next_index);
+                    lowlink.insert(successor, next_index);
+                    next_index += 1;
+                    stack.push(successor);
+                    on_stack.insert(successor);
+                    work.push((successor, visit(successor)));
+                } else if on_stack.contains(successor) {
+                    let low = lowlink[node].min(index[successor]);
+                    lowlink.insert(node, low);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some((parent, _)) = work.last() {
+                let low = lowlink[*parent].min(lowlink[node]);
+                lowlink.insert(parent, low);
+            }
+            if lowlink[node] == index[node] {
+                let mut component = Vec::new();
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack.remove(member);
+                    component.push(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                let is_cyclic = component.len() > 1
+                    || graph.get(node).map_or(false, |edges| edges.contains(node));
+                if is_cyclic {
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+    }
+    components.sort();
+    components
+}
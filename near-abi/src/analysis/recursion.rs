@@ -0,0 +1,159 @@
+use super::{cyclic_components, TypeGraph};
+use crate::walk::{borsh_type_schemas, referenced_definitions};
+use crate::AbiBody;
+use borsh::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+use schemars::schema::RootSchema;
+
+/// Recursive types found in an ABI.
+///
+/// Code generators must introduce indirection (e.g. `Box`) for every member of a group, and
+/// inlining transformations must stop expanding them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecursionReport {
+    /// Groups of mutually recursive root schema definitions.
+    pub json: Vec<Vec<String>>,
+    /// Groups of mutually recursive Borsh declarations across all function signatures.
+    pub borsh: Vec<Vec<Declaration>>,
+}
+
+impl RecursionReport {
+    /// Whether the ABI contains no recursive types.
+    pub fn is_empty(&self) -> bool {
+        self.json.is_empty() && self.borsh.is_empty()
+    }
+
+    /// Whether the root schema definition `name` is part of a recursive group.
+    pub fn is_json_recursive(&self, name: &str) -> bool {
+        self.json.iter().flatten().any(|member| member == name)
+    }
+
+    /// Whether the Borsh declaration is part of a recursive group.
+    pub fn is_borsh_recursive(&self, declaration: &str) -> bool {
+        self.borsh
+            .iter()
+            .flatten()
+            .any(|member| member == declaration)
+    }
+}
+
+/// Detects recursive types in both the root schema and the Borsh type schemas of an ABI.
+pub fn recursive_types(body: &AbiBody) -> RecursionReport {
+    let mut borsh_graph = TypeGraph::new();
+    for (_, _, container) in borsh_type_schemas(body) {
+        borsh_graph.extend(borsh_graph_of(container));
+    }
+    RecursionReport {
+        json: json_cycles(&body.root_schema),
+        borsh: cyclic_components(&borsh_graph),
+    }
+}
+
+/// Groups of mutually recursive (or self-referencing) root schema definitions.
+pub fn json_cycles(root_schema: &RootSchema) -> Vec<Vec<String>> {
+    let graph: TypeGraph = root_schema
+        .definitions
+        .iter()
+        .map(|(name, schema)| (name.clone(), referenced_definitions(schema)))
+        .collect();
+    cyclic_components(&graph)
+}
+
+/// Groups of mutually recursive (or self-referencing) declarations of a Borsh schema.
+pub fn borsh_cycles(container: &BorshSchemaContainer) -> Vec<Vec<Declaration>> {
+    cyclic_components(&borsh_graph_of(container))
+}
+
+fn borsh_graph_of(container: &BorshSchemaContainer) -> TypeGraph {
+    container
+        .definitions()
+        .map(|(declaration, definition)| {
+            let edges = match definition {
+                Definition::Primitive(_) => Vec::new(),
+                Definition::Sequence { elements, .. } => vec![elements.clone()],
+                Definition::Tuple { elements } => elements.clone(),
+                Definition::Enum { variants, .. } => variants
+                    .iter()
+                    .map(|(_, _, declaration)| declaration.clone())
+                    .collect(),
+                Definition::Struct { fields } => match fields {
+                    Fields::NamedFields(fields) => fields
+                        .iter()
+                        .map(|(_, declaration)| declaration.clone())
+                        .collect(),
+                    Fields::UnnamedFields(fields) => fields.clone(),
+                    Fields::Empty => Vec::new(),
+                },
+            };
+            (declaration.clone(), edges.into_iter().collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiFunction, AbiFunctionKind, AbiType};
+    use borsh::BorshSchema;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_cycles() {
+        let root_schema: RootSchema = serde_json::from_value(json!({
+            "definitions": {
+                "Tree": {
+                    "type": "object",
+                    "properties": {
+                        "children": { "type": "array", "items": { "$ref": "#/definitions/Forest" } }
+                    }
+                },
+                "Forest": {
+                    "type": "array",
+                    "items": { "$ref": "#/definitions/Tree" }
+                },
+                "List": {
+                    "anyOf": [{ "$ref": "#/definitions/List" }, { "type": "null" }]
+                },
+                "Leaf": { "type": "string" },
+                "Holder": { "$ref": "#/definitions/Tree" }
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            json_cycles(&root_schema),
+            vec![
+                vec!["Forest".to_string(), "Tree".to_string()],
+                vec!["List".to_string()]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_recursive_borsh_types() {
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        struct Node {
+            value: u32,
+            children: Vec<Node>,
+        }
+        let body = AbiBody {
+            functions: vec![AbiFunction {
+                result: Some(AbiType::Borsh {
+                    type_schema: borsh::schema_container_of::<Node>(),
+                }),
+                ..AbiFunction::new("get_tree", AbiFunctionKind::View)
+            }],
+            root_schema: RootSchema::default(),
+            state: None,
+            constants: Vec::new(),
+            external_contracts: Vec::new(),
+        };
+        let report = recursive_types(&body);
+        assert!(report.json.is_empty());
+        assert_eq!(
+            report.borsh,
+            vec![vec!["Node".to_string(), "Vec<Node>".to_string()]]
+        );
+        assert!(report.is_borsh_recursive("Node"));
+        assert!(!report.is_borsh_recursive("u32"));
+    }
+}
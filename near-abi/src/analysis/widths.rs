@@ -0,0 +1,241 @@
+use crate::walk::borsh_type_schemas;
+use crate::AbiBody;
+use borsh::schema::{BorshSchemaContainer, Declaration, Definition};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Widths in bytes that Borsh length prefixes and enum tags can be encoded in.
+const WIDTHS: [u8; 4] = [1, 2, 4, 8];
+
+/// Checks that the length prefixes and enum tags of every definition in a Borsh schema are
+/// consistent with the values they have to encode.
+///
+/// Unlike [`BorshSchemaContainer::validate`], which only follows the definitions reachable from
+/// the declaration, every definition of the container is checked.
+pub fn borsh_width_errors(container: &BorshSchemaContainer) -> Vec<BorshWidthError> {
+    let mut errors = Vec::new();
+    for (declaration, definition) in container.definitions() {
+        let mut error = |kind| {
+            errors.push(BorshWidthError {
+                declaration: declaration.clone(),
+                kind,
+            })
+        };
+        match definition {
+            Definition::Sequence {
+                length_width,
+                length_range,
+                ..
+            } => {
+                if length_range.is_empty() {
+                    error(BorshWidthErrorKind::EmptyLengthRange);
+                } else if *length_width == Definition::ARRAY_LENGTH_WIDTH {
+                    if length_range.start() != length_range.end() {
+                        error(BorshWidthErrorKind::MissingLengthPrefix);
+                    }
+                } else if !WIDTHS.contains(length_width) {
+                    error(BorshWidthErrorKind::InvalidLengthWidth(*length_width));
+                } else if *length_range.end() > max_value(*length_width) {
+                    error(BorshWidthErrorKind::LengthNotRepresentable {
+                        length_width: *length_width,
+                        max_length: *length_range.end(),
+                    });
+                }
+            }
+            Definition::Enum {
+                tag_width,
+                variants,
+            } => {
+                if !WIDTHS.contains(tag_width) {
+                    error(BorshWidthErrorKind::InvalidTagWidth(*tag_width));
+                    continue;
+                }
+                let mut seen = BTreeSet::new();
+                for (discriminant, name, _) in variants {
+                    if *discriminant < 0 || *discriminant as u64 > max_value(*tag_width) {
+                        error(BorshWidthErrorKind::DiscriminantNotRepresentable {
+                            variant: name.clone(),
+                            discriminant: *discriminant,
+                            tag_width: *tag_width,
+                        });
+                    } else if !seen.insert(*discriminant) {
+                        error(BorshWidthErrorKind::DuplicateDiscriminant {
+                            variant: name.clone(),
+                            discriminant: *discriminant,
+                        });
+                    }
+                }
+            }
+            Definition::Primitive(_) | Definition::Tuple { .. } | Definition::Struct { .. } => {}
+        }
+    }
+    errors
+}
+
+/// Runs [`borsh_width_errors`] on every Borsh type schema of an ABI, reporting the errors along
+/// with the name of the function they were found in.
+pub fn check_borsh_widths(body: &AbiBody) -> Vec<(String, BorshWidthError)> {
+    borsh_type_schemas(body)
+        .into_iter()
+        .flat_map(|(function, _, container)| {
+            borsh_width_errors(container)
+                .into_iter()
+                .map(move |error| (function.to_string(), error))
+        })
+        .collect()
+}
+
+fn max_value(width: u8) -> u64 {
+    match width {
+        8.. => u64::MAX,
+        width => (1 << (u32::from(width) * 8)) - 1,
+    }
+}
+
+/// Inconsistency between a Borsh definition and the width of its length prefix or enum tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BorshWidthError {
+    declaration: Declaration,
+    kind: BorshWidthErrorKind,
+}
+
+impl BorshWidthError {
+    /// Declaration of the inconsistent definition.
+    pub fn declaration(&self) -> &Declaration {
+        &self.declaration
+    }
+
+    pub fn kind(&self) -> &BorshWidthErrorKind {
+        &self.kind
+    }
+}
+
+impl std::error::Error for BorshWidthError {}
+impl fmt::Display for BorshWidthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}`: {}", self.declaration, self.kind)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BorshWidthErrorKind {
+    /// The sequence can not have any length.
+    EmptyLengthRange,
+    /// The sequence has no length prefix but its length is not fixed.
+    MissingLengthPrefix,
+    /// The length prefix width is not 1, 2, 4 or 8 bytes.
+    InvalidLengthWidth(u8),
+    /// The maximum sequence length does not fit in the length prefix.
+    LengthNotRepresentable { length_width: u8, max_length: u64 },
+    /// The tag width is not 1, 2, 4 or 8 bytes.
+    InvalidTagWidth(u8),
+    /// The variant discriminant does not fit in the tag.
+    DiscriminantNotRepresentable {
+        variant: String,
+        discriminant: i64,
+        tag_width: u8,
+    },
+    /// The variant discriminant is already used by a previous variant.
+    DuplicateDiscriminant { variant: String, discriminant: i64 },
+}
+
+impl fmt::Display for BorshWidthErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyLengthRange => write!(f, "sequence length range is empty"),
+            Self::MissingLengthPrefix => {
+                write!(f, "sequence of variable length has no length prefix")
+            }
+            Self::InvalidLengthWidth(width) => {
+                write!(f, "length prefix width of {} bytes is invalid", width)
+            }
+            Self::LengthNotRepresentable {
+                length_width,
+                max_length,
+            } => write!(
+                f,
+                "maximum length {} does not fit in a {}-byte length prefix",
+                max_length, length_width
+            ),
+            Self::InvalidTagWidth(width) => write!(f, "tag width of {} bytes is invalid", width),
+            Self::DiscriminantNotRepresentable {
+                variant,
+                discriminant,
+                tag_width,
+            } => write!(
+                f,
+                "discriminant {} of variant `{}` does not fit in a {}-byte tag",
+                discriminant, variant, tag_width
+            ),
+            Self::DuplicateDiscriminant {
+                variant,
+                discriminant,
+            } => write!(
+                f,
+                "discriminant {} of variant `{}` is already in use",
+                discriminant, variant
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_valid_containers() {
+        assert!(borsh_width_errors(&borsh::schema_container_of::<Vec<u8>>()).is_empty());
+        assert!(borsh_width_errors(&borsh::schema_container_of::<[u8; 32]>()).is_empty());
+        assert!(borsh_width_errors(&borsh::schema_container_of::<Option<String>>()).is_empty());
+    }
+
+    #[test]
+    fn test_width_errors() {
+        let definitions = BTreeMap::from([
+            (
+                "Short".to_string(),
+                Definition::Sequence {
+                    length_width: 1,
+                    length_range: 0..=300,
+                    elements: "u8".to_string(),
+                },
+            ),
+            (
+                "Unprefixed".to_string(),
+                Definition::Sequence {
+                    length_width: 0,
+                    length_range: 0..=4,
+                    elements: "u8".to_string(),
+                },
+            ),
+            (
+                "Tagged".to_string(),
+                Definition::Enum {
+                    tag_width: 1,
+                    variants: vec![
+                        (0, "A".to_string(), "u8".to_string()),
+                        (256, "B".to_string(), "u8".to_string()),
+                        (0, "C".to_string(), "u8".to_string()),
+                    ],
+                },
+            ),
+            ("u8".to_string(), Definition::Primitive(1)),
+        ]);
+        let container = BorshSchemaContainer::new("Short".to_string(), definitions);
+        let errors: Vec<_> = borsh_width_errors(&container)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "`Short`: maximum length 300 does not fit in a 1-byte length prefix",
+                "`Tagged`: discriminant 256 of variant `B` does not fit in a 1-byte tag",
+                "`Tagged`: discriminant 0 of variant `C` is already in use",
+                "`Unprefixed`: sequence of variable length has no length prefix",
+            ]
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Access to custom schema annotations.
+//!
+//! Keywords that `schemars` does not model, such as `$comment` or vendor-specific `x-*`
+//! keywords, are kept in [`SchemaObject::extensions`] and therefore survive parsing,
+//! transformation passes and serialization. This module provides typed access to them.
+//!
+//! [`SchemaObject::extensions`]: schemars::schema::SchemaObject::extensions
+
+use schemars::schema::Schema;
+use serde_json::Value;
+
+/// Keyword holding free-form comments for schema authors and maintainers.
+pub const COMMENT_KEYWORD: &str = "$comment";
+
+/// Prefix of vendor-specific keywords.
+pub const VENDOR_PREFIX: &str = "x-";
+
+/// Returns the `$comment` of a schema, if any.
+pub fn comment(schema: &Schema) -> Option<&str> {
+    annotation(schema, COMMENT_KEYWORD).and_then(Value::as_str)
+}
+
+/// Returns the value of an arbitrary annotation keyword not modelled by `schemars`.
+pub fn annotation<'a>(schema: &'a Schema, keyword: &str) -> Option<&'a Value> {
+    match schema {
+        Schema::Object(object) => object.extensions.get(keyword),
+        Schema::Bool(_) => None,
+    }
+}
+
+/// Iterates over the vendor-specific (`x-*`) annotations of a schema.
+pub fn vendor_annotations(schema: &Schema) -> impl Iterator<Item = (&str, &Value)> {
+    let extensions = match schema {
+        Schema::Object(object) => Some(&object.extensions),
+        Schema::Bool(_) => None,
+    };
+    extensions
+        .into_iter()
+        .flatten()
+        .filter(|(keyword, _)| keyword.starts_with(VENDOR_PREFIX))
+        .map(|(keyword, value)| (keyword.as_str(), value))
+}
+
+/// Sets an annotation keyword on a schema. Boolean schemas are converted into equivalent schema
+/// objects first, since they can not carry annotations.
+pub fn set_annotation(schema: &mut Schema, keyword: impl Into<String>, value: Value) {
+    if let Schema::Bool(accepts) = *schema {
+        *schema = Schema::Bool(accepts).into_object().into();
+    }
+    if let Schema::Object(object) = schema {
+        object.extensions.insert(keyword.into(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draft::{abi_from_value, abi_to_value, SchemaDraft};
+    use crate::{AbiRoot, SCHEMA_VERSION};
+    use serde_json::json;
+
+    #[test]
+    fn test_annotations_survive_roundtrip() {
+        let abi = json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    {
+                        "name": "get",
+                        "kind": "view",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [
+                                {
+                                    "name": "id",
+                                    "type_schema": {
+                                        "$ref": "#/definitions/Id",
+                                        "$comment": "lookup key",
+                                        "x-widget": "account-picker"
+                                    }
+                                }
+                            ]
+                        }
+                    }
+                ],
+                "root_schema": {
+                    "definitions": {
+                        "Id": {
+                            "type": "string",
+                            "$comment": "account id",
+                            "x-format": { "name": "near-account-id" }
+                        }
+                    }
+                }
+            }
+        });
+        let mut abi_root: AbiRoot = serde_json::from_value(abi.clone()).unwrap();
+        crate::transform::dedup_schemas(&mut abi_root.body);
+        let converted = abi_to_value(&abi_root, SchemaDraft::Draft2020_12);
+        let abi_root = abi_from_value(converted).unwrap();
+        assert_eq!(serde_json::to_value(&abi_root).unwrap(), abi);
+
+        let param = match &abi_root.body.functions[0].params {
+            crate::AbiParameters::Json { args } => &args[0].type_schema,
+            _ => unreachable!(),
+        };
+        assert_eq!(comment(param), Some("lookup key"));
+        assert_eq!(
+            vendor_annotations(param).collect::<Vec<_>>(),
+            vec![("x-widget", &json!("account-picker"))]
+        );
+        let id = &abi_root.body.root_schema.definitions["Id"];
+        assert_eq!(comment(id), Some("account id"));
+        assert_eq!(
+            annotation(id, "x-format"),
+            Some(&json!({ "name": "near-account-id" }))
+        );
+    }
+
+    #[test]
+    fn test_set_annotation_on_bool_schema() {
+        let mut schema = Schema::Bool(true);
+        set_annotation(&mut schema, COMMENT_KEYWORD, json!("anything goes"));
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({ "$comment": "anything goes" })
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Batched view calls for RPC clients.
+//!
+//! [`view_batch`] checks a list of view calls against the ABI of a contract and produces a
+//! [`ViewCall`] descriptor per entry: the method name, the base64-encoded arguments expected by
+//! the `call_function` RPC query and a [`ResultDecoder`] turning the raw result bytes back into
+//! JSON, whatever the serialization type of the function result.
+
+use crate::value::{
+    decode_borsh, validate_borsh_args, validate_json, validate_json_args, ValueError,
+};
+use crate::{AbiFunction, AbiFunctionKind, AbiParameters, AbiRoot, AbiType, ArityError};
+use base64::Engine;
+use serde_json::Value;
+use std::fmt;
+
+/// Arguments of a view call.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ViewArgs {
+    /// Arguments of a function taking JSON parameters.
+    Json(Value),
+    /// Serialized arguments of a function taking Borsh parameters.
+    Borsh(Vec<u8>),
+}
+
+/// A validated view call, ready to be sent as part of an RPC batch.
+#[derive(Clone, Debug)]
+pub struct ViewCall<'a> {
+    method_name: String,
+    args_base64: String,
+    decoder: ResultDecoder<'a>,
+}
+
+impl<'a> ViewCall<'a> {
+    /// Name of the view function to call.
+    pub fn method_name(&self) -> &str {
+        &self.method_name
+    }
+
+    /// Serialized arguments of the call, encoded with standard base64.
+    pub fn args_base64(&self) -> &str {
+        &self.args_base64
+    }
+
+    /// Decoder of the call result.
+    pub fn decoder(&self) -> &ResultDecoder<'a> {
+        &self.decoder
+    }
+}
+
+/// Decodes the raw result of a view call according to the ABI of the function.
+#[derive(Clone, Debug)]
+pub struct ResultDecoder<'a> {
+    abi: &'a AbiRoot,
+    function: &'a AbiFunction,
+}
+
+impl ResultDecoder<'_> {
+    /// Whether the decoded result can be `null`, see [`AbiFunction::result_is_nullable`].
+    pub fn is_nullable(&self) -> bool {
+        self.function.result_is_nullable(&self.abi.body.root_schema)
+    }
+
+    /// Decodes `bytes` into a JSON value.
+    ///
+    /// JSON results are parsed and validated against their schema, Borsh results are decoded as
+    /// described in [`decode_borsh`]. Functions without a result decode to `null`.
+    pub fn decode(&self, bytes: &[u8]) -> Result<Value, ValueError> {
+        match &self.function.result {
+            None if bytes.is_empty() => Ok(Value::Null),
+            None => Err(ValueError::new("", "the function does not return a value")),
+            Some(AbiType::Json { type_schema }) => {
+                let value: Value = serde_json::from_slice(bytes)
+                    .map_err(|err| ValueError::new("", format!("invalid JSON: {}", err)))?;
+                validate_json(&self.abi.body.root_schema, type_schema, &value)?;
+                Ok(value)
+            }
+            Some(AbiType::Borsh { type_schema }) => decode_borsh(type_schema, bytes),
+        }
+    }
+}
+
+/// Builds the descriptors of a batch of view calls, given as function names and arguments.
+///
+/// Fails on the first call that is not a valid view call of the contract.
+pub fn view_batch<'a, I, S>(abi: &'a AbiRoot, calls: I) -> Result<Vec<ViewCall<'a>>, BatchError>
+where
+    I: IntoIterator<Item = (S, ViewArgs)>,
+    S: AsRef<str>,
+{
+    calls
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, args))| {
+            view_call(abi, name.as_ref(), args).map_err(|kind| BatchError { index, kind })
+        })
+        .collect()
+}
+
+fn view_call<'a>(
+    abi: &'a AbiRoot,
+    name: &str,
+    args: ViewArgs,
+) -> Result<ViewCall<'a>, BatchErrorKind> {
+    let function = abi
+        .body
+        .functions
+        .iter()
+        .find(|f| f.name == name)
+        .ok_or_else(|| BatchErrorKind::UnknownFunction(name.to_string()))?;
+    if function.kind != AbiFunctionKind::View {
+        return Err(BatchErrorKind::NotAViewFunction(name.to_string()));
+    }
+    let args = match (&function.params, args) {
+        (AbiParameters::Json { args: params }, ViewArgs::Json(args)) => {
+            function.check_arity(&args).map_err(BatchErrorKind::Arity)?;
+            validate_json_args(&abi.body.root_schema, params, &args)
+                .map_err(BatchErrorKind::InvalidArgs)?;
+            serde_json::to_vec(&args).expect("JSON values can always be serialized")
+        }
+        (AbiParameters::Borsh { args: params }, ViewArgs::Borsh(args)) => {
+            validate_borsh_args(params, &args).map_err(BatchErrorKind::InvalidArgs)?;
+            args
+        }
+        _ => return Err(BatchErrorKind::SerializationMismatch(name.to_string())),
+    };
+    Ok(ViewCall {
+        method_name: function.name.clone(),
+        args_base64: base64::engine::general_purpose::STANDARD.encode(args),
+        decoder: ResultDecoder { abi, function },
+    })
+}
+
+/// Error returned when a batch entry is not a valid view call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchError {
+    index: usize,
+    kind: BatchErrorKind,
+}
+
+impl BatchError {
+    /// Position of the offending entry in the batch.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn kind(&self) -> &BatchErrorKind {
+        &self.kind
+    }
+}
+
+impl std::error::Error for BatchError {}
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "batch entry {}: {}", self.index, self.kind)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BatchErrorKind {
+    /// The function is not declared in the ABI.
+    UnknownFunction(String),
+    /// The function is not a view function.
+    NotAViewFunction(String),
+    /// The arguments are not of the serialization type of the function parameters.
+    SerializationMismatch(String),
+    /// The argument names do not match the function parameters.
+    Arity(ArityError),
+    /// The arguments do not conform to the function parameters.
+    InvalidArgs(ValueError),
+}
+
+impl fmt::Display for BatchErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownFunction(function) => {
+                write!(f, "function `{}` is not declared in the ABI", function)
+            }
+            Self::NotAViewFunction(function) => {
+                write!(f, "function `{}` is not a view function", function)
+            }
+            Self::SerializationMismatch(function) => write!(
+                f,
+                "arguments do not match the serialization type of `{}` parameters",
+                function
+            ),
+            Self::Arity(error) => write!(f, "invalid arguments: {}", error),
+            Self::InvalidArgs(error) => write!(f, "invalid arguments: {}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    fn abi() -> AbiRoot {
+        let mut abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    {
+                        "name": "ft_balance_of",
+                        "kind": "view",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [{ "name": "account_id", "type_schema": { "type": "string" } }]
+                        },
+                        "result": {
+                            "serialization_type": "json",
+                            "type_schema": { "type": "string" }
+                        }
+                    },
+                    { "name": "ft_transfer", "kind": "call" }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+        let mut counter = AbiFunction::new("get_counter", AbiFunctionKind::View);
+        counter.result = Some(AbiType::Borsh {
+            type_schema: borsh::schema_container_of::<(u8, u64)>(),
+        });
+        abi.body.functions.push(counter);
+        abi
+    }
+
+    #[test]
+    fn test_view_batch() {
+        let abi = abi();
+        let batch = view_batch(
+            &abi,
+            vec![
+                (
+                    "ft_balance_of",
+                    ViewArgs::Json(json!({ "account_id": "alice.near" })),
+                ),
+                ("get_counter", ViewArgs::Json(json!({}))),
+            ],
+        )
+        .unwrap();
+        assert_eq!(batch[0].method_name(), "ft_balance_of");
+        assert_eq!(
+            batch[0].args_base64(),
+            "eyJhY2NvdW50X2lkIjoiYWxpY2UubmVhciJ9"
+        );
+        assert_eq!(
+            batch[0].decoder().decode(br#""100""#).unwrap(),
+            json!("100")
+        );
+        assert_eq!(
+            batch[0].decoder().decode(b"100").unwrap_err().to_string(),
+            "expected string, found number"
+        );
+        assert!(!batch[0].decoder().is_nullable());
+        assert_eq!(batch[1].args_base64(), "e30=");
+        let mut counter = vec![3];
+        counter.extend(42u64.to_le_bytes());
+        assert_eq!(batch[1].decoder().decode(&counter).unwrap(), json!([3, 42]));
+    }
+
+    #[test]
+    fn test_view_batch_errors() {
+        let abi = abi();
+        let err = view_batch(
+            &abi,
+            vec![
+                ("get_counter", ViewArgs::Json(json!({}))),
+                ("ft_transfer", ViewArgs::Json(json!({}))),
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(err.index(), 1);
+        assert_eq!(
+            err.to_string(),
+            "batch entry 1: function `ft_transfer` is not a view function"
+        );
+        assert_eq!(
+            view_batch(&abi, vec![("ft_balance_of", ViewArgs::Borsh(Vec::new()))])
+                .unwrap_err()
+                .kind(),
+            &BatchErrorKind::SerializationMismatch("ft_balance_of".to_string())
+        );
+        assert!(matches!(
+            view_batch(&abi, vec![("ft_balance_of", ViewArgs::Json(json!({})))])
+                .unwrap_err()
+                .kind(),
+            BatchErrorKind::Arity(_)
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+//! ABIs of several contracts in one document.
+//!
+//! Cargo workspaces often produce several contracts that are deployed together, e.g. a factory
+//! and the child contracts it deploys. [`AbiBundle`] holds their ABIs under the name of each
+//! contract.
+
+use crate::{ensure_current_version, AbiRoot, SCHEMA_VERSION};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// ABIs of the contracts of a workspace, by contract name.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiBundle {
+    /// Semver of the ABI schema format, shared by all contracts.
+    #[serde(deserialize_with = "ensure_current_version")]
+    pub schema_version: String,
+    pub contracts: BTreeMap<String, AbiRoot>,
+}
+
+impl Default for AbiBundle {
+    fn default() -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION.to_string(),
+            contracts: BTreeMap::new(),
+        }
+    }
+}
+
+impl AbiBundle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ABI of the contract named `name`.
+    pub fn get(&self, name: &str) -> Option<&AbiRoot> {
+        self.contracts.get(name)
+    }
+
+    /// Adds the ABI of a contract, returning the ABI previously held under `name`.
+    pub fn insert(&mut self, name: impl Into<String>, abi: AbiRoot) -> Option<AbiRoot> {
+        self.contracts.insert(name.into(), abi)
+    }
+
+    /// Contract names and ABIs, in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AbiRoot)> {
+        self.contracts
+            .iter()
+            .map(|(name, abi)| (name.as_str(), abi))
+    }
+
+    /// Converts the bundle into a JSON value.
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("ABI serialization can not fail")
+    }
+
+    /// Parses a bundle from a JSON value, see [`AbiRoot::from_value`].
+    pub fn from_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        if let Some(schema_version) = value.get("schema_version") {
+            ensure_current_version(schema_version)?;
+        }
+        serde_json::from_value(value)
+    }
+
+    /// Checks that contract names are not empty and that all ABIs have the schema version of the
+    /// bundle.
+    pub fn validate(&self) -> Result<(), BundleError> {
+        for (name, abi) in &self.contracts {
+            if name.is_empty() {
+                return Err(BundleError::EmptyName);
+            }
+            if abi.schema_version != self.schema_version {
+                return Err(BundleError::SchemaVersionMismatch {
+                    contract: name.clone(),
+                    schema_version: abi.schema_version.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by [`AbiBundle::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BundleError {
+    /// A contract has an empty name.
+    EmptyName,
+    /// The ABI of a contract has a schema version other than the one of the bundle.
+    SchemaVersionMismatch {
+        contract: String,
+        schema_version: String,
+    },
+}
+
+impl std::error::Error for BundleError {}
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EmptyName => write!(f, "contract names can not be empty"),
+            Self::SchemaVersionMismatch {
+                contract,
+                schema_version,
+            } => write!(
+                f,
+                "the ABI of `{}` has schema version {}, which differs from the one of the bundle",
+                contract, schema_version
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bundle() {
+        let abi = |name: &str| {
+            json!({
+                "schema_version": SCHEMA_VERSION,
+                "metadata": { "name": name },
+                "body": { "functions": [{ "name": "new", "kind": "call", "modifiers": ["init"] }], "root_schema": {} }
+            })
+        };
+        let value = json!({
+            "schema_version": SCHEMA_VERSION,
+            "contracts": { "factory": abi("factory"), "child": abi("child") }
+        });
+        let mut bundle = AbiBundle::from_value(value.clone()).unwrap();
+        assert_eq!(bundle.to_value(), value);
+        assert_eq!(bundle.validate(), Ok(()));
+        assert_eq!(
+            bundle.get("child").unwrap().metadata.name.as_deref(),
+            Some("child")
+        );
+        assert_eq!(
+            bundle.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            ["child", "factory"]
+        );
+
+        let mut child = bundle.get("child").unwrap().clone();
+        child.schema_version = "0.4.1".to_string();
+        bundle.insert("child", child);
+        assert_eq!(
+            bundle.validate(),
+            Err(BundleError::SchemaVersionMismatch {
+                contract: "child".to_string(),
+                schema_version: "0.4.1".to_string()
+            })
+        );
+
+        assert!(
+            AbiBundle::from_value(json!({ "schema_version": "0.1.0", "contracts": {} }))
+                .unwrap_err()
+                .to_string()
+                .contains("re-generating")
+        );
+    }
+}
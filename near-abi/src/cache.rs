@@ -0,0 +1,167 @@
+//! Caching of parsed ABIs for long-running services.
+//!
+//! RPC gateways and indexers decode calls of many contracts, most of them over and over again.
+//! [`AbiCache`] keeps the most recently used ABIs parsed, keyed by the hash of the contract code
+//! they describe, and hands them out as [`Arc`]s so that lookups never copy an ABI.
+
+use crate::wasm_hash::WasmHash;
+use crate::AbiRoot;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+/// Bounded cache of parsed ABIs, evicting the least recently used ABI when full.
+///
+/// The cache can be shared between threads, parsing happens outside of its lock.
+#[derive(Debug)]
+pub struct AbiCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<WasmHash, (Arc<AbiRoot>, u64)>,
+    /// Code hashes of the entries by the time they were last used.
+    recency: BTreeMap<u64, WasmHash>,
+    clock: u64,
+}
+
+impl CacheState {
+    fn touch(&mut self, hash: &WasmHash) -> Option<Arc<AbiRoot>> {
+        self.clock += 1;
+        let clock = self.clock;
+        let (abi, used) = self.entries.get_mut(hash)?;
+        self.recency.remove(used);
+        *used = clock;
+        self.recency.insert(clock, *hash);
+        Some(abi.clone())
+    }
+}
+
+impl AbiCache {
+    /// Creates a cache holding at most `capacity` ABIs.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The ABI of the contract code with hash `code_hash`, if cached.
+    pub fn get(&self, code_hash: &WasmHash) -> Option<Arc<AbiRoot>> {
+        self.lock().touch(code_hash)
+    }
+
+    /// Caches the ABI of the contract code with hash `code_hash`, replacing the ABI cached for
+    /// that code, and evicting the least recently used ABI if the cache is full.
+    pub fn insert(&self, code_hash: WasmHash, abi: AbiRoot) -> Arc<AbiRoot> {
+        let abi = Arc::new(abi);
+        if self.capacity == 0 {
+            return abi;
+        }
+        let mut state = self.lock();
+        state.clock += 1;
+        let clock = state.clock;
+        if let Some((_, used)) = state.entries.insert(code_hash, (abi.clone(), clock)) {
+            state.recency.remove(&used);
+        }
+        state.recency.insert(clock, code_hash);
+        while state.entries.len() > self.capacity {
+            let (_, evicted) = state
+                .recency
+                .pop_first()
+                .expect("every entry has a recency");
+            state.entries.remove(&evicted);
+        }
+        abi
+    }
+
+    /// The ABI of the contract code with hash `code_hash`, parsed with `parse` and cached if it
+    /// is not cached yet. Parsing errors are returned as is and not cached.
+    pub fn get_or_try_insert_with<E>(
+        &self,
+        code_hash: WasmHash,
+        parse: impl FnOnce() -> Result<AbiRoot, E>,
+    ) -> Result<Arc<AbiRoot>, E> {
+        if let Some(abi) = self.get(&code_hash) {
+            return Ok(abi);
+        }
+        Ok(self.insert(code_hash, parse()?))
+    }
+
+    /// Removes the ABI of the contract code with hash `code_hash` from the cache.
+    pub fn remove(&self, code_hash: &WasmHash) -> Option<Arc<AbiRoot>> {
+        let mut state = self.lock();
+        let (abi, used) = state.entries.remove(code_hash)?;
+        state.recency.remove(&used);
+        Some(abi)
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.entries.clear();
+        state.recency.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        // The state is consistent between statements, a panic of another user does not taint it.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    fn abi(name: &str) -> AbiRoot {
+        AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": { "name": name },
+            "body": { "functions": [], "root_schema": {} }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = AbiCache::new(2);
+        let [a, b, c] = [b"a", b"b", b"c"].map(|code| WasmHash::of(code));
+        cache.insert(a, abi("a"));
+        cache.insert(b, abi("b"));
+        // `a` becomes the most recently used ABI, `b` is evicted.
+        assert_eq!(cache.get(&a).unwrap().metadata.name.as_deref(), Some("a"));
+        cache.insert(c, abi("c"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&b).is_none());
+
+        let parsed = cache
+            .get_or_try_insert_with(c, || Err::<AbiRoot, ()>(()))
+            .unwrap();
+        assert!(Arc::ptr_eq(&parsed, &cache.get(&c).unwrap()));
+        assert!(cache
+            .get_or_try_insert_with(b, || AbiRoot::from_value(json!({})))
+            .is_err());
+        assert!(cache.get(&b).is_none());
+
+        assert!(cache.remove(&a).is_some());
+        cache.clear();
+        assert!(cache.is_empty());
+        AbiCache::new(0).insert(a, abi("a"));
+    }
+}
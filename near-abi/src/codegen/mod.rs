@@ -0,0 +1,197 @@
+//! Generation of typed contract clients from ABIs.
+//!
+//! Generators emit source code, to be written to a file of the client crate or package, with a
+//! method per ABI function and a type per root schema definition: [`rust`] clients are built on
+//! `near-workspaces` and [`typescript`] ones on `near-api-js`.
+
+pub mod rust;
+pub mod typescript;
+
+use crate::{AbiFunction, AbiFunctionModifier, AbiRoot};
+use std::fmt;
+
+/// Error returned when a client can not be generated from an ABI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CodegenError {
+    /// A Borsh schema of a function has no definition for a type it declares.
+    MissingBorshDefinition {
+        function: String,
+        declaration: String,
+    },
+    /// A Borsh schema of a function declares a type without Rust counterpart, e.g. an enum
+    /// with explicit discriminants and fields.
+    UnsupportedBorshType {
+        function: String,
+        declaration: String,
+    },
+}
+
+impl std::error::Error for CodegenError {}
+impl fmt::Display for CodegenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingBorshDefinition {
+                function,
+                declaration,
+            } => write!(
+                f,
+                "the Borsh schema of `{}` has no definition for `{}`",
+                function, declaration
+            ),
+            Self::UnsupportedBorshType {
+                function,
+                declaration,
+            } => write!(
+                f,
+                "the Borsh schema of `{}` declares `{}`, which has no Rust counterpart",
+                function, declaration
+            ),
+        }
+    }
+}
+
+/// Name of the type generated for the type named `name` in the ABI: its alphanumeric parts in
+/// PascalCase, e.g. `FungibleTokenMetadata` for `fungible_token_metadata` and `WrapperU64` for
+/// `Wrapper<u64>`.
+fn type_name(name: &str) -> String {
+    let name: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    match name.chars().next() {
+        Some(first) if first.is_ascii_alphabetic() => name,
+        _ => format!("T{}", name),
+    }
+}
+
+/// Name of the contract of `abi`, as written in the header of generated clients.
+fn contract_name(abi: &AbiRoot) -> &str {
+    abi.metadata.name.as_deref().unwrap_or("contract")
+}
+
+/// Name of the generated client class or struct, e.g. `FungibleTokenClient`.
+fn client_name(abi: &AbiRoot) -> String {
+    format!("{}Client", type_name(contract_name(abi)))
+}
+
+/// Functions with a client method: every function but private ones, which are only called by
+/// the contract itself.
+fn client_functions(abi: &AbiRoot) -> Vec<&AbiFunction> {
+    abi.body
+        .functions
+        .iter()
+        .filter(|function| !function.modifiers.contains(&AbiFunctionModifier::Private))
+        .collect()
+}
+
+/// ABI exercising the code generators: JSON and Borsh functions of every kind, and definitions
+/// of structs, enums and recursive types.
+#[cfg(test)]
+fn example_abi() -> AbiRoot {
+    use crate::{AbiBorshParameter, AbiFunctionKind, AbiParameters, AbiType, SCHEMA_VERSION};
+    use borsh::BorshSchema;
+    use serde_json::{json, Value};
+
+    #[derive(BorshSchema)]
+    #[allow(dead_code)]
+    struct Record {
+        id: u64,
+        tags: Vec<String>,
+        hash: [u8; 4],
+        next: Option<Box<Record>>,
+    }
+
+    let json = |schema: Value| json!({ "serialization_type": "json", "type_schema": schema });
+    let account_ref = json!({ "$ref": "#/definitions/Account" });
+    let mut abi = AbiRoot::from_value(json!({
+        "schema_version": SCHEMA_VERSION,
+        "metadata": { "name": "registry" },
+        "body": {
+            "functions": [
+                {
+                    "name": "get_account",
+                    "kind": "view",
+                    "doc": " Looks up an account.",
+                    "params": { "serialization_type": "json", "args": [
+                        { "name": "account_id", "type_schema": { "$ref": "#/definitions/AccountId" } }
+                    ] },
+                    "result": json(json!({ "anyOf": [account_ref, { "type": "null" }] }))
+                },
+                {
+                    "name": "apply",
+                    "kind": "call",
+                    "params": { "serialization_type": "json", "args": [
+                        { "name": "action", "type_schema": { "$ref": "#/definitions/Action" } },
+                        { "name": "signer", "type_schema": { "type": "string" } }
+                    ] }
+                },
+                {
+                    "name": "deposit",
+                    "kind": "call",
+                    "modifiers": ["payable"],
+                    "result": json(json!({ "type": "integer", "format": "uint64", "minimum": 0 }))
+                },
+                { "name": "on_applied", "kind": "call", "modifiers": ["private"] }
+            ],
+            "root_schema": { "definitions": {
+                "AccountId": { "type": "string" },
+                "Account": {
+                    "description": "A registered account.",
+                    "type": "object",
+                    "required": ["balance", "type"],
+                    "properties": {
+                        "balance": { "type": "string" },
+                        "type": { "$ref": "#/definitions/Kind" },
+                        "memoText": { "type": ["string", "null"] },
+                        "parent": { "$ref": "#/definitions/Account" }
+                    }
+                },
+                "Kind": { "type": "string", "enum": ["personal", "business"] },
+                "Action": { "oneOf": [
+                    { "type": "string", "enum": ["Noop"] },
+                    {
+                        "type": "object",
+                        "required": ["Transfer"],
+                        "properties": { "Transfer": {
+                            "type": "object",
+                            "required": ["amount"],
+                            "properties": { "amount": { "type": "string" } }
+                        } },
+                        "additionalProperties": false
+                    },
+                    {
+                        "type": "object",
+                        "required": ["Tags"],
+                        "properties": { "Tags": {
+                            "type": "object",
+                            "additionalProperties": { "type": "integer", "format": "uint32" }
+                        } },
+                        "additionalProperties": false
+                    }
+                ] }
+            } }
+        }
+    }))
+    .unwrap();
+    abi.body.functions.push(AbiFunction {
+        params: AbiParameters::Borsh {
+            args: vec![AbiBorshParameter::new(
+                "record",
+                borsh::schema_container_of::<Record>(),
+            )],
+        },
+        result: Some(AbiType::Borsh {
+            type_schema: borsh::schema_container_of::<Option<Record>>(),
+        }),
+        ..AbiFunction::new("store", AbiFunctionKind::Call)
+    });
+
+    abi
+}
@@ -0,0 +1,1056 @@
+//! Rust clients, for tests and scripts built on `near-workspaces`.
+
+use super::{client_functions, client_name, contract_name, type_name, CodegenError};
+use crate::analysis::json_cycles;
+use crate::transform::definition_name;
+use crate::{AbiFunction, AbiFunctionKind, AbiFunctionModifier, AbiParameters, AbiRoot, AbiType};
+use borsh::schema::{BorshSchemaContainer, Definition, Fields};
+use schemars::schema::{InstanceType, ObjectValidation, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Rust keywords, which are escaped as raw identifiers.
+const KEYWORDS: &[&str] = &[
+    "abstract",
+    "as",
+    "async",
+    "await",
+    "become",
+    "box",
+    "break",
+    "const",
+    "continue",
+    "do",
+    "dyn",
+    "else",
+    "enum",
+    "extern",
+    "false",
+    "final",
+    "fn",
+    "for",
+    "gen",
+    "if",
+    "impl",
+    "in",
+    "let",
+    "loop",
+    "macro",
+    "match",
+    "mod",
+    "move",
+    "mut",
+    "override",
+    "priv",
+    "pub",
+    "ref",
+    "return",
+    "static",
+    "struct",
+    "trait",
+    "true",
+    "try",
+    "type",
+    "typeof",
+    "unsafe",
+    "unsized",
+    "use",
+    "virtual",
+    "where",
+    "while",
+    "yield",
+    "union",
+    "macro_rules",
+];
+
+/// Prelude types used by the generated types, which definitions must not shadow.
+const PRELUDE_TYPES: [&str; 5] = ["Box", "Option", "Result", "String", "Vec"];
+
+const JSON_DERIVES: &str =
+    "#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]\n";
+const BORSH_DERIVES: &str =
+    "#[derive(Clone, Debug, PartialEq, borsh::BorshSerialize, borsh::BorshDeserialize)]\n";
+
+/// Builders of the transactions of call methods, emitted when the client has any.
+const CALL_BUILDER: &str = r#"/// A function call transaction, whose result decodes into `T`.
+pub struct Call<T> {
+    transaction: near_workspaces::operations::CallTransaction,
+    decode: fn(near_workspaces::result::ExecutionSuccess) -> near_workspaces::Result<T>,
+}
+
+impl<T> Call<T> {
+    fn new(
+        transaction: near_workspaces::operations::CallTransaction,
+        decode: fn(near_workspaces::result::ExecutionSuccess) -> near_workspaces::Result<T>,
+    ) -> Self {
+        Self { transaction, decode }
+    }
+
+    /// Sets the gas attached to the call.
+    pub fn gas(mut self, gas: near_workspaces::types::Gas) -> Self {
+        self.transaction = self.transaction.gas(gas);
+        self
+    }
+
+    /// Sends the transaction, failing if its execution fails, and decodes the result.
+    pub async fn transact(self) -> near_workspaces::Result<T> {
+        let outcome = self.transaction.transact().await?.into_result()?;
+        (self.decode)(outcome)
+    }
+
+    /// The transaction, to send it without decoding its result.
+    pub fn into_transaction(self) -> near_workspaces::operations::CallTransaction {
+        self.transaction
+    }
+}
+"#;
+
+const PAYABLE_CALL_BUILDER: &str = r#"/// A call transaction of a payable function, which can attach a deposit.
+pub struct PayableCall<T>(Call<T>);
+
+impl<T> PayableCall<T> {
+    /// Sets the deposit attached to the call.
+    pub fn deposit(mut self, deposit: near_workspaces::types::NearToken) -> Self {
+        self.0.transaction = self.0.transaction.deposit(deposit);
+        self
+    }
+
+    /// Sets the gas attached to the call.
+    pub fn gas(self, gas: near_workspaces::types::Gas) -> Self {
+        Self(self.0.gas(gas))
+    }
+
+    /// Sends the transaction, failing if its execution fails, and decodes the result.
+    pub async fn transact(self) -> near_workspaces::Result<T> {
+        self.0.transact().await
+    }
+
+    /// The transaction, to send it without decoding its result.
+    pub fn into_transaction(self) -> near_workspaces::operations::CallTransaction {
+        self.0.into_transaction()
+    }
+}
+"#;
+
+/// Generates the source of a Rust client of the contract described by `abi`, built on
+/// `near-workspaces`.
+///
+/// The generated source consists of:
+///
+/// * a `types` module, with a type per root schema definition deriving `serde` traits, and a
+///   type per struct or enum of the Borsh schemas deriving `borsh` traits;
+/// * a `{Name}Client` wrapping a `near_workspaces::Contract`, named after the contract, with a
+///   method per function taking its parameters as typed arguments. View methods query the
+///   contract and return the decoded result. Call methods take the signing account and return a
+///   `Call` builder, or a `PayableCall` for payable functions, to set the attached gas and
+///   deposit before sending the transaction with `transact`.
+///
+/// Private functions, only called by the contract itself, are left out of the client. JSON
+/// schemas without a Rust counterpart, e.g. untagged unions, are represented by
+/// `serde_json::Value`. Names that would clash in Rust are told apart by a numeric suffix, e.g.
+/// the methods of `getX` and `get_x`, or different Borsh types declared as `Record` by
+/// different functions.
+///
+/// The generated code depends on the `near-workspaces`, `serde` and `serde_json` crates, and on
+/// `borsh` for Borsh functions.
+pub fn generate(abi: &AbiRoot) -> Result<String, CodegenError> {
+    let mut generator = Generator::new(abi);
+    let contract = contract_name(abi);
+    let functions = client_functions(abi);
+    let mut methods = Vec::new();
+    for function in &functions {
+        methods.push(generator.method(function)?);
+    }
+
+    let mut source = format!(
+        "// Client of the `{}` contract, generated from its ABI by near-abi.\n\n",
+        contract
+    );
+    source.push_str("/// Types of the parameters and results of the contract functions.\n");
+    source.push_str("pub mod types {\n");
+    let items: Vec<String> = abi
+        .body
+        .root_schema
+        .definitions
+        .iter()
+        .map(|(name, schema)| generator.json_item(name, schema))
+        .chain(generator.borsh_items.values().cloned())
+        .collect();
+    source.push_str(&indent(&items.join("\n")));
+    source.push_str("}\n");
+
+    let calls = functions.iter().filter(|f| f.kind == AbiFunctionKind::Call);
+    if calls.clone().next().is_some() {
+        source.push('\n');
+        source.push_str(CALL_BUILDER);
+    }
+    if calls
+        .clone()
+        .any(|f| f.modifiers.contains(&AbiFunctionModifier::Payable))
+    {
+        source.push('\n');
+        source.push_str(PAYABLE_CALL_BUILDER);
+    }
+
+    let client = client_name(abi);
+    source.push_str(&format!("\n/// Client of the `{}` contract.\n", contract));
+    source.push_str(&format!("pub struct {} {{\n", client));
+    source.push_str("    pub contract: near_workspaces::Contract,\n}\n\n");
+    source.push_str(&format!(
+        "impl From<near_workspaces::Contract> for {} {{\n",
+        client
+    ));
+    source.push_str("    fn from(contract: near_workspaces::Contract) -> Self {\n");
+    source.push_str("        Self { contract }\n    }\n}\n\n");
+    source.push_str(&format!("impl {} {{\n", client));
+    source.push_str(&indent(&methods.join("\n")));
+    source.push_str("}\n");
+    Ok(source)
+}
+
+struct Generator {
+    /// Names of the types generated for root schema definitions.
+    names: BTreeMap<String, String>,
+    /// Groups of mutually recursive root schema definitions.
+    cycles: Vec<Vec<String>>,
+    /// Items generated for the structs and enums of Borsh schemas, by type name.
+    borsh_items: BTreeMap<String, String>,
+    /// Names of the items generated for Borsh declarations, along with the definitions they
+    /// depend on, as different containers may declare different types under the same name.
+    borsh_names: Vec<(BorshType, String)>,
+    /// Identifiers of the generated methods.
+    methods: BTreeSet<String>,
+}
+
+/// A Borsh declaration with the definitions reachable from it.
+type BorshType = (String, BTreeMap<String, Definition>);
+
+impl Generator {
+    fn new(abi: &AbiRoot) -> Self {
+        let root_schema = &abi.body.root_schema;
+        let mut used = BTreeSet::new();
+        let mut names = BTreeMap::new();
+        for name in root_schema.definitions.keys() {
+            names.insert(name.clone(), unique_ident(&mut used, name));
+        }
+        Self {
+            names,
+            cycles: json_cycles(root_schema),
+            borsh_items: BTreeMap::new(),
+            borsh_names: Vec::new(),
+            methods: BTreeSet::new(),
+        }
+    }
+
+    fn method(&mut self, function: &AbiFunction) -> Result<String, CodegenError> {
+        let name = function.name.as_str();
+        let is_view = function.kind == AbiFunctionKind::View;
+        let mut params = Vec::new();
+        let args = match &function.params {
+            AbiParameters::Json { args } if args.is_empty() => None,
+            AbiParameters::Json { args } => {
+                let mut fields = Vec::new();
+                for arg in args {
+                    let ident = param_ident(&arg.name, is_view);
+                    fields.push(format!("{:?}: {}", arg.name, ident));
+                    params.push((ident, self.json_type(&arg.type_schema, "types::")));
+                }
+                Some(format!(
+                    ".args_json(serde_json::json!({{ {} }}))",
+                    fields.join(", ")
+                ))
+            }
+            AbiParameters::Borsh { args } if args.is_empty() => None,
+            AbiParameters::Borsh { args } => {
+                let mut fields = Vec::new();
+                for arg in args {
+                    let ident = param_ident(&arg.name, is_view);
+                    let declaration = arg.type_schema.declaration();
+                    let ty = self.borsh_type(name, &arg.type_schema, declaration, "types::")?;
+                    fields.push(ident.clone());
+                    params.push((ident, ty));
+                }
+                Some(format!(".args_borsh({})", tuple(&fields)))
+            }
+        };
+        let result = match &function.result {
+            None => None,
+            Some(AbiType::Json { type_schema }) => {
+                Some((self.json_type(type_schema, "types::"), "json"))
+            }
+            Some(AbiType::Borsh { type_schema }) => {
+                let declaration = type_schema.declaration();
+                let ty = self.borsh_type(name, type_schema, declaration, "types::")?;
+                Some((ty, "borsh"))
+            }
+        };
+
+        let mut source = String::new();
+        doc_comment(&mut source, function.doc.as_deref());
+        let mut signature = vec![String::from("&self")];
+        if !is_view {
+            signature.push(String::from("signer: &near_workspaces::Account"));
+        }
+        signature.extend(
+            params
+                .iter()
+                .map(|(ident, ty)| format!("{}: {}", ident, ty)),
+        );
+        let signature = signature.join(", ");
+        let result_type = result.as_ref().map_or("()", |(ty, _)| ty.as_str());
+        let mut ident = field_ident(name);
+        if self.methods.contains(&ident) {
+            ident = (2..)
+                .map(|i| format!("{}_{}", ident, i))
+                .find(|ident| !self.methods.contains(ident))
+                .unwrap();
+        }
+        self.methods.insert(ident.clone());
+
+        if is_view {
+            source.push_str(&format!(
+                "pub async fn {}({}) -> near_workspaces::Result<{}> {{\n",
+                ident, signature, result_type
+            ));
+            source.push_str(&format!("    self.contract\n        .view({:?})\n", name));
+            if let Some(args) = &args {
+                source.push_str(&format!("        {}\n", args));
+            }
+            match &result {
+                Some((_, format)) => {
+                    source.push_str(&format!("        .await?\n        .{}()\n", format))
+                }
+                None => source.push_str("        .await?;\n    Ok(())\n"),
+            }
+            source.push_str("}\n");
+            return Ok(source);
+        }
+
+        let (builder, open, close) = if function.modifiers.contains(&AbiFunctionModifier::Payable) {
+            ("PayableCall", "PayableCall(Call::new(", "))")
+        } else {
+            ("Call", "Call::new(", ")")
+        };
+        source.push_str(&format!(
+            "pub fn {}({}) -> {}<{}> {{\n",
+            ident, signature, builder, result_type
+        ));
+        source.push_str(&format!(
+            "    {}\n        signer\n            .call(self.contract.id(), {:?})",
+            open, name
+        ));
+        if let Some(args) = &args {
+            source.push_str(&format!("\n            {}", args));
+        }
+        let decode = match &result {
+            Some((_, format)) => format!("|outcome| outcome.{}()", format),
+            None => String::from("|_| Ok(())"),
+        };
+        source.push_str(&format!(",\n        {},\n    {}\n}}\n", decode, close));
+        Ok(source)
+    }
+
+    /// Rust type of the values of the JSON `schema`, referencing the types generated for
+    /// definitions through `prefix`.
+    fn json_type(&self, schema: &Schema, prefix: &str) -> String {
+        let object = match schema {
+            Schema::Object(object) => object,
+            Schema::Bool(_) => return String::from("serde_json::Value"),
+        };
+        if let Some(inner) = nullable_inner(object) {
+            return format!("Option<{}>", self.json_type(&inner, prefix));
+        }
+        if let Some(wrapped) = single_all_of(object) {
+            return self.json_type(wrapped, prefix);
+        }
+        if let Some(reference) = &object.reference {
+            return match definition_name(reference).and_then(|name| self.names.get(name)) {
+                Some(name) => format!("{}{}", prefix, name),
+                None => String::from("serde_json::Value"),
+            };
+        }
+        let instance_type = match &object.instance_type {
+            Some(SingleOrVec::Single(instance_type)) => **instance_type,
+            _ => return String::from("serde_json::Value"),
+        };
+        match instance_type {
+            InstanceType::String => String::from("String"),
+            InstanceType::Integer => integer_type(object),
+            InstanceType::Number => String::from("f64"),
+            InstanceType::Boolean => String::from("bool"),
+            InstanceType::Null => String::from("()"),
+            InstanceType::Array => match object.array.as_ref().and_then(|a| a.items.as_ref()) {
+                Some(SingleOrVec::Single(items)) => {
+                    format!("Vec<{}>", self.json_type(items, prefix))
+                }
+                Some(SingleOrVec::Vec(items)) => tuple(
+                    &items
+                        .iter()
+                        .map(|item| self.json_type(item, prefix))
+                        .collect::<Vec<_>>(),
+                ),
+                None => String::from("Vec<serde_json::Value>"),
+            },
+            InstanceType::Object => match object.object.as_deref() {
+                Some(ObjectValidation {
+                    properties,
+                    additional_properties: Some(values),
+                    ..
+                }) if properties.is_empty() && **values != Schema::Bool(false) => format!(
+                    "std::collections::HashMap<String, {}>",
+                    self.json_type(values, prefix)
+                ),
+                _ => String::from("serde_json::Value"),
+            },
+        }
+    }
+
+    /// Item generated for the root schema definition `name`: a struct for objects with
+    /// properties, an enum for string enums and externally tagged unions, and a type alias
+    /// otherwise.
+    fn json_item(&self, name: &str, schema: &Schema) -> String {
+        let type_name = &self.names[name];
+        let mut source = String::new();
+        doc_comment(&mut source, description(schema));
+        let object = match schema {
+            Schema::Object(object) => object,
+            Schema::Bool(_) => {
+                source.push_str(&format!("pub type {} = serde_json::Value;\n", type_name));
+                return source;
+            }
+        };
+        if let Some(validation) = struct_properties(object) {
+            source.push_str(JSON_DERIVES);
+            source.push_str(&format!("pub struct {} {{\n", type_name));
+            source.push_str(&indent(&self.json_fields(name, validation, "pub ")));
+            source.push_str("}\n");
+        } else if let Some(variants) = self.json_variants(name, object) {
+            source.push_str(JSON_DERIVES);
+            source.push_str(&format!("pub enum {} {{\n", type_name));
+            source.push_str(&indent(&variants));
+            source.push_str("}\n");
+        } else {
+            let recursive = self
+                .cycles
+                .iter()
+                .any(|cycle| cycle.iter().any(|n| n == name));
+            let aliased = if recursive {
+                String::from("serde_json::Value")
+            } else {
+                self.json_type(schema, "")
+            };
+            source.push_str(&format!("pub type {} = {};\n", type_name, aliased));
+        }
+        source
+    }
+
+    /// Fields of a struct, or struct variant, of the definition `owner` with the properties of
+    /// `validation`. Optional properties are `Option`s, omitted when `None`.
+    fn json_fields(&self, owner: &str, validation: &ObjectValidation, vis: &str) -> String {
+        let mut source = String::new();
+        for (name, schema) in &validation.properties {
+            doc_comment(&mut source, description(schema));
+            let ident = field_ident(name);
+            let (schema, nullable) = match schema {
+                Schema::Object(object) => match nullable_inner(object) {
+                    Some(inner) => (inner, true),
+                    None => (schema.clone(), false),
+                },
+                Schema::Bool(_) => (schema.clone(), false),
+            };
+            let mut ty = self.json_type(&schema, "");
+            if self.is_recursive(owner, &schema) {
+                ty = format!("Box<{}>", ty);
+            }
+            let required = validation.required.contains(name);
+            if nullable || !required {
+                ty = format!("Option<{}>", ty);
+            }
+            let mut attributes = Vec::new();
+            if ident.trim_start_matches("r#") != name {
+                attributes.push(format!("rename = {:?}", name));
+            }
+            if !required {
+                attributes.push(String::from(
+                    "default, skip_serializing_if = \"Option::is_none\"",
+                ));
+            }
+            if !attributes.is_empty() {
+                source.push_str(&format!("#[serde({})]\n", attributes.join(", ")));
+            }
+            source.push_str(&format!("{}{}: {},\n", vis, ident, ty));
+        }
+        source
+    }
+
+    /// Variants of the enum of the definition `owner`, if `object` is an enum of strings or a
+    /// `oneOf` union of externally tagged variants, as generated by `schemars` for Rust enums.
+    fn json_variants(&self, owner: &str, object: &SchemaObject) -> Option<String> {
+        let mut used = BTreeSet::new();
+        let mut source = String::new();
+        if let Some(values) = &object.enum_values {
+            unit_variants(&mut source, &mut used, values)?;
+            return Some(source);
+        }
+        let variants = object.subschemas.as_ref()?.one_of.as_ref()?;
+        let mut tagged = Vec::new();
+        for variant in variants {
+            let variant = match variant {
+                Schema::Object(variant) => variant,
+                Schema::Bool(_) => return None,
+            };
+            if let Some(values) = &variant.enum_values {
+                unit_variants(&mut source, &mut used, values)?;
+                continue;
+            }
+            let validation = variant.object.as_deref()?;
+            if validation.properties.len() != 1 || validation.required.len() != 1 {
+                return None;
+            }
+            tagged.push((variant, validation.properties.iter().next()?));
+        }
+        for (variant, (tag, payload)) in tagged {
+            let doc = variant
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.description.as_deref())
+                .or_else(|| description(payload));
+            variant_header(&mut source, &mut used, tag, doc);
+            match payload {
+                Schema::Object(payload) if struct_properties(payload).is_some() => {
+                    let validation = struct_properties(payload)?;
+                    source.push_str(" {\n");
+                    source.push_str(&indent(&self.json_fields(owner, validation, "")));
+                    source.push_str("},\n");
+                }
+                _ => {
+                    let mut ty = self.json_type(payload, "");
+                    if self.is_recursive(owner, payload) {
+                        ty = format!("Box<{}>", ty);
+                    }
+                    source.push_str(&format!("({}),\n", ty));
+                }
+            }
+        }
+        Some(source)
+    }
+
+    /// Whether `schema` references a definition that is mutually recursive with `owner`, so
+    /// that it must be boxed.
+    fn is_recursive(&self, owner: &str, schema: &Schema) -> bool {
+        let target = match schema {
+            Schema::Object(object) => match single_all_of(object) {
+                Some(wrapped) => return self.is_recursive(owner, wrapped),
+                None => object.reference.as_deref(),
+            },
+            Schema::Bool(_) => None,
+        };
+        let target = match target.and_then(definition_name) {
+            Some(target) => target,
+            None => return false,
+        };
+        self.cycles.iter().any(|cycle| {
+            cycle.iter().any(|name| name == owner) && cycle.iter().any(|name| name == target)
+        })
+    }
+
+    /// Rust type of `declaration` in the Borsh schema `container` of `function`, generating
+    /// items for its structs and enums. Generated types are referenced through `prefix`.
+    fn borsh_type(
+        &mut self,
+        function: &str,
+        container: &BorshSchemaContainer,
+        declaration: &str,
+        prefix: &str,
+    ) -> Result<String, CodegenError> {
+        let unsupported = || CodegenError::UnsupportedBorshType {
+            function: function.to_string(),
+            declaration: declaration.to_string(),
+        };
+        let definition = match container.get_definition(declaration) {
+            Some(definition) => definition,
+            None if is_borsh_primitive(declaration) => return Ok(declaration.to_string()),
+            None => {
+                return Err(CodegenError::MissingBorshDefinition {
+                    function: function.to_string(),
+                    declaration: declaration.to_string(),
+                })
+            }
+        };
+        Ok(match definition {
+            Definition::Primitive(_) if is_borsh_primitive(declaration) => declaration.to_string(),
+            Definition::Primitive(_) => return Err(unsupported()),
+            Definition::Sequence { elements, .. }
+                if declaration == "String" && elements == "u8" =>
+            {
+                String::from("String")
+            }
+            Definition::Sequence {
+                length_width,
+                length_range,
+                elements,
+            } => {
+                let element = self.borsh_type(function, container, elements, prefix)?;
+                if *length_width == Definition::ARRAY_LENGTH_WIDTH
+                    && length_range.start() == length_range.end()
+                {
+                    format!("[{}; {}]", element, length_range.start())
+                } else {
+                    format!("Vec<{}>", element)
+                }
+            }
+            Definition::Tuple { elements } => {
+                let mut types = Vec::new();
+                for element in elements {
+                    types.push(self.borsh_type(function, container, element, prefix)?);
+                }
+                tuple(&types)
+            }
+            Definition::Enum { variants, .. } if declaration.starts_with("Option<") => {
+                match variants.as_slice() {
+                    [(0, none, _), (1, some, inner)] if none == "None" && some == "Some" => {
+                        let inner = self.borsh_type(function, container, inner, prefix)?;
+                        format!("Option<{}>", inner)
+                    }
+                    _ => return Err(unsupported()),
+                }
+            }
+            Definition::Enum { variants, .. } if declaration.starts_with("Result<") => {
+                let variant = |name: &str| variants.iter().find(|(_, n, _)| n == name);
+                match (variant("Ok"), variant("Err")) {
+                    (Some((_, _, ok)), Some((_, _, err))) => {
+                        let ok = self.borsh_type(function, container, ok, prefix)?;
+                        let err = self.borsh_type(function, container, err, prefix)?;
+                        format!("Result<{}, {}>", ok, err)
+                    }
+                    _ => return Err(unsupported()),
+                }
+            }
+            Definition::Struct { .. } | Definition::Enum { .. } => {
+                let name = self.borsh_item(function, container, declaration, definition)?;
+                format!("{}{}", prefix, name)
+            }
+        })
+    }
+
+    /// Generates the item of the struct or enum `declaration`, unless already generated, and
+    /// returns its name.
+    fn borsh_item(
+        &mut self,
+        function: &str,
+        container: &BorshSchemaContainer,
+        declaration: &str,
+        definition: &Definition,
+    ) -> Result<String, CodegenError> {
+        let ty = (
+            declaration.to_string(),
+            borsh_closure(container, declaration),
+        );
+        if let Some((_, name)) = self.borsh_names.iter().find(|(known, _)| *known == ty) {
+            return Ok(name.clone());
+        }
+        let json_names: BTreeSet<String> = self.names.values().cloned().collect();
+        let mut name = unique_type_name(&BTreeSet::new(), declaration);
+        if json_names.contains(&name) {
+            name.push_str("Borsh");
+        }
+        if self.borsh_items.contains_key(&name) {
+            name = (2..)
+                .map(|i| format!("{}{}", name, i))
+                .find(|name| !self.borsh_items.contains_key(name) && !json_names.contains(name))
+                .unwrap();
+        }
+        // Registered ahead of the fields, which may reference the type itself.
+        self.borsh_names.push((ty, name.clone()));
+        self.borsh_items.insert(name.clone(), String::new());
+
+        let mut source = String::from(BORSH_DERIVES);
+        match definition {
+            Definition::Struct { fields } => {
+                let fields = self.borsh_fields(function, container, &name, fields, "pub ")?;
+                source.push_str(&format!("pub struct {}{}", name, fields));
+                if !fields.ends_with('}') {
+                    source.push(';');
+                }
+                source.push('\n');
+            }
+            Definition::Enum { variants, .. } => {
+                let sequential = variants
+                    .iter()
+                    .enumerate()
+                    .all(|(i, (discriminant, _, _))| *discriminant == i as i64);
+                if !sequential {
+                    source.push_str("#[borsh(use_discriminant = true)]\n");
+                }
+                source.push_str(&format!("pub enum {} {{\n", name));
+                let mut used = BTreeSet::new();
+                for (discriminant, variant, payload) in variants {
+                    let fields = match container.get_definition(payload) {
+                        Some(Definition::Struct { fields }) => {
+                            self.borsh_fields(function, container, &name, fields, "")?
+                        }
+                        _ => {
+                            return Err(CodegenError::UnsupportedBorshType {
+                                function: function.to_string(),
+                                declaration: declaration.to_string(),
+                            })
+                        }
+                    };
+                    if !sequential && !fields.is_empty() {
+                        return Err(CodegenError::UnsupportedBorshType {
+                            function: function.to_string(),
+                            declaration: declaration.to_string(),
+                        });
+                    }
+                    let ident = unique_ident(&mut used, variant);
+                    let discriminant = if sequential {
+                        String::new()
+                    } else {
+                        format!(" = {}", discriminant)
+                    };
+                    source.push_str(&indent(&format!("{}{}{},\n", ident, fields, discriminant)));
+                }
+                source.push_str("}\n");
+            }
+            _ => unreachable!("only structs and enums are generated as items"),
+        }
+        self.borsh_items.insert(name.clone(), source);
+        Ok(name)
+    }
+
+    /// Fields of a struct, or enum variant, named `owner`: `{ .. }` for named fields, `(..)`
+    /// for unnamed ones and nothing for unit structs.
+    fn borsh_fields(
+        &mut self,
+        function: &str,
+        container: &BorshSchemaContainer,
+        owner: &str,
+        fields: &Fields,
+        vis: &str,
+    ) -> Result<String, CodegenError> {
+        let boxed = |ty: String| {
+            if ty == owner {
+                format!("Box<{}>", ty)
+            } else if ty == format!("Option<{}>", owner) {
+                format!("Option<Box<{}>>", owner)
+            } else {
+                ty
+            }
+        };
+        Ok(match fields {
+            Fields::NamedFields(fields) => {
+                let mut source = String::from(" {\n");
+                for (name, declaration) in fields {
+                    let ty = self.borsh_type(function, container, declaration, "")?;
+                    source.push_str(&format!(
+                        "    {}{}: {},\n",
+                        vis,
+                        field_ident(name),
+                        boxed(ty)
+                    ));
+                }
+                source.push('}');
+                source
+            }
+            Fields::UnnamedFields(fields) => {
+                let mut types = Vec::new();
+                for declaration in fields {
+                    let ty = self.borsh_type(function, container, declaration, "")?;
+                    types.push(format!("{}{}", vis, boxed(ty)));
+                }
+                format!("({})", types.join(", "))
+            }
+            Fields::Empty => String::new(),
+        })
+    }
+}
+
+/// The definitions of `container` reachable from `declaration`, including its own.
+fn borsh_closure(
+    container: &BorshSchemaContainer,
+    declaration: &str,
+) -> BTreeMap<String, Definition> {
+    let mut closure = BTreeMap::new();
+    let mut pending = vec![declaration.to_string()];
+    while let Some(declaration) = pending.pop() {
+        let definition = match container.get_definition(&declaration) {
+            Some(definition) if !closure.contains_key(&declaration) => definition,
+            _ => continue,
+        };
+        match definition {
+            Definition::Primitive(_) => {}
+            Definition::Sequence { elements, .. } => pending.push(elements.clone()),
+            Definition::Tuple { elements } => pending.extend(elements.iter().cloned()),
+            Definition::Enum { variants, .. } => {
+                pending.extend(variants.iter().map(|(_, _, payload)| payload.clone()))
+            }
+            Definition::Struct { fields } => match fields {
+                Fields::NamedFields(fields) => {
+                    pending.extend(fields.iter().map(|(_, declaration)| declaration.clone()))
+                }
+                Fields::UnnamedFields(fields) => pending.extend(fields.iter().cloned()),
+                Fields::Empty => {}
+            },
+        }
+        closure.insert(declaration, definition.clone());
+    }
+    closure
+}
+
+/// The schema of the values of `object` other than `null`, if `object` is nullable: a type list
+/// of `null` and another type, or a union of `null` and another schema.
+fn nullable_inner(object: &SchemaObject) -> Option<Schema> {
+    if let Some(SingleOrVec::Vec(types)) = &object.instance_type {
+        let other: Vec<InstanceType> = types
+            .iter()
+            .copied()
+            .filter(|ty| *ty != InstanceType::Null)
+            .collect();
+        return match other.as_slice() {
+            [ty] if other.len() < types.len() => Some(Schema::Object(SchemaObject {
+                instance_type: Some(SingleOrVec::from(*ty)),
+                ..object.clone()
+            })),
+            _ => None,
+        };
+    }
+    let subschemas = object.subschemas.as_deref()?;
+    let variants = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref())?;
+    let is_null = |schema: &Schema| match schema {
+        Schema::Object(object) => {
+            object.instance_type == Some(SingleOrVec::from(InstanceType::Null))
+        }
+        Schema::Bool(_) => false,
+    };
+    match variants.as_slice() {
+        [inner, null] | [null, inner] if is_null(null) && !is_null(inner) => Some(inner.clone()),
+        _ => None,
+    }
+}
+
+/// The schema wrapped by `object` if it is a single-member `allOf`, as generated by `schemars`
+/// for documented references.
+fn single_all_of(object: &SchemaObject) -> Option<&Schema> {
+    match object.subschemas.as_deref()?.all_of.as_deref() {
+        Some([wrapped]) if object.instance_type.is_none() => Some(wrapped),
+        _ => None,
+    }
+}
+
+/// The properties of `object` if it is an object with a fixed set of properties.
+fn struct_properties(object: &SchemaObject) -> Option<&ObjectValidation> {
+    let validation = object.object.as_deref()?;
+    let is_object = object.instance_type == Some(SingleOrVec::from(InstanceType::Object));
+    (is_object && !validation.properties.is_empty()).then_some(validation)
+}
+
+fn description(schema: &Schema) -> Option<&str> {
+    match schema {
+        Schema::Object(SchemaObject {
+            metadata: Some(metadata),
+            ..
+        }) => metadata.description.as_deref(),
+        _ => None,
+    }
+}
+
+fn integer_type(object: &SchemaObject) -> String {
+    let ty = match object.format.as_deref() {
+        Some("uint8") => "u8",
+        Some("uint16") => "u16",
+        Some("uint32") => "u32",
+        Some("uint64") | Some("uint") => "u64",
+        Some("uint128") => "u128",
+        Some("int8") => "i8",
+        Some("int16") => "i16",
+        Some("int32") => "i32",
+        Some("int128") => "i128",
+        _ => match object.number.as_ref().and_then(|number| number.minimum) {
+            Some(minimum) if minimum >= 0.0 => "u64",
+            _ => "i64",
+        },
+    };
+    String::from(ty)
+}
+
+fn is_borsh_primitive(declaration: &str) -> bool {
+    matches!(
+        declaration,
+        "u8" | "u16"
+            | "u32"
+            | "u64"
+            | "u128"
+            | "i8"
+            | "i16"
+            | "i32"
+            | "i64"
+            | "i128"
+            | "f32"
+            | "f64"
+            | "bool"
+            | "()"
+            | "String"
+    )
+}
+
+/// Writes the unit variants tagged with the strings of `values`, if they all are strings.
+fn unit_variants(source: &mut String, used: &mut BTreeSet<String>, values: &[Value]) -> Option<()> {
+    for value in values {
+        variant_header(source, used, value.as_str()?, None);
+        source.push_str(",\n");
+    }
+    Some(())
+}
+
+/// Writes the attributes and the name of the enum variant tagged `tag`.
+fn variant_header(source: &mut String, used: &mut BTreeSet<String>, tag: &str, doc: Option<&str>) {
+    doc_comment(source, doc);
+    let ident = unique_ident(used, tag);
+    if ident != tag {
+        source.push_str(&format!("#[serde(rename = {:?})]\n", tag));
+    }
+    source.push_str(&ident);
+}
+
+/// Type name of the definition `name`, not shadowing prelude types nor any of `used`.
+fn unique_type_name(used: &BTreeSet<String>, name: &str) -> String {
+    let mut name = type_name(name);
+    if PRELUDE_TYPES.contains(&name.as_str()) {
+        name.push_str("Type");
+    }
+    if !used.contains(&name) {
+        return name;
+    }
+    (2..)
+        .map(|i| format!("{}{}", name, i))
+        .find(|name| !used.contains(name))
+        .unwrap()
+}
+
+fn unique_ident(used: &mut BTreeSet<String>, name: &str) -> String {
+    let ident = unique_type_name(used, name);
+    used.insert(ident.clone());
+    ident
+}
+
+/// Identifier of a field or function named `name`, in `snake_case`.
+fn field_ident(name: &str) -> String {
+    let mut ident = String::new();
+    let mut after_lowercase = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if after_lowercase {
+                ident.push('_');
+            }
+            ident.push(c.to_ascii_lowercase());
+            after_lowercase = false;
+        } else if c.is_ascii_alphanumeric() || c == '_' {
+            ident.push(c);
+            after_lowercase = c.is_ascii_lowercase() || c.is_ascii_digit();
+        } else {
+            ident.push('_');
+            after_lowercase = false;
+        }
+    }
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    match ident.as_str() {
+        "_" | "self" | "super" | "crate" => ident + "_",
+        keyword if KEYWORDS.contains(&keyword) => format!("r#{}", ident),
+        _ => ident,
+    }
+}
+
+/// Identifier of the parameter `name` of a method, which must not clash with the `signer`
+/// parameter of call methods.
+fn param_ident(name: &str, is_view: bool) -> String {
+    let ident = field_ident(name);
+    if !is_view && ident == "signer" {
+        ident + "_"
+    } else {
+        ident
+    }
+}
+
+fn tuple(elements: &[String]) -> String {
+    match elements {
+        [element] => format!("({},)", element),
+        _ => format!("({})", elements.join(", ")),
+    }
+}
+
+fn doc_comment(source: &mut String, doc: Option<&str>) {
+    for line in doc.into_iter().flat_map(str::lines) {
+        match line {
+            "" => source.push_str("///\n"),
+            line if line.starts_with(' ') => source.push_str(&format!("///{}\n", line)),
+            line => source.push_str(&format!("/// {}\n", line)),
+        }
+    }
+}
+
+fn indent(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::from("\n")
+            } else {
+                format!("    {}\n", line)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::example_abi;
+    use crate::AbiBorshParameter;
+
+    #[test]
+    fn test_generate() {
+        let mut abi = example_abi();
+        let source = generate(&abi).unwrap();
+        syn::parse_file(&source).unwrap();
+        insta::assert_snapshot!(source);
+
+        abi.body.functions[4].params = AbiParameters::Borsh {
+            args: vec![AbiBorshParameter::new(
+                "record",
+                BorshSchemaContainer::new("Record".to_string(), Default::default()),
+            )],
+        };
+        assert_eq!(
+            generate(&abi).unwrap_err().to_string(),
+            "the Borsh schema of `store` has no definition for `Record`"
+        );
+    }
+    #[test]
+    fn test_generate_name_clashes() {
+        mod other {
+            #[derive(borsh::BorshSchema)]
+            #[allow(dead_code)]
+            pub struct Record {
+                pub flag: bool,
+            }
+        }
+        let mut abi = example_abi();
+        let view = |name: &str, result| AbiFunction {
+            result,
+            ..AbiFunction::new(name, AbiFunctionKind::View)
+        };
+        abi.body.functions.extend([
+            view("load", Some(AbiType::borsh_of::<other::Record>())),
+            view("getX", None),
+            view("get_x", None),
+        ]);
+        let source = generate(&abi).unwrap();
+        syn::parse_file(&source).unwrap();
+        assert!(source.contains("pub struct Record2 {\n        pub flag: bool,\n    }"));
+        assert!(source.contains("-> near_workspaces::Result<types::Record2>"));
+        assert!(source.contains("pub async fn get_x(&self)"));
+        assert!(source.contains("pub async fn get_x_2(&self)"));
+    }
+}
@@ -0,0 +1,437 @@
+//! TypeScript typings and JavaScript clients, built on `near-api-js`.
+
+use super::{client_functions, client_name, contract_name, type_name};
+use crate::transform::definition_name;
+use crate::{AbiFunction, AbiFunctionKind, AbiFunctionModifier, AbiParameters, AbiRoot, AbiType};
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+
+/// Options of the call methods of the client, and of payable ones, emitted in the typings.
+const CALL_OPTIONS: &str = "/** Options of a function call transaction. */
+export interface CallOptions {
+  /** Gas attached to the call. */
+  gas?: bigint;
+}
+
+/** Options of a function call transaction of a payable function. */
+export interface PayableCallOptions extends CallOptions {
+  /** Deposit attached to the call, in yoctoNEAR. */
+  attachedDeposit?: bigint;
+}
+";
+
+/// Generates the TypeScript typings, i.e. the content of a `.d.ts` file, of the contract
+/// described by `abi` and of the client generated by [`client`].
+///
+/// Every root schema definition is exported as an `interface` for objects with properties and
+/// as a `type` otherwise. The client class is declared with a method per function, taking the
+/// JSON parameters of the function as a single object, whose nullable properties are optional,
+/// and resolving to its result. Borsh parameters and results are encoded bytes, `Uint8Array`.
+/// Call methods take `CallOptions`, or `PayableCallOptions` for payable functions.
+pub fn typings(abi: &AbiRoot) -> String {
+    let mut source = format!(
+        "// Typings of the client of the `{}` contract, generated from its ABI by near-abi.\n\n",
+        contract_name(abi)
+    );
+    source.push_str("import type { Account as NearAccount } from \"near-api-js\";\n");
+    for (name, schema) in &abi.body.root_schema.definitions {
+        source.push('\n');
+        source.push_str(&definition(name, schema));
+    }
+    source.push('\n');
+    source.push_str(CALL_OPTIONS);
+
+    source.push_str(&format!(
+        "\n/** Client of the `{}` contract. */\n",
+        contract_name(abi)
+    ));
+    source.push_str(&format!("export declare class {} {{\n", client_name(abi)));
+    source.push_str("  readonly account: NearAccount;\n");
+    source.push_str("  readonly contractId: string;\n");
+    source.push_str("  constructor(account: NearAccount, contractId: string);\n");
+    for function in client_functions(abi) {
+        source.push_str(&indent(&doc_comment(function.doc.as_deref())));
+        source.push_str(&format!("  {};\n", method_signature(function)));
+    }
+    source.push_str("}\n");
+    source
+}
+
+/// Generates a JavaScript module exporting the client class of the contract described by `abi`,
+/// typed by [`typings`].
+///
+/// The client wraps a `near-api-js` `Account` and the id of the contract. View methods call
+/// `viewFunction` and resolve to the decoded result. Call methods sign the transaction with the
+/// account through `functionCall`, and resolve to the result of the last receipt. Private
+/// functions, only called by the contract itself, are left out of the client.
+pub fn client(abi: &AbiRoot) -> String {
+    let functions = client_functions(abi);
+    let mut source = format!(
+        "// Client of the `{}` contract, generated from its ABI by near-abi.\n\n",
+        contract_name(abi)
+    );
+    let json_calls = functions.iter().any(|function| {
+        function.kind == AbiFunctionKind::Call
+            && matches!(function.result, Some(AbiType::Json { .. }))
+    });
+    if json_calls {
+        source.push_str("import { providers } from \"near-api-js\";\n\n");
+    }
+    source.push_str(&doc_comment(Some(&format!(
+        "Client of the `{}` contract.",
+        contract_name(abi)
+    ))));
+    source.push_str(&format!("export class {} {{\n", client_name(abi)));
+    source.push_str("  constructor(account, contractId) {\n");
+    source.push_str("    this.account = account;\n");
+    source.push_str("    this.contractId = contractId;\n");
+    source.push_str("  }\n");
+    for function in functions {
+        source.push('\n');
+        source.push_str(&indent(&method(function)));
+    }
+    source.push_str("}\n");
+    source
+}
+
+/// Exported declaration of the root schema definition `name`.
+fn definition(name: &str, schema: &Schema) -> String {
+    let mut source = doc_comment(description(schema));
+    let name = type_name(name);
+    match schema {
+        Schema::Object(object) if is_interface(object) => {
+            source.push_str(&format!(
+                "export interface {} {}\n",
+                name,
+                object_type(object)
+            ));
+        }
+        _ => source.push_str(&format!("export type {} = {};\n", name, ts_type(schema))),
+    }
+    source
+}
+
+/// TypeScript type of the values of the JSON `schema`.
+fn ts_type(schema: &Schema) -> String {
+    let object = match schema {
+        Schema::Object(object) => object,
+        Schema::Bool(true) => return String::from("unknown"),
+        Schema::Bool(false) => return String::from("never"),
+    };
+    if let Some(reference) = &object.reference {
+        return match definition_name(reference) {
+            Some(name) => type_name(name),
+            None => String::from("unknown"),
+        };
+    }
+    if let Some(value) = &object.const_value {
+        return value.to_string();
+    }
+    if let Some(values) = &object.enum_values {
+        return union(values.iter().map(|value| value.to_string()));
+    }
+    if let Some(subschemas) = object.subschemas.as_deref() {
+        if let Some(variants) = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref()) {
+            return union(variants.iter().map(ts_type));
+        }
+        if let Some(schemas) = &subschemas.all_of {
+            let types: Vec<String> = schemas.iter().map(ts_type).map(parenthesize).collect();
+            return types.join(" & ");
+        }
+    }
+    match &object.instance_type {
+        Some(SingleOrVec::Single(instance_type)) => instance_ts_type(object, **instance_type),
+        Some(SingleOrVec::Vec(types)) => {
+            union(types.iter().map(|ty| instance_ts_type(object, *ty)))
+        }
+        None => String::from("unknown"),
+    }
+}
+
+fn instance_ts_type(object: &SchemaObject, instance_type: InstanceType) -> String {
+    match instance_type {
+        InstanceType::String => String::from("string"),
+        InstanceType::Integer | InstanceType::Number => String::from("number"),
+        InstanceType::Boolean => String::from("boolean"),
+        InstanceType::Null => String::from("null"),
+        InstanceType::Array => match object.array.as_ref().and_then(|a| a.items.as_ref()) {
+            Some(SingleOrVec::Single(items)) => format!("{}[]", parenthesize(ts_type(items))),
+            Some(SingleOrVec::Vec(items)) => {
+                let items: Vec<String> = items.iter().map(ts_type).collect();
+                format!("[{}]", items.join(", "))
+            }
+            None => String::from("unknown[]"),
+        },
+        InstanceType::Object => object_type(object),
+    }
+}
+
+/// Inline type of the values of the object schema `object`: a type literal with its
+/// properties, or a `Record` of its additional properties.
+fn object_type(object: &SchemaObject) -> String {
+    let validation = match object.object.as_deref() {
+        Some(validation) => validation,
+        None => return String::from("Record<string, unknown>"),
+    };
+    if validation.properties.is_empty() {
+        return match validation.additional_properties.as_deref() {
+            Some(Schema::Bool(false)) => String::from("Record<string, never>"),
+            Some(values) => format!("Record<string, {}>", ts_type(values)),
+            None => String::from("Record<string, unknown>"),
+        };
+    }
+    let mut source = String::from("{\n");
+    for (name, schema) in &validation.properties {
+        source.push_str(&indent(&doc_comment(description(schema))));
+        let optional = if validation.required.contains(name) {
+            ""
+        } else {
+            "?"
+        };
+        source.push_str(&format!(
+            "  {}{}: {};\n",
+            property_name(name),
+            optional,
+            ts_type(schema).replace('\n', "\n  ")
+        ));
+    }
+    source.push('}');
+    source
+}
+
+/// Whether the definition `object` is declared as an interface: an object with properties.
+fn is_interface(object: &SchemaObject) -> bool {
+    object.instance_type == Some(SingleOrVec::from(InstanceType::Object))
+        && object.reference.is_none()
+        && object.subschemas.is_none()
+        && object
+            .object
+            .as_ref()
+            .map_or(false, |validation| !validation.properties.is_empty())
+}
+
+/// Declaration of the client method of `function`, e.g.
+/// `get(args: { key: string }): Promise<string | null>`.
+fn method_signature(function: &AbiFunction) -> String {
+    let mut params = Vec::new();
+    match &function.params {
+        AbiParameters::Json { args } if !args.is_empty() => {
+            let mut fields = Vec::new();
+            for arg in args {
+                let ty = ts_type(&arg.type_schema);
+                let optional = if is_nullable(&arg.type_schema) {
+                    "?"
+                } else {
+                    ""
+                };
+                fields.push(format!("{}{}: {}", property_name(&arg.name), optional, ty));
+            }
+            params.push(format!("args: {{ {} }}", fields.join("; ")));
+        }
+        AbiParameters::Borsh { args } if !args.is_empty() => {
+            params.push(String::from("args: Uint8Array"));
+        }
+        _ => {}
+    }
+    if function.kind == AbiFunctionKind::Call {
+        if function.modifiers.contains(&AbiFunctionModifier::Payable) {
+            params.push(String::from("options?: PayableCallOptions"));
+        } else {
+            params.push(String::from("options?: CallOptions"));
+        }
+    }
+    let result = match &function.result {
+        Some(AbiType::Json { type_schema }) => ts_type(type_schema),
+        Some(AbiType::Borsh { .. }) => String::from("Uint8Array"),
+        None => String::from("void"),
+    };
+    format!(
+        "{}({}): Promise<{}>",
+        function.name,
+        params.join(", "),
+        result
+    )
+}
+
+/// Implementation of the client method of `function`.
+fn method(function: &AbiFunction) -> String {
+    let mut source = doc_comment(function.doc.as_deref());
+    let is_borsh = matches!(function.params, AbiParameters::Borsh { .. });
+    let has_args = match &function.params {
+        AbiParameters::Json { args } => !args.is_empty(),
+        AbiParameters::Borsh { args } => !args.is_empty(),
+    };
+    let mut params = Vec::new();
+    if has_args {
+        params.push("args");
+    }
+    let mut fields = vec![
+        String::from("contractId: this.contractId"),
+        format!("methodName: {:?}", function.name),
+    ];
+    if has_args {
+        fields.push(String::from("args"));
+    }
+
+    if function.kind == AbiFunctionKind::View {
+        if has_args && is_borsh {
+            fields.push(String::from("stringify: (args) => Buffer.from(args)"));
+        }
+        if let Some(AbiType::Borsh { .. }) = function.result {
+            fields.push(String::from("parse: (bytes) => bytes"));
+        }
+        source.push_str(&format!("{}({}) {{\n", function.name, params.join(", ")));
+        source.push_str(&format!(
+            "  return this.account.viewFunction({});\n}}\n",
+            object_literal(&fields, "  ")
+        ));
+        return source;
+    }
+
+    params.push("options = {}");
+    fields.push(String::from("gas: options.gas"));
+    if function.modifiers.contains(&AbiFunctionModifier::Payable) {
+        fields.push(String::from("attachedDeposit: options.attachedDeposit"));
+    }
+    source.push_str(&format!(
+        "async {}({}) {{\n",
+        function.name,
+        params.join(", ")
+    ));
+    let call = format!(
+        "await this.account.functionCall({})",
+        object_literal(&fields, "  ")
+    );
+    match &function.result {
+        Some(AbiType::Json { .. }) => {
+            source.push_str(&format!("  const outcome = {};\n", call));
+            source.push_str("  return providers.getTransactionLastResult(outcome);\n");
+        }
+        Some(AbiType::Borsh { .. }) => {
+            source.push_str(&format!("  const outcome = {};\n", call));
+            source.push_str("  return Buffer.from(outcome.status.SuccessValue, \"base64\");\n");
+        }
+        None => source.push_str(&format!("  {};\n", call)),
+    }
+    source.push_str("}\n");
+    source
+}
+
+/// Whether the values of `schema` can be `null`.
+fn is_nullable(schema: &Schema) -> bool {
+    let object = match schema {
+        Schema::Object(object) => object,
+        Schema::Bool(nullable) => return *nullable,
+    };
+    let null = InstanceType::Null;
+    match &object.instance_type {
+        Some(SingleOrVec::Single(ty)) if **ty == null => return true,
+        Some(SingleOrVec::Vec(types)) if types.contains(&null) => return true,
+        _ => {}
+    }
+    object.subschemas.as_deref().map_or(false, |subschemas| {
+        let variants = subschemas.any_of.iter().chain(&subschemas.one_of);
+        variants.flatten().any(is_nullable)
+    })
+}
+
+fn description(schema: &Schema) -> Option<&str> {
+    match schema {
+        Schema::Object(SchemaObject {
+            metadata: Some(metadata),
+            ..
+        }) => metadata.description.as_deref(),
+        _ => None,
+    }
+}
+
+/// Key of the property `name` in a type literal, quoted unless it is an identifier.
+fn property_name(name: &str) -> String {
+    let mut chars = name.chars();
+    let is_identifier = chars
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        name.to_string()
+    } else {
+        serde_json::Value::from(name).to_string()
+    }
+}
+
+fn union(types: impl Iterator<Item = String>) -> String {
+    let mut members: Vec<String> = Vec::new();
+    for ty in types {
+        if !members.contains(&ty) {
+            members.push(ty);
+        }
+    }
+    match members.len() {
+        0 => String::from("never"),
+        _ => members.join(" | "),
+    }
+}
+
+/// Wraps unions and intersections in parentheses, so that they can be followed by `[]` or
+/// joined into an intersection.
+fn parenthesize(ty: String) -> String {
+    if !ty.starts_with('{') && (ty.contains(" | ") || ty.contains(" & ")) {
+        format!("({})", ty)
+    } else {
+        ty
+    }
+}
+
+fn object_literal(fields: &[String], indentation: &str) -> String {
+    let fields: String = fields
+        .iter()
+        .map(|field| format!("{}  {},\n", indentation, field))
+        .collect();
+    format!("{{\n{}{}}}", fields, indentation)
+}
+
+fn doc_comment(doc: Option<&str>) -> String {
+    let lines: Vec<&str> = doc
+        .into_iter()
+        .flat_map(str::lines)
+        .map(str::trim)
+        .collect();
+    match lines.as_slice() {
+        [] => String::new(),
+        [line] => format!("/** {} */\n", line.replace("*/", "*\\/")),
+        lines => {
+            let mut source = String::from("/**\n");
+            for line in lines {
+                source.push_str(format!(" * {}", line.replace("*/", "*\\/")).trim_end());
+                source.push('\n');
+            }
+            source.push_str(" */\n");
+            source
+        }
+    }
+}
+
+fn indent(source: &str) -> String {
+    source
+        .lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::from("\n")
+            } else {
+                format!("  {}\n", line)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codegen::example_abi;
+
+    #[test]
+    fn test_typings_and_client() {
+        let abi = example_abi();
+        insta::assert_snapshot!("typings", typings(&abi));
+        insta::assert_snapshot!("client", client(&abi));
+    }
+}
@@ -0,0 +1,660 @@
+//! Conversions between JSON and Borsh type schemas.
+//!
+//! [`borsh_to_json`] describes the JSON representation of Borsh values, as produced by
+//! [`decode_borsh`](crate::value::decode_borsh). [`json_to_borsh`] picks a Borsh type for the
+//! subset of JSON Schema emitted by `schemars` for plain Rust types and rejects schemas without
+//! an obvious Borsh counterpart.
+
+use crate::value::resolve_reference;
+use borsh::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+use schemars::schema::{
+    ArrayValidation, InstanceType, NumberValidation, ObjectValidation, RootSchema, Schema,
+    SchemaObject, SingleOrVec, SubschemaValidation,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Integer formats emitted by `schemars`, along with the matching Borsh primitive and its size.
+const INTEGER_FORMATS: [(&str, &str, u8); 12] = [
+    ("uint8", "u8", 1),
+    ("uint16", "u16", 2),
+    ("uint32", "u32", 4),
+    ("uint64", "u64", 8),
+    ("uint128", "u128", 16),
+    ("uint", "u64", 8),
+    ("int8", "i8", 1),
+    ("int16", "i16", 2),
+    ("int32", "i32", 4),
+    ("int64", "i64", 8),
+    ("int128", "i128", 16),
+    ("int", "i64", 8),
+];
+
+/// Describes the JSON representation of the values of a Borsh type.
+///
+/// Integers wider than 64 bits are strings with a `uint128` or `int128` format, `Option`s accept
+/// `null` and other enums are single-key objects keyed by the variant name. Recursive types can
+/// not be described without definitions and are rejected.
+pub fn borsh_to_json(container: &BorshSchemaContainer) -> Result<Schema, ConvertError> {
+    borsh_schema(container, container.declaration(), &mut Vec::new()).map(Schema::Object)
+}
+
+fn borsh_schema(
+    container: &BorshSchemaContainer,
+    declaration: &Declaration,
+    stack: &mut Vec<Declaration>,
+) -> Result<SchemaObject, ConvertError> {
+    if stack.contains(declaration) {
+        return Err(ConvertError::Recursive(declaration.clone()));
+    }
+    let definition = container
+        .get_definition(declaration)
+        .ok_or_else(|| ConvertError::MissingDefinition(declaration.clone()))?;
+    stack.push(declaration.clone());
+    let schema = match definition {
+        Definition::Primitive(size) => primitive_schema(declaration, *size),
+        Definition::Sequence {
+            length_width,
+            length_range,
+            elements,
+        } => {
+            if declaration == "String" && elements == "u8" {
+                typed(InstanceType::String)
+            } else {
+                let items = borsh_schema(container, elements, stack)?;
+                let (start, end) = (*length_range.start(), *length_range.end());
+                let max_items = if *length_width == 0 || end < u64::from(u32::MAX) {
+                    u32::try_from(end).ok()
+                } else {
+                    None
+                };
+                array(
+                    SingleOrVec::Single(Box::new(items.into())),
+                    u32::try_from(start).ok().filter(|start| *start > 0),
+                    max_items,
+                )
+            }
+        }
+        Definition::Tuple { elements } => tuple(container, elements, stack)?,
+        Definition::Enum { variants, .. } => {
+            let some = variants
+                .iter()
+                .find(|(_, name, _)| name == "Some")
+                .map(|(_, _, some)| some);
+            match some {
+                Some(some) if declaration.starts_with("Option<") && variants.len() == 2 => {
+                    let inner = borsh_schema(container, some, stack)?;
+                    SchemaObject {
+                        subschemas: Some(Box::new(SubschemaValidation {
+                            any_of: Some(vec![inner.into(), typed(InstanceType::Null).into()]),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    }
+                }
+                _ => {
+                    let mut one_of = Vec::new();
+                    for (_, name, variant) in variants {
+                        let value = borsh_schema(container, variant, stack)?;
+                        one_of.push(object(vec![(name.clone(), value)]).into());
+                    }
+                    SchemaObject {
+                        subschemas: Some(Box::new(SubschemaValidation {
+                            one_of: Some(one_of),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    }
+                }
+            }
+        }
+        Definition::Struct { fields } => match fields {
+            Fields::NamedFields(fields) => {
+                let mut properties = Vec::new();
+                for (name, field) in fields {
+                    properties.push((name.clone(), borsh_schema(container, field, stack)?));
+                }
+                object(properties)
+            }
+            Fields::UnnamedFields(fields) => tuple(container, fields, stack)?,
+            Fields::Empty => typed(InstanceType::Null),
+        },
+    };
+    stack.pop();
+    Ok(schema)
+}
+
+fn primitive_schema(declaration: &str, size: u8) -> SchemaObject {
+    let integer = |format: &str, minimum: Option<f64>| SchemaObject {
+        format: Some(format.to_string()),
+        number: minimum.map(|minimum| {
+            Box::new(NumberValidation {
+                minimum: Some(minimum),
+                ..Default::default()
+            })
+        }),
+        ..typed(InstanceType::Integer)
+    };
+    let with_format = |ty, format: &str| SchemaObject {
+        format: Some(format.to_string()),
+        ..typed(ty)
+    };
+    match declaration {
+        "bool" => typed(InstanceType::Boolean),
+        "u8" | "u16" | "u32" | "u64" => integer(&format!("uint{}", 8 * size), Some(0.0)),
+        "i8" | "i16" | "i32" | "i64" => integer(&format!("int{}", 8 * size), None),
+        "u128" => with_format(InstanceType::String, "uint128"),
+        "i128" => with_format(InstanceType::String, "int128"),
+        "f32" => with_format(InstanceType::Number, "float"),
+        "f64" => with_format(InstanceType::Number, "double"),
+        "()" => typed(InstanceType::Null),
+        // Other primitives are decoded as their raw bytes.
+        _ => array(
+            SingleOrVec::Single(Box::new(integer("uint8", Some(0.0)).into())),
+            Some(u32::from(size)),
+            Some(u32::from(size)),
+        ),
+    }
+}
+
+fn tuple(
+    container: &BorshSchemaContainer,
+    elements: &[Declaration],
+    stack: &mut Vec<Declaration>,
+) -> Result<SchemaObject, ConvertError> {
+    let mut items = Vec::new();
+    for element in elements {
+        items.push(borsh_schema(container, element, stack)?.into());
+    }
+    let len = u32::try_from(elements.len()).ok();
+    Ok(array(SingleOrVec::Vec(items), len, len))
+}
+
+fn typed(ty: InstanceType) -> SchemaObject {
+    SchemaObject {
+        instance_type: Some(ty.into()),
+        ..Default::default()
+    }
+}
+
+fn array(
+    items: SingleOrVec<Schema>,
+    min_items: Option<u32>,
+    max_items: Option<u32>,
+) -> SchemaObject {
+    SchemaObject {
+        array: Some(Box::new(ArrayValidation {
+            items: Some(items),
+            min_items,
+            max_items,
+            ..Default::default()
+        })),
+        ..typed(InstanceType::Array)
+    }
+}
+
+fn object(properties: Vec<(String, SchemaObject)>) -> SchemaObject {
+    let mut validation = ObjectValidation {
+        additional_properties: Some(Box::new(Schema::Bool(false))),
+        ..Default::default()
+    };
+    for (name, schema) in properties {
+        validation.required.insert(name.clone());
+        validation.properties.insert(name, schema.into());
+    }
+    SchemaObject {
+        object: Some(Box::new(validation)),
+        ..typed(InstanceType::Object)
+    }
+}
+
+/// Picks a Borsh type for values described by `schema`, resolving references against
+/// `root_schema`.
+///
+/// Objects and enums become structs and enums named after the root schema definition they are
+/// declared by, and can not be declared inline. Struct fields follow the order of the schema
+/// properties and optional properties become `Option`s. Strings with a 64-bit or 128-bit integer
+/// format become integers.
+pub fn json_to_borsh(
+    root_schema: &RootSchema,
+    schema: &Schema,
+) -> Result<BorshSchemaContainer, ConvertError> {
+    let mut converter = JsonConverter {
+        root_schema,
+        definitions: BTreeMap::new(),
+        stack: Vec::new(),
+    };
+    let declaration = converter.convert(schema, None)?;
+    Ok(BorshSchemaContainer::new(
+        declaration,
+        converter.definitions,
+    ))
+}
+
+struct JsonConverter<'a> {
+    root_schema: &'a RootSchema,
+    definitions: BTreeMap<Declaration, Definition>,
+    /// Definitions being converted, to tie the knot of recursive types.
+    stack: Vec<String>,
+}
+
+impl JsonConverter<'_> {
+    fn convert(
+        &mut self,
+        schema: &Schema,
+        name: Option<&str>,
+    ) -> Result<Declaration, ConvertError> {
+        let object = match schema {
+            Schema::Object(object) => object,
+            Schema::Bool(_) => {
+                return Err(ConvertError::Unsupported(
+                    "boolean schemas accept any value".to_string(),
+                ))
+            }
+        };
+        if let Some(reference) = &object.reference {
+            let target = reference.rsplit('/').next().unwrap_or(reference);
+            if self.stack.iter().any(|name| name == target) {
+                return Ok(target.to_string());
+            }
+            let resolved = resolve_reference(self.root_schema, reference)
+                .ok_or_else(|| ConvertError::MissingDefinition(reference.clone()))?;
+            self.stack.push(target.to_string());
+            let declaration = self.convert(resolved, Some(target));
+            self.stack.pop();
+            return declaration;
+        }
+        if let Some(subschemas) = &object.subschemas {
+            return self.convert_subschemas(subschemas, name);
+        }
+        if let Some(values) = &object.enum_values {
+            let variants = values
+                .iter()
+                .map(|value| match value {
+                    Value::String(variant) => Ok((variant.clone(), "()".to_string())),
+                    _ => Err(ConvertError::Unsupported(
+                        "enumerations of non-string values".to_string(),
+                    )),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            self.primitive("()", 0);
+            return self.named_enum(name, variants);
+        }
+        let ty = match &object.instance_type {
+            Some(SingleOrVec::Single(ty)) => **ty,
+            Some(SingleOrVec::Vec(types)) => {
+                let non_null: Vec<_> = types
+                    .iter()
+                    .filter(|ty| **ty != InstanceType::Null)
+                    .collect();
+                match non_null[..] {
+                    [ty] if types.len() == 2 => {
+                        let inner = SchemaObject {
+                            instance_type: Some((*ty).into()),
+                            ..object.clone()
+                        };
+                        let inner = self.convert(&inner.into(), name)?;
+                        return Ok(self.option(inner));
+                    }
+                    _ => {
+                        return Err(ConvertError::Unsupported(
+                            "values of several types".to_string(),
+                        ))
+                    }
+                }
+            }
+            None => {
+                return Err(ConvertError::Unsupported(
+                    "schemas without a type".to_string(),
+                ))
+            }
+        };
+        let format = object.format.as_deref();
+        let declaration = match ty {
+            InstanceType::Null => self.primitive("()", 0),
+            InstanceType::Boolean => self.primitive("bool", 1),
+            InstanceType::Integer => match format.and_then(integer_primitive) {
+                Some((primitive, size)) => self.primitive(primitive, size),
+                None if minimum(object) >= Some(0.0) => self.primitive("u64", 8),
+                None => self.primitive("i64", 8),
+            },
+            InstanceType::Number if format == Some("float") => self.primitive("f32", 4),
+            InstanceType::Number => self.primitive("f64", 8),
+            InstanceType::String => match format.and_then(integer_primitive) {
+                Some((primitive, size)) => self.primitive(primitive, size),
+                None => {
+                    let u8 = self.primitive("u8", 1);
+                    self.insert(
+                        "String".to_string(),
+                        Definition::Sequence {
+                            length_width: Definition::DEFAULT_LENGTH_WIDTH,
+                            length_range: Definition::DEFAULT_LENGTH_RANGE,
+                            elements: u8,
+                        },
+                    )
+                }
+            },
+            InstanceType::Array => self.convert_array(object.array.as_deref())?,
+            InstanceType::Object => self.convert_object(object.object.as_deref(), name)?,
+        };
+        Ok(declaration)
+    }
+
+    fn convert_subschemas(
+        &mut self,
+        subschemas: &SubschemaValidation,
+        name: Option<&str>,
+    ) -> Result<Declaration, ConvertError> {
+        if let Some([schema]) = subschemas.all_of.as_deref() {
+            return self.convert(schema, name);
+        }
+        let alternatives = subschemas
+            .one_of
+            .as_deref()
+            .or(subschemas.any_of.as_deref())
+            .ok_or_else(|| {
+                ConvertError::Unsupported("schema composition other than alternatives".to_string())
+            })?;
+        let is_null = |schema: &Schema| match schema {
+            Schema::Object(object) => {
+                object.instance_type == Some(InstanceType::Null.into())
+                    && object.subschemas.is_none()
+            }
+            Schema::Bool(_) => false,
+        };
+        if let [a, b] = alternatives {
+            if is_null(a) != is_null(b) {
+                let inner = if is_null(a) { b } else { a };
+                let inner = self.convert(inner, name)?;
+                return Ok(self.option(inner));
+            }
+        }
+        let mut variants = Vec::new();
+        for alternative in alternatives {
+            let object = match alternative {
+                Schema::Object(object) => object,
+                Schema::Bool(_) => {
+                    return Err(ConvertError::Unsupported(
+                        "boolean schemas accept any value".to_string(),
+                    ))
+                }
+            };
+            if let Some(values) = &object.enum_values {
+                for value in values {
+                    match value {
+                        Value::String(variant) => {
+                            variants.push((variant.clone(), self.primitive("()", 0)))
+                        }
+                        _ => {
+                            return Err(ConvertError::Unsupported(
+                                "enumerations of non-string values".to_string(),
+                            ))
+                        }
+                    }
+                }
+                continue;
+            }
+            let single_property = object
+                .object
+                .as_deref()
+                .filter(|validation| {
+                    validation.properties.len() == 1 && validation.required.len() == 1
+                })
+                .and_then(|validation| validation.properties.iter().next());
+            match single_property {
+                Some((variant, schema)) => {
+                    let declaration = self.convert(schema, None)?;
+                    variants.push((variant.clone(), declaration));
+                }
+                None => {
+                    return Err(ConvertError::Unsupported(
+                        "alternatives other than externally tagged enum variants".to_string(),
+                    ))
+                }
+            }
+        }
+        self.named_enum(name, variants)
+    }
+
+    fn convert_array(
+        &mut self,
+        validation: Option<&ArrayValidation>,
+    ) -> Result<Declaration, ConvertError> {
+        let validation = validation
+            .filter(|validation| validation.items.is_some())
+            .ok_or_else(|| ConvertError::Unsupported("arrays of any value".to_string()))?;
+        match validation.items.as_ref().unwrap() {
+            SingleOrVec::Single(items) => {
+                let elements = self.convert(items, None)?;
+                Ok(match (validation.min_items, validation.max_items) {
+                    (Some(min), Some(max)) if min == max => self.insert(
+                        format!("[{}; {}]", elements, min),
+                        Definition::Sequence {
+                            length_width: Definition::ARRAY_LENGTH_WIDTH,
+                            length_range: u64::from(min)..=u64::from(min),
+                            elements,
+                        },
+                    ),
+                    _ => self.insert(
+                        format!("Vec<{}>", elements),
+                        Definition::Sequence {
+                            length_width: Definition::DEFAULT_LENGTH_WIDTH,
+                            length_range: Definition::DEFAULT_LENGTH_RANGE,
+                            elements,
+                        },
+                    ),
+                })
+            }
+            SingleOrVec::Vec(items) => {
+                let mut elements = Vec::new();
+                for item in items {
+                    elements.push(self.convert(item, None)?);
+                }
+                Ok(self.tuple(elements))
+            }
+        }
+    }
+
+    fn convert_object(
+        &mut self,
+        validation: Option<&ObjectValidation>,
+        name: Option<&str>,
+    ) -> Result<Declaration, ConvertError> {
+        let validation = validation
+            .ok_or_else(|| ConvertError::Unsupported("objects of any shape".to_string()))?;
+        if validation.properties.is_empty() {
+            // Maps with string keys, e.g. `HashMap<String, T>`.
+            if let Some(values) = validation.additional_properties.as_deref() {
+                let values = self.convert(values, None)?;
+                let string = self.convert(&typed(InstanceType::String).into(), None)?;
+                let entry = self.tuple(vec![string, values.clone()]);
+                return Ok(self.insert(
+                    format!("HashMap<String, {}>", values),
+                    Definition::Sequence {
+                        length_width: Definition::DEFAULT_LENGTH_WIDTH,
+                        length_range: Definition::DEFAULT_LENGTH_RANGE,
+                        elements: entry,
+                    },
+                ));
+            }
+        }
+        let name = name.ok_or_else(|| {
+            ConvertError::Unsupported("objects not declared in the root schema".to_string())
+        })?;
+        let mut fields = Vec::new();
+        for (field, schema) in &validation.properties {
+            let mut declaration = self.convert(schema, None)?;
+            if !validation.required.contains(field) && !declaration.starts_with("Option<") {
+                declaration = self.option(declaration);
+            }
+            fields.push((field.clone(), declaration));
+        }
+        let fields = if fields.is_empty() {
+            Fields::Empty
+        } else {
+            Fields::NamedFields(fields)
+        };
+        Ok(self.insert(name.to_string(), Definition::Struct { fields }))
+    }
+
+    fn named_enum(
+        &mut self,
+        name: Option<&str>,
+        variants: Vec<(String, Declaration)>,
+    ) -> Result<Declaration, ConvertError> {
+        let name = name.ok_or_else(|| {
+            ConvertError::Unsupported("enums not declared in the root schema".to_string())
+        })?;
+        let tag_width = if variants.len() > 256 { 4 } else { 1 };
+        let variants = variants
+            .into_iter()
+            .enumerate()
+            .map(|(i, (variant, declaration))| (i as i64, variant, declaration))
+            .collect();
+        Ok(self.insert(
+            name.to_string(),
+            Definition::Enum {
+                tag_width,
+                variants,
+            },
+        ))
+    }
+
+    fn option(&mut self, inner: Declaration) -> Declaration {
+        let unit = self.primitive("()", 0);
+        self.insert(
+            format!("Option<{}>", inner),
+            Definition::Enum {
+                tag_width: 1,
+                variants: vec![
+                    (0, "None".to_string(), unit),
+                    (1, "Some".to_string(), inner),
+                ],
+            },
+        )
+    }
+
+    fn tuple(&mut self, elements: Vec<Declaration>) -> Declaration {
+        let declaration = if elements.len() == 1 {
+            format!("({},)", elements[0])
+        } else {
+            format!("({})", elements.join(", "))
+        };
+        self.insert(declaration, Definition::Tuple { elements })
+    }
+
+    fn primitive(&mut self, declaration: &str, size: u8) -> Declaration {
+        self.insert(declaration.to_string(), Definition::Primitive(size))
+    }
+
+    fn insert(&mut self, declaration: Declaration, definition: Definition) -> Declaration {
+        self.definitions
+            .entry(declaration.clone())
+            .or_insert(definition);
+        declaration
+    }
+}
+
+fn integer_primitive(format: &str) -> Option<(&'static str, u8)> {
+    INTEGER_FORMATS
+        .iter()
+        .find(|(name, _, _)| *name == format)
+        .map(|(_, primitive, size)| (*primitive, *size))
+}
+
+fn minimum(object: &SchemaObject) -> Option<f64> {
+    object.number.as_ref().and_then(|number| number.minimum)
+}
+
+/// Error returned when a type schema has no counterpart in the other serialization format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The Borsh type is recursive and can not be described by an inline JSON schema.
+    Recursive(String),
+    /// A Borsh declaration or a JSON schema reference has no definition.
+    MissingDefinition(String),
+    /// The JSON schema uses constructs without a Borsh counterpart.
+    Unsupported(String),
+}
+
+impl std::error::Error for ConvertError {}
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Recursive(declaration) => {
+                write!(f, "recursive type `{}` can not be inlined", declaration)
+            }
+            Self::MissingDefinition(name) => write!(f, "`{}` has no definition", name),
+            Self::Unsupported(what) => write!(f, "unsupported JSON schema: {}", what),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSchema;
+    use schemars::JsonSchema;
+    use serde_json::json;
+
+    #[derive(BorshSchema, JsonSchema)]
+    #[allow(dead_code)]
+    struct Transfer {
+        amount: u128,
+        memo: Option<String>,
+        receiver_id: String,
+        tags: Vec<u8>,
+    }
+
+    #[test]
+    fn test_json_to_borsh() {
+        let mut gen = schemars::gen::SchemaGenerator::default();
+        let schema = gen.subschema_for::<Transfer>();
+        let root_schema = gen.into_root_schema_for::<()>();
+        assert_eq!(
+            json_to_borsh(&root_schema, &schema).unwrap(),
+            borsh::schema_container_of::<Transfer>()
+        );
+
+        let inline: Schema = serde_json::from_value(json!({ "type": "object" })).unwrap();
+        assert_eq!(
+            json_to_borsh(&root_schema, &inline),
+            Err(ConvertError::Unsupported(
+                "objects of any shape".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_borsh_to_json() {
+        let schema = borsh_to_json(&borsh::schema_container_of::<Transfer>()).unwrap();
+        assert_eq!(
+            serde_json::to_value(schema).unwrap(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "amount": { "type": "string", "format": "uint128" },
+                    "memo": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                    "receiver_id": { "type": "string" },
+                    "tags": {
+                        "type": "array",
+                        "items": { "type": "integer", "format": "uint8", "minimum": 0.0 }
+                    }
+                },
+                "required": ["amount", "memo", "receiver_id", "tags"],
+                "additionalProperties": false
+            })
+        );
+
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        struct Node {
+            children: Vec<Node>,
+        }
+        assert_eq!(
+            borsh_to_json(&borsh::schema_container_of::<Node>()),
+            Err(ConvertError::Recursive("Node".to_string()))
+        );
+    }
+}
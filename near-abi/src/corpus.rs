@@ -0,0 +1,97 @@
+//! Corpus of ABI documents covering every released schema version.
+//!
+//! Each entry is an ABI document in the exact shape emitted for its schema version, starting with
+//! `0.1.0`. Tools consuming ABIs can use the corpus to regression-test their handling of
+//! historical artifacts, including rejecting the ones they do not support.
+
+use crate::AbiRoot;
+use semver::{Version, VersionReq};
+use serde_json::Value;
+
+/// A single ABI document of the corpus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CorpusEntry {
+    /// Name of the contract the document describes.
+    pub name: &'static str,
+    /// Schema version the document was emitted in.
+    pub schema_version: &'static str,
+    /// Raw JSON of the document.
+    pub json: &'static str,
+}
+
+impl CorpusEntry {
+    /// Parsed schema version of the document.
+    pub fn version(&self) -> Version {
+        Version::parse(self.schema_version).expect("corpus schema versions are valid semver")
+    }
+
+    /// Whether the document can be parsed into the current ABI models.
+    pub fn is_current(&self) -> bool {
+        crate::version::current_requirement().matches(&self.version())
+    }
+
+    /// The document as a JSON value.
+    pub fn to_value(&self) -> Value {
+        serde_json::from_str(self.json).expect("corpus documents are valid JSON")
+    }
+
+    /// Parses the document into the current ABI models. Fails for documents of incompatible
+    /// schema versions.
+    pub fn parse(&self) -> Result<AbiRoot, serde_json::Error> {
+        AbiRoot::from_value(self.to_value())
+    }
+}
+
+macro_rules! entries {
+    ($(($version:literal, $name:literal)),* $(,)?) => {
+        &[$(CorpusEntry {
+            name: $name,
+            schema_version: $version,
+            json: include_str!(concat!("../corpus/", $version, "-", $name, ".json")),
+        }),*]
+    };
+}
+
+const ENTRIES: &[CorpusEntry] = entries![
+    ("0.1.0", "adder"),
+    ("0.2.0", "adder"),
+    ("0.2.1", "adder"),
+    ("0.3.0", "adder"),
+    ("0.4.0", "adder"),
+];
+
+/// Iterates over all corpus entries, oldest schema version first.
+pub fn iter() -> impl Iterator<Item = &'static CorpusEntry> {
+    ENTRIES.iter()
+}
+
+/// Iterates over the corpus entries whose schema version satisfies `requirement`.
+pub fn for_version(requirement: &VersionReq) -> impl Iterator<Item = &'static CorpusEntry> + '_ {
+    iter().filter(move |entry| requirement.matches(&entry.version()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corpus_entries() {
+        for entry in iter() {
+            assert_eq!(entry.to_value()["schema_version"], entry.schema_version);
+            match entry.parse() {
+                Ok(abi) => {
+                    assert!(entry.is_current(), "{} parsed", entry.schema_version);
+                    assert_eq!(abi.to_value(), entry.to_value());
+                }
+                Err(err) => {
+                    assert!(!entry.is_current(), "{}: {}", entry.schema_version, err);
+                    assert!(err.to_string().contains("re-generating"), "{}", err);
+                }
+            }
+        }
+        assert_eq!(
+            for_version(&crate::version::current_requirement()).count(),
+            1
+        );
+    }
+}
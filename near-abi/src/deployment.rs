@@ -0,0 +1,170 @@
+//! Deployments of contracts.
+//!
+//! [`AbiMetadata::deployments`] records where the exact code described by an ABI is live, so a
+//! single artifact documents its mainnet and testnet accounts. Tools check the records against
+//! the code hash reported by the network with [`AbiMetadata::verify_deployment`].
+
+use crate::wasm_hash::WasmHash;
+use crate::{AbiDeployment, AbiMetadata};
+use std::fmt;
+
+pub(crate) fn add(
+    metadata: &mut AbiMetadata,
+    deployment: AbiDeployment,
+) -> Result<(), DeploymentError> {
+    if let Some(expected) = metadata.wasm_hash {
+        if deployment.wasm_hash != expected {
+            return Err(DeploymentError::HashMismatch {
+                expected,
+                found: deployment.wasm_hash,
+            });
+        }
+    }
+    if find(metadata, &deployment.network, &deployment.account_id).is_some() {
+        return Err(DeploymentError::AlreadyRecorded {
+            network: deployment.network,
+            account_id: deployment.account_id,
+        });
+    }
+    metadata.deployments.push(deployment);
+    Ok(())
+}
+
+pub(crate) fn verify<'a>(
+    metadata: &'a AbiMetadata,
+    network: &str,
+    account_id: &str,
+    code_hash: &WasmHash,
+) -> Result<&'a AbiDeployment, DeploymentError> {
+    let deployment =
+        find(metadata, network, account_id).ok_or_else(|| DeploymentError::NotRecorded {
+            network: network.to_string(),
+            account_id: account_id.to_string(),
+        })?;
+    if deployment.wasm_hash != *code_hash {
+        return Err(DeploymentError::HashMismatch {
+            expected: deployment.wasm_hash,
+            found: *code_hash,
+        });
+    }
+    Ok(deployment)
+}
+
+fn find<'a>(
+    metadata: &'a AbiMetadata,
+    network: &str,
+    account_id: &str,
+) -> Option<&'a AbiDeployment> {
+    metadata
+        .deployments
+        .iter()
+        .find(|deployment| deployment.network == network && deployment.account_id == account_id)
+}
+
+/// Error returned when recording or verifying a deployment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeploymentError {
+    /// The deployed code is not the code described by the ABI.
+    HashMismatch { expected: WasmHash, found: WasmHash },
+    /// A deployment to the account is already recorded.
+    AlreadyRecorded { network: String, account_id: String },
+    /// No deployment to the account is recorded.
+    NotRecorded { network: String, account_id: String },
+}
+
+impl std::error::Error for DeploymentError {}
+impl fmt::Display for DeploymentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::HashMismatch { expected, found } => write!(
+                f,
+                "expected code with hash {}, but the deployed code has hash {}",
+                expected, found
+            ),
+            Self::AlreadyRecorded {
+                network,
+                account_id,
+            } => write!(
+                f,
+                "a deployment to `{}` on {} is already recorded",
+                account_id, network
+            ),
+            Self::NotRecorded {
+                network,
+                account_id,
+            } => write!(
+                f,
+                "no deployment to `{}` on {} is recorded",
+                account_id, network
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_deployments() {
+        let code = WasmHash::of(b"\0asm");
+        let mut metadata: AbiMetadata = serde_json::from_value(json!({
+            "wasm_hash": code.to_string(),
+            "deployments": [{
+                "network": "testnet",
+                "account_id": "token.testnet",
+                "wasm_hash": code.to_string(),
+                "block_height": 180000000
+            }]
+        }))
+        .unwrap();
+
+        let mainnet = AbiDeployment {
+            network: "mainnet".to_string(),
+            account_id: "token.near".to_string(),
+            wasm_hash: code,
+            block_height: 130000000,
+        };
+        metadata.add_deployment(mainnet.clone()).unwrap();
+        assert_eq!(
+            metadata.add_deployment(mainnet.clone()),
+            Err(DeploymentError::AlreadyRecorded {
+                network: "mainnet".to_string(),
+                account_id: "token.near".to_string(),
+            })
+        );
+        let other = WasmHash::of(b"other");
+        let err = metadata
+            .add_deployment(AbiDeployment {
+                account_id: "token-v2.near".to_string(),
+                wasm_hash: other,
+                ..mainnet.clone()
+            })
+            .unwrap_err();
+        assert_eq!(
+            err,
+            DeploymentError::HashMismatch {
+                expected: code,
+                found: other
+            }
+        );
+
+        assert_eq!(
+            metadata.verify_deployment("mainnet", "token.near", &code),
+            Ok(&mainnet)
+        );
+        assert!(matches!(
+            metadata.verify_deployment("mainnet", "token.near", &other),
+            Err(DeploymentError::HashMismatch { .. })
+        ));
+        assert_eq!(
+            metadata
+                .verify_deployment("mainnet", "token.testnet", &code)
+                .unwrap_err()
+                .to_string(),
+            "no deployment to `token.testnet` on mainnet is recorded"
+        );
+        assert_eq!(metadata.deployments_on("testnet").count(), 1);
+    }
+}
@@ -0,0 +1,151 @@
+//! Deprecation timelines.
+//!
+//! [`AbiRoot::deprecations`] lists the deprecated functions of a contract along with the version
+//! they were deprecated in and the function to migrate to, so that dashboards can track how many
+//! integrations still call deprecated methods.
+
+use crate::{AbiDeprecation, AbiFunction, AbiRoot};
+use semver::Version;
+use serde::Serialize;
+
+/// Phrases of deprecation notes that precede the name of the replacement, in lowercase.
+const REPLACEMENT_MARKERS: [&str; 6] = [
+    "use ",
+    "replaced by ",
+    "superseded by ",
+    "in favor of ",
+    "in favour of ",
+    "migrate to ",
+];
+
+/// A deprecated function.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Deprecation {
+    pub method: String,
+    /// Contract version the function was deprecated in.
+    pub deprecated_in: Option<String>,
+    /// Function to call instead, as suggested by the deprecation note.
+    pub replacement: Option<String>,
+    pub note: Option<String>,
+}
+
+impl Deprecation {
+    fn new(function: &AbiFunction, deprecation: &AbiDeprecation) -> Self {
+        Self {
+            method: function.name.clone(),
+            deprecated_in: deprecation.since.clone(),
+            replacement: deprecation.note.as_deref().and_then(parse_replacement),
+            note: deprecation.note.clone(),
+        }
+    }
+}
+
+pub(crate) fn timeline(abi: &AbiRoot) -> Vec<Deprecation> {
+    let mut deprecations: Vec<_> = abi
+        .body
+        .functions
+        .iter()
+        .filter_map(|function| {
+            let deprecation = function.deprecated.as_ref()?;
+            Some(Deprecation::new(function, deprecation))
+        })
+        .collect();
+    // Versions that are not semver are compared as strings, after all semver ones.
+    deprecations.sort_by_cached_key(|deprecation| match &deprecation.deprecated_in {
+        Some(since) => match Version::parse(since.trim_start_matches('v')) {
+            Ok(version) => (0, Some(version), None),
+            Err(_) => (1, None, Some(since.clone())),
+        },
+        None => (2, None, None),
+    });
+    deprecations
+}
+
+/// Extracts the replacement suggested by a deprecation note, e.g. `ft_transfer` from
+/// "Use `ft_transfer` instead." Bare words are only accepted when followed by "instead", to avoid
+/// picking up phrases like "use with care". Markers only match whole words, so that "because"
+/// is not taken for "use".
+fn parse_replacement(note: &str) -> Option<String> {
+    let lowercase = note.to_ascii_lowercase();
+    REPLACEMENT_MARKERS.iter().find_map(|marker| {
+        let (index, _) = lowercase.match_indices(marker).find(|(index, _)| {
+            lowercase[..*index]
+                .chars()
+                .next_back()
+                .map_or(true, |c| c.is_whitespace() || c.is_ascii_punctuation())
+        })?;
+        let start = index + marker.len();
+        let rest = &note[start..];
+        let name = match rest.strip_prefix('`') {
+            Some(quoted) => quoted.split('`').next()?,
+            None => {
+                let (word, after) = rest.split_once(' ').unwrap_or((rest, ""));
+                let word = word.trim_end_matches(|c: char| c.is_ascii_punctuation() && c != ')');
+                if *marker == "use " && !after.to_ascii_lowercase().starts_with("instead") {
+                    return None;
+                }
+                word
+            }
+        };
+        let name = name.trim_end_matches("()");
+        (!name.is_empty()).then(|| name.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_deprecations() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    { "name": "transfer", "kind": "call", "deprecated": { "since": "1.10.0", "note": "Use `ft_transfer()` instead." } },
+                    { "name": "get_owner", "kind": "view", "deprecated": { "note": "Use with care" } },
+                    { "name": "ft_transfer", "kind": "call" },
+                    { "name": "balance", "kind": "view", "deprecated": { "since": "1.2.0", "note": "Replaced by ft_balance_of." } }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            abi.deprecations(),
+            [
+                Deprecation {
+                    method: "balance".to_string(),
+                    deprecated_in: Some("1.2.0".to_string()),
+                    replacement: Some("ft_balance_of".to_string()),
+                    note: Some("Replaced by ft_balance_of.".to_string()),
+                },
+                Deprecation {
+                    method: "transfer".to_string(),
+                    deprecated_in: Some("1.10.0".to_string()),
+                    replacement: Some("ft_transfer".to_string()),
+                    note: Some("Use `ft_transfer()` instead.".to_string()),
+                },
+                Deprecation {
+                    method: "get_owner".to_string(),
+                    deprecated_in: None,
+                    replacement: None,
+                    note: Some("Use with care".to_string()),
+                },
+            ]
+        );
+        assert_eq!(
+            parse_replacement("use ft_transfer instead"),
+            Some("ft_transfer".to_string())
+        );
+        assert_eq!(parse_replacement("Deprecated because `ft_old` leaks"), None);
+        assert_eq!(
+            parse_replacement("Deprecated because it leaks, (use `ft_new`)."),
+            Some("ft_new".to_string())
+        );
+    }
+}
@@ -0,0 +1,1963 @@
+//! Differences between two versions of a contract ABI.
+//!
+//! [`AbiDiff`] matches functions by name and breaks the changes of every function down into
+//! [`MemberChange`]s: its kind, modifiers, parameters, result and documentation. Changed types
+//! are described by the [`SchemaChange`]s between their old and new schemas, both for the
+//! parameters and results of functions and for the root schema definitions they reference.
+//! Whether a changed type accepts more or fewer values is decided structurally by
+//! [`json_compat_errors`] and [`borsh_compat_errors`], following references into the root schema
+//! definitions.
+//!
+//! [`AbiDiffOptions`] leaves out changes that do not matter to a given use, e.g. documentation
+//! changes or test-only functions, so CI gates only fail on meaningful interface changes.
+//!
+//! Functions are matched by name. Removed and added functions whose signatures are
+//! identical are reported as [`AbiRename`]s instead, so that upgrade reviews show the intent of
+//! the change rather than an unrelated removal and addition.
+//!
+//! Parameter order is part of the interface of functions taking Borsh parameters or JSON
+//! parameters that may be passed positionally. [`AbiDiff::breaking_reorders`] reports such
+//! functions whose parameters were reordered.
+
+use crate::analysis::{borsh_compat_errors, json_compat_errors, BorshCompatRules};
+use crate::walk::{function_json_schemas, reachable_definitions, TypeSite};
+use crate::{
+    transform, AbiBody, AbiFunction, AbiFunctionKind, AbiFunctionModifier, AbiParameters,
+    AbiResultKind, AbiReturn, AbiRoot, AbiStability, AbiType,
+};
+use schemars::schema::{RootSchema, Schema};
+use semver::Version;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Differences between the functions of two ABIs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AbiDiff {
+    /// Functions only present in the new ABI, in the order they are declared.
+    pub added: Vec<AbiFunction>,
+    /// Functions only present in the old ABI, in the order they were declared.
+    pub removed: Vec<AbiFunction>,
+    /// Functions present in both ABIs whose declarations differ.
+    pub changed: Vec<AbiFunctionChange>,
+    /// Functions that are likely to have been renamed.
+    pub renamed: Vec<AbiRename>,
+    /// Root schema definitions added, removed or changed, sorted by name.
+    pub definitions: Vec<DefinitionChange>,
+}
+
+/// Configuration of the changes an [`AbiDiff`] reports, e.g.
+/// `AbiDiffOptions::new().ignore_docs(true).ignore_namespace("test")`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AbiDiffOptions {
+    /// Ignores documentation changes: the documentation of functions and callbacks, and the
+    /// annotations of schemas (`title`, `description`, `examples` and `$comment`).
+    pub ignore_docs: bool,
+    /// Ignores [`MemberChange::Metadata`] changes, e.g. of gas estimates or deprecation.
+    pub ignore_metadata: bool,
+    /// Names of the functions left out of the comparison.
+    pub ignored_functions: BTreeSet<String>,
+    /// Namespaces whose functions are left out of the comparison.
+    pub ignored_namespaces: BTreeSet<String>,
+}
+
+impl AbiDiffOptions {
+    /// Options reporting every change, like [`AbiDiff::new`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ignore_docs(mut self, ignore: bool) -> Self {
+        self.ignore_docs = ignore;
+        self
+    }
+
+    pub fn ignore_metadata(mut self, ignore: bool) -> Self {
+        self.ignore_metadata = ignore;
+        self
+    }
+
+    /// Leaves the function named `name` out of the comparison.
+    pub fn ignore_function(mut self, name: impl Into<String>) -> Self {
+        self.ignored_functions.insert(name.into());
+        self
+    }
+
+    /// Leaves the functions of `namespace` out of the comparison.
+    pub fn ignore_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.ignored_namespaces.insert(namespace.into());
+        self
+    }
+
+    /// Compares the functions of `old` and `new`, leaving out the ignored changes.
+    ///
+    /// When functions are ignored, so are the root schema definitions only they use.
+    pub fn diff(&self, old: &AbiRoot, new: &AbiRoot) -> AbiDiff {
+        let (old, new) = (self.prepare(&old.body), self.prepare(&new.body));
+        let mut diff = AbiDiff::between(
+            &old.functions,
+            &old.root_schema,
+            &new.functions,
+            &new.root_schema,
+        );
+        if self.ignore_metadata {
+            for change in &mut diff.changed {
+                change
+                    .members
+                    .retain(|member| *member != MemberChange::Metadata);
+            }
+            diff.changed.retain(|change| !change.members.is_empty());
+        }
+        diff
+    }
+
+    fn ignores(&self, function: &AbiFunction) -> bool {
+        self.ignored_functions.contains(&function.name)
+            || function.namespace.as_ref().map_or(false, |namespace| {
+                self.ignored_namespaces.contains(namespace)
+            })
+    }
+
+    /// Copy of `body` without the ignored functions and documentation.
+    fn prepare(&self, body: &AbiBody) -> AbiBody {
+        let mut body = body.clone();
+        let count = body.functions.len();
+        body.functions.retain(|function| !self.ignores(function));
+        if body.functions.len() != count {
+            transform::prune_definitions(&mut body);
+        }
+        if self.ignore_docs {
+            for function in &mut body.functions {
+                function.doc = None;
+                for callback in &mut function.callbacks {
+                    callback.doc = None;
+                }
+            }
+            transform::minify_schemas(&mut body);
+        }
+        body
+    }
+}
+
+/// A function declared differently in the two ABIs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiFunctionChange {
+    pub old: AbiFunction,
+    pub new: AbiFunction,
+    /// What changed in the declaration, in the order of [`MemberChange`] variants.
+    pub members: Vec<MemberChange>,
+}
+
+/// A change to one part of the declaration of a function.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MemberChange {
+    Kind {
+        old: AbiFunctionKind,
+        new: AbiFunctionKind,
+    },
+    ModifierAdded(AbiFunctionModifier),
+    ModifierRemoved(AbiFunctionModifier),
+    /// Parameters switched between JSON and Borsh serialization. Individual parameters are not
+    /// compared in that case.
+    ParamsSerialization,
+    ParamAdded {
+        name: String,
+        ty: AbiType,
+        /// Whether callers can omit the parameter, i.e. it is a nullable JSON parameter.
+        optional: bool,
+    },
+    ParamRemoved {
+        name: String,
+        ty: AbiType,
+    },
+    /// The type of a parameter present in both declarations changed.
+    ParamType {
+        name: String,
+        change: TypeChange,
+    },
+    /// The parameters present in both declarations are in a different order, see
+    /// [`AbiFunctionChange::param_reorder`].
+    ParamOrder,
+    /// The callbacks of the function changed.
+    Callbacks,
+    ResultAdded(AbiType),
+    ResultRemoved(AbiType),
+    ResultType(TypeChange),
+    ResultKind {
+        old: AbiResultKind,
+        new: AbiResultKind,
+    },
+    Doc,
+    /// Any other part of the declaration changed, e.g. its gas estimate or deprecation.
+    Metadata,
+}
+
+/// A type used in both declarations of a function that changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypeChange {
+    pub old: AbiType,
+    pub new: AbiType,
+    /// Changes between the type schemas. A change of serialization is reported as a single change
+    /// of the whole type.
+    pub schema: Vec<SchemaChange>,
+    /// Whether the new type accepts every value of the old type.
+    pub widens: bool,
+    /// Whether the old type accepts every value of the new type.
+    pub narrows: bool,
+}
+
+/// A root schema definition added, removed, renamed or changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DefinitionChange {
+    pub name: String,
+    /// The name of the new definition if it was renamed, i.e. removed and replaced by a single
+    /// added definition accepting the same values.
+    pub new_name: Option<String>,
+    /// The old definition, `None` if it was added.
+    pub old: Option<Schema>,
+    /// The new definition, `None` if it was removed.
+    pub new: Option<Schema>,
+    /// Changes between the two definitions, when both exist.
+    pub schema: Vec<SchemaChange>,
+    /// Whether the new definition accepts every value of the old one, `false` unless both exist.
+    pub widens: bool,
+    /// Whether the old definition accepts every value of the new one, `false` unless both exist.
+    pub narrows: bool,
+    /// Whether the parameters or callbacks of functions use the definition, directly or through
+    /// other definitions, in either ABI.
+    pub in_params: bool,
+    /// Whether the results of functions use the definition, directly or through other
+    /// definitions, in either ABI.
+    pub in_results: bool,
+}
+
+/// A keyword or value of a schema added, removed or changed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaChange {
+    /// JSON Pointer of the change inside the schema, empty for the whole schema.
+    pub path: String,
+    /// The old value, `None` if it was added.
+    pub old: Option<Value>,
+    /// The new value, `None` if it was removed.
+    pub new: Option<Value>,
+}
+
+/// Lists the differences between two JSON values, typically serialized schemas, by comparing
+/// objects key by key and arrays index by index.
+pub fn schema_changes(old: &Value, new: &Value) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    collect_schema_changes(old, new, "", &mut changes);
+    changes
+}
+
+fn collect_schema_changes(old: &Value, new: &Value, path: &str, changes: &mut Vec<SchemaChange>) {
+    let change = |old: Option<&Value>, new: Option<&Value>, path: String| SchemaChange {
+        path,
+        old: old.cloned(),
+        new: new.cloned(),
+    };
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let keys: BTreeSet<&String> = old_map.keys().chain(new_map.keys()).collect();
+            for key in keys {
+                let path = format!("{}/{}", path, key.replace('~', "~0").replace('/', "~1"));
+                match (old_map.get(key), new_map.get(key)) {
+                    (Some(old), Some(new)) => collect_schema_changes(old, new, &path, changes),
+                    (old, new) => changes.push(change(old, new, path)),
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            for i in 0..old_items.len().max(new_items.len()) {
+                let path = format!("{}/{}", path, i);
+                match (old_items.get(i), new_items.get(i)) {
+                    (Some(old), Some(new)) => collect_schema_changes(old, new, &path, changes),
+                    (old, new) => changes.push(change(old, new, path)),
+                }
+            }
+        }
+        (old, new) if old != new => changes.push(change(Some(old), Some(new), path.to_string())),
+        _ => {}
+    }
+}
+
+impl TypeChange {
+    fn new(old: AbiType, new: AbiType, old_root: &RootSchema, new_root: &RootSchema) -> Self {
+        let schema = type_schema_changes(&old, &new);
+        let (widens, narrows) = match (&old, &new) {
+            (AbiType::Json { type_schema: old }, AbiType::Json { type_schema: new }) => (
+                json_compat_errors(old_root, old, new_root, new).is_empty(),
+                json_compat_errors(new_root, new, old_root, old).is_empty(),
+            ),
+            (AbiType::Borsh { type_schema: old }, AbiType::Borsh { type_schema: new }) => {
+                let rules = BorshCompatRules::default();
+                (
+                    borsh_compat_errors(old, new, &rules).is_empty(),
+                    borsh_compat_errors(new, old, &rules).is_empty(),
+                )
+            }
+            _ => (false, false),
+        };
+        Self {
+            old,
+            new,
+            schema,
+            widens,
+            narrows,
+        }
+    }
+
+    fn variance(&self) -> Variance {
+        match (self.widens, self.narrows) {
+            (true, true) => Variance::Same,
+            (true, false) => Variance::Widened,
+            (false, true) => Variance::Narrowed,
+            (false, false) => Variance::Changed,
+        }
+    }
+}
+
+fn type_schema_changes(old: &AbiType, new: &AbiType) -> Vec<SchemaChange> {
+    let old_value = serde_json::to_value(old).unwrap();
+    let new_value = serde_json::to_value(new).unwrap();
+    if old_value["serialization_type"] == new_value["serialization_type"] {
+        schema_changes(&old_value["type_schema"], &new_value["type_schema"])
+    } else {
+        schema_changes(&old_value, &new_value)
+    }
+}
+
+fn param_types(function: &AbiFunction) -> Vec<(&str, AbiType)> {
+    match &function.params {
+        AbiParameters::Json { args } => args
+            .iter()
+            .map(|arg| {
+                let ty = AbiType::Json {
+                    type_schema: arg.type_schema.clone(),
+                };
+                (arg.name.as_str(), ty)
+            })
+            .collect(),
+        AbiParameters::Borsh { args } => args
+            .iter()
+            .map(|arg| {
+                let ty = AbiType::Borsh {
+                    type_schema: arg.type_schema.clone(),
+                };
+                (arg.name.as_str(), ty)
+            })
+            .collect(),
+    }
+}
+
+fn common_names<'a>(params: &[(&'a str, AbiType)], other: &[(&str, AbiType)]) -> Vec<&'a str> {
+    params
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| other.iter().any(|(other, _)| other == name))
+        .collect()
+}
+
+fn member_changes(
+    old: &AbiFunction,
+    new: &AbiFunction,
+    old_root: &RootSchema,
+    new_root: &RootSchema,
+) -> Vec<MemberChange> {
+    let mut changes = Vec::new();
+    if old.kind != new.kind {
+        changes.push(MemberChange::Kind {
+            old: old.kind.clone(),
+            new: new.kind.clone(),
+        });
+    }
+    for modifier in &new.modifiers {
+        if !old.modifiers.contains(modifier) {
+            changes.push(MemberChange::ModifierAdded(modifier.clone()));
+        }
+    }
+    for modifier in &old.modifiers {
+        if !new.modifiers.contains(modifier) {
+            changes.push(MemberChange::ModifierRemoved(modifier.clone()));
+        }
+    }
+
+    let same_serialization = matches!(
+        (&old.params, &new.params),
+        (AbiParameters::Json { .. }, AbiParameters::Json { .. })
+            | (AbiParameters::Borsh { .. }, AbiParameters::Borsh { .. })
+    ) || (old.params.is_empty() && new.params.is_empty());
+    if !same_serialization {
+        changes.push(MemberChange::ParamsSerialization);
+    } else {
+        let (old_params, new_params) = (param_types(old), param_types(new));
+        let find = |params: &[(&str, AbiType)], name: &str| {
+            params
+                .iter()
+                .find(|(other, _)| *other == name)
+                .map(|(_, ty)| ty.clone())
+        };
+        for (name, ty) in &new_params {
+            if find(&old_params, name).is_none() {
+                changes.push(MemberChange::ParamAdded {
+                    name: name.to_string(),
+                    ty: ty.clone(),
+                    optional: matches!(ty, AbiType::Json { .. }) && ty.is_nullable(new_root),
+                });
+            }
+        }
+        for (name, ty) in &old_params {
+            match find(&new_params, name) {
+                None => changes.push(MemberChange::ParamRemoved {
+                    name: name.to_string(),
+                    ty: ty.clone(),
+                }),
+                Some(new_ty) if new_ty != *ty => changes.push(MemberChange::ParamType {
+                    name: name.to_string(),
+                    change: TypeChange::new(ty.clone(), new_ty, old_root, new_root),
+                }),
+                Some(_) => {}
+            }
+        }
+        if common_names(&old_params, &new_params) != common_names(&new_params, &old_params) {
+            changes.push(MemberChange::ParamOrder);
+        }
+    }
+
+    if old.callbacks != new.callbacks || old.callbacks_vec != new.callbacks_vec {
+        changes.push(MemberChange::Callbacks);
+    }
+    match (&old.result, &new.result) {
+        (None, Some(new)) => changes.push(MemberChange::ResultAdded(new.clone())),
+        (Some(old), None) => changes.push(MemberChange::ResultRemoved(old.clone())),
+        (Some(old), Some(new)) if old != new => changes.push(MemberChange::ResultType(
+            TypeChange::new(old.clone(), new.clone(), old_root, new_root),
+        )),
+        _ => {}
+    }
+    if old.result_kind != new.result_kind {
+        changes.push(MemberChange::ResultKind {
+            old: old.result_kind,
+            new: new.result_kind,
+        });
+    }
+    if old.doc != new.doc {
+        changes.push(MemberChange::Doc);
+    }
+
+    let mut rest = new.clone();
+    rest.kind = old.kind.clone();
+    rest.modifiers = old.modifiers.clone();
+    rest.params = old.params.clone();
+    rest.callbacks = old.callbacks.clone();
+    rest.callbacks_vec = old.callbacks_vec.clone();
+    rest.result = old.result.clone();
+    rest.result_kind = old.result_kind;
+    rest.doc = old.doc.clone();
+    if rest != *old {
+        changes.push(MemberChange::Metadata);
+    }
+    changes
+}
+
+/// Root schema definitions used by the parameters and callbacks of `functions`, and those used by
+/// their results.
+fn definition_uses(
+    functions: &[AbiFunction],
+    root_schema: &RootSchema,
+) -> (BTreeSet<String>, BTreeSet<String>) {
+    let (mut params, mut results) = (Vec::new(), Vec::new());
+    for function in functions {
+        for (site, schema) in function_json_schemas(function) {
+            match site {
+                TypeSite::Result => results.push(schema),
+                _ => params.push(schema),
+            }
+        }
+    }
+    (
+        reachable_definitions(root_schema, params),
+        reachable_definitions(root_schema, results),
+    )
+}
+
+fn definition_changes(
+    old_functions: &[AbiFunction],
+    old_root: &RootSchema,
+    new_functions: &[AbiFunction],
+    new_root: &RootSchema,
+) -> Vec<DefinitionChange> {
+    let (old_params, old_results) = definition_uses(old_functions, old_root);
+    let (new_params, new_results) = definition_uses(new_functions, new_root);
+    let names: BTreeSet<&String> = old_root
+        .definitions
+        .keys()
+        .chain(new_root.definitions.keys())
+        .collect();
+    let mut changes: Vec<DefinitionChange> = names
+        .into_iter()
+        .filter_map(|name| {
+            let old_schema = old_root.definitions.get(name);
+            let new_schema = new_root.definitions.get(name);
+            if old_schema == new_schema {
+                return None;
+            }
+            let (schema, widens, narrows) = match (old_schema, new_schema) {
+                (Some(old), Some(new)) => (
+                    schema_changes(
+                        &serde_json::to_value(old).unwrap(),
+                        &serde_json::to_value(new).unwrap(),
+                    ),
+                    json_compat_errors(old_root, old, new_root, new).is_empty(),
+                    json_compat_errors(new_root, new, old_root, old).is_empty(),
+                ),
+                _ => (Vec::new(), false, false),
+            };
+            Some(DefinitionChange {
+                name: name.clone(),
+                new_name: None,
+                old: old_schema.cloned(),
+                new: new_schema.cloned(),
+                schema,
+                widens,
+                narrows,
+                in_params: old_params.contains(name) || new_params.contains(name),
+                in_results: old_results.contains(name) || new_results.contains(name),
+            })
+        })
+        .collect();
+    detect_definition_renames(&mut changes, old_root, new_root);
+    changes
+}
+
+/// Merges removed definitions with the single added definition accepting the same values into
+/// renames. Definitions equivalent to several definitions on either side are ambiguous and left
+/// as removals and additions.
+fn detect_definition_renames(
+    changes: &mut Vec<DefinitionChange>,
+    old_root: &RootSchema,
+    new_root: &RootSchema,
+) {
+    let equivalent = |old: &Schema, new: &Schema| {
+        json_compat_errors(old_root, old, new_root, new).is_empty()
+            && json_compat_errors(new_root, new, old_root, old).is_empty()
+    };
+    let removed: Vec<usize> = (0..changes.len())
+        .filter(|&i| changes[i].new.is_none())
+        .collect();
+    let added: Vec<usize> = (0..changes.len())
+        .filter(|&i| changes[i].old.is_none())
+        .collect();
+    let mut renames = Vec::new();
+    for &i in &removed {
+        let old = changes[i].old.as_ref().unwrap();
+        let matches: Vec<usize> = added
+            .iter()
+            .copied()
+            .filter(|&j| equivalent(old, changes[j].new.as_ref().unwrap()))
+            .collect();
+        if let [j] = matches[..] {
+            let new = changes[j].new.as_ref().unwrap();
+            let unique = removed
+                .iter()
+                .filter(|&&k| equivalent(changes[k].old.as_ref().unwrap(), new))
+                .count()
+                == 1;
+            if unique {
+                renames.push((i, j));
+            }
+        }
+    }
+    for &(i, j) in &renames {
+        let added = changes[j].clone();
+        let change = &mut changes[i];
+        change.schema = schema_changes(
+            &serde_json::to_value(&change.old).unwrap(),
+            &serde_json::to_value(&added.new).unwrap(),
+        );
+        change.new_name = Some(added.name);
+        change.new = added.new;
+        change.widens = true;
+        change.narrows = true;
+        change.in_params |= added.in_params;
+        change.in_results |= added.in_results;
+    }
+    let merged: BTreeSet<usize> = renames.into_iter().map(|(_, j)| j).collect();
+    *changes = std::mem::take(changes)
+        .into_iter()
+        .enumerate()
+        .filter(|(j, _)| !merged.contains(j))
+        .map(|(_, change)| change)
+        .collect();
+}
+
+impl AbiFunctionChange {
+    /// The reordering of the parameters of the function, if the new declaration takes the same
+    /// parameters as the old one in a different order.
+    pub fn param_reorder(&self) -> Option<ParamReorder> {
+        let (old, new) = (param_names(&self.old), param_names(&self.new));
+        let same_params = match (&self.old.params, &self.new.params) {
+            (AbiParameters::Json { args: old_args }, AbiParameters::Json { args: new_args }) => {
+                old_args.len() == new_args.len()
+                    && old_args.iter().all(|arg| new_args.contains(arg))
+            }
+            (AbiParameters::Borsh { args: old_args }, AbiParameters::Borsh { args: new_args }) => {
+                old_args.len() == new_args.len()
+                    && old_args.iter().all(|arg| new_args.contains(arg))
+            }
+            _ => false,
+        };
+        if !same_params || old == new {
+            return None;
+        }
+        Some(ParamReorder {
+            function: self.new.name.clone(),
+            old,
+            new,
+            breaking: self.old.param_order_matters() || self.new.param_order_matters(),
+        })
+    }
+}
+
+/// Parameters of a function declared in a different order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamReorder {
+    pub function: String,
+    /// Parameter names in the old order.
+    pub old: Vec<String>,
+    /// Parameter names in the new order.
+    pub new: Vec<String>,
+    /// Whether callers passing the parameters in the old order break, see
+    /// [`AbiFunction::param_order_matters`].
+    pub breaking: bool,
+}
+
+fn param_names(function: &AbiFunction) -> Vec<String> {
+    match &function.params {
+        AbiParameters::Json { args } => args.iter().map(|arg| arg.name.clone()).collect(),
+        AbiParameters::Borsh { args } => args.iter().map(|arg| arg.name.clone()).collect(),
+    }
+}
+
+/// A function removed from the old ABI and added to the new one under a different name, with an
+/// identical signature.
+///
+/// Documentation, namespace and gas estimates may differ between the two declarations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiRename {
+    pub old: AbiFunction,
+    pub new: AbiFunction,
+}
+
+impl AbiDiff {
+    /// Compares the functions of `old` and `new`.
+    pub fn new(old: &AbiRoot, new: &AbiRoot) -> Self {
+        Self::between(
+            &old.body.functions,
+            &old.body.root_schema,
+            &new.body.functions,
+            &new.body.root_schema,
+        )
+    }
+
+    /// Compares two sets of functions along with the root schemas their types refer to.
+    pub(crate) fn between(
+        old_functions: &[AbiFunction],
+        old_root_schema: &RootSchema,
+        new_functions: &[AbiFunction],
+        new_root_schema: &RootSchema,
+    ) -> Self {
+        let find = |functions: &[AbiFunction], name: &str| {
+            functions
+                .iter()
+                .find(|function| function.name == name)
+                .cloned()
+        };
+        let mut diff = AbiDiff::default();
+        for function in old_functions {
+            match find(new_functions, &function.name) {
+                Some(new_function) if new_function != *function => {
+                    diff.changed.push(AbiFunctionChange {
+                        members: member_changes(
+                            function,
+                            &new_function,
+                            old_root_schema,
+                            new_root_schema,
+                        ),
+                        old: function.clone(),
+                        new: new_function,
+                    })
+                }
+                Some(_) => {}
+                None => diff.removed.push(function.clone()),
+            }
+        }
+        for function in new_functions {
+            if find(old_functions, &function.name).is_none() {
+                diff.added.push(function.clone());
+            }
+        }
+        diff.detect_renames();
+        diff.definitions = definition_changes(
+            old_functions,
+            old_root_schema,
+            new_functions,
+            new_root_schema,
+        );
+        diff
+    }
+
+    /// Returns `true` if both ABIs declare the same functions and root schema definitions.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.renamed.is_empty()
+            && self.definitions.is_empty()
+    }
+
+    /// Changed functions whose parameters were reordered in a way that breaks callers.
+    pub fn breaking_reorders(&self) -> Vec<ParamReorder> {
+        self.changed
+            .iter()
+            .filter_map(AbiFunctionChange::param_reorder)
+            .filter(|reorder| reorder.breaking)
+            .collect()
+    }
+
+    /// Renders the differences as a CHANGELOG section for the `new_version` release, listing
+    /// added, changed, renamed and removed methods with their signatures, and changed types.
+    pub fn to_changelog(&self, old_version: &str, new_version: &str) -> String {
+        let mut changelog = format!("## [{}]\n\nChanges since {}.\n", new_version, old_version);
+        if self.is_empty() {
+            changelog.push_str("\nNo interface changes.\n");
+            return changelog;
+        }
+        let mut section = |title: &str, entries: Vec<String>| {
+            if !entries.is_empty() {
+                changelog.push_str(&format!("\n### {}\n\n", title));
+                for entry in entries {
+                    changelog.push_str(&format!("- {}\n", entry));
+                }
+            }
+        };
+        section(
+            "Added",
+            self.added
+                .iter()
+                .map(|function| match function.stability {
+                    Some(AbiStability::Unstable) => format!("`{}` (unstable)", signature(function)),
+                    _ => format!("`{}`", signature(function)),
+                })
+                .collect(),
+        );
+        section(
+            "Changed",
+            self.changed
+                .iter()
+                .map(|change| {
+                    let (old, new) = (signature(&change.old), signature(&change.new));
+                    if old != new {
+                        return format!("`{}` → `{}`", old, new);
+                    }
+                    // Type schemas may change without their display names changing.
+                    let hidden: Vec<String> =
+                        change.members.iter().filter_map(member_summary).collect();
+                    if hidden.is_empty() {
+                        format!("`{}`: documentation or metadata updated", change.new.name)
+                    } else {
+                        format!("`{}`: {}", change.new.name, hidden.join(", "))
+                    }
+                })
+                .collect(),
+        );
+        section(
+            "Renamed",
+            self.renamed
+                .iter()
+                .map(|rename| format!("`{}` → `{}`", rename.old.name, rename.new.name))
+                .collect(),
+        );
+        section(
+            "Removed",
+            self.removed
+                .iter()
+                .map(|function| format!("`{}`", signature(function)))
+                .collect(),
+        );
+        section(
+            "Types",
+            self.definitions
+                .iter()
+                .map(
+                    |change| match (&change.old, &change.new, &change.new_name) {
+                        (_, _, Some(new_name)) => {
+                            format!("`{}` renamed to `{}`", change.name, new_name)
+                        }
+                        (None, _, _) => format!("`{}` added", change.name),
+                        (_, None, _) => format!("`{}` removed", change.name),
+                        _ => format!("`{}` changed", change.name),
+                    },
+                )
+                .collect(),
+        );
+        changelog
+    }
+
+    /// Pairs up removed and added functions with identical signatures. Signatures matching
+    /// several functions on either side are ambiguous and left as removals and additions.
+    fn detect_renames(&mut self) {
+        let count = |functions: &[AbiFunction], function: &AbiFunction| {
+            functions
+                .iter()
+                .filter(|other| same_signature(other, function))
+                .count()
+        };
+        let mut renames = Vec::new();
+        for (i, removed) in self.removed.iter().enumerate() {
+            if count(&self.removed, removed) != 1 || count(&self.added, removed) != 1 {
+                continue;
+            }
+            let j = self
+                .added
+                .iter()
+                .position(|added| same_signature(added, removed))
+                .expect("a matching function was counted");
+            renames.push((i, j));
+        }
+        let mut removed: Vec<Option<AbiFunction>> = self.removed.drain(..).map(Some).collect();
+        let mut added: Vec<Option<AbiFunction>> = self.added.drain(..).map(Some).collect();
+        for (i, j) in renames {
+            if let (Some(old), Some(new)) = (removed[i].take(), added[j].take()) {
+                self.renamed.push(AbiRename { old, new });
+            }
+        }
+        self.removed = removed.into_iter().flatten().collect();
+        self.added = added.into_iter().flatten().collect();
+    }
+}
+
+/// Changelog wording of `member`, `None` for documentation and metadata changes.
+fn member_summary(member: &MemberChange) -> Option<String> {
+    let summary = match member {
+        MemberChange::Kind { .. } => "kind changed".to_string(),
+        MemberChange::ModifierAdded(modifier) => format!("`#[{}]` added", modifier.as_str()),
+        MemberChange::ModifierRemoved(modifier) => format!("`#[{}]` removed", modifier.as_str()),
+        MemberChange::ParamsSerialization => "serialization of the parameters changed".to_string(),
+        MemberChange::ParamAdded { name, .. } => format!("parameter `{}` added", name),
+        MemberChange::ParamRemoved { name, .. } => format!("parameter `{}` removed", name),
+        MemberChange::ParamType { name, .. } => format!("type of parameter `{}` changed", name),
+        MemberChange::ParamOrder => "parameters reordered".to_string(),
+        MemberChange::Callbacks => "callbacks changed".to_string(),
+        MemberChange::ResultAdded(_) => "result added".to_string(),
+        MemberChange::ResultRemoved(_) => "result removed".to_string(),
+        MemberChange::ResultType(_) => "result type changed".to_string(),
+        MemberChange::ResultKind { .. } => "result kind changed".to_string(),
+        MemberChange::Doc | MemberChange::Metadata => return None,
+    };
+    Some(summary)
+}
+
+/// Human-readable signature of a function, e.g. `#[payable] call ft_transfer(receiver_id:
+/// AccountId, amount: U128)`.
+fn signature(function: &AbiFunction) -> String {
+    let mut modifiers = function.modifiers.clone();
+    modifiers.sort();
+    let mut signature: String = modifiers
+        .iter()
+        .map(|modifier| format!("#[{}] ", modifier.as_str()))
+        .collect();
+    signature.push_str(match function.kind {
+        AbiFunctionKind::View => "view ",
+        AbiFunctionKind::Call => "call ",
+    });
+    let params: Vec<String> = match &function.params {
+        AbiParameters::Json { args } => args
+            .iter()
+            .map(|arg| {
+                let ty = AbiType::Json {
+                    type_schema: arg.type_schema.clone(),
+                };
+                format!("{}: {}", arg.name, ty.display_name())
+            })
+            .collect(),
+        AbiParameters::Borsh { args } => args
+            .iter()
+            .map(|arg| format!("{}: {}", arg.name, arg.type_schema.declaration()))
+            .collect(),
+    };
+    signature.push_str(&format!("{}({})", function.name, params.join(", ")));
+    let result = match function.returns() {
+        AbiReturn::Value(result) => result.display_name(),
+        AbiReturn::Nothing => "()".to_string(),
+        AbiReturn::Unknown if function.result_kind.is_value() => return signature,
+        AbiReturn::Unknown => "Any".to_string(),
+    };
+    match function.result_kind {
+        AbiResultKind::Value => signature.push_str(&format!(" -> {}", result)),
+        AbiResultKind::Promise => signature.push_str(&format!(" -> Promise<{}>", result)),
+        AbiResultKind::PromiseOrValue => {
+            signature.push_str(&format!(" -> PromiseOrValue<{}>", result))
+        }
+    }
+    signature
+}
+
+/// Whether two functions can be called the same way, regardless of their name and the parts of
+/// their declarations that do not affect callers.
+pub(crate) fn same_signature(a: &AbiFunction, b: &AbiFunction) -> bool {
+    let mut modifiers_a = a.modifiers.clone();
+    let mut modifiers_b = b.modifiers.clone();
+    modifiers_a.sort();
+    modifiers_b.sort();
+    a.kind == b.kind
+        && modifiers_a == modifiers_b
+        && a.params == b.params
+        && a.callbacks
+            .iter()
+            .map(|c| &c.ty)
+            .eq(b.callbacks.iter().map(|c| &c.ty))
+        && a.callbacks_vec == b.callbacks_vec
+        && a.returns() == b.returns()
+        && a.result_kind == b.result_kind
+}
+
+/// How a change affects clients of the contract, from least to most disruptive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChangeClass {
+    /// Only documentation or annotations changed, the interface is the same.
+    DocOnly,
+    /// The interface was extended in a way existing callers are unaffected by.
+    Compatible,
+    /// Existing callers may fail, e.g. a function was removed or a parameter type narrowed.
+    Breaking,
+}
+
+/// Semver component to bump for a release containing a set of changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SemverBump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+/// What a [`ClassifiedChange`] applies to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChangeSubject {
+    Function(String),
+    Definition(String),
+}
+
+/// A single change of an [`AbiDiff`] with its [`ChangeClass`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClassifiedChange {
+    pub subject: ChangeSubject,
+    pub class: ChangeClass,
+    /// Human-readable description of the change.
+    pub description: String,
+}
+
+/// A reason why clients generated from an old ABI fail against a contract implementing a new
+/// one, see [`is_compatible_caller`](crate::is_compatible_caller).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Incompatibility {
+    pub subject: ChangeSubject,
+    pub reason: String,
+}
+
+impl fmt::Display for ChangeSubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Function(name) => write!(f, "function `{}`", name),
+            Self::Definition(name) => write!(f, "definition `{}`", name),
+        }
+    }
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.subject, self.reason)
+    }
+}
+
+/// The changes of an [`AbiDiff`] classified by their effect on clients, see
+/// [`AbiDiff::classify`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Classification {
+    pub changes: Vec<ClassifiedChange>,
+}
+
+impl Classification {
+    /// The most disruptive class among the changes, `None` if there are no changes.
+    pub fn class(&self) -> Option<ChangeClass> {
+        self.changes.iter().map(|change| change.class).max()
+    }
+
+    /// Changes that may break existing callers.
+    pub fn breaking(&self) -> impl Iterator<Item = &ClassifiedChange> {
+        self.changes
+            .iter()
+            .filter(|change| change.class == ChangeClass::Breaking)
+    }
+
+    /// The breaking changes as reasons for existing callers to fail.
+    pub fn incompatibilities(&self) -> Vec<Incompatibility> {
+        self.breaking()
+            .map(|change| Incompatibility {
+                subject: change.subject.clone(),
+                reason: change.description.clone(),
+            })
+            .collect()
+    }
+
+    /// Semver component to bump: major for breaking changes, minor for compatible additions and
+    /// patch for documentation changes.
+    pub fn bump(&self) -> SemverBump {
+        match self.class() {
+            None => SemverBump::None,
+            Some(ChangeClass::DocOnly) => SemverBump::Patch,
+            Some(ChangeClass::Compatible) => SemverBump::Minor,
+            Some(ChangeClass::Breaking) => SemverBump::Major,
+        }
+    }
+
+    /// Suggests the contract version following `current`, e.g. the
+    /// [`AbiMetadata::version`](crate::AbiMetadata::version) of the old ABI.
+    ///
+    /// As with Cargo, while the major version is `0` breaking changes bump the minor version and
+    /// other changes bump the patch version. Pre-release and build metadata are dropped.
+    pub fn next_version(&self, current: &str) -> Result<String, semver::Error> {
+        let current = Version::parse(current)?;
+        let (major, minor, patch) = (current.major, current.minor, current.patch);
+        let next = match (self.bump(), major) {
+            (SemverBump::None, _) => Version::new(major, minor, patch),
+            (SemverBump::Major, 0) => Version::new(0, minor + 1, 0),
+            (SemverBump::Minor | SemverBump::Patch, 0) | (SemverBump::Patch, _) => {
+                Version::new(major, minor, patch + 1)
+            }
+            (SemverBump::Minor, _) => Version::new(major, minor + 1, 0),
+            (SemverBump::Major, _) => Version::new(major + 1, 0, 0),
+        };
+        Ok(next.to_string())
+    }
+}
+
+/// How a type changed with regard to the values it accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Variance {
+    Same,
+    Widened,
+    Narrowed,
+    Changed,
+}
+
+fn modifier_class(modifier: &AbiFunctionModifier, added: bool) -> ChangeClass {
+    use AbiFunctionModifier::*;
+    match (modifier, added) {
+        (Init, _) | (Private, true) | (Payable | IgnoreState, false) => ChangeClass::Breaking,
+        _ => ChangeClass::Compatible,
+    }
+}
+
+fn member_class(change: &AbiFunctionChange, member: &MemberChange) -> (ChangeClass, String) {
+    use ChangeClass::*;
+    let variance_class = |variance, compatible| match variance {
+        Variance::Same => DocOnly,
+        variance if variance == compatible => Compatible,
+        _ => Breaking,
+    };
+    match member {
+        MemberChange::Kind { old, new } => (
+            Breaking,
+            format!("kind changed from {:?} to {:?}", old, new).to_lowercase(),
+        ),
+        MemberChange::ModifierAdded(modifier) => (
+            modifier_class(modifier, true),
+            format!("modifier `{}` added", modifier.as_str()),
+        ),
+        MemberChange::ModifierRemoved(modifier) => (
+            modifier_class(modifier, false),
+            format!("modifier `{}` removed", modifier.as_str()),
+        ),
+        MemberChange::ParamsSerialization => {
+            (Breaking, "parameter serialization changed".to_string())
+        }
+        MemberChange::ParamAdded { name, optional, .. } => {
+            if *optional {
+                (Compatible, format!("optional parameter `{}` added", name))
+            } else {
+                (Breaking, format!("required parameter `{}` added", name))
+            }
+        }
+        // Unknown fields of JSON arguments are ignored, unread Borsh bytes are rejected.
+        MemberChange::ParamRemoved { name, ty } => match ty {
+            AbiType::Json { .. } => (Compatible, format!("parameter `{}` removed", name)),
+            AbiType::Borsh { .. } => (Breaking, format!("parameter `{}` removed", name)),
+        },
+        MemberChange::ParamType { name, change } => {
+            let class = variance_class(change.variance(), Variance::Widened);
+            let description = match class {
+                DocOnly => format!("schema of parameter `{}` changed equivalently", name),
+                Compatible => format!("type of parameter `{}` widened", name),
+                Breaking => format!("type of parameter `{}` changed", name),
+            };
+            (class, description)
+        }
+        MemberChange::ParamOrder => {
+            let breaking = change
+                .param_reorder()
+                .map_or(false, |reorder| reorder.breaking);
+            let class = if breaking { Breaking } else { Compatible };
+            (class, "parameters reordered".to_string())
+        }
+        MemberChange::Callbacks => (Compatible, "callbacks changed".to_string()),
+        MemberChange::ResultAdded(_) => (Compatible, "result added".to_string()),
+        MemberChange::ResultRemoved(_) => (Breaking, "result removed".to_string()),
+        MemberChange::ResultType(change) => {
+            let class = variance_class(change.variance(), Variance::Narrowed);
+            let description = match class {
+                DocOnly => "schema of the result changed equivalently",
+                Compatible => "result type narrowed",
+                Breaking => "result type changed",
+            };
+            (class, description.to_string())
+        }
+        MemberChange::ResultKind { .. } => (Breaking, "result kind changed".to_string()),
+        MemberChange::Doc => (DocOnly, "documentation changed".to_string()),
+        MemberChange::Metadata => (DocOnly, "annotations changed".to_string()),
+    }
+}
+
+fn definition_class(definition: &DefinitionChange) -> (ChangeClass, String) {
+    use ChangeClass::*;
+    if let Some(new_name) = &definition.new_name {
+        return (DocOnly, format!("definition renamed to `{}`", new_name));
+    }
+    let description = match (&definition.old, &definition.new) {
+        (None, _) => return (Compatible, "definition added".to_string()),
+        // Types referencing the definition changed as well and are classified where they are
+        // used.
+        (_, None) => return (Compatible, "definition removed".to_string()),
+        _ if definition.widens && definition.narrows => {
+            return (DocOnly, "definition changed equivalently".to_string())
+        }
+        _ if !definition.in_params && !definition.in_results => {
+            "definition not used by functions changed"
+        }
+        _ if !definition.in_results && definition.widens => "definition widened",
+        _ if !definition.in_params && definition.narrows => "definition narrowed",
+        _ => return (Breaking, "definition changed".to_string()),
+    };
+    (Compatible, description.to_string())
+}
+
+impl AbiDiff {
+    /// Classifies every change as breaking, compatible or documentation-only.
+    ///
+    /// Removed and renamed functions are breaking, added functions are compatible. Changes of a
+    /// function are classified member by member: parameter types may be widened and result types
+    /// narrowed without breaking callers, as decided by [`json_compat_errors`] and
+    /// [`borsh_compat_errors`]. Root schema definitions are classified by where functions use
+    /// them: a definition used by parameters may be widened, one used by results narrowed, and
+    /// one used by both must stay equivalent. Renamed definitions only change documentation.
+    pub fn classify(&self) -> Classification {
+        let mut changes = Vec::new();
+        let mut push = |subject: ChangeSubject, class, description: String| {
+            changes.push(ClassifiedChange {
+                subject,
+                class,
+                description,
+            })
+        };
+        let function = |name: &str| ChangeSubject::Function(name.to_string());
+        for added in &self.added {
+            push(
+                function(&added.name),
+                ChangeClass::Compatible,
+                "function added".to_string(),
+            );
+        }
+        for change in &self.changed {
+            for member in &change.members {
+                let (class, description) = member_class(change, member);
+                push(function(&change.new.name), class, description);
+            }
+        }
+        for rename in &self.renamed {
+            push(
+                function(&rename.old.name),
+                ChangeClass::Breaking,
+                format!("function renamed to `{}`", rename.new.name),
+            );
+        }
+        for removed in &self.removed {
+            push(
+                function(&removed.name),
+                ChangeClass::Breaking,
+                "function removed".to_string(),
+            );
+        }
+        for definition in &self.definitions {
+            let (class, description) = definition_class(definition);
+            push(
+                ChangeSubject::Definition(definition.name.clone()),
+                class,
+                description,
+            );
+        }
+        Classification { changes }
+    }
+}
+
+impl Classification {
+    /// Descriptions of the changes of `class`, grouped by subject in order of first appearance.
+    fn grouped(&self, class: ChangeClass) -> Vec<(&ChangeSubject, Vec<&str>)> {
+        let mut groups: Vec<(&ChangeSubject, Vec<&str>)> = Vec::new();
+        for change in self.changes.iter().filter(|change| change.class == class) {
+            match groups
+                .iter_mut()
+                .find(|(subject, _)| *subject == &change.subject)
+            {
+                Some((_, descriptions)) => descriptions.push(&change.description),
+                None => groups.push((&change.subject, vec![&change.description])),
+            }
+        }
+        groups
+    }
+}
+
+/// Sections of rendered diffs, most disruptive first.
+const SECTIONS: [(ChangeClass, &str); 3] = [
+    (ChangeClass::Breaking, "Breaking changes"),
+    (ChangeClass::Compatible, "Compatible changes"),
+    (ChangeClass::DocOnly, "Documentation changes"),
+];
+
+impl AbiDiff {
+    /// Renders the classified differences as Markdown, e.g. for pull request descriptions and
+    /// release notes. Changes are grouped by [`ChangeClass`], most disruptive first, then by
+    /// function or definition. The plain text rendering is available through `Display`.
+    pub fn to_markdown(&self) -> String {
+        self.render(true)
+    }
+
+    fn render(&self, markdown: bool) -> String {
+        if self.is_empty() {
+            return "No interface changes.\n".to_string();
+        }
+        let classification = self.classify();
+        let (item, nested) = if markdown {
+            ("- ", "  - ")
+        } else {
+            ("  ", "    - ")
+        };
+        let mut sections = Vec::new();
+        for (class, title) in SECTIONS {
+            let groups = classification.grouped(class);
+            if groups.is_empty() {
+                continue;
+            }
+            let mut section = if markdown {
+                format!("### {}\n\n", title)
+            } else {
+                format!("{}:\n", title)
+            };
+            for (subject, descriptions) in groups {
+                match descriptions.as_slice() {
+                    [description] => {
+                        section.push_str(&format!("{}{}: {}\n", item, subject, description))
+                    }
+                    _ => {
+                        section.push_str(&format!("{}{}:\n", item, subject));
+                        for description in descriptions {
+                            section.push_str(&format!("{}{}\n", nested, description));
+                        }
+                    }
+                }
+            }
+            sections.push(section);
+        }
+        sections.join("\n")
+    }
+}
+
+impl fmt::Display for AbiDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.render(false))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::{json, Value};
+
+    fn abi(functions: Value) -> AbiRoot {
+        AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": { "functions": functions, "root_schema": {} }
+        }))
+        .unwrap()
+    }
+
+    fn names(functions: &[AbiFunction]) -> Vec<&str> {
+        functions.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_param_reorders() {
+        let params = |names: &[&str]| {
+            let args: Vec<Value> = names
+                .iter()
+                .map(|name| json!({ "name": name, "type_schema": { "type": "string" } }))
+                .collect();
+            json!({ "serialization_type": "json", "args": args })
+        };
+        let old = abi(json!([
+            { "name": "transfer", "kind": "call", "params": params(&["receiver_id", "amount"]) },
+            { "name": "set_memo", "kind": "call", "args_unordered": true, "params": params(&["key", "memo"]) },
+            { "name": "add_key", "kind": "call", "params": params(&["key"]) }
+        ]));
+        let new = abi(json!([
+            { "name": "transfer", "kind": "call", "params": params(&["amount", "receiver_id"]) },
+            { "name": "set_memo", "kind": "call", "args_unordered": true, "params": params(&["memo", "key"]) },
+            { "name": "add_key", "kind": "call", "params": params(&["key", "allowance"]) }
+        ]));
+
+        let diff = AbiDiff::new(&old, &new);
+        assert_eq!(diff.changed.len(), 3);
+        let reorder = diff.changed[1].param_reorder().unwrap();
+        assert!(!reorder.breaking);
+        assert_eq!(diff.changed[2].param_reorder(), None);
+        assert_eq!(
+            diff.breaking_reorders(),
+            [ParamReorder {
+                function: "transfer".to_string(),
+                old: vec!["receiver_id".to_string(), "amount".to_string()],
+                new: vec!["amount".to_string(), "receiver_id".to_string()],
+                breaking: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_diff_detects_renames() {
+        let string_param = |name: &str| {
+            json!({
+                "serialization_type": "json",
+                "args": [{ "name": name, "type_schema": { "type": "string" } }]
+            })
+        };
+        let old = abi(json!([
+            { "name": "get_owner", "kind": "view", "result": { "serialization_type": "json", "type_schema": { "type": "string" } } },
+            { "name": "set_owner", "kind": "call", "modifiers": ["private"], "params": string_param("owner_id") },
+            { "name": "ping", "kind": "call" },
+            { "name": "pong", "kind": "call" }
+        ]));
+        let new = abi(json!([
+            { "name": "owner", "kind": "view", "doc": " Current owner.", "result": { "serialization_type": "json", "type_schema": { "type": "string" } } },
+            { "name": "set_owner", "kind": "call", "params": string_param("owner_id") },
+            { "name": "ping_v2", "kind": "call", "stability": "unstable" },
+            { "name": "pong_v2", "kind": "call" }
+        ]));
+
+        let diff = AbiDiff::new(&old, &new);
+        assert_eq!(diff.renamed.len(), 1);
+        assert_eq!(diff.renamed[0].old.name, "get_owner");
+        assert_eq!(diff.renamed[0].new.name, "owner");
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].old.name, "set_owner");
+        // Several functions share the same signature, the renames are ambiguous.
+        assert_eq!(names(&diff.removed), ["ping", "pong"]);
+        assert_eq!(names(&diff.added), ["ping_v2", "pong_v2"]);
+
+        assert!(AbiDiff::new(&new, &new).is_empty());
+        assert_eq!(
+            diff.to_changelog("1.0.0", "1.1.0"),
+            "## [1.1.0]
+
+Changes since 1.0.0.
+
+### Added
+
+- `call ping_v2()` (unstable)
+- `call pong_v2()`
+
+### Changed
+
+- `#[private] call set_owner(owner_id: String)` → `call set_owner(owner_id: String)`
+
+### Renamed
+
+- `get_owner` → `owner`
+
+### Removed
+
+- `call ping()`
+- `call pong()`
+"
+        );
+        assert_eq!(
+            AbiDiff::new(&new, &new).to_changelog("1.1.0", "1.1.1"),
+            "## [1.1.1]\n\nChanges since 1.1.0.\n\nNo interface changes.\n"
+        );
+    }
+
+    #[test]
+    fn test_changelog_hidden_changes() {
+        let set_memo = |memo: Value, doc: &str| {
+            json!({
+                "name": "set_memo",
+                "kind": "call",
+                "doc": doc,
+                "params": { "serialization_type": "json", "args": [{ "name": "memo", "type_schema": memo }] }
+            })
+        };
+        let string = json!({ "type": "string" });
+        let old = abi(json!([set_memo(string.clone(), " Sets the memo.")]));
+        let documented = abi(json!([set_memo(string, " Sets the memo of the caller.")]));
+        let bounded = abi(json!([set_memo(
+            json!({ "type": "string", "maxLength": 3 }),
+            " Sets the memo."
+        )]));
+
+        assert!(AbiDiff::new(&old, &documented)
+            .to_changelog("1.0.0", "1.0.1")
+            .ends_with("- `set_memo`: documentation or metadata updated\n"));
+        let diff = AbiDiff::new(&old, &bounded);
+        assert_eq!(diff.classify().bump(), SemverBump::Major);
+        assert!(diff
+            .to_changelog("1.0.0", "2.0.0")
+            .ends_with("- `set_memo`: type of parameter `memo` changed\n"));
+    }
+
+    #[test]
+    fn test_diff_members_and_definitions() {
+        let root = |functions: Value, definitions: Value| {
+            AbiRoot::from_value(json!({
+                "schema_version": SCHEMA_VERSION,
+                "metadata": {},
+                "body": { "functions": functions, "root_schema": { "definitions": definitions } }
+            }))
+            .unwrap()
+        };
+        let params = |args: Value| json!({ "serialization_type": "json", "args": args });
+        let old = root(
+            json!([{
+                "name": "transfer",
+                "kind": "call",
+                "params": params(json!([
+                    { "name": "receiver_id", "type_schema": { "type": "string" } },
+                    { "name": "amount", "type_schema": { "type": "integer", "format": "uint64" } }
+                ]))
+            }]),
+            json!({
+                "Token": { "type": "object", "properties": { "id": { "type": "string" } } },
+                "Legacy": { "type": "string" }
+            }),
+        );
+        let new = root(
+            json!([{
+                "name": "transfer",
+                "kind": "call",
+                "modifiers": ["payable"],
+                "params": params(json!([
+                    { "name": "receiver_id", "type_schema": { "type": "string" } },
+                    { "name": "amount", "type_schema": { "type": "string" } },
+                    { "name": "memo", "type_schema": { "type": "string" } }
+                ])),
+                "result": { "serialization_type": "json", "type_schema": { "type": "boolean" } }
+            }]),
+            json!({
+                "Token": { "type": "object", "properties": { "id": { "type": "integer" } } }
+            }),
+        );
+
+        let diff = crate::diff(&old, &new);
+        assert_eq!(
+            diff.changed[0].members,
+            [
+                MemberChange::ModifierAdded(AbiFunctionModifier::Payable),
+                MemberChange::ParamAdded {
+                    name: "memo".to_string(),
+                    ty: AbiType::Json {
+                        type_schema: serde_json::from_value(json!({ "type": "string" })).unwrap()
+                    },
+                    optional: false,
+                },
+                MemberChange::ParamType {
+                    name: "amount".to_string(),
+                    change: TypeChange {
+                        old: AbiType::Json {
+                            type_schema: serde_json::from_value(
+                                json!({ "type": "integer", "format": "uint64" })
+                            )
+                            .unwrap()
+                        },
+                        new: AbiType::Json {
+                            type_schema: serde_json::from_value(json!({ "type": "string" }))
+                                .unwrap()
+                        },
+                        schema: vec![
+                            SchemaChange {
+                                path: "/format".to_string(),
+                                old: Some(json!("uint64")),
+                                new: None,
+                            },
+                            SchemaChange {
+                                path: "/type".to_string(),
+                                old: Some(json!("integer")),
+                                new: Some(json!("string")),
+                            },
+                        ],
+                        widens: false,
+                        narrows: false,
+                    },
+                },
+                MemberChange::ResultAdded(new.body.functions[0].result.clone().unwrap()),
+            ]
+        );
+
+        let definitions: Vec<(&str, bool, bool)> = diff
+            .definitions
+            .iter()
+            .map(|change| {
+                (
+                    change.name.as_str(),
+                    change.old.is_some(),
+                    change.new.is_some(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            definitions,
+            [("Legacy", true, false), ("Token", true, true)]
+        );
+        assert_eq!(
+            diff.definitions[1].schema,
+            [SchemaChange {
+                path: "/properties/id/type".to_string(),
+                old: Some(json!("string")),
+                new: Some(json!("integer")),
+            }]
+        );
+        assert!(diff
+            .to_changelog("1.0.0", "2.0.0")
+            .ends_with("### Types\n\n- `Legacy` removed\n- `Token` changed\n"));
+        assert!(crate::diff(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_classify() {
+        let params = |args: Value| json!({ "serialization_type": "json", "args": args });
+        let string = json!({ "type": "string" });
+        let old = abi(json!([
+            { "name": "get_balance", "kind": "view", "params": params(json!([
+                { "name": "account_id", "type_schema": { "type": "string", "maxLength": 64 } }
+            ])) },
+            { "name": "transfer", "kind": "call", "params": params(json!([
+                { "name": "receiver_id", "type_schema": string }
+            ])) },
+            { "name": "burn", "kind": "call" },
+            { "name": "get_memo", "kind": "view", "result": {
+                "serialization_type": "json", "type_schema": { "type": ["string", "null"] }
+            } }
+        ]));
+        let mut new = abi(json!([
+            { "name": "get_balance", "kind": "view", "params": params(json!([
+                { "name": "account_id", "type_schema": { "type": "string", "description": "Owner." } },
+                { "name": "at_block", "type_schema": { "type": ["integer", "null"] } }
+            ])) },
+            { "name": "transfer", "kind": "call", "params": params(json!([
+                { "name": "receiver_id", "type_schema": string },
+                { "name": "amount", "type_schema": string }
+            ])) },
+            { "name": "burn", "kind": "call", "doc": " Burns tokens." },
+            { "name": "get_memo", "kind": "view", "result": {
+                "serialization_type": "json", "type_schema": { "type": ["null", "string"] }
+            } }
+        ]));
+
+        let classification = AbiDiff::new(&old, &new).classify();
+        let classes: Vec<(&ChangeSubject, ChangeClass, &str)> = classification
+            .changes
+            .iter()
+            .map(|change| (&change.subject, change.class, change.description.as_str()))
+            .collect();
+        let function = |name: &str| ChangeSubject::Function(name.to_string());
+        assert_eq!(
+            classes,
+            [
+                (
+                    &function("get_balance"),
+                    ChangeClass::Compatible,
+                    "optional parameter `at_block` added"
+                ),
+                (
+                    &function("get_balance"),
+                    ChangeClass::Compatible,
+                    "type of parameter `account_id` widened"
+                ),
+                (
+                    &function("transfer"),
+                    ChangeClass::Breaking,
+                    "required parameter `amount` added"
+                ),
+                (
+                    &function("burn"),
+                    ChangeClass::DocOnly,
+                    "documentation changed"
+                ),
+                (
+                    &function("get_memo"),
+                    ChangeClass::DocOnly,
+                    "schema of the result changed equivalently"
+                ),
+            ]
+        );
+        assert_eq!(classification.bump(), SemverBump::Major);
+        assert_eq!(classification.next_version("1.2.3").unwrap(), "2.0.0");
+        assert_eq!(classification.next_version("0.2.3").unwrap(), "0.3.0");
+
+        // Without the breaking change, only the minor version is bumped.
+        new.body.functions[1] = old.body.functions[1].clone();
+        let classification = AbiDiff::new(&old, &new).classify();
+        assert_eq!(classification.class(), Some(ChangeClass::Compatible));
+        assert_eq!(classification.next_version("1.2.3").unwrap(), "1.3.0");
+        assert_eq!(classification.next_version("0.2.3").unwrap(), "0.2.4");
+        assert_eq!(AbiDiff::new(&old, &old).classify().bump(), SemverBump::None);
+    }
+
+    #[test]
+    fn test_classify_definitions() {
+        let root = |param: &str, result: &str, definitions: Value| {
+            AbiRoot::from_value(json!({
+                "schema_version": SCHEMA_VERSION,
+                "metadata": {},
+                "body": {
+                    "functions": [
+                        { "name": "set", "kind": "call", "params": {
+                            "serialization_type": "json",
+                            "args": [{ "name": "value", "type_schema": { "$ref": param } }]
+                        } },
+                        { "name": "get", "kind": "view", "result": {
+                            "serialization_type": "json", "type_schema": { "$ref": result }
+                        } }
+                    ],
+                    "root_schema": { "definitions": definitions }
+                }
+            }))
+            .unwrap()
+        };
+        let memo = json!({ "type": "string", "maxLength": 32 });
+        let old = root(
+            "#/definitions/Foo",
+            "#/definitions/Output",
+            json!({ "Foo": { "type": "integer" }, "Memo": memo, "Output": { "type": "string" } }),
+        );
+
+        // `Foo` renamed to `Bar` with an identical wire format.
+        let renamed = root(
+            "#/definitions/Bar",
+            "#/definitions/Output",
+            json!({ "Bar": { "type": "integer" }, "Memo": memo, "Output": { "type": "string" } }),
+        );
+        let diff = AbiDiff::new(&old, &renamed);
+        assert_eq!(diff.definitions.len(), 1);
+        assert_eq!(diff.definitions[0].new_name.as_deref(), Some("Bar"));
+        let classification = diff.classify();
+        assert_eq!(classification.class(), Some(ChangeClass::DocOnly));
+        assert!(diff
+            .to_changelog("1.0.0", "1.0.1")
+            .ends_with("### Types\n\n- `Foo` renamed to `Bar`\n"));
+
+        // `Foo` is only used by parameters and accepts more values, `Memo` is not used at all.
+        let widened = root(
+            "#/definitions/Foo",
+            "#/definitions/Output",
+            json!({
+                "Foo": { "type": ["integer", "null"] },
+                "Memo": { "type": "string" },
+                "Output": { "type": "string" }
+            }),
+        );
+        let classification = AbiDiff::new(&old, &widened).classify();
+        let classes: Vec<(&ChangeSubject, ChangeClass, &str)> = classification
+            .changes
+            .iter()
+            .map(|change| (&change.subject, change.class, change.description.as_str()))
+            .collect();
+        let definition = |name: &str| ChangeSubject::Definition(name.to_string());
+        assert_eq!(
+            classes,
+            [
+                (
+                    &definition("Foo"),
+                    ChangeClass::Compatible,
+                    "definition widened"
+                ),
+                (
+                    &definition("Memo"),
+                    ChangeClass::Compatible,
+                    "definition not used by functions changed"
+                ),
+            ]
+        );
+        assert_eq!(classification.bump(), SemverBump::Minor);
+        // Narrowing what callers may pass breaks them.
+        assert_eq!(
+            AbiDiff::new(&widened, &old).classify().bump(),
+            SemverBump::Major
+        );
+
+        // `Output` is used by results, which may only be narrowed.
+        let narrowed = root(
+            "#/definitions/Foo",
+            "#/definitions/Output",
+            json!({ "Foo": { "type": "integer" }, "Memo": memo, "Output": { "type": "string", "minLength": 1 } }),
+        );
+        let classification = AbiDiff::new(&old, &narrowed).classify();
+        assert_eq!(classification.changes[0].description, "definition narrowed");
+        assert_eq!(classification.bump(), SemverBump::Minor);
+        assert_eq!(
+            AbiDiff::new(&narrowed, &old).classify().bump(),
+            SemverBump::Major
+        );
+    }
+
+    #[test]
+    fn test_is_compatible_caller() {
+        let result = |schema: Value| json!({ "serialization_type": "json", "type_schema": schema });
+        let old = abi(json!([
+            { "name": "get_count", "kind": "view", "result": result(json!({ "type": "integer", "minimum": 0 })) },
+            { "name": "get_name", "kind": "view", "result": result(json!({ "type": "string" })) },
+            { "name": "reset", "kind": "call" }
+        ]));
+        let new = abi(json!([
+            { "name": "get_count", "kind": "view", "result": result(json!({ "type": "integer" })) },
+            { "name": "get_name", "kind": "view", "result": result(json!({ "type": "string", "maxLength": 32 })) },
+            { "name": "get_owner", "kind": "view", "result": result(json!({ "type": "string" })) }
+        ]));
+
+        let incompatibilities = crate::is_compatible_caller(&old, &new).unwrap_err();
+        let reasons: Vec<String> = incompatibilities.iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            reasons,
+            [
+                "function `get_count`: result type changed",
+                "function `reset`: function removed"
+            ]
+        );
+        // Clients of the new ABI expect a bounded name and `get_owner`.
+        let incompatibilities = crate::is_compatible_caller(&new, &old).unwrap_err();
+        let subjects: Vec<&ChangeSubject> = incompatibilities.iter().map(|i| &i.subject).collect();
+        assert_eq!(
+            subjects,
+            [
+                &ChangeSubject::Function("get_name".to_string()),
+                &ChangeSubject::Function("get_owner".to_string())
+            ]
+        );
+        assert!(crate::is_compatible_caller(&old, &old).is_ok());
+    }
+
+    #[test]
+    fn test_classify_nested_definitions() {
+        let root = |amount: Value| {
+            AbiRoot::from_value(json!({
+                "schema_version": SCHEMA_VERSION,
+                "metadata": {},
+                "body": {
+                    "functions": [{ "name": "deposit", "kind": "call", "params": {
+                        "serialization_type": "json",
+                        "args": [{ "name": "deposit", "type_schema": { "$ref": "#/definitions/Deposit" } }]
+                    } }],
+                    "root_schema": { "definitions": {
+                        "Deposit": {
+                            "type": "object",
+                            "properties": { "amount": { "$ref": "#/definitions/Amount" } }
+                        },
+                        "Amount": amount
+                    } }
+                }
+            }))
+            .unwrap()
+        };
+        let old = root(json!({ "type": "string", "pattern": "^[0-9]+$" }));
+        let new = root(json!({ "type": "string" }));
+
+        // `Amount` is only used by parameters through `Deposit`.
+        let diff = AbiDiff::new(&old, &new);
+        assert_eq!(diff.definitions.len(), 1);
+        assert!(diff.definitions[0].in_params && !diff.definitions[0].in_results);
+        assert_eq!(
+            diff.classify().changes,
+            [ClassifiedChange {
+                subject: ChangeSubject::Definition("Amount".to_string()),
+                class: ChangeClass::Compatible,
+                description: "definition widened".to_string(),
+            }]
+        );
+        assert_eq!(
+            AbiDiff::new(&new, &old).classify().class(),
+            Some(ChangeClass::Breaking)
+        );
+    }
+
+    #[test]
+    fn test_is_compatible_caller_definitions() {
+        let root = |name: &str, account: Value| {
+            AbiRoot::from_value(json!({
+                "schema_version": SCHEMA_VERSION,
+                "metadata": {},
+                "body": {
+                    "functions": [{ "name": "register", "kind": "call", "params": {
+                        "serialization_type": "json",
+                        "args": [{
+                            "name": "account",
+                            "type_schema": { "$ref": format!("#/definitions/{}", name) }
+                        }]
+                    } }],
+                    "root_schema": { "definitions": { name: account } }
+                }
+            }))
+            .unwrap()
+        };
+        let account = json!({
+            "type": "object",
+            "properties": { "id": { "type": "string" } },
+            "required": ["id"]
+        });
+        let old = root("Account", account.clone());
+        assert!(crate::is_compatible_caller(&old, &root("AccountV1", account)).is_ok());
+        let optional_id = json!({
+            "type": "object",
+            "properties": { "id": { "type": "string" } }
+        });
+        let widened = root("Account", optional_id);
+        assert!(crate::is_compatible_caller(&old, &widened).is_ok());
+        assert_eq!(
+            crate::is_compatible_caller(&widened, &old).unwrap_err(),
+            [Incompatibility {
+                subject: ChangeSubject::Definition("Account".to_string()),
+                reason: "definition changed".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let params = |name: &str| {
+            json!({
+                "serialization_type": "json",
+                "args": [{ "name": name, "type_schema": { "type": "string" } }]
+            })
+        };
+        let old = abi(json!([
+            { "name": "transfer", "kind": "call", "params": params("receiver_id") },
+            { "name": "reset", "kind": "call" }
+        ]));
+        let new = abi(json!([
+            { "name": "transfer", "kind": "view", "doc": " Transfers tokens.", "params": params("account_id") },
+            { "name": "mint", "kind": "call", "params": params("amount") }
+        ]));
+
+        let diff = AbiDiff::new(&old, &new);
+        assert_eq!(
+            diff.to_markdown(),
+            "### Breaking changes
+
+- function `transfer`:
+  - kind changed from call to view
+  - required parameter `account_id` added
+- function `reset`: function removed
+
+### Compatible changes
+
+- function `mint`: function added
+- function `transfer`: parameter `receiver_id` removed
+
+### Documentation changes
+
+- function `transfer`: documentation changed
+"
+        );
+        assert_eq!(
+            diff.to_string(),
+            "Breaking changes:
+  function `transfer`:
+    - kind changed from call to view
+    - required parameter `account_id` added
+  function `reset`: function removed
+
+Compatible changes:
+  function `mint`: function added
+  function `transfer`: parameter `receiver_id` removed
+
+Documentation changes:
+  function `transfer`: documentation changed
+"
+        );
+        assert_eq!(
+            AbiDiff::new(&old, &old).to_string(),
+            "No interface changes.\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_options() {
+        let root = |functions: Value, memo: Value| {
+            AbiRoot::from_value(json!({
+                "schema_version": SCHEMA_VERSION,
+                "metadata": {},
+                "body": {
+                    "functions": functions,
+                    "root_schema": { "definitions": { "Memo": memo } }
+                }
+            }))
+            .unwrap()
+        };
+        let memo_param = json!({
+            "serialization_type": "json",
+            "args": [{ "name": "memo", "type_schema": { "$ref": "#/definitions/Memo" } }]
+        });
+        let old = root(
+            json!([
+                { "name": "get", "kind": "view", "doc": " Old." },
+                { "name": "set", "kind": "call", "gas_estimate": { "min": 1, "median": 2, "max": 3 } },
+                { "name": "test_log", "kind": "call", "namespace": "test", "params": memo_param }
+            ]),
+            json!({ "type": "string" }),
+        );
+        let new = root(
+            json!([
+                { "name": "get", "kind": "view", "doc": " New." },
+                { "name": "set", "kind": "call" },
+                { "name": "test_log", "kind": "call", "namespace": "test", "params": memo_param }
+            ]),
+            json!({ "type": "integer" }),
+        );
+
+        let diff = AbiDiffOptions::new().diff(&old, &new);
+        assert_eq!(diff, AbiDiff::new(&old, &new));
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.definitions.len(), 1);
+
+        let diff = AbiDiffOptions::new()
+            .ignore_docs(true)
+            .ignore_namespace("test")
+            .diff(&old, &new);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].members, [MemberChange::Metadata]);
+        assert!(diff.definitions.is_empty());
+
+        let options = AbiDiffOptions::new()
+            .ignore_docs(true)
+            .ignore_metadata(true)
+            .ignore_function("test_log");
+        assert!(options.diff(&old, &new).is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+//! Structured view of rustdoc-style function documentation.
+//!
+//! Function documentation is stored in the ABI as the raw doc comment text. [`AbiDoc`] splits it
+//! along the usual rustdoc conventions: a summary paragraph, an extended description and the
+//! `# Arguments`, `# Returns` and `# Examples` sections.
+
+/// Structured function documentation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AbiDoc {
+    /// First paragraph of the documentation.
+    pub summary: String,
+    /// Remaining prose, including sections without special meaning.
+    pub description: Option<String>,
+    /// Documentation of the parameters, in the order they are listed in the `# Arguments`
+    /// section.
+    pub params: Vec<(String, String)>,
+    /// Contents of the `# Returns` section.
+    pub returns: Option<String>,
+    /// Code blocks of the `# Examples` section.
+    pub examples: Vec<String>,
+}
+
+#[derive(PartialEq)]
+enum Section {
+    Prose,
+    Arguments,
+    Returns,
+    Examples,
+}
+
+impl AbiDoc {
+    /// Parses the raw documentation of a function.
+    pub fn parse(doc: &str) -> Self {
+        let mut parsed = AbiDoc::default();
+        let mut prose: Vec<&str> = Vec::new();
+        let mut returns: Vec<&str> = Vec::new();
+        let mut section = Section::Prose;
+        let mut example: Option<Vec<&str>> = None;
+
+        for raw in doc.lines() {
+            // Doc comments keep the space following `///`.
+            let line = raw.strip_prefix(' ').unwrap_or(raw);
+            let trimmed = line.trim();
+            if section == Section::Examples {
+                if trimmed.starts_with("```") {
+                    match example.take() {
+                        Some(lines) => parsed.examples.push(lines.join("\n")),
+                        None => example = Some(Vec::new()),
+                    }
+                    continue;
+                }
+                if let Some(lines) = &mut example {
+                    lines.push(line);
+                    continue;
+                }
+            }
+            if let Some(heading) = trimmed.strip_prefix('#') {
+                section = match heading.trim_start_matches('#').trim() {
+                    "Arguments" | "Parameters" => Section::Arguments,
+                    "Returns" => Section::Returns,
+                    "Examples" | "Example" => Section::Examples,
+                    _ => {
+                        prose.push(trimmed);
+                        Section::Prose
+                    }
+                };
+                continue;
+            }
+            match section {
+                Section::Prose => prose.push(trimmed),
+                Section::Arguments => {
+                    if let Some(item) = trimmed
+                        .strip_prefix('*')
+                        .or_else(|| trimmed.strip_prefix('-'))
+                    {
+                        if let Some((name, doc)) = parse_argument(item.trim_start()) {
+                            parsed.params.push((name.to_string(), doc.to_string()));
+                        }
+                    } else if let Some((_, doc)) = parsed.params.last_mut() {
+                        if !trimmed.is_empty() {
+                            doc.push(' ');
+                            doc.push_str(trimmed);
+                        }
+                    }
+                }
+                Section::Returns => returns.push(trimmed),
+                Section::Examples => {}
+            }
+        }
+
+        let prose = prose.join("\n");
+        let mut paragraphs = prose.trim().splitn(2, "\n\n");
+        parsed.summary = paragraphs
+            .next()
+            .unwrap_or_default()
+            .lines()
+            .collect::<Vec<_>>()
+            .join(" ");
+        parsed.description = paragraphs
+            .next()
+            .map(|rest| rest.trim().to_string())
+            .filter(|rest| !rest.is_empty());
+        parsed.returns = Some(returns.join("\n").trim().to_string()).filter(|r| !r.is_empty());
+        parsed
+    }
+
+    /// Renders the documentation back into rustdoc conventions, in the form stored in the ABI.
+    pub fn to_doc_string(&self) -> String {
+        let mut blocks = vec![self.summary.clone()];
+        blocks.extend(self.description.clone());
+        if !self.params.is_empty() {
+            blocks.push("# Arguments".to_string());
+            blocks.push(
+                self.params
+                    .iter()
+                    .map(|(name, doc)| format!("* `{}` - {}", name, doc))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            );
+        }
+        if let Some(returns) = &self.returns {
+            blocks.push("# Returns".to_string());
+            blocks.push(returns.clone());
+        }
+        if !self.examples.is_empty() {
+            blocks.push("# Examples".to_string());
+            for example in &self.examples {
+                blocks.push(format!("```\n{}\n```", example));
+            }
+        }
+        blocks
+            .join("\n\n")
+            .lines()
+            .map(|line| format!(" {}", line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn parse_argument(item: &str) -> Option<(&str, &str)> {
+    let (name, doc) = item.strip_prefix('`')?.split_once('`')?;
+    let doc = doc.trim_start_matches(|c: char| c == '-' || c == ':' || c.is_whitespace());
+    Some((name, doc.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_doc() {
+        let doc = AbiDoc::parse(
+            " Transfers tokens to the receiver.\n \n Requires exactly 1 yoctoNEAR attached.\n \n # Arguments\n \n * `receiver_id` - Account receiving\n   the tokens.\n * `amount` - Amount to transfer.\n \n # Returns\n \n Nothing.\n \n # Examples\n \n ```\n contract.ft_transfer(bob, 10.into());\n ```",
+        );
+        assert_eq!(
+            doc,
+            AbiDoc {
+                summary: "Transfers tokens to the receiver.".to_string(),
+                description: Some("Requires exactly 1 yoctoNEAR attached.".to_string()),
+                params: vec![
+                    (
+                        "receiver_id".to_string(),
+                        "Account receiving the tokens.".to_string()
+                    ),
+                    ("amount".to_string(), "Amount to transfer.".to_string()),
+                ],
+                returns: Some("Nothing.".to_string()),
+                examples: vec!["contract.ft_transfer(bob, 10.into());".to_string()],
+            }
+        );
+        assert_eq!(AbiDoc::parse(&doc.to_doc_string()), doc);
+    }
+
+    #[test]
+    fn test_parse_plain_doc() {
+        let doc = AbiDoc::parse(" Returns the\n total supply.");
+        assert_eq!(doc.summary, "Returns the total supply.");
+        assert_eq!(doc.description, None);
+        assert!(doc.params.is_empty());
+    }
+}
@@ -0,0 +1,351 @@
+//! Static HTML reference of a contract.
+
+use crate::doc::AbiDoc;
+use crate::transform::definition_name;
+use crate::{AbiFunction, AbiFunctionKind, AbiParameters, AbiRoot, AbiType};
+use schemars::schema::{Schema, SchemaObject};
+
+const STYLE: &str = "body { margin: 0; font: 16px/1.5 system-ui, sans-serif; color: #1f2328; }
+header, main { max-width: 56rem; margin: 0 auto; padding: 0 1.5rem; }
+nav { max-width: 56rem; margin: 0 auto; padding: 0 1.5rem; columns: 2; }
+nav h2 { margin: 0; font-size: 1rem; }
+nav ul { margin-top: 0.25rem; padding-left: 1.25rem; }
+code, pre { font: 14px/1.4 ui-monospace, monospace; }
+pre { overflow-x: auto; padding: 0.75rem; background: #f6f8fa; border-radius: 6px; }
+article { border-top: 1px solid #d0d7de; padding: 0.5rem 0 1rem; }
+h3 a { color: inherit; text-decoration: none; }
+.badge { font-size: 0.75rem; font-weight: normal; padding: 0.1rem 0.4rem; margin-left: 0.25rem; border-radius: 1rem; background: #eaeef2; }
+.badge.view { background: #dafbe1; }
+.badge.call { background: #ddf4ff; }
+.badge.deprecated { background: #ffebe9; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; vertical-align: top; padding: 0.25rem 0.5rem; border-bottom: 1px solid #d0d7de; }
+details pre { margin: 0.25rem 0; }
+summary { cursor: pointer; }
+";
+
+/// Renders the ABI as a single-page HTML reference of the contract, to be hosted as is.
+///
+/// The page lists the functions of the contract, then the root schema definitions. Every function
+/// and type has an anchor, `#fn.{qualified name}` and `#type.{name}`, linked from the table of
+/// contents and from the parameters and results referencing the type. Function documentation is
+/// split along rustdoc conventions, see [`AbiDoc`], and rendered as plain paragraphs with inline
+/// code. Type schemas are collapsed `<details>` elements, expandable without scripts.
+pub fn render(abi: &AbiRoot) -> String {
+    let name = abi.metadata.name.as_deref().unwrap_or("Contract");
+    let definitions = &abi.body.root_schema.definitions;
+    let mut page = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n");
+    page.push_str("<meta charset=\"utf-8\">\n");
+    page.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    page.push_str(&format!("<title>{} reference</title>\n", escape(name)));
+    page.push_str(&format!("<style>\n{}</style>\n</head>\n<body>\n", STYLE));
+
+    page.push_str(&format!("<header>\n<h1>{}</h1>\n", escape(name)));
+    if let Some(version) = &abi.metadata.version {
+        page.push_str(&format!("<p>Version {}</p>\n", escape(version)));
+    }
+    if let Some(doc) = &abi.metadata.doc {
+        page.push_str(&prose(doc));
+    }
+    page.push_str("</header>\n");
+
+    page.push_str("<nav>\n<h2>Functions</h2>\n<ul>\n");
+    for function in &abi.body.functions {
+        let name = escape(&function.qualified_name());
+        page.push_str(&format!(
+            "<li><a href=\"#fn.{}\"><code>{}</code></a></li>\n",
+            name, name
+        ));
+    }
+    page.push_str("</ul>\n<h2>Types</h2>\n<ul>\n");
+    for name in definitions.keys() {
+        let name = escape(name);
+        page.push_str(&format!(
+            "<li><a href=\"#type.{}\"><code>{}</code></a></li>\n",
+            name, name
+        ));
+    }
+    page.push_str("</ul>\n</nav>\n<main>\n");
+
+    page.push_str("<section id=\"functions\">\n<h2>Functions</h2>\n");
+    for function in &abi.body.functions {
+        page.push_str(&function_article(function));
+    }
+    page.push_str("</section>\n<section id=\"types\">\n<h2>Types</h2>\n");
+    for (name, schema) in definitions {
+        let anchor = format!("type.{}", escape(name));
+        page.push_str(&format!(
+            "<article id=\"{}\">\n<h3><a href=\"#{}\">{}</a></h3>\n",
+            anchor,
+            anchor,
+            escape(name)
+        ));
+        if let Schema::Object(SchemaObject {
+            metadata: Some(metadata),
+            ..
+        }) = schema
+        {
+            if let Some(description) = &metadata.description {
+                page.push_str(&prose(description));
+            }
+        }
+        page.push_str(&schema_details(
+            "JSON schema",
+            &serde_json::to_value(schema).expect("schema serialization can not fail"),
+        ));
+        page.push_str("</article>\n");
+    }
+    page.push_str("</section>\n</main>\n</body>\n</html>\n");
+    page
+}
+
+fn function_article(function: &AbiFunction) -> String {
+    let anchor = format!("fn.{}", escape(&function.qualified_name()));
+    let kind = match function.kind {
+        AbiFunctionKind::View => "view",
+        AbiFunctionKind::Call => "call",
+    };
+    let mut article = format!(
+        "<article id=\"{}\">\n<h3><a href=\"#{}\">{}</a> <span class=\"badge {}\">{}</span>",
+        anchor,
+        anchor,
+        escape(&function.qualified_name()),
+        kind,
+        kind
+    );
+    for modifier in &function.modifiers {
+        article.push_str(&format!(
+            " <span class=\"badge\">{}</span>",
+            escape(modifier.as_str())
+        ));
+    }
+    if function.deprecated.is_some() {
+        article.push_str(" <span class=\"badge deprecated\">deprecated</span>");
+    }
+    article.push_str("</h3>\n");
+
+    let params: Vec<(&str, AbiType)> = match &function.params {
+        AbiParameters::Json { args } => args
+            .iter()
+            .map(|arg| {
+                let ty = AbiType::Json {
+                    type_schema: arg.type_schema.clone(),
+                };
+                (arg.name.as_str(), ty)
+            })
+            .collect(),
+        AbiParameters::Borsh { args } => args
+            .iter()
+            .map(|arg| {
+                let ty = AbiType::Borsh {
+                    type_schema: arg.type_schema.clone(),
+                };
+                (arg.name.as_str(), ty)
+            })
+            .collect(),
+    };
+    let signature: Vec<String> = params
+        .iter()
+        .map(|(name, ty)| format!("{}: {}", name, ty.display_name()))
+        .collect();
+    let mut signature = format!("fn {}({})", function.name, signature.join(", "));
+    if let Some(result) = &function.result {
+        signature.push_str(&format!(" -> {}", result.display_name()));
+    }
+    article.push_str(&format!("<pre><code>{}</code></pre>\n", escape(&signature)));
+
+    if let Some(deprecation) = &function.deprecated {
+        let mut notice = String::from("Deprecated");
+        if let Some(since) = &deprecation.since {
+            notice.push_str(&format!(" since {}", since));
+        }
+        match &deprecation.note {
+            Some(note) => notice.push_str(&format!(": {}", note)),
+            None => notice.push('.'),
+        }
+        article.push_str(&format!(
+            "<p class=\"deprecation\">{}</p>\n",
+            inline(&notice)
+        ));
+    }
+    let doc = function.structured_doc().unwrap_or_default();
+    article.push_str(&prose(&doc.summary));
+    if let Some(description) = &doc.description {
+        article.push_str(&prose(description));
+    }
+
+    if !params.is_empty() {
+        article.push_str("<h4>Parameters</h4>\n<table>\n");
+        article.push_str("<tr><th>Name</th><th>Type</th><th>Description</th></tr>\n");
+        for (name, ty) in &params {
+            let description = param_doc(&doc, name).map(inline).unwrap_or_default();
+            article.push_str(&format!(
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+                escape(name),
+                type_details(ty),
+                description
+            ));
+        }
+        article.push_str("</table>\n");
+    }
+    if let Some(result) = &function.result {
+        article.push_str("<h4>Returns</h4>\n");
+        article.push_str(&type_details(result));
+        if let Some(returns) = &doc.returns {
+            article.push_str(&prose(returns));
+        }
+    }
+    if !doc.examples.is_empty() {
+        article.push_str("<h4>Examples</h4>\n");
+        for example in &doc.examples {
+            article.push_str(&format!("<pre><code>{}</code></pre>\n", escape(example)));
+        }
+    }
+    article.push_str("</article>\n");
+    article
+}
+
+fn param_doc<'a>(doc: &'a AbiDoc, name: &str) -> Option<&'a str> {
+    doc.params
+        .iter()
+        .find(|(param, _)| param == name)
+        .map(|(_, doc)| doc.as_str())
+}
+
+/// Name of `ty`, linking to the referenced definition if any, followed by its expandable
+/// schema.
+fn type_details(ty: &AbiType) -> String {
+    let name = format!("<code>{}</code>", escape(&ty.display_name()));
+    let reference = match ty {
+        AbiType::Json {
+            type_schema: Schema::Object(object),
+        } => object.reference.as_deref().and_then(definition_name),
+        _ => None,
+    };
+    let name = match reference {
+        Some(definition) => format!("<a href=\"#type.{}\">{}</a>", escape(definition), name),
+        None => name,
+    };
+    let schema = match ty {
+        AbiType::Json { type_schema } => {
+            serde_json::to_value(type_schema).expect("schema serialization can not fail")
+        }
+        AbiType::Borsh { .. } => {
+            serde_json::to_value(ty).expect("schema serialization can not fail")
+        }
+    };
+    format!("{}\n{}", name, schema_details("Schema", &schema))
+}
+
+fn schema_details(label: &str, schema: &serde_json::Value) -> String {
+    let json = serde_json::to_string_pretty(schema).expect("JSON serialization can not fail");
+    format!(
+        "<details><summary>{}</summary><pre><code>{}</code></pre></details>\n",
+        label,
+        escape(&json)
+    )
+}
+
+/// Paragraphs of `text`, separated by blank lines.
+fn prose(text: &str) -> String {
+    let mut html = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim).chain(std::iter::once("")) {
+        if !line.is_empty() {
+            paragraph.push(line);
+        } else if !paragraph.is_empty() {
+            html.push_str(&format!("<p>{}</p>\n", inline(&paragraph.join(" "))));
+            paragraph.clear();
+        }
+    }
+    html
+}
+
+/// Escapes `text`, rendering spans delimited by backticks as code.
+fn inline(text: &str) -> String {
+    let spans: Vec<&str> = text.split('`').collect();
+    if spans.len() % 2 == 0 {
+        // Unbalanced backticks are kept verbatim.
+        return escape(text);
+    }
+    spans
+        .iter()
+        .enumerate()
+        .map(|(i, span)| {
+            if i % 2 == 1 {
+                format!("<code>{}</code>", escape(span))
+            } else {
+                escape(span)
+            }
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_render() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {
+                "name": "registry",
+                "version": "1.2.0",
+                "doc": " Registry of <accounts>.\n\n Accounts are keyed by `AccountId`."
+            },
+            "body": {
+                "functions": [
+                    {
+                        "name": "get_account",
+                        "kind": "view",
+                        "doc": " Looks up an account.\n\n # Arguments\n\n * `account_id` - Id of the `Account`.\n\n # Returns\n\n The account, if registered.",
+                        "params": { "serialization_type": "json", "args": [
+                            { "name": "account_id", "type_schema": { "$ref": "#/definitions/AccountId" } }
+                        ] },
+                        "result": { "serialization_type": "json", "type_schema": {
+                            "anyOf": [{ "$ref": "#/definitions/Account" }, { "type": "null" }]
+                        } }
+                    },
+                    {
+                        "name": "burn",
+                        "namespace": "admin",
+                        "kind": "call",
+                        "modifiers": ["payable"],
+                        "deprecated": { "since": "1.1.0", "note": "use `admin::destroy` instead" },
+                        "params": { "serialization_type": "borsh", "args": [
+                            { "name": "amount", "type_schema": { "declaration": "u128", "definitions": {} } }
+                        ] }
+                    }
+                ],
+                "root_schema": { "definitions": {
+                    "AccountId": { "description": "NEAR account id.", "type": "string" },
+                    "Account": {
+                        "type": "object",
+                        "properties": { "id": { "$ref": "#/definitions/AccountId" } }
+                    }
+                } }
+            }
+        }))
+        .unwrap();
+
+        insta::assert_snapshot!(render(&abi));
+    }
+}
@@ -0,0 +1,7 @@
+//! Rendering of contract references from ABIs.
+//!
+//! Renderers document a whole contract: its functions, split along rustdoc conventions by
+//! [`AbiDoc`](crate::doc::AbiDoc), and the root schema definitions they refer to. [`html`] renders
+//! a static page to be hosted alongside explorer pages.
+
+pub mod html;
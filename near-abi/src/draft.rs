@@ -0,0 +1,363 @@
+//! Conversion of emitted JSON schemas between JSON Schema drafts.
+//!
+//! ABI models (and `schemars`) use draft-07 idioms: definitions live under `definitions`, tuples
+//! are expressed with an array-valued `items` and nullable types are expressed with a `"null"`
+//! type. Draft 2020-12 validators expect `$defs`, `prefixItems` and no array-valued `items`, so
+//! this module rewrites schema documents into the idioms of the requested draft. Only keywords in
+//! schema positions are rewritten: property names and Borsh type schemas are left untouched.
+
+use crate::AbiRoot;
+use schemars::schema::RootSchema;
+use serde_json::{Map, Value};
+
+/// JSON Schema draft that emitted schemas are expressed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SchemaDraft {
+    /// Draft-07, the draft ABI models are defined in.
+    #[default]
+    Draft7,
+    /// Draft 2020-12.
+    Draft2020_12,
+}
+
+impl SchemaDraft {
+    /// Meta-schema URI identifying the draft in the `$schema` keyword.
+    pub fn meta_schema(&self) -> &'static str {
+        match self {
+            Self::Draft7 => "http://json-schema.org/draft-07/schema#",
+            Self::Draft2020_12 => "https://json-schema.org/draft/2020-12/schema",
+        }
+    }
+
+    fn definitions_keyword(&self) -> &'static str {
+        match self {
+            Self::Draft7 => "definitions",
+            Self::Draft2020_12 => "$defs",
+        }
+    }
+}
+
+/// Serializes an ABI, expressing its root schema and function type schemas in `draft` idioms.
+pub fn abi_to_value(abi: &AbiRoot, draft: SchemaDraft) -> Value {
+    let mut value = abi.to_value();
+    convert_abi_value(&mut value, draft);
+    value
+}
+
+/// Parses an ABI whose schemas may be expressed in any supported draft.
+pub fn abi_from_value(mut value: Value) -> Result<AbiRoot, serde_json::Error> {
+    convert_abi_value(&mut value, SchemaDraft::Draft7);
+    AbiRoot::from_value(value)
+}
+
+/// Serializes a root schema in `draft` idioms.
+pub fn root_schema_to_value(root_schema: &RootSchema, draft: SchemaDraft) -> Value {
+    let mut value = serde_json::to_value(root_schema).expect("schema serialization can not fail");
+    convert_schema(&mut value, draft);
+    if let Value::Object(map) = &mut value {
+        if map.contains_key("$schema") {
+            map.insert("$schema".to_string(), draft.meta_schema().into());
+        }
+    }
+    value
+}
+
+/// Rewrites a serialized JSON schema (and all of its subschemas) in place into `draft` idioms.
+pub fn convert_schema(schema: &mut Value, draft: SchemaDraft) {
+    let map = match schema {
+        Value::Object(map) => map,
+        _ => return,
+    };
+
+    // Definitions and references.
+    for keyword in ["definitions", "$defs"] {
+        if keyword != draft.definitions_keyword() {
+            if let Some(definitions) = map.remove(keyword) {
+                map.insert(draft.definitions_keyword().to_string(), definitions);
+            }
+        }
+    }
+    if let Some(Value::String(reference)) = map.get_mut("$ref") {
+        for prefix in ["#/definitions/", "#/$defs/"] {
+            if let Some(name) = reference.strip_prefix(prefix) {
+                *reference = format!("#/{}/{}", draft.definitions_keyword(), name);
+                break;
+            }
+        }
+    }
+
+    // Tuples.
+    match draft {
+        SchemaDraft::Draft7 => {
+            if let Some(prefix_items) = map.remove("prefixItems") {
+                if let Some(items) = map.remove("items") {
+                    map.insert("additionalItems".to_string(), items);
+                }
+                map.insert("items".to_string(), prefix_items);
+            }
+        }
+        SchemaDraft::Draft2020_12 => {
+            if matches!(map.get("items"), Some(Value::Array(_))) {
+                let items = map.remove("items").unwrap();
+                map.insert("prefixItems".to_string(), items);
+                if let Some(additional_items) = map.remove("additionalItems") {
+                    map.insert("items".to_string(), additional_items);
+                }
+            }
+        }
+    }
+
+    // OpenAPI-style nullability is not understood by either draft.
+    if map.remove("nullable") == Some(Value::Bool(true)) {
+        make_nullable(map);
+    }
+
+    for (keyword, subschema) in map.iter_mut() {
+        match keyword.as_str() {
+            "definitions" | "$defs" | "properties" | "patternProperties" | "dependentSchemas" => {
+                if let Value::Object(schemas) = subschema {
+                    for schema in schemas.values_mut() {
+                        convert_schema(schema, draft);
+                    }
+                }
+            }
+            "allOf" | "anyOf" | "oneOf" | "prefixItems" => {
+                if let Value::Array(schemas) = subschema {
+                    for schema in schemas {
+                        convert_schema(schema, draft);
+                    }
+                }
+            }
+            "items" => match subschema {
+                Value::Array(schemas) => {
+                    for schema in schemas {
+                        convert_schema(schema, draft);
+                    }
+                }
+                schema => convert_schema(schema, draft),
+            },
+            "additionalItems"
+            | "additionalProperties"
+            | "contains"
+            | "not"
+            | "if"
+            | "then"
+            | "else"
+            | "propertyNames" => convert_schema(subschema, draft),
+            _ => {}
+        }
+    }
+}
+
+fn make_nullable(map: &mut Map<String, Value>) {
+    let null = Value::from("null");
+    match map.get_mut("type") {
+        Some(Value::String(ty)) => {
+            let ty = Value::from(ty.as_str());
+            map.insert("type".to_string(), Value::Array(vec![ty, null]));
+        }
+        Some(Value::Array(types)) => {
+            if !types.contains(&null) {
+                types.push(null);
+            }
+        }
+        _ => {
+            if let Some(Value::Array(values)) = map.get_mut("enum") {
+                if !values.contains(&Value::Null) {
+                    values.push(Value::Null);
+                }
+            }
+        }
+    }
+}
+
+fn convert_abi_value(value: &mut Value, draft: SchemaDraft) {
+    let body = match value.get_mut("body") {
+        Some(Value::Object(body)) => body,
+        _ => return,
+    };
+    if let Some(root_schema) = body.get_mut("root_schema") {
+        convert_schema(root_schema, draft);
+        if let Value::Object(map) = root_schema {
+            if map.contains_key("$schema") {
+                map.insert("$schema".to_string(), draft.meta_schema().into());
+            }
+        }
+    }
+    if let Some(root) = body
+        .get_mut("state")
+        .and_then(|state| state.get_mut("root"))
+        .filter(|root| is_json(root))
+    {
+        if let Some(type_schema) = root.get_mut("type_schema") {
+            convert_schema(type_schema, draft);
+        }
+    }
+    if let Some(Value::Array(constants)) = body.get_mut("constants") {
+        for constant in constants {
+            if let Some(type_schema) = constant.get_mut("type_schema") {
+                convert_schema(type_schema, draft);
+            }
+        }
+    }
+    if let Some(Value::Array(contracts)) = body.get_mut("external_contracts") {
+        for contract in contracts {
+            if let Some(Value::Array(functions)) = contract.get_mut("functions") {
+                convert_functions(functions, draft);
+            }
+        }
+    }
+    if let Some(Value::Array(functions)) = body.get_mut("functions") {
+        convert_functions(functions, draft);
+    }
+}
+
+fn convert_functions(functions: &mut [Value], draft: SchemaDraft) {
+    for function in functions {
+        let function = match function {
+            Value::Object(function) => function,
+            _ => continue,
+        };
+        for key in ["params", "callbacks"] {
+            if let Some(Value::Array(args)) = function
+                .get_mut(key)
+                .filter(|list| is_json(list))
+                .and_then(|list| list.get_mut("args"))
+            {
+                for arg in args {
+                    if let Some(type_schema) = arg.get_mut("type_schema") {
+                        convert_schema(type_schema, draft);
+                    }
+                }
+            }
+        }
+        let mut types: Vec<&mut Value> = Vec::new();
+        for (key, value) in function.iter_mut() {
+            match (key.as_str(), value) {
+                ("callbacks", Value::Array(callbacks)) => types.extend(callbacks.iter_mut()),
+                ("callbacks_vec" | "result", value) => types.push(value),
+                _ => {}
+            }
+        }
+        for abi_type in types.into_iter().filter(|abi_type| is_json(abi_type)) {
+            if let Some(type_schema) = abi_type.get_mut("type_schema") {
+                convert_schema(type_schema, draft);
+            }
+        }
+    }
+}
+
+fn is_json(value: &Value) -> bool {
+    value.get("serialization_type") == Some(&Value::from("json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_convert_schema_roundtrip() {
+        let draft7 = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "definitions": {
+                "Pair": {
+                    "type": "array",
+                    "items": [{ "$ref": "#/definitions/Id" }, { "type": "string" }],
+                    "maxItems": 2,
+                    "minItems": 2
+                },
+                "Id": { "type": "integer" },
+                "Holder": {
+                    "type": "object",
+                    "properties": {
+                        "definitions": { "type": "string" },
+                        "pair": { "$ref": "#/definitions/Pair" }
+                    }
+                }
+            }
+        });
+        let root_schema: RootSchema = serde_json::from_value(draft7.clone()).unwrap();
+
+        let draft2020 = root_schema_to_value(&root_schema, SchemaDraft::Draft2020_12);
+        assert_eq!(
+            draft2020,
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$defs": {
+                    "Pair": {
+                        "type": "array",
+                        "prefixItems": [{ "$ref": "#/$defs/Id" }, { "type": "string" }],
+                        "maxItems": 2,
+                        "minItems": 2
+                    },
+                    "Id": { "type": "integer" },
+                    "Holder": {
+                        "type": "object",
+                        "properties": {
+                            "definitions": { "type": "string" },
+                            "pair": { "$ref": "#/$defs/Pair" }
+                        }
+                    }
+                }
+            })
+        );
+
+        let mut back = draft2020;
+        convert_schema(&mut back, SchemaDraft::Draft7);
+        back["$schema"] = SchemaDraft::Draft7.meta_schema().into();
+        assert_eq!(back, draft7);
+    }
+
+    #[test]
+    fn test_convert_nullable() {
+        let mut schema = json!({ "type": "string", "nullable": true });
+        convert_schema(&mut schema, SchemaDraft::Draft2020_12);
+        assert_eq!(schema, json!({ "type": ["string", "null"] }));
+    }
+
+    #[test]
+    fn test_abi_roundtrip_keeps_borsh_schemas() {
+        let abi = json!({
+            "schema_version": crate::SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    {
+                        "name": "get",
+                        "kind": "view",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [{ "name": "id", "type_schema": { "$ref": "#/definitions/Id" } }]
+                        },
+                        "callbacks": {
+                            "serialization_type": "json",
+                            "args": [{ "name": "previous", "type_schema": { "$ref": "#/definitions/Id" } }]
+                        },
+                        "result": {
+                            "serialization_type": "borsh",
+                            "type_schema": {
+                                "declaration": "u8",
+                                "definitions": { "u8": { "Primitive": 1 } }
+                            }
+                        }
+                    }
+                ],
+                "root_schema": { "definitions": { "Id": { "type": "string" } } }
+            }
+        });
+        let abi: AbiRoot = serde_json::from_value(abi).unwrap();
+        let value = abi_to_value(&abi, SchemaDraft::Draft2020_12);
+        assert_eq!(
+            value["body"]["functions"][0]["params"]["args"][0]["type_schema"],
+            json!({ "$ref": "#/$defs/Id" })
+        );
+        assert_eq!(
+            value["body"]["functions"][0]["callbacks"][0]["type_schema"],
+            json!({ "$ref": "#/$defs/Id" })
+        );
+        assert!(value["body"]["functions"][0]["result"]["type_schema"]["definitions"].is_object());
+        assert!(value["body"]["root_schema"]["$defs"].is_object());
+        assert_eq!(abi_from_value(value).unwrap(), abi);
+    }
+}
@@ -0,0 +1,182 @@
+//! NEAR semantic formats of JSON schemas.
+//!
+//! Account ids, balances, gas amounts and binary blobs are all serialized as JSON strings. The
+//! `format` keyword of their schemas tells consumers such as form generators what the string
+//! means, so that they can render proper widgets and validate input.
+
+use crate::path::AbiPath;
+use crate::walk::{function_json_schemas, subschemas};
+use crate::AbiBody;
+use schemars::schema::Schema;
+use std::fmt;
+
+/// Semantic format of a string in a JSON schema.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NearFormat {
+    /// A NEAR account id, e.g. `alice.near`.
+    AccountId,
+    /// An amount of NEAR tokens in yoctoNEAR, as a decimal string.
+    YoctoNear,
+    /// An amount of gas, as a decimal string.
+    Gas,
+    /// Binary data in standard Base64.
+    Base64,
+    /// Binary data in Base58, as used for hashes and public keys.
+    Base58,
+}
+
+impl NearFormat {
+    /// All known formats.
+    pub const ALL: [NearFormat; 5] = [
+        Self::AccountId,
+        Self::YoctoNear,
+        Self::Gas,
+        Self::Base64,
+        Self::Base58,
+    ];
+
+    /// Value of the `format` keyword for this format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AccountId => "near-account-id",
+            Self::YoctoNear => "yocto-near",
+            Self::Gas => "gas",
+            Self::Base64 => "base64",
+            Self::Base58 => "base58",
+        }
+    }
+
+    /// Format with the given `format` keyword value, if it is a known one.
+    pub fn from_keyword(keyword: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.as_str() == keyword)
+    }
+}
+
+impl fmt::Display for NearFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Names of well-known `near-sdk` definitions, along with their format.
+pub(crate) const WELL_KNOWN_DEFINITIONS: [(&str, NearFormat); 5] = [
+    ("AccountId", NearFormat::AccountId),
+    ("NearToken", NearFormat::YoctoNear),
+    ("Gas", NearFormat::Gas),
+    ("Base64VecU8", NearFormat::Base64),
+    ("Base58CryptoHash", NearFormat::Base58),
+];
+
+/// Returns the NEAR format of a schema, if it has a known one. References are not followed.
+pub fn format_of(schema: &Schema) -> Option<NearFormat> {
+    match schema {
+        Schema::Object(object) => object.format.as_deref().and_then(NearFormat::from_keyword),
+        Schema::Bool(_) => None,
+    }
+}
+
+/// Sets the `format` keyword of a schema, replacing any previous one. Boolean schemas are
+/// converted into equivalent schema objects first.
+pub fn set_format(schema: &mut Schema, format: NearFormat) {
+    if let Schema::Bool(accepts) = *schema {
+        *schema = Schema::Bool(accepts).into_object().into();
+    }
+    if let Schema::Object(object) = schema {
+        object.format = Some(format.as_str().to_string());
+    }
+}
+
+/// A schema with a NEAR format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatSite {
+    /// Where the schema is, e.g. `functions/ft_transfer/params/receiver_id` or
+    /// `definitions/StorageBalance`.
+    pub location: AbiPath,
+    pub format: NearFormat,
+}
+
+/// Lists the schemas with a NEAR format in function signatures and root schema definitions.
+/// Uses of a formatted definition through `$ref` are reported once, at the definition.
+pub fn format_sites(body: &AbiBody) -> Vec<FormatSite> {
+    let mut sites = Vec::new();
+    for function in &body.functions {
+        for (site, schema) in function_json_schemas(function) {
+            collect(schema, &AbiPath::from_site(function, &site), &mut sites);
+        }
+    }
+    for (name, schema) in body.definitions() {
+        collect(schema, &AbiPath::Definition(name.to_string()), &mut sites);
+    }
+    sites
+}
+
+fn collect(schema: &Schema, location: &AbiPath, sites: &mut Vec<FormatSite>) {
+    let object = match schema {
+        Schema::Object(object) => object,
+        Schema::Bool(_) => return,
+    };
+    if let Some(format) = format_of(schema) {
+        sites.push(FormatSite {
+            location: location.clone(),
+            format,
+        });
+    }
+    for child in subschemas(object) {
+        collect(child, location, sites);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_format_sites() {
+        let mut body: AbiBody = serde_json::from_value(json!({
+            "functions": [
+                {
+                    "name": "ft_transfer",
+                    "kind": "call",
+                    "params": {
+                        "serialization_type": "json",
+                        "args": [
+                            { "name": "receiver_id", "type_schema": { "$ref": "#/definitions/AccountId" } },
+                            { "name": "memo", "type_schema": { "type": ["string", "null"] } }
+                        ]
+                    }
+                }
+            ],
+            "root_schema": {
+                "definitions": {
+                    "AccountId": { "type": "string", "format": "near-account-id" },
+                    "Blob": { "type": "string", "format": "binary" }
+                }
+            }
+        }))
+        .unwrap();
+        if let crate::AbiParameters::Json { args } = &mut body.functions[0].params {
+            set_format(&mut args[1].type_schema, NearFormat::Base64);
+        }
+        assert_eq!(
+            format_sites(&body),
+            [
+                FormatSite {
+                    location: "functions/ft_transfer/params/memo".parse().unwrap(),
+                    format: NearFormat::Base64,
+                },
+                FormatSite {
+                    location: AbiPath::Definition("AccountId".to_string()),
+                    format: NearFormat::AccountId,
+                },
+            ]
+        );
+        assert_eq!(format_of(body.definition("Blob").unwrap()), None);
+        assert_eq!(
+            NearFormat::from_keyword("yocto-near"),
+            Some(NearFormat::YoctoNear)
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Incremental ABI regeneration.
+//!
+//! Build tools with incremental compilation only know the signatures of the functions that were
+//! recompiled. [`AbiUpdate`] carries those, and [`AbiUpdate::apply`] merges them into the ABI of
+//! the previous build, reusing the schema definitions of unchanged functions instead of
+//! regenerating the whole ABI.
+
+use crate::{transform, AbiFunction, AbiRoot};
+use schemars::schema::RootSchema;
+
+/// Functions regenerated by an incremental build.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AbiUpdate {
+    /// Signatures of the recompiled functions.
+    pub functions: Vec<AbiFunction>,
+    /// Definitions of the types used by `functions`.
+    pub root_schema: RootSchema,
+    /// Names of the functions that no longer exist.
+    pub removed: Vec<String>,
+}
+
+impl AbiUpdate {
+    pub fn new(functions: Vec<AbiFunction>, root_schema: RootSchema) -> Self {
+        Self {
+            functions,
+            root_schema,
+            removed: Vec::new(),
+        }
+    }
+
+    /// Produces the ABI of the new build from the ABI of the `previous` one.
+    ///
+    /// Recompiled functions replace the functions of the same name in place, new ones are
+    /// appended. Definitions of the update take precedence over previous definitions of the same
+    /// name, since types are shared across functions and the update reflects their latest
+    /// version. Definitions no longer used by any function are dropped.
+    pub fn apply(self, previous: &AbiRoot) -> AbiRoot {
+        let mut abi = previous.clone();
+        let body = &mut abi.body;
+        body.functions
+            .retain(|function| !self.removed.contains(&function.name));
+        for function in self.functions {
+            match body.functions.iter_mut().find(|f| f.name == function.name) {
+                Some(previous) => *previous = function,
+                None => body.functions.push(function),
+            }
+        }
+        body.root_schema
+            .definitions
+            .extend(self.root_schema.definitions);
+        transform::prune_definitions(body);
+        abi
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiFunctionKind, AbiType, SCHEMA_VERSION};
+    use serde_json::json;
+
+    #[test]
+    fn test_apply_update() {
+        let previous = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": { "name": "token" },
+            "body": {
+                "functions": [
+                    { "name": "get", "kind": "view", "result": { "serialization_type": "json", "type_schema": { "$ref": "#/definitions/Balance" } } },
+                    { "name": "set", "kind": "call" },
+                    { "name": "legacy", "kind": "view", "result": { "serialization_type": "json", "type_schema": { "$ref": "#/definitions/Legacy" } } }
+                ],
+                "root_schema": {
+                    "definitions": {
+                        "Balance": { "type": "string" },
+                        "Legacy": { "type": "integer" }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let mut gen = schemars::gen::SchemaGenerator::default();
+        let set = AbiFunction {
+            result: Some(AbiType::json_of::<Option<String>>(&mut gen)),
+            ..AbiFunction::new("set", AbiFunctionKind::Call)
+        };
+        let update = AbiUpdate {
+            removed: vec!["legacy".to_string()],
+            ..AbiUpdate::new(
+                vec![set.clone(), AbiFunction::new("ping", AbiFunctionKind::Call)],
+                gen.into_root_schema_for::<()>(),
+            )
+        };
+
+        let abi = update.apply(&previous);
+        assert_eq!(
+            abi.body
+                .functions
+                .iter()
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>(),
+            ["get", "set", "ping"]
+        );
+        assert_eq!(abi.body.functions[1], set);
+        assert_eq!(
+            abi.body.root_schema.definitions.keys().collect::<Vec<_>>(),
+            ["Balance"]
+        );
+        assert_eq!(abi.metadata, previous.metadata);
+    }
+}
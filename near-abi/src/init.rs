@@ -0,0 +1,219 @@
+//! Contract initialization for deploy tooling.
+//!
+//! [`InitCall`] picks the init function of a contract from its ABI and checks the supplied
+//! arguments against it, producing the method name and serialized arguments of the function call
+//! action that follows the deploy action.
+
+use crate::value::{validate_borsh_args, validate_json_args, ValueError};
+use crate::{AbiFunction, AbiFunctionModifier, AbiParameters, AbiRoot, ArityError};
+use serde_json::Value;
+use std::fmt;
+
+/// Arguments of an init call.
+#[derive(Clone, Debug, PartialEq)]
+pub enum InitArgs {
+    /// Arguments of a function taking JSON parameters.
+    Json(Value),
+    /// Serialized arguments of a function taking Borsh parameters.
+    Borsh(Vec<u8>),
+}
+
+/// A validated call of a contract init function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InitCall {
+    method_name: String,
+    args: Vec<u8>,
+    ignore_state: bool,
+}
+
+impl InitCall {
+    /// Builds a call of the single init function of the contract.
+    pub fn new(abi: &AbiRoot, args: InitArgs) -> Result<Self, InitError> {
+        let function = abi.init_function()?.ok_or(InitError::NoInitFunction)?;
+        Self::build(abi, function, args)
+    }
+
+    /// Builds a call of the init function named `function`, for contracts that have several.
+    pub fn with_function(abi: &AbiRoot, function: &str, args: InitArgs) -> Result<Self, InitError> {
+        let abi_function = abi
+            .body
+            .functions
+            .iter()
+            .find(|f| f.name == function)
+            .ok_or_else(|| InitError::UnknownFunction(function.to_string()))?;
+        if !is_init(abi_function) {
+            return Err(InitError::NotAnInitFunction(function.to_string()));
+        }
+        Self::build(abi, abi_function, args)
+    }
+
+    fn build(abi: &AbiRoot, function: &AbiFunction, args: InitArgs) -> Result<Self, InitError> {
+        let args = match (&function.params, args) {
+            (AbiParameters::Json { args: params }, InitArgs::Json(args)) => {
+                function.check_arity(&args).map_err(InitError::Arity)?;
+                validate_json_args(&abi.body.root_schema, params, &args)
+                    .map_err(InitError::InvalidArgs)?;
+                serde_json::to_vec(&args).expect("JSON values can always be serialized")
+            }
+            (AbiParameters::Borsh { args: params }, InitArgs::Borsh(args)) => {
+                validate_borsh_args(params, &args).map_err(InitError::InvalidArgs)?;
+                args
+            }
+            _ => return Err(InitError::SerializationMismatch(function.name.clone())),
+        };
+        Ok(Self {
+            method_name: function.name.clone(),
+            args,
+            ignore_state: function.init_ignores_state(),
+        })
+    }
+
+    /// Name of the init function to call.
+    pub fn method_name(&self) -> &str {
+        &self.method_name
+    }
+
+    /// Serialized arguments of the call.
+    pub fn args(&self) -> &[u8] {
+        &self.args
+    }
+
+    /// Whether the call may be made on a contract whose state is already initialized. Other init
+    /// functions fail in that case, so the call has to be made in the same transaction as the
+    /// first deploy.
+    pub fn ignores_state(&self) -> bool {
+        self.ignore_state
+    }
+
+    /// Splits the call into its method name and serialized arguments.
+    pub fn into_parts(self) -> (String, Vec<u8>) {
+        (self.method_name, self.args)
+    }
+}
+
+pub(crate) fn is_init(function: &AbiFunction) -> bool {
+    function.modifiers.contains(&AbiFunctionModifier::Init)
+}
+
+/// Error returned when an init function can not be picked or called.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InitError {
+    /// The contract has no init function.
+    NoInitFunction,
+    /// The contract has several init functions and none was picked explicitly.
+    MultipleInitFunctions(Vec<String>),
+    /// The function is not declared in the ABI.
+    UnknownFunction(String),
+    /// The function is not an init function.
+    NotAnInitFunction(String),
+    /// The arguments are not of the serialization type of the function parameters.
+    SerializationMismatch(String),
+    /// The argument names do not match the function parameters.
+    Arity(ArityError),
+    /// The arguments do not conform to the function parameters.
+    InvalidArgs(ValueError),
+}
+
+impl std::error::Error for InitError {}
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoInitFunction => write!(f, "the contract has no init function"),
+            Self::MultipleInitFunctions(functions) => write!(
+                f,
+                "the contract has several init functions: {}",
+                functions.join(", ")
+            ),
+            Self::UnknownFunction(function) => {
+                write!(f, "function `{}` is not declared in the ABI", function)
+            }
+            Self::NotAnInitFunction(function) => {
+                write!(f, "function `{}` is not an init function", function)
+            }
+            Self::SerializationMismatch(function) => write!(
+                f,
+                "arguments do not match the serialization type of `{}` parameters",
+                function
+            ),
+            Self::Arity(error) => write!(f, "invalid init arguments: {}", error),
+            Self::InvalidArgs(error) => write!(f, "invalid init arguments: {}", error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiFunctionKind, SCHEMA_VERSION};
+    use serde_json::json;
+
+    fn abi(functions: Value) -> AbiRoot {
+        AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": { "functions": functions, "root_schema": {} }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_init_call() {
+        let abi = abi(json!([
+            {
+                "name": "new",
+                "kind": "call",
+                "modifiers": ["init"],
+                "params": {
+                    "serialization_type": "json",
+                    "args": [{ "name": "owner_id", "type_schema": { "type": "string" } }]
+                }
+            },
+            { "name": "get", "kind": "view" }
+        ]));
+        assert_eq!(abi.init_function().unwrap().unwrap().name, "new");
+
+        let call =
+            InitCall::new(&abi, InitArgs::Json(json!({ "owner_id": "alice.near" }))).unwrap();
+        assert_eq!(call.method_name(), "new");
+        assert_eq!(call.args(), br#"{"owner_id":"alice.near"}"#);
+        assert!(!call.ignores_state());
+
+        let err = InitCall::new(&abi, InitArgs::Json(json!({}))).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid init arguments: missing arguments: `owner_id`"
+        );
+        assert_eq!(
+            InitCall::with_function(&abi, "get", InitArgs::Json(json!({}))),
+            Err(InitError::NotAnInitFunction("get".to_string()))
+        );
+        assert_eq!(
+            InitCall::new(&abi, InitArgs::Borsh(Vec::new())),
+            Err(InitError::SerializationMismatch("new".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_multiple_init_functions() {
+        let mut abi = abi(json!([
+            { "name": "new", "kind": "call", "modifiers": ["init"] },
+            { "name": "new_default", "kind": "call", "modifiers": ["init", "ignore_state"] }
+        ]));
+        assert_eq!(
+            abi.init_function(),
+            Err(InitError::MultipleInitFunctions(vec![
+                "new".to_string(),
+                "new_default".to_string()
+            ]))
+        );
+        let call = InitCall::with_function(&abi, "new_default", InitArgs::Json(json!({}))).unwrap();
+        assert!(call.ignores_state());
+        assert_eq!(
+            call.into_parts(),
+            ("new_default".to_string(), b"{}".to_vec())
+        );
+
+        abi.body.functions = vec![AbiFunction::new("get", AbiFunctionKind::View)];
+        assert_eq!(abi.init_function(), Ok(None));
+    }
+}
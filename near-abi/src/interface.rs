@@ -0,0 +1,442 @@
+//! Abstract contract interfaces.
+//!
+//! [`AbiInterface`] describes a set of functions independently of any contract implementing
+//! them, e.g. the functions of a standard. It is extracted from the ABI of a concrete contract
+//! with [`AbiInterface::extract`], or computed as the common callable surface of several
+//! deployments with [`AbiInterface::intersection`]. [`AbiInterface::check_conformance`] checks
+//! that a contract implements an interface, e.g. that a token conforms to NEP-141.
+
+use crate::walk::{function_json_schemas, reachable_definitions};
+use crate::{diff, transform, AbiBody, AbiFunction, AbiFunctionModifier, AbiRoot};
+use schemars::schema::{RootSchema, Schema};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Functions of an abstract interface, along with the definitions of the types they use.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiInterface {
+    pub functions: Vec<AbiFunction>,
+    pub root_schema: RootSchema,
+}
+
+impl AbiInterface {
+    /// Extracts the functions named `function_names` from the ABI of a contract, in the order
+    /// they are requested.
+    ///
+    /// Everything specific to the contract is stripped: its metadata, state and constants, the
+    /// documentation, namespace, selector and gas estimates of the functions, and the root schema
+    /// definitions the extracted functions do not use. Private functions and upgrade entrypoints
+    /// are implementation details of the contract and are left out.
+    pub fn extract<I, S>(abi: &AbiRoot, function_names: I) -> Result<Self, ExtractError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut functions = Vec::new();
+        for name in function_names {
+            let name = name.as_ref();
+            let function = abi
+                .body
+                .functions
+                .iter()
+                .find(|function| function.name == name)
+                .ok_or_else(|| ExtractError::UnknownFunction(name.to_string()))?;
+            if function.modifiers.contains(&AbiFunctionModifier::Private)
+                || function.is_upgrade_entrypoint()
+            {
+                continue;
+            }
+            functions.push(function.clone());
+        }
+        Ok(Self::from_functions(&abi.body.root_schema, functions))
+    }
+
+    /// The functions that can be called the same way on every contract of `abis`, e.g. on all
+    /// the deployed versions of a contract, in the order they are declared in the first ABI.
+    ///
+    /// A function is part of the intersection when every ABI declares it with the same
+    /// signature, and the root schema definitions its signature uses are identical in every ABI.
+    /// Functions are stripped like in [`AbiInterface::extract`] and the definitions are taken
+    /// from the first ABI.
+    pub fn intersection(abis: &[AbiRoot]) -> Self {
+        let (first, others) = match abis.split_first() {
+            Some(split) => split,
+            None => {
+                return Self {
+                    functions: Vec::new(),
+                    root_schema: RootSchema::default(),
+                }
+            }
+        };
+        let functions = first
+            .body
+            .functions
+            .iter()
+            .filter(|function| {
+                !function.modifiers.contains(&AbiFunctionModifier::Private)
+                    && !function.is_upgrade_entrypoint()
+            })
+            .filter(|function| {
+                let definitions = used_definitions(&first.body.root_schema, function);
+                others.iter().all(|other| {
+                    other.body.functions.iter().any(|other_function| {
+                        other_function.name == function.name
+                            && diff::same_signature(other_function, function)
+                            && used_definitions(&other.body.root_schema, other_function)
+                                == definitions
+                    })
+                })
+            })
+            .cloned()
+            .collect();
+        Self::from_functions(&first.body.root_schema, functions)
+    }
+
+    /// Checks that the contract of `abi` implements the interface: every function of the
+    /// interface must be declared, callable from other accounts, and existing callers of the
+    /// interface must keep working against it (see
+    /// [`is_compatible_caller`](crate::is_compatible_caller)).
+    ///
+    /// The contract may declare more functions, take more optional parameters or accept wider
+    /// parameter types. Types are matched by the name of their root schema definitions.
+    pub fn check_conformance(&self, abi: &AbiRoot) -> Result<(), Vec<ConformanceError>> {
+        let mut errors = Vec::new();
+        let mut expected = Vec::new();
+        let mut implemented = Vec::new();
+        for function in &self.functions {
+            match abi.body.functions.iter().find(|f| f.name == function.name) {
+                None => errors.push(ConformanceError::MissingFunction(function.name.clone())),
+                Some(f) if f.modifiers.contains(&AbiFunctionModifier::Private) => {
+                    errors.push(ConformanceError::PrivateFunction(function.name.clone()))
+                }
+                Some(f) => {
+                    expected.push(function.clone());
+                    implemented.push(f.clone());
+                }
+            }
+        }
+        let expected = Self::from_functions(&self.root_schema, expected);
+        let implemented = Self::from_functions(&abi.body.root_schema, implemented);
+        let diff = diff::AbiDiff::between(
+            &expected.functions,
+            &expected.root_schema,
+            &implemented.functions,
+            &implemented.root_schema,
+        );
+        errors.extend(
+            diff.classify()
+                .incompatibilities()
+                .into_iter()
+                .map(ConformanceError::Incompatible),
+        );
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Builds an interface out of `functions`, stripping everything specific to the contract
+    /// declaring them and the definitions of `root_schema` they do not use.
+    fn from_functions(root_schema: &RootSchema, functions: Vec<AbiFunction>) -> Self {
+        let functions = functions
+            .into_iter()
+            .map(|function| AbiFunction {
+                selector: None,
+                doc: None,
+                namespace: None,
+                gas_estimate: None,
+                ..function
+            })
+            .collect();
+        let mut body = AbiBody {
+            functions,
+            root_schema: root_schema.clone(),
+            state: None,
+            constants: Vec::new(),
+            external_contracts: Vec::new(),
+        };
+        transform::prune_definitions(&mut body);
+        Self {
+            functions: body.functions,
+            root_schema: body.root_schema,
+        }
+    }
+
+    /// Converts the interface into an ABI body, e.g. to publish it along with a standard.
+    pub fn into_body(self) -> AbiBody {
+        AbiBody {
+            functions: self.functions,
+            root_schema: self.root_schema,
+            state: None,
+            constants: Vec::new(),
+            external_contracts: Vec::new(),
+        }
+    }
+}
+
+impl From<AbiBody> for AbiInterface {
+    /// Reads a published interface, see [`AbiInterface::into_body`].
+    fn from(body: AbiBody) -> Self {
+        Self {
+            functions: body.functions,
+            root_schema: body.root_schema,
+        }
+    }
+}
+
+/// Root schema definitions transitively used by the signature of `function`, along with their
+/// schemas. Missing definitions are mapped to `None`.
+fn used_definitions<'a>(
+    root_schema: &'a RootSchema,
+    function: &AbiFunction,
+) -> BTreeMap<String, Option<&'a Schema>> {
+    let schemas = function_json_schemas(function)
+        .into_iter()
+        .map(|(_, schema)| schema);
+    reachable_definitions(root_schema, schemas)
+        .into_iter()
+        .map(|name| {
+            let schema = root_schema.definitions.get(&name);
+            (name, schema)
+        })
+        .collect()
+}
+
+/// Error returned when an interface can not be extracted from an ABI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExtractError {
+    /// The function is not declared in the ABI.
+    UnknownFunction(String),
+}
+
+impl std::error::Error for ExtractError {}
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownFunction(function) => {
+                write!(f, "function `{}` is not declared in the ABI", function)
+            }
+        }
+    }
+}
+
+/// A way in which a contract does not implement an interface.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConformanceError {
+    /// The function of the interface is not declared by the contract.
+    MissingFunction(String),
+    /// The function of the interface is private to the contract.
+    PrivateFunction(String),
+    /// Callers of the interface fail against the contract.
+    Incompatible(diff::Incompatibility),
+}
+
+impl std::error::Error for ConformanceError {}
+impl fmt::Display for ConformanceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingFunction(function) => {
+                write!(f, "function `{}` is not implemented", function)
+            }
+            Self::PrivateFunction(function) => write!(f, "function `{}` is private", function),
+            Self::Incompatible(incompatibility) => incompatibility.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_interface() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": { "name": "token", "version": "1.0.0" },
+            "body": {
+                "functions": [
+                    {
+                        "name": "ft_balance_of",
+                        "namespace": "public",
+                        "doc": " Balance of the account on this deployment.",
+                        "kind": "view",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [{ "name": "account_id", "type_schema": { "$ref": "#/definitions/AccountId" } }]
+                        },
+                        "result": { "serialization_type": "json", "type_schema": { "$ref": "#/definitions/U128" } },
+                        "gas_estimate": { "min": 1, "median": 2, "max": 3 }
+                    },
+                    { "name": "ft_resolve_transfer", "kind": "call", "modifiers": ["private"] },
+                    { "name": "mint", "kind": "call", "params": {
+                        "serialization_type": "json",
+                        "args": [{ "name": "memo", "type_schema": { "$ref": "#/definitions/Memo" } }]
+                    } }
+                ],
+                "root_schema": {
+                    "definitions": {
+                        "AccountId": { "type": "string" },
+                        "U128": { "type": "string" },
+                        "Memo": { "type": "string" }
+                    }
+                },
+                "constants": [{ "name": "FEE", "type_schema": { "type": "integer" }, "value": 1 }]
+            }
+        }))
+        .unwrap();
+
+        let interface =
+            AbiInterface::extract(&abi, ["ft_balance_of", "ft_resolve_transfer"]).unwrap();
+        assert_eq!(interface.functions.len(), 1);
+        let function = &interface.functions[0];
+        assert_eq!(function.name, "ft_balance_of");
+        assert_eq!(function.doc, None);
+        assert_eq!(function.namespace, None);
+        assert_eq!(function.gas_estimate, None);
+        assert_eq!(function.params, abi.body.functions[0].params);
+        assert_eq!(
+            interface.root_schema.definitions.keys().collect::<Vec<_>>(),
+            vec!["AccountId", "U128"]
+        );
+        assert!(interface.into_body().constants.is_empty());
+
+        assert_eq!(
+            AbiInterface::extract(&abi, ["ft_transfer"]),
+            Err(ExtractError::UnknownFunction("ft_transfer".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_intersection() {
+        let abi = |functions: serde_json::Value, memo: serde_json::Value| {
+            AbiRoot::from_value(json!({
+                "schema_version": SCHEMA_VERSION,
+                "metadata": {},
+                "body": {
+                    "functions": functions,
+                    "root_schema": {
+                        "definitions": { "AccountId": { "type": "string" }, "Memo": memo }
+                    }
+                }
+            }))
+            .unwrap()
+        };
+        let account_param = json!({
+            "serialization_type": "json",
+            "args": [{ "name": "account_id", "type_schema": { "$ref": "#/definitions/AccountId" } }]
+        });
+        let memo_param = json!({
+            "serialization_type": "json",
+            "args": [{ "name": "memo", "type_schema": { "$ref": "#/definitions/Memo" } }]
+        });
+        let v1 = abi(
+            json!([
+                { "name": "balance_of", "kind": "view", "params": account_param },
+                { "name": "log", "kind": "call", "params": memo_param },
+                { "name": "ping", "kind": "call" },
+                { "name": "resolve", "kind": "call", "modifiers": ["private"] }
+            ]),
+            json!({ "type": "string" }),
+        );
+        let v2 = abi(
+            json!([
+                { "name": "ping", "kind": "call", "doc": " Pings the contract." },
+                { "name": "balance_of", "kind": "view", "params": account_param },
+                { "name": "log", "kind": "call", "params": memo_param },
+                { "name": "resolve", "kind": "call", "modifiers": ["private"] }
+            ]),
+            json!({ "type": "string", "maxLength": 32 }),
+        );
+        let v3 = abi(
+            json!([
+                { "name": "balance_of", "kind": "view", "params": account_param },
+                { "name": "log", "kind": "call", "params": memo_param },
+                { "name": "ping", "kind": "view" }
+            ]),
+            json!({ "type": "string" }),
+        );
+
+        let interface = AbiInterface::intersection(&[v1.clone(), v2]);
+        let names: Vec<_> = interface.functions.iter().map(|f| &f.name).collect();
+        // `log` takes a differently constrained memo, `resolve` is private.
+        assert_eq!(names, ["balance_of", "ping"]);
+        assert_eq!(interface.functions[1].doc, None);
+        assert_eq!(
+            interface.root_schema.definitions.keys().collect::<Vec<_>>(),
+            vec!["AccountId"]
+        );
+
+        let interface = AbiInterface::intersection(&[v1.clone(), v3]);
+        let names: Vec<_> = interface.functions.iter().map(|f| &f.name).collect();
+        assert_eq!(names, ["balance_of", "log"]);
+
+        assert_eq!(
+            AbiInterface::intersection(std::slice::from_ref(&v1)),
+            AbiInterface::extract(&v1, ["balance_of", "log", "ping", "resolve"]).unwrap()
+        );
+        assert!(AbiInterface::intersection(&[]).functions.is_empty());
+    }
+
+    #[test]
+    fn test_check_conformance() {
+        let abi = |functions: serde_json::Value| {
+            AbiRoot::from_value(json!({
+                "schema_version": SCHEMA_VERSION,
+                "metadata": {},
+                "body": {
+                    "functions": functions,
+                    "root_schema": {
+                        "definitions": { "AccountId": { "type": "string" }, "U128": { "type": "string" } }
+                    }
+                }
+            }))
+            .unwrap()
+        };
+        let balance_of = json!({
+            "name": "ft_balance_of",
+            "kind": "view",
+            "params": {
+                "serialization_type": "json",
+                "args": [{ "name": "account_id", "type_schema": { "$ref": "#/definitions/AccountId" } }]
+            },
+            "result": { "serialization_type": "json", "type_schema": { "$ref": "#/definitions/U128" } }
+        });
+        let standard = abi(json!([
+            balance_of,
+            { "name": "ft_total_supply", "kind": "view", "result": { "serialization_type": "json", "type_schema": { "$ref": "#/definitions/U128" } } },
+            { "name": "ft_transfer", "kind": "call", "modifiers": ["payable"] }
+        ]));
+        let interface = AbiInterface::from(standard.body.clone());
+
+        assert_eq!(interface.check_conformance(&standard), Ok(()));
+        let token = abi(json!([
+            balance_of,
+            { "name": "ft_total_supply", "kind": "view", "doc": " Total supply.", "result": { "serialization_type": "json", "type_schema": { "$ref": "#/definitions/U128" } } },
+            { "name": "ft_transfer", "kind": "call", "modifiers": ["payable"] },
+            { "name": "mint", "kind": "call" }
+        ]));
+        assert_eq!(interface.check_conformance(&token), Ok(()));
+
+        let token = abi(json!([
+            balance_of,
+            { "name": "ft_total_supply", "kind": "call", "modifiers": ["private"] },
+            { "name": "ft_transfer", "kind": "call" }
+        ]));
+        let errors: Vec<String> = interface
+            .check_conformance(&token)
+            .unwrap_err()
+            .iter()
+            .map(|error| error.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "function `ft_total_supply` is private",
+                "function `ft_transfer`: modifier `payable` removed"
+            ]
+        );
+    }
+}
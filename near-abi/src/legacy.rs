@@ -0,0 +1,175 @@
+//! Helpers for ABI documents produced by older versions of the schema.
+//!
+//! Before `0.3.0`, functions described their semantics with the `is_view`, `is_init`,
+//! `is_payable` and `is_private` boolean flags instead of a [`AbiFunctionKind`] and a list of
+//! [`AbiFunctionModifier`]s. Later, functions returning `()` could not be told apart from
+//! functions whose return type was not captured, see [`mark_unit_results`]. Links to the
+//! contract repository and documentation used to be stored as arbitrary metadata, see
+//! [`move_links`].
+
+use crate::{AbiBody, AbiFunctionKind, AbiFunctionModifier, AbiMetadata};
+
+/// Function flags of ABI schema versions before `0.3.0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LegacyFlags {
+    pub is_view: bool,
+    pub is_init: bool,
+    pub is_payable: bool,
+    pub is_private: bool,
+}
+
+/// Maps legacy function flags to a function kind and modifiers, listed in declaration order.
+pub fn from_legacy_flags(
+    is_view: bool,
+    is_init: bool,
+    is_payable: bool,
+    is_private: bool,
+) -> (AbiFunctionKind, Vec<AbiFunctionModifier>) {
+    let kind = if is_view {
+        AbiFunctionKind::View
+    } else {
+        AbiFunctionKind::Call
+    };
+    let modifiers = [
+        (is_init, AbiFunctionModifier::Init),
+        (is_private, AbiFunctionModifier::Private),
+        (is_payable, AbiFunctionModifier::Payable),
+    ]
+    .into_iter()
+    .filter(|(set, _)| *set)
+    .map(|(_, modifier)| modifier)
+    .collect();
+    (kind, modifiers)
+}
+
+/// Maps a function kind and modifiers back to legacy function flags.
+pub fn to_legacy_flags(kind: &AbiFunctionKind, modifiers: &[AbiFunctionModifier]) -> LegacyFlags {
+    LegacyFlags {
+        is_view: *kind == AbiFunctionKind::View,
+        is_init: modifiers.contains(&AbiFunctionModifier::Init),
+        is_payable: modifiers.contains(&AbiFunctionModifier::Payable),
+        is_private: modifiers.contains(&AbiFunctionModifier::Private),
+    }
+}
+
+/// Marks the functions without a result as returning nothing.
+///
+/// ABIs predating [`AbiFunction::returns_nothing`](crate::AbiFunction::returns_nothing) leave out
+/// the result of functions returning `()`. This migration is only correct for ABIs whose
+/// producer captured every return type, which is the case of `cargo-near`.
+///
+/// Returns the number of functions that were marked.
+pub fn mark_unit_results(body: &mut AbiBody) -> usize {
+    let mut marked = 0;
+    for function in &mut body.functions {
+        if function.result.is_none() && !function.returns_nothing {
+            function.returns_nothing = true;
+            marked += 1;
+        }
+    }
+    marked
+}
+
+/// Moves the `repository`, `homepage` and `documentation` entries of the arbitrary metadata of
+/// a contract to [`AbiMetadata::links`], unless the corresponding link is already set.
+///
+/// Returns the number of links that were moved.
+pub fn move_links(metadata: &mut AbiMetadata) -> usize {
+    let mut moved = 0;
+    let links = &mut metadata.links;
+    for (key, link) in [
+        ("repository", &mut links.repository),
+        ("homepage", &mut links.homepage),
+        ("documentation", &mut links.documentation),
+    ] {
+        if link.is_none() {
+            if let Some(url) = metadata.other.remove(key) {
+                *link = Some(url);
+                moved += 1;
+            }
+        }
+    }
+    moved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark_unit_results() {
+        use crate::{AbiFunction, AbiReturn, AbiType};
+
+        let mut body = AbiBody {
+            functions: vec![
+                AbiFunction::new("ping", AbiFunctionKind::Call),
+                AbiFunction {
+                    result: Some(AbiType::borsh_of::<u8>()),
+                    ..AbiFunction::new("get", AbiFunctionKind::View)
+                },
+            ],
+            root_schema: Default::default(),
+            state: None,
+            constants: Vec::new(),
+            external_contracts: Vec::new(),
+        };
+        assert_eq!(body.functions[0].returns(), AbiReturn::Unknown);
+        assert_eq!(mark_unit_results(&mut body), 1);
+        assert_eq!(body.functions[0].returns(), AbiReturn::Nothing);
+        assert!(matches!(body.functions[1].returns(), AbiReturn::Value(_)));
+        assert_eq!(mark_unit_results(&mut body), 0);
+        assert_eq!(
+            serde_json::to_value(&body.functions[0]).unwrap(),
+            serde_json::json!({ "name": "ping", "kind": "call", "returns_nothing": true })
+        );
+    }
+
+    #[test]
+    fn test_move_links() {
+        let mut metadata: AbiMetadata = serde_json::from_value(serde_json::json!({
+            "name": "token",
+            "repository": "https://github.com/near/token",
+            "homepage": "https://token.near.org",
+            "links": { "homepage": "https://near.org" }
+        }))
+        .unwrap();
+        assert_eq!(move_links(&mut metadata), 1);
+        assert_eq!(
+            serde_json::to_value(&metadata).unwrap(),
+            serde_json::json!({
+                "name": "token",
+                "links": {
+                    "repository": "https://github.com/near/token",
+                    "homepage": "https://near.org"
+                },
+                "homepage": "https://token.near.org"
+            })
+        );
+    }
+
+    #[test]
+    fn test_legacy_flags_roundtrip() {
+        for bits in 0..16u8 {
+            let flags = LegacyFlags {
+                is_view: bits & 1 != 0,
+                is_init: bits & 2 != 0,
+                is_payable: bits & 4 != 0,
+                is_private: bits & 8 != 0,
+            };
+            let (kind, modifiers) = from_legacy_flags(
+                flags.is_view,
+                flags.is_init,
+                flags.is_payable,
+                flags.is_private,
+            );
+            assert_eq!(to_legacy_flags(&kind, &modifiers), flags);
+        }
+        assert_eq!(
+            from_legacy_flags(false, true, true, false),
+            (
+                AbiFunctionKind::Call,
+                vec![AbiFunctionModifier::Init, AbiFunctionModifier::Payable]
+            )
+        );
+    }
+}
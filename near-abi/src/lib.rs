@@ -6,12 +6,58 @@ use schemars::JsonSchema;
 use semver::Version;
 use serde::{de, Deserialize, Deserializer, Serialize};
 use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 
 #[doc(hidden)]
 #[cfg(feature = "__chunked-entries")]
 #[path = "private.rs"]
 pub mod __private;
 
+pub mod access_key;
+pub mod analysis;
+pub mod annotations;
+pub mod batch;
+pub mod bundle;
+pub mod cache;
+pub mod codegen;
+pub mod convert;
+#[cfg(feature = "corpus")]
+pub mod corpus;
+pub mod deployment;
+pub mod deprecation;
+pub mod diff;
+pub mod doc;
+pub mod docs;
+pub mod draft;
+pub mod formats;
+pub mod incremental;
+pub mod init;
+pub mod interface;
+pub mod legacy;
+pub mod lint;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod nep330;
+pub mod openapi;
+pub mod ordering;
+pub mod path;
+pub mod permissions;
+pub mod reconcile;
+pub mod redact;
+pub mod requirements;
+pub mod selector;
+pub mod simulation;
+pub mod state_reads;
+pub mod strict;
+pub mod test_vectors;
+pub mod transform;
+pub mod type_hash;
+pub mod validate;
+pub mod value;
+pub mod version;
+mod walk;
+pub mod wasm_hash;
+
 // Keep in sync with SCHEMA_VERSION below.
 const SCHEMA_SEMVER: Version = Version {
     major: 0,
@@ -22,11 +68,22 @@ const SCHEMA_SEMVER: Version = Version {
 };
 
 /// Current version of the ABI schema format.
+///
+/// Every field added since near-abi `0.4.0` is optional and omitted when unset, so documents
+/// not using them are still read by older `0.4` readers. Those deny unknown fields though, and
+/// reject documents setting any of:
+/// - `test_vectors` and `x-` extensions of the root,
+/// - `state`, `constants` and `external_contracts` of the body,
+/// - everything but `name`, `doc`, `kind`, `modifiers`, `params`, `callbacks`, `callbacks_vec`
+///   and `result` of functions, as well as the `ignore_state`, `upgrade`, `migrate` and
+///   `pure` modifiers,
+/// - `name` and `doc` of callbacks, and `x-` extensions of parameters.
+///
+/// Metadata accepts unknown fields, so its new fields are ignored by older readers.
 pub const SCHEMA_VERSION: &str = "0.4.0";
 
 /// Contract ABI.
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
-#[serde(deny_unknown_fields)]
 pub struct AbiRoot {
     /// Semver of the ABI schema format.
     #[serde(deserialize_with = "ensure_current_version")]
@@ -35,13 +92,309 @@ pub struct AbiRoot {
     pub metadata: AbiMetadata,
     /// Core ABI information (functions and types).
     pub body: AbiBody,
+    /// Encoded arguments paired with their expected decoding, for conformance testing of ABI
+    /// implementations, see [`test_vectors`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub test_vectors: Vec<test_vectors::AbiTestVector>,
+    /// Vendor extensions.
+    #[serde(flatten, skip_serializing_if = "AbiExtensions::is_empty")]
+    pub extensions: AbiExtensions,
+}
+
+/// Data attached by third-party tooling, under keys prefixed with `x-` (e.g.
+/// `"x-indexer-priority": 1`). Extensions are not interpreted by this crate and round-trip as is.
+///
+/// Objects holding extensions flatten them and can not use `#[serde(deny_unknown_fields)]`,
+/// which does not support `flatten`. Every key they do not claim ends up in their extensions
+/// instead, whose deserialization rejects the keys not starting with `x-`.
+#[derive(Clone, Serialize, Debug, Default, PartialEq)]
+#[serde(transparent)]
+pub struct AbiExtensions(BTreeMap<String, serde_json::Value>);
+
+impl AbiExtensions {
+    /// Pattern of the keys of extensions.
+    pub const KEY_PATTERN: &'static str = "^x-";
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&serde_json::Value> {
+        self.0.get(key)
+    }
+
+    /// Sets an extension, returning its previous value. Fails if `key` does not start with `x-`.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: serde_json::Value,
+    ) -> Result<Option<serde_json::Value>, ExtensionKeyError> {
+        let key = key.into();
+        if !key.starts_with("x-") {
+            return Err(ExtensionKeyError(key));
+        }
+        Ok(self.0.insert(key, value))
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<serde_json::Value> {
+        self.0.remove(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &serde_json::Value)> {
+        self.0.iter().map(|(key, value)| (key.as_str(), value))
+    }
+}
+
+impl<'de> Deserialize<'de> for AbiExtensions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let map = BTreeMap::<String, serde_json::Value>::deserialize(deserializer)?;
+        if let Some(key) = map.keys().find(|key| !key.starts_with("x-")) {
+            return Err(de::Error::custom(format_args!(
+                "unknown field `{}`, extension fields must start with `x-`",
+                key
+            )));
+        }
+        Ok(Self(map))
+    }
+}
+
+impl JsonSchema for AbiExtensions {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "AbiExtensions".to_string()
+    }
+
+    fn json_schema(_gen: &mut schemars::gen::SchemaGenerator) -> Schema {
+        let mut schema_object = schemars::schema::SchemaObject {
+            instance_type: Some(schemars::schema::InstanceType::Object.into()),
+            ..Default::default()
+        };
+        let object_validation = schema_object.object();
+        object_validation
+            .pattern_properties
+            .insert(Self::KEY_PATTERN.to_string(), Schema::Bool(true));
+        object_validation.additional_properties = Some(Schema::Bool(false).into());
+        schema_object.into()
+    }
+}
+
+/// Error returned when an extension key does not start with `x-`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExtensionKeyError(String);
+
+impl std::error::Error for ExtensionKeyError {}
+impl fmt::Display for ExtensionKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "extension key `{}` does not start with `x-`", self.0)
+    }
+}
+
+impl AbiRoot {
+    /// Converts the ABI into a JSON value without going through its string representation.
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).expect("ABI serialization can not fail")
+    }
+
+    /// Parses the ABI from a JSON value without going through its string representation.
+    ///
+    /// The schema version is checked before the rest of the document is parsed, so ABIs of
+    /// incompatible versions are rejected early.
+    pub fn from_value(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        if let Some(schema_version) = value.get("schema_version") {
+            ensure_current_version(schema_version)?;
+        }
+        serde_json::from_value(value)
+    }
+
+    /// Parses the ABI like [`AbiRoot::from_value`], and additionally rejects it if it fails
+    /// any check of the [strict profile](strict::DeserializeOptions::strict).
+    pub fn from_value_strict(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        strict::DeserializeOptions::strict().from_value(value)
+    }
+
+    /// Parses the ABI from a JSON string like [`AbiRoot::from_value_strict`].
+    pub fn from_str_strict(s: &str) -> Result<Self, serde_json::Error> {
+        strict::DeserializeOptions::strict().from_str(s)
+    }
+
+    /// Parses the ABI like [`AbiRoot::from_value`], but preserves function modifiers introduced
+    /// by newer schema versions as [`AbiFunctionModifier::Unknown`] and accepts malformed method
+    /// names instead of failing, so that tools can pass such ABIs through unchanged.
+    ///
+    /// Newer schema versions of the same major version are accepted too, e.g. `0.6.0` while the
+    /// current one is `0.4.0`. Fields introduced by such versions are still rejected.
+    pub fn from_value_lenient(value: serde_json::Value) -> Result<Self, serde_json::Error> {
+        lenient::enable(|| Self::from_value(value))
+    }
+
+    /// Brings the ABI into canonical form for hashing and diffing, see
+    /// [`transform::canonicalize`].
+    pub fn normalize(&mut self) {
+        transform::canonicalize(self);
+    }
+
+    /// Checks the internal consistency of the ABI, see [`validate::validate`].
+    pub fn validate(&self) -> Vec<validate::Violation> {
+        validate::validate(self)
+    }
+
+    /// The init function of the contract, if any.
+    ///
+    /// Fails if the contract has several init functions; use [`AbiRoot::init_functions`] and
+    /// [`init::InitCall::with_function`] for contracts that explicitly allow several.
+    pub fn init_function(&self) -> Result<Option<&AbiFunction>, init::InitError> {
+        let mut functions = self.init_functions();
+        let first = functions.next();
+        if first.is_some() && functions.next().is_some() {
+            return Err(init::InitError::MultipleInitFunctions(
+                self.init_functions().map(|f| f.name.clone()).collect(),
+            ));
+        }
+        Ok(first)
+    }
+
+    /// All init functions of the contract.
+    pub fn init_functions(&self) -> impl Iterator<Item = &AbiFunction> {
+        self.body.functions.iter().filter(|f| init::is_init(f))
+    }
+
+    /// Deprecated functions, ordered by the version they were deprecated in. Functions without
+    /// a version come last, in declaration order.
+    pub fn deprecations(&self) -> Vec<deprecation::Deprecation> {
+        deprecation::timeline(self)
+    }
+
+    /// Elements of the ABI whose wire format changes if the type named `type_name` changes, see
+    /// [`analysis::type_impact`].
+    pub fn impact_of_type_change(&self, type_name: &str) -> Vec<path::AbiPath> {
+        analysis::type_impact(&self.body, type_name)
+    }
+
+    /// Functions that first appeared in contract version `version`, in declaration order.
+    pub fn introduced_in<'a>(&'a self, version: &'a str) -> impl Iterator<Item = &'a AbiFunction> {
+        self.body
+            .functions
+            .iter()
+            .filter(move |function| function.since.as_deref() == Some(version))
+    }
+
+    /// Functions grouped by the trait they implement, in declaration order. Functions declared
+    /// outside of trait impl blocks are grouped under the `None` key.
+    pub fn functions_by_interface(&self) -> BTreeMap<Option<&str>, Vec<&AbiFunction>> {
+        let mut groups = BTreeMap::<Option<&str>, Vec<&AbiFunction>>::new();
+        for function in &self.body.functions {
+            groups
+                .entry(function.interface.as_deref())
+                .or_default()
+                .push(function);
+        }
+        groups
+    }
+
+    /// Splits the ABI into one sub-ABI per function namespace. Functions without a namespace are
+    /// grouped under the `None` key.
+    ///
+    /// Every sub-ABI shares the metadata of the original one and only keeps the root schema
+    /// definitions reachable from its own functions.
+    pub fn split_by_namespace(&self) -> BTreeMap<Option<String>, AbiRoot> {
+        let mut groups = BTreeMap::<Option<String>, Vec<AbiFunction>>::new();
+        for function in &self.body.functions {
+            groups
+                .entry(function.namespace.clone())
+                .or_default()
+                .push(function.clone());
+        }
+        groups
+            .into_iter()
+            .map(|(namespace, functions)| {
+                let mut body = AbiBody {
+                    functions,
+                    root_schema: self.body.root_schema.clone(),
+                    state: self.body.state.clone(),
+                    constants: self.body.constants.clone(),
+                    external_contracts: self.body.external_contracts.clone(),
+                };
+                transform::prune_definitions(&mut body);
+                let test_vectors = self
+                    .test_vectors
+                    .iter()
+                    .filter(|vector| body.functions.iter().any(|f| f.name == vector.function))
+                    .cloned()
+                    .collect();
+                let abi = AbiRoot {
+                    schema_version: self.schema_version.clone(),
+                    metadata: self.metadata.clone(),
+                    body,
+                    test_vectors,
+                    extensions: self.extensions.clone(),
+                };
+                (namespace, abi)
+            })
+            .collect()
+    }
+}
+
+/// Differences between two versions of a contract ABI, see [`diff::AbiDiff`].
+pub fn diff(old: &AbiRoot, new: &AbiRoot) -> diff::AbiDiff {
+    diff::AbiDiff::new(old, new)
+}
+
+/// Whether clients generated from the `old` ABI keep working against a contract implementing the
+/// `new` one, i.e. whether none of the differences between them is breaking (see
+/// [`diff::AbiDiff::classify`]).
+///
+/// New functions and optional parameters are accepted, as are parameter types accepting more
+/// values and result types producing fewer. Removed functions, new required parameters and
+/// widened results are not. References are resolved, so the same holds for root schema
+/// definitions according to the sites using them, and renamed definitions are accepted.
+pub fn is_compatible_caller(
+    old: &AbiRoot,
+    new: &AbiRoot,
+) -> Result<(), Vec<diff::Incompatibility>> {
+    let incompatibilities = diff(old, new).classify().incompatibilities();
+    if incompatibilities.is_empty() {
+        Ok(())
+    } else {
+        Err(incompatibilities)
+    }
+}
+
+/// JSON Schema of ABI documents, as published in the `metaschema` directory of this repository.
+pub fn metaschema() -> RootSchema {
+    schemars::gen::SchemaGenerator::default().root_schema_for::<AbiRoot>()
+}
+
+/// A part of a JSON document that does not conform to the [`metaschema`].
+pub type ValidationError = value::ValueError;
+
+/// Checks an arbitrary JSON document against the [`metaschema`], reporting every nonconforming
+/// part of it (see [`value::json_errors`]).
+///
+/// Unlike parsing with [`AbiRoot::from_value`], validation does not stop at the first error and
+/// works on documents that are not ABIs at all. It does not check `pattern`s, nor the
+/// compatibility of the `schema_version`.
+pub fn validate_value(value: &serde_json::Value) -> Result<(), Vec<ValidationError>> {
+    let metaschema = metaschema();
+    let schema = Schema::Object(metaschema.schema.clone());
+    let errors = value::json_errors(&metaschema, &schema, value);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
 }
 
 fn ensure_current_version<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
     let unchecked = String::deserialize(d)?;
     let version = Version::parse(&unchecked)
         .map_err(|_| de::Error::custom("expected `schema_version` to be a valid semver value"))?;
-    if version.major != SCHEMA_SEMVER.major || version.minor != SCHEMA_SEMVER.minor {
+    // Lenient parsing passes through documents of newer versions with the same major version.
+    let accepted_newer =
+        lenient::is_enabled() && version.major == SCHEMA_SEMVER.major && version > SCHEMA_SEMVER;
+    if !version::is_compatible(&version, &SCHEMA_SEMVER) && !accepted_newer {
         if version < SCHEMA_SEMVER {
             return Err(de::Error::custom(format!(
                 "expected `schema_version` to be ~{}.{}, but got {}: consider re-generating your ABI file with a newer version of SDK and cargo-near",
@@ -54,77 +407,998 @@ fn ensure_current_version<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::
             )));
         }
     }
-    Ok(unchecked)
-}
-
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default, JsonSchema)]
-pub struct BuildInfo {
-    /// The compiler (versioned) that was used to build the contract.
-    pub compiler: String,
-    /// The build tool (versioned) that was used to build the contract.
-    pub builder: String,
-    /// The docker image (versioned) where the contract was built.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub image: Option<String>,
-}
+    Ok(unchecked)
+}
+
+fn ensure_method_name<'de, D: Deserializer<'de>>(d: D) -> Result<String, D::Error> {
+    let name = String::deserialize(d)?;
+    if !lenient::is_enabled() {
+        validate::check_method_name(&name).map_err(de::Error::custom)?;
+    }
+    Ok(name)
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default, JsonSchema)]
+pub struct BuildInfo {
+    /// The compiler (versioned) that was used to build the contract.
+    pub compiler: String,
+    /// The build tool (versioned) that was used to build the contract.
+    pub builder: String,
+    /// The docker image (versioned) where the contract was built.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Link to the snapshot of the source code the contract was built from (e.g.
+    /// `git+https://github.com/near/token?rev=<commit>`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_code_snapshot: Option<String>,
+    /// Path of the contract crate inside the source code snapshot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contract_path: Option<String>,
+    /// The command that was run inside `image` to build the contract.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub build_command: Vec<String>,
+    /// The commit of the source repository the contract was built from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_commit: Option<String>,
+    /// The SHA-256 hash of the `Cargo.lock` file the contract was built with, in hex format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo_lock_hash: Option<String>,
+    /// The exact `rustc` version, as printed by `rustc --version`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rustc_version: Option<String>,
+}
+
+impl BuildInfo {
+    /// Whether the information is enough to attempt a byte-for-byte rebuild of the contract: the
+    /// build image and command, the exact sources and the locked dependencies.
+    pub fn is_reproducible(&self) -> bool {
+        self.image.is_some()
+            && !self.build_command.is_empty()
+            && (self.source_code_snapshot.is_some() || self.source_commit.is_some())
+            && self.cargo_lock_hash.is_some()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default, JsonSchema)]
+pub struct AbiMetadata {
+    /// The name of the smart contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The version of the smart contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The authors of the smart contract.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authors: Vec<String>,
+    /// Overview of the smart contract, parsed from the crate-level (`//!`) documentation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Keywords describing the smart contract, as in the `keywords` of its `Cargo.toml` (e.g.
+    /// `nft` or `defi`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// The information about how this contract was built.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildInfo>,
+    /// The SHA-256 hash of the contract WASM code in Base58 format.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm_hash: Option<wasm_hash::WasmHash>,
+    /// The standards (NEPs) implemented by the smart contract.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub standards: Vec<AbiStandard>,
+    /// Links to resources about the smart contract.
+    #[serde(default, skip_serializing_if = "AbiLinks::is_empty")]
+    pub links: AbiLinks,
+    /// What the smart contract needs from the network it is deployed to.
+    #[serde(default, skip_serializing_if = "AbiRequirements::is_empty")]
+    pub requirements: AbiRequirements,
+    /// Accounts the contract is deployed to, on mainnet, testnet or other networks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deployments: Vec<AbiDeployment>,
+    /// Other arbitrary metadata. Serialized sorted by key.
+    #[serde(
+        default,
+        flatten,
+        skip_serializing_if = "HashMap::is_empty",
+        serialize_with = "serialize_sorted"
+    )]
+    pub other: HashMap<String, String>,
+}
+
+/// Links to resources about a contract, rendered by explorers.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiLinks {
+    /// URL of the source code repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// URL of the project homepage.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    /// URL of the user documentation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    /// URLs of the security audit reports of the contract.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub audit_reports: Vec<String>,
+}
+
+impl AbiLinks {
+    pub fn is_empty(&self) -> bool {
+        self.repository.is_none()
+            && self.homepage.is_none()
+            && self.documentation.is_none()
+            && self.audit_reports.is_empty()
+    }
+}
+
+/// Runtime requirements of a contract, checked by deployment tooling with
+/// [`AbiRequirements::check`].
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiRequirements {
+    /// Minimum NEAR protocol version of the network (e.g. `73`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_protocol_version: Option<u32>,
+    /// Minimum version of `near-sdk` the contract is meant to be built with (e.g. `5.1.0`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_sdk_version: Option<String>,
+    /// Host functions imported by the contract (e.g. `promise_yield_create`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub host_functions: Vec<String>,
+}
+
+impl AbiRequirements {
+    pub fn is_empty(&self) -> bool {
+        self.min_protocol_version.is_none()
+            && self.min_sdk_version.is_none()
+            && self.host_functions.is_empty()
+    }
+
+    /// Checks that `network` meets the requirements.
+    pub fn check(
+        &self,
+        network: &requirements::Network,
+    ) -> Result<(), requirements::RequirementsError> {
+        requirements::check(self, network)
+    }
+}
+
+impl AbiMetadata {
+    /// The overview documentation of the contract split along rustdoc conventions.
+    pub fn structured_doc(&self) -> Option<doc::AbiDoc> {
+        self.doc.as_deref().map(doc::AbiDoc::parse)
+    }
+
+    /// Records a deployment of the contract. Fails if the deployed code is not the one described
+    /// by [`AbiMetadata::wasm_hash`], or if a deployment to the same account is already recorded.
+    pub fn add_deployment(
+        &mut self,
+        deployment: AbiDeployment,
+    ) -> Result<(), deployment::DeploymentError> {
+        deployment::add(self, deployment)
+    }
+
+    /// Checks that the code currently deployed to `account_id` on `network`, whose hash is
+    /// `code_hash`, is the recorded deployment of the contract.
+    pub fn verify_deployment(
+        &self,
+        network: &str,
+        account_id: &str,
+        code_hash: &wasm_hash::WasmHash,
+    ) -> Result<&AbiDeployment, deployment::DeploymentError> {
+        deployment::verify(self, network, account_id, code_hash)
+    }
+
+    /// The declaration of the standard named `standard` (e.g. `nep171`), ignoring ASCII case.
+    pub fn standard(&self, standard: &str) -> Option<&AbiStandard> {
+        self.standards
+            .iter()
+            .find(|candidate| candidate.standard.eq_ignore_ascii_case(standard))
+    }
+
+    /// The standard implemented by the trait named `interface`, if declared.
+    pub fn standard_of_interface(&self, interface: &str) -> Option<&AbiStandard> {
+        self.standards
+            .iter()
+            .find(|standard| standard.interfaces.iter().any(|i| i == interface))
+    }
+
+    /// Whether the contract is tagged with `keyword`, ignoring ASCII case.
+    pub fn has_keyword(&self, keyword: &str) -> bool {
+        self.keywords
+            .iter()
+            .any(|candidate| candidate.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Deployments of the contract on `network`, in the order they were recorded.
+    pub fn deployments_on<'a>(
+        &'a self,
+        network: &'a str,
+    ) -> impl Iterator<Item = &'a AbiDeployment> {
+        self.deployments
+            .iter()
+            .filter(move |deployment| deployment.network == network)
+    }
+}
+
+/// A deployment of the contract to an account.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiDeployment {
+    /// Network the contract is deployed on (e.g. `mainnet` or `testnet`).
+    pub network: String,
+    /// Account the contract is deployed to (e.g. `token.near`).
+    pub account_id: String,
+    /// The SHA-256 hash of the deployed WASM code in Base58 format.
+    pub wasm_hash: wasm_hash::WasmHash,
+    /// Height of the block including the deployment.
+    pub block_height: u64,
+}
+
+/// A standard implemented by the contract, e.g. `nep141@1.0.0`.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiStandard {
+    /// Name of the standard (e.g. `nep141`).
+    pub standard: String,
+    /// Implemented version of the standard (e.g. `1.0.0`).
+    pub version: String,
+    /// Traits implementing the standard (e.g. `FungibleTokenCore`), as named by the
+    /// [`interface`](AbiFunction::interface) of their functions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interfaces: Vec<String>,
+}
+
+impl AbiStandard {
+    pub fn new(standard: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            standard: standard.into(),
+            version: version.into(),
+            interfaces: Vec::new(),
+        }
+    }
+
+    /// The implemented version, if it is a semver version. A leading `v` is ignored.
+    pub fn semver(&self) -> Option<Version> {
+        Version::parse(self.version.trim_start_matches('v')).ok()
+    }
+
+    /// Whether the implemented version is `min_version` or a newer version compatible with it,
+    /// e.g. `1.2.0` for a minimum of `1.1.0`, but not `1.1.0` for a minimum of `1.1.5`, nor
+    /// `2.0.0`.
+    pub fn satisfies(&self, min_version: &Version) -> bool {
+        self.semver().map_or(false, |version| {
+            version >= *min_version && version::is_compatible(&version, min_version)
+        })
+    }
+
+    /// NEP number of the standard, if it is named `nep<number>`.
+    pub fn nep(&self) -> Option<u32> {
+        self.standard
+            .strip_prefix("nep")
+            .or_else(|| self.standard.strip_prefix("NEP"))
+            .and_then(|number| number.parse().ok())
+    }
+}
+
+impl fmt::Display for AbiStandard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}@{}", self.standard, self.version)
+    }
+}
+
+impl std::str::FromStr for AbiStandard {
+    type Err = ParseStandardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('@') {
+            Some((standard, version)) if !standard.is_empty() && !version.is_empty() => {
+                Ok(Self::new(standard, version))
+            }
+            _ => Err(ParseStandardError(s.to_string())),
+        }
+    }
+}
+
+/// Error returned when parsing an [`AbiStandard`] not in the `<standard>@<version>` format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseStandardError(String);
+
+impl std::error::Error for ParseStandardError {}
+impl fmt::Display for ParseStandardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected a standard in the `<standard>@<version>` format, got `{}`",
+            self.0
+        )
+    }
+}
+
+fn serialize_sorted<S: serde::Serializer>(
+    map: &HashMap<String, String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Core ABI information.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiBody {
+    /// ABIs of all contract's functions.
+    pub functions: Vec<AbiFunction>,
+    /// Root JSON Schema containing all types referenced in the functions.
+    pub root_schema: RootSchema,
+    /// Persistent state of the contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<AbiState>,
+    /// Compile-time constants exposed by the contract.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constants: Vec<AbiConstant>,
+    /// Interfaces of other contracts called by this contract, declared with `#[ext_contract]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub external_contracts: Vec<AbiExternalContract>,
+}
+
+impl AbiBody {
+    /// Root schema definitions, sorted by name.
+    ///
+    /// Definitions are read from the root schema regardless of whether the document stored them
+    /// under `definitions` or `$defs`.
+    pub fn definitions(&self) -> impl Iterator<Item = (&str, &Schema)> {
+        self.root_schema
+            .definitions
+            .iter()
+            .map(|(name, schema)| (name.as_str(), schema))
+    }
+
+    /// The root schema definition named `name`.
+    pub fn definition(&self, name: &str) -> Option<&Schema> {
+        self.root_schema.definitions.get(name)
+    }
+
+    /// Mutable access to the root schema definition named `name`.
+    pub fn definition_mut(&mut self, name: &str) -> Option<&mut Schema> {
+        self.root_schema.definitions.get_mut(name)
+    }
+
+    /// The root schema definition a `$ref` points to, e.g. `#/definitions/AccountId` or
+    /// `#/$defs/AccountId`.
+    pub fn resolve_reference(&self, reference: &str) -> Option<&Schema> {
+        value::resolve_reference(&self.root_schema, reference)
+    }
+
+    /// References that do not resolve to a root schema definition, see
+    /// [`validate::dangling_references`].
+    pub fn dangling_references(&self) -> Vec<validate::DanglingReference> {
+        validate::dangling_references(self)
+    }
+
+    /// The function named `name`, either by its method name (e.g. `set_fee`) or by its
+    /// [qualified name](AbiFunction::qualified_name) (e.g. `admin::set_fee`).
+    pub fn function(&self, name: &str) -> Option<&AbiFunction> {
+        match name.rsplit_once("::") {
+            Some((namespace, name)) => self
+                .functions
+                .iter()
+                .find(|f| f.name == name && f.namespace.as_deref() == Some(namespace)),
+            None => self.functions.iter().find(|f| f.name == name),
+        }
+    }
+
+    /// The function whose computed selector is `selector`.
+    pub fn function_by_selector(&self, selector: &selector::AbiSelector) -> Option<&AbiFunction> {
+        self.functions
+            .iter()
+            .find(|function| function.selector() == *selector)
+    }
+
+    /// Functions of the given namespace, `None` standing for functions without a namespace.
+    pub fn functions_in<'a>(
+        &'a self,
+        namespace: Option<&'a str>,
+    ) -> impl Iterator<Item = &'a AbiFunction> {
+        self.functions
+            .iter()
+            .filter(move |f| f.namespace.as_deref() == namespace)
+    }
+}
+
+/// Description of the persistent state of a contract.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiState {
+    /// Type identifier of the top-level state struct, stored under the `STATE` key.
+    pub root: AbiType,
+    /// Collections keeping their entries under separate storage keys.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collections: Vec<AbiStateCollection>,
+}
+
+/// A compile-time constant of the contract (e.g. a fee percentage or a storage cost).
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiConstant {
+    pub name: String,
+    /// Human-readable documentation parsed from the source file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// JSON Subschema of the constant value.
+    pub type_schema: Schema,
+    /// Value of the constant in its JSON representation.
+    pub value: serde_json::Value,
+}
+
+/// Interface of another contract this contract calls into.
+///
+/// Type schemas of the functions reference the root schema of the ABI they are declared in.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiExternalContract {
+    /// Name of the interface (e.g. `ext_ft_core`).
+    pub name: String,
+    /// Human-readable documentation parsed from the source file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Signatures of the functions of the other contract called by this contract.
+    pub functions: Vec<AbiFunction>,
+}
+
+/// A collection of the contract state whose entries are stored outside of the state struct.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiStateCollection {
+    /// Path of the state struct field holding the collection (e.g. `accounts` or
+    /// `metadata.tokens`).
+    pub field: String,
+    /// Storage-key prefix shared by all entries of the collection.
+    pub prefix: Vec<u8>,
+}
+
+/// ABI of a single function.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
+pub struct AbiFunction {
+    /// Method name of the function, a [valid NEAR method name](validate::check_method_name).
+    #[serde(deserialize_with = "ensure_method_name")]
+    #[schemars(length(min = 1, max = 256), regex(pattern = r"^\S+$"))]
+    pub name: String,
+    /// Selector of the function, when embedded by the producer (see [`AbiFunction::selector`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<selector::AbiSelector>,
+    /// Logical group the function belongs to (e.g. `admin` or `internal`), used to publish
+    /// separate interface artifacts for different audiences.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Trait the function implements (e.g. `FungibleTokenCore`), if it was declared in a trait
+    /// impl block. Lets generated clients produce one module per implemented interface.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    /// Human-readable documentation parsed from the source file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Contract version the function first appeared in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// Stability guarantee of the function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stability: Option<AbiStability>,
+    /// Deprecation of the function, from its `#[deprecated]` attribute.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<AbiDeprecation>,
+    /// Function kind that regulates whether the function has to be invoked from a transaction.
+    pub kind: AbiFunctionKind,
+    /// List of modifiers affecting the function.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<AbiFunctionModifier>,
+    /// Accounts allowed to call the function, when restricted by the contract logic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access: Option<AbiAccess>,
+    /// Type identifiers of the function parameters.
+    #[serde(default, skip_serializing_if = "AbiParameters::is_empty")]
+    pub params: AbiParameters,
+    /// Whether the JSON arguments of the function are only ever passed by name, so that the
+    /// order of its parameters does not matter to callers.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub args_unordered: bool,
+    /// Named callbacks of the function, sharing a single serialization type. Serialized as a
+    /// list of callbacks repeating their serialization type; the consolidated form, stating it
+    /// once, is accepted as well (see [`AbiFunction::consolidated_callbacks`]).
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "serialize_callbacks",
+        deserialize_with = "deserialize_callbacks"
+    )]
+    #[schemars(schema_with = "callbacks_schema")]
+    pub callbacks: Vec<AbiCallback>,
+    /// Type identifier of the vararg callbacks of the function.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callbacks_vec: Option<AbiType>,
+    /// Return type identifier. For functions returning a promise, type of the value the promise
+    /// resolves to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<AbiType>,
+    /// How the function produces its result.
+    #[serde(default, skip_serializing_if = "AbiResultKind::is_value")]
+    pub result_kind: AbiResultKind,
+    /// Whether the function is known to return nothing (`()`), as opposed to a function whose
+    /// return type was not captured. Only meaningful when `result` is not set.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub returns_nothing: bool,
+    /// Parts of the contract state read by the function, when supplied by the producer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_reads: Option<AbiStateReads>,
+    /// Side effects of the function, when supplied by the producer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effects: Option<AbiEffects>,
+    /// Measured gas usage of the function, populated by profiling tools.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_estimate: Option<AbiGasEstimate>,
+    /// Storage the function allocates, for functions creating state (e.g. `storage_deposit`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_usage: Option<AbiStorageUsage>,
+    /// Sample invocations of the function, for documentation and mocks.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<AbiExample>,
+    /// Vendor extensions.
+    #[serde(flatten, skip_serializing_if = "AbiExtensions::is_empty")]
+    pub extensions: AbiExtensions,
+}
+
+/// Access restriction enforced by a function on its caller (e.g. with `#[only(owner)]`).
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AbiAccess {
+    /// Only the owner of the contract may call the function.
+    Owner,
+    /// Only the DAO governing the contract may call the function.
+    Dao,
+    /// Only the listed accounts may call the function.
+    Accounts { accounts: Vec<String> },
+    /// Only accounts granted the role may call the function.
+    Role { role: String },
+}
+
+/// How a function produces its result.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AbiResultKind {
+    /// The result is returned immediately.
+    #[default]
+    Value,
+    /// The function returns a `Promise`, the result is the value of the last receipt of the
+    /// promise chain.
+    Promise,
+    /// The function returns a `PromiseOrValue<T>`: either the value immediately, or a promise
+    /// resolving to it.
+    PromiseOrValue,
+}
+
+impl AbiResultKind {
+    pub fn is_value(&self) -> bool {
+        *self == Self::Value
+    }
+}
+
+/// Return type of a function, see [`AbiFunction::returns`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AbiReturn<'a> {
+    /// The function returns a value of the given type.
+    Value(&'a AbiType),
+    /// The function returns nothing.
+    Nothing,
+    /// The return type of the function was not captured.
+    Unknown,
+}
+
+/// Stability guarantee of a function.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AbiStability {
+    /// The function only changes in backwards-compatible ways until it is deprecated.
+    Stable,
+    /// The function may change or be removed in any version.
+    Unstable,
+}
+
+/// Deprecation of a function, mirroring the `#[deprecated(since = "...", note = "...")]`
+/// attribute.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiDeprecation {
+    /// Contract version the function was deprecated in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// Explanation of the deprecation, usually pointing to a replacement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Side effects a function may have, declared by the producer of the ABI so that indexers can
+/// tell which calls they have to track.
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiEffects {
+    /// The function may change token balances, e.g. by transferring fungible tokens.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mutates_balances: bool,
+    /// The function may emit events (NEP-297 logs).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub emits_events: bool,
+    /// The function may create promises, i.e. produce receipts for other contracts.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub creates_promises: bool,
+}
+
+/// Parts of the contract state a function reads, see [`state_reads`].
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiStateReads {
+    /// The function reads the top-level state struct, stored under the `STATE` key.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub root: bool,
+    /// Fields of the state collections read by the function, as named in
+    /// [`AbiStateCollection::field`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub collections: Vec<String>,
+}
+
+/// Gas usage of a function measured over a set of sample invocations, in gas units.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiGasEstimate {
+    pub min: u64,
+    pub median: u64,
+    pub max: u64,
+}
+
+/// Storage allocated by a function, used by clients to attach the deposit covering it.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiStorageUsage {
+    /// Estimated number of bytes of storage allocated for every entry created by the function
+    /// (e.g. a registered account or a minted token).
+    pub bytes_per_entry: u64,
+}
+
+impl AbiStorageUsage {
+    /// Cost of storing a byte on NEAR, in yoctoNEAR (10^19, i.e. 1 NEAR per 100 kB).
+    pub const BYTE_COST: u128 = 10_000_000_000_000_000_000;
+
+    /// Deposit in yoctoNEAR covering the storage of `entries` entries.
+    pub fn required_deposit(&self, entries: u64) -> u128 {
+        u128::from(self.bytes_per_entry)
+            .saturating_mul(u128::from(entries))
+            .saturating_mul(Self::BYTE_COST)
+    }
+}
+
+/// A sample invocation of a function.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiExample {
+    /// What the example demonstrates.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Arguments of the invocation.
+    pub args: AbiExampleValue,
+    /// Expected result of the invocation, absent if the function returns nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<AbiExampleValue>,
+}
+
+/// Arguments or result of an [`AbiExample`], in the serialization of the function.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum AbiExampleValue {
+    Json(serde_json::Value),
+    /// Borsh-serialized bytes, encoded with standard base64.
+    Borsh(String),
+}
+
+impl AbiExampleValue {
+    /// Bytes of the value as sent to or returned by the contract.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        use base64::Engine;
+        match self {
+            Self::Json(value) => Ok(serde_json::to_vec(value).unwrap()),
+            Self::Borsh(base64) => base64::engine::general_purpose::STANDARD.decode(base64),
+        }
+    }
+
+    /// Whether `bytes` encode this value. JSON values are compared after parsing, so that
+    /// whitespace and key order do not matter.
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        match self {
+            Self::Json(value) => serde_json::from_slice::<serde_json::Value>(bytes)
+                .map_or(false, |parsed| &parsed == value),
+            Self::Borsh(_) => self.to_bytes().map_or(false, |decoded| decoded == bytes),
+        }
+    }
+}
+
+impl AbiFunction {
+    /// Creates a function without documentation, modifiers, parameters, callbacks or result.
+    pub fn new(name: impl Into<String>, kind: AbiFunctionKind) -> Self {
+        Self {
+            name: name.into(),
+            selector: None,
+            namespace: None,
+            interface: None,
+            doc: None,
+            since: None,
+            stability: None,
+            deprecated: None,
+            kind,
+            modifiers: Vec::new(),
+            access: None,
+            params: AbiParameters::default(),
+            args_unordered: false,
+            callbacks: Vec::new(),
+            callbacks_vec: None,
+            result: None,
+            result_kind: AbiResultKind::Value,
+            returns_nothing: false,
+            state_reads: None,
+            effects: None,
+            gas_estimate: None,
+            storage_usage: None,
+            examples: Vec::new(),
+            extensions: AbiExtensions::default(),
+        }
+    }
+
+    /// Name of the function prefixed with its namespace, e.g. `admin::set_fee`. Functions
+    /// without a namespace are named after their method name.
+    pub fn qualified_name(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}::{}", namespace, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Signature identifying the function, made of its qualified name and its parameters with
+    /// their type names, e.g. `ft_transfer(receiver_id:AccountId,amount:U128)`.
+    pub fn canonical_signature(&self) -> String {
+        selector::canonical_signature(self)
+    }
+
+    /// Selector of the function, computed from its canonical signature. Unlike
+    /// [`AbiFunction::selector`](field@AbiFunction::selector), this is never out of date.
+    pub fn selector(&self) -> selector::AbiSelector {
+        selector::AbiSelector::of(&self.canonical_signature())
+    }
+
+    /// The documentation of the function split along rustdoc conventions.
+    pub fn structured_doc(&self) -> Option<doc::AbiDoc> {
+        self.doc.as_deref().map(doc::AbiDoc::parse)
+    }
+
+    /// Whether the function upgrades the contract code or migrates its state, and should be
+    /// left out of user-facing clients.
+    pub fn is_upgrade_entrypoint(&self) -> bool {
+        self.modifiers.iter().any(|modifier| {
+            matches!(
+                modifier,
+                AbiFunctionModifier::Upgrade | AbiFunctionModifier::Migrate
+            )
+        })
+    }
+
+    /// Whether the function is an init function that can be called on an already initialized
+    /// contract.
+    pub fn init_ignores_state(&self) -> bool {
+        self.modifiers.contains(&AbiFunctionModifier::Init)
+            && self.modifiers.contains(&AbiFunctionModifier::IgnoreState)
+    }
+
+    /// Whether the function is a view function computing its result without reading the
+    /// contract state.
+    pub fn is_pure(&self) -> bool {
+        self.kind == AbiFunctionKind::View && self.modifiers.contains(&AbiFunctionModifier::Pure)
+    }
+
+    /// Whether indexers have to follow the receipts produced by calls of the function. Call
+    /// functions without declared effects are assumed to need it.
+    pub fn requires_receipt_tracking(&self) -> bool {
+        match (&self.kind, &self.effects) {
+            (AbiFunctionKind::View, _) => false,
+            (AbiFunctionKind::Call, Some(effects)) => effects.creates_promises,
+            (AbiFunctionKind::Call, None) => true,
+        }
+    }
+
+    /// Whether the result of the function may be produced by a promise, in which case callers
+    /// have to wait for the outcome of the receipts it spawns.
+    pub fn may_return_promise(&self) -> bool {
+        !self.result_kind.is_value()
+    }
+
+    /// Whether reordering the parameters of the function breaks its callers. This is the case of
+    /// Borsh parameters, which are positional, and of JSON parameters unless they are declared
+    /// [unordered](AbiFunction::args_unordered).
+    pub fn param_order_matters(&self) -> bool {
+        match self.params {
+            AbiParameters::Json { .. } => !self.args_unordered,
+            AbiParameters::Borsh { .. } => true,
+        }
+    }
+
+    /// What the function is known to return.
+    pub fn returns(&self) -> AbiReturn<'_> {
+        match &self.result {
+            Some(result) => AbiReturn::Value(result),
+            None if self.returns_nothing => AbiReturn::Nothing,
+            None => AbiReturn::Unknown,
+        }
+    }
+
+    /// Whether a call of the function can produce a `null` result, see [`AbiType::is_nullable`].
+    /// Functions without a result always do.
+    pub fn result_is_nullable(&self, root_schema: &RootSchema) -> bool {
+        self.result
+            .as_ref()
+            .map_or(true, |result| result.is_nullable(root_schema))
+    }
+
+    /// The callbacks of the function in their consolidated form. Fails if the callbacks do not
+    /// share the same serialization type.
+    pub fn consolidated_callbacks(&self) -> Result<AbiCallbacks, MixedCallbacksError> {
+        AbiCallbacks::from_types(self.callbacks.iter().map(|c| c.ty.clone()).collect())
+    }
+
+    /// Replaces the callback types of the function. Names and documentation of the existing
+    /// callbacks are kept, new callbacks are named after their position.
+    pub fn set_callbacks(&mut self, callbacks: &AbiCallbacks) {
+        let previous = std::mem::take(&mut self.callbacks);
+        self.callbacks = callbacks
+            .to_types()
+            .into_iter()
+            .enumerate()
+            .map(|(i, ty)| match previous.get(i) {
+                Some(callback) => AbiCallback {
+                    ty,
+                    ..callback.clone()
+                },
+                None => AbiCallback::new(callback_name(i), ty),
+            })
+            .collect();
+    }
+
+    /// Checks that the keys of a JSON arguments object exactly match the declared parameter
+    /// names, without validating the argument values themselves.
+    ///
+    /// This is meant to run before deep schema validation to give clear errors for the most
+    /// common call mistakes, such as misspelled or forgotten arguments.
+    pub fn check_arity(&self, args: &serde_json::Value) -> Result<(), ArityError> {
+        let params = match &self.params {
+            AbiParameters::Json { args } => args,
+            AbiParameters::Borsh { .. } => return Err(ArityError::BorshParameters),
+        };
+        let args = args.as_object().ok_or(ArityError::NotAnObject)?;
+        let missing: Vec<_> = params
+            .iter()
+            .filter(|param| !args.contains_key(&param.name))
+            .map(|param| param.name.clone())
+            .collect();
+        let unknown: Vec<_> = args
+            .keys()
+            .filter(|key| !params.iter().any(|param| &param.name == *key))
+            .cloned()
+            .collect();
+        if missing.is_empty() && unknown.is_empty() {
+            Ok(())
+        } else {
+            Err(ArityError::Mismatch { missing, unknown })
+        }
+    }
 
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default, JsonSchema)]
-pub struct AbiMetadata {
-    /// The name of the smart contract.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    /// The version of the smart contract.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub version: Option<String>,
-    /// The authors of the smart contract.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub authors: Vec<String>,
-    /// The information about how this contract was built.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub build: Option<BuildInfo>,
-    /// The SHA-256 hash of the contract WASM code in Base58 format.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub wasm_hash: Option<String>,
-    /// Other arbitrary metadata.
-    #[serde(default, flatten, skip_serializing_if = "HashMap::is_empty")]
-    pub other: HashMap<String, String>,
+    /// A self-contained JSON schema of the arguments object of the function, with a property per
+    /// parameter, that can be handed to any JSON Schema validator.
+    ///
+    /// Parameters are required unless their type accepts `null`, as in
+    /// [`value::validate_json_args`], and unknown arguments are rejected. The root schema
+    /// definitions referenced by the parameters, directly or not, are bundled into the returned
+    /// schema. Borsh parameters are described by the JSON representation of their values, see
+    /// [`convert::borsh_to_json`], and accept any value when it can not be described.
+    pub fn args_schema(&self, root_schema: &RootSchema) -> RootSchema {
+        let mut properties = schemars::Map::new();
+        let mut required = std::collections::BTreeSet::new();
+        match &self.params {
+            AbiParameters::Json { args } => {
+                for arg in args {
+                    properties.insert(arg.name.clone(), arg.type_schema.clone());
+                    let null = serde_json::Value::Null;
+                    if value::validate_json(root_schema, &arg.type_schema, &null).is_err() {
+                        required.insert(arg.name.clone());
+                    }
+                }
+            }
+            AbiParameters::Borsh { args } => {
+                for arg in args {
+                    let schema =
+                        convert::borsh_to_json(&arg.type_schema).unwrap_or(Schema::Bool(true));
+                    properties.insert(arg.name.clone(), schema);
+                    required.insert(arg.name.clone());
+                }
+            }
+        }
+        let definitions = walk::reachable_definitions(root_schema, properties.values())
+            .into_iter()
+            .filter_map(|name| {
+                let schema = root_schema.definitions.get(&name)?.clone();
+                Some((name, schema))
+            })
+            .collect();
+        RootSchema {
+            meta_schema: Some(
+                root_schema
+                    .meta_schema
+                    .clone()
+                    .unwrap_or_else(|| draft::SchemaDraft::Draft7.meta_schema().to_string()),
+            ),
+            schema: schemars::schema::SchemaObject {
+                instance_type: Some(schemars::schema::InstanceType::Object.into()),
+                object: Some(Box::new(schemars::schema::ObjectValidation {
+                    properties,
+                    required,
+                    additional_properties: Some(Box::new(Schema::Bool(false))),
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+            definitions,
+        }
+    }
 }
 
-/// Core ABI information.
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
-#[serde(deny_unknown_fields)]
-pub struct AbiBody {
-    /// ABIs of all contract's functions.
-    pub functions: Vec<AbiFunction>,
-    /// Root JSON Schema containing all types referenced in the functions.
-    pub root_schema: RootSchema,
+/// Error returned by [`AbiFunction::check_arity`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArityError {
+    /// The function takes Borsh-serialized parameters, which are positional.
+    BorshParameters,
+    /// The supplied arguments are not a JSON object.
+    NotAnObject,
+    /// The argument names do not match the declared parameters.
+    Mismatch {
+        /// Declared parameters without a supplied argument.
+        missing: Vec<String>,
+        /// Supplied arguments that are not declared parameters.
+        unknown: Vec<String>,
+    },
 }
 
-/// ABI of a single function.
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
-#[serde(deny_unknown_fields)]
-pub struct AbiFunction {
-    pub name: String,
-    /// Human-readable documentation parsed from the source file.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub doc: Option<String>,
-    /// Function kind that regulates whether the function has to be invoked from a transaction.
-    pub kind: AbiFunctionKind,
-    /// List of modifiers affecting the function.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub modifiers: Vec<AbiFunctionModifier>,
-    /// Type identifiers of the function parameters.
-    #[serde(default, skip_serializing_if = "AbiParameters::is_empty")]
-    pub params: AbiParameters,
-    /// Type identifiers of the callbacks of the function.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub callbacks: Vec<AbiType>,
-    /// Type identifier of the vararg callbacks of the function.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub callbacks_vec: Option<AbiType>,
-    /// Return type identifier.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub result: Option<AbiType>,
+impl std::error::Error for ArityError {}
+impl fmt::Display for ArityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BorshParameters => {
+                write!(f, "arity can only be checked for JSON parameters")
+            }
+            Self::NotAnObject => write!(f, "arguments must be a JSON object"),
+            Self::Mismatch { missing, unknown } => {
+                let list = |names: &[String]| {
+                    names
+                        .iter()
+                        .map(|name| format!("`{}`", name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+                if !missing.is_empty() {
+                    write!(f, "missing arguments: {}", list(missing))?;
+                }
+                if !unknown.is_empty() {
+                    if !missing.is_empty() {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "unknown arguments: {}", list(unknown))?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 /// Function kind regulates whether this function's invocation requires a transaction (so-called
@@ -137,11 +1411,14 @@ pub enum AbiFunctionKind {
 }
 
 /// Function can have multiple modifiers that can change its semantics.
-#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
-#[serde(rename_all = "lowercase")]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, JsonSchema)]
+#[serde(rename_all = "snake_case")]
 pub enum AbiFunctionModifier {
     /// Init functions can be used to initialize the state of the contract.
     Init,
+    /// Init functions with this modifier (`#[init(ignore_state)]`) can be called on a contract
+    /// whose state is already initialized, overwriting it.
+    IgnoreState,
     /// Private functions can only be called from the contract containing them. Usually, when a
     /// contract has to have a callback for a remote cross-contract call, this callback method
     /// should only be called by the contract itself.
@@ -150,6 +1427,94 @@ pub enum AbiFunctionModifier {
     /// This is done so that contracts can define a fee in tokens that needs to be payed when
     /// they are used.
     Payable,
+    /// Upgrade functions deploy new code to the contract account. They are meant to be called
+    /// by the contract administrators with a full-access key, not by regular users.
+    Upgrade,
+    /// Migrate functions convert the contract state after an upgrade. Like upgrade functions,
+    /// they are not part of the user-facing interface of the contract.
+    Migrate,
+    /// Pure view functions compute their result from their arguments alone, without reading the
+    /// contract state. Their results are safely cacheable and can even be computed locally.
+    Pure,
+    /// A modifier introduced by a newer schema version, preserved as is. Only produced when
+    /// parsing leniently (see [`AbiRoot::from_value_lenient`]).
+    #[schemars(skip)]
+    Unknown(String),
+}
+
+impl AbiFunctionModifier {
+    fn as_str(&self) -> &str {
+        match self {
+            Self::Init => "init",
+            Self::IgnoreState => "ignore_state",
+            Self::Private => "private",
+            Self::Payable => "payable",
+            Self::Upgrade => "upgrade",
+            Self::Migrate => "migrate",
+            Self::Pure => "pure",
+            Self::Unknown(modifier) => modifier,
+        }
+    }
+}
+
+impl Serialize for AbiFunctionModifier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for AbiFunctionModifier {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let modifier = String::deserialize(deserializer)?;
+        match modifier.as_str() {
+            "init" => Ok(Self::Init),
+            "ignore_state" => Ok(Self::IgnoreState),
+            "private" => Ok(Self::Private),
+            "payable" => Ok(Self::Payable),
+            "upgrade" => Ok(Self::Upgrade),
+            "migrate" => Ok(Self::Migrate),
+            "pure" => Ok(Self::Pure),
+            _ if lenient::is_enabled() => Ok(Self::Unknown(modifier)),
+            _ => Err(de::Error::unknown_variant(
+                &modifier,
+                &[
+                    "init",
+                    "ignore_state",
+                    "private",
+                    "payable",
+                    "upgrade",
+                    "migrate",
+                    "pure",
+                ],
+            )),
+        }
+    }
+}
+
+/// Lenient parsing mode, in which parts of the ABI introduced by newer schema versions are
+/// preserved instead of rejected.
+mod lenient {
+    use std::cell::Cell;
+
+    std::thread_local! {
+        static LENIENT: Cell<bool> = const { Cell::new(false) };
+    }
+
+    pub(crate) fn is_enabled() -> bool {
+        LENIENT.with(Cell::get)
+    }
+
+    /// Runs `f` with lenient parsing enabled on the current thread.
+    pub(crate) fn enable<T>(f: impl FnOnce() -> T) -> T {
+        struct Reset(bool);
+        impl Drop for Reset {
+            fn drop(&mut self) {
+                LENIENT.with(|lenient| lenient.set(self.0));
+            }
+        }
+        let _reset = Reset(LENIENT.with(|lenient| lenient.replace(true)));
+        f()
+    }
 }
 
 /// A list of function parameters sharing the same serialization type.
@@ -177,27 +1542,428 @@ impl AbiParameters {
             Self::Borsh { args } => args.is_empty(),
         }
     }
+
+    /// Appends a JSON parameter. An empty list takes the serialization type of its first
+    /// parameter, a list of Borsh parameters is left untouched.
+    pub fn push_json(&mut self, param: AbiJsonParameter) -> Result<(), MixedParametersError> {
+        if self.is_empty() {
+            *self = Self::Json { args: Vec::new() };
+        }
+        match self {
+            Self::Json { args } => {
+                args.push(param);
+                Ok(())
+            }
+            Self::Borsh { .. } => Err(MixedParametersError { name: param.name }),
+        }
+    }
+
+    /// Appends a Borsh parameter. An empty list takes the serialization type of its first
+    /// parameter, a list of JSON parameters is left untouched.
+    pub fn push_borsh(&mut self, param: AbiBorshParameter) -> Result<(), MixedParametersError> {
+        if self.is_empty() {
+            *self = Self::Borsh { args: Vec::new() };
+        }
+        match self {
+            Self::Borsh { args } => {
+                args.push(param);
+                Ok(())
+            }
+            Self::Json { .. } => Err(MixedParametersError { name: param.name }),
+        }
+    }
+
+    /// Converts Borsh parameters into JSON parameters with inline schemas describing the JSON
+    /// representation of their values (see [`convert::borsh_to_json`]). JSON parameters are
+    /// returned as is.
+    pub fn to_json(&self) -> Result<Self, convert::ConvertError> {
+        match self {
+            Self::Json { .. } => Ok(self.clone()),
+            Self::Borsh { args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| {
+                        Ok(AbiJsonParameter::new(
+                            arg.name.clone(),
+                            convert::borsh_to_json(&arg.type_schema)?,
+                        ))
+                    })
+                    .collect::<Result<_, convert::ConvertError>>()?;
+                Ok(Self::Json { args })
+            }
+        }
+    }
+
+    /// Converts JSON parameters into Borsh parameters, resolving their schema references against
+    /// `root_schema` (see [`convert::json_to_borsh`]). Borsh parameters are returned as is.
+    pub fn to_borsh(&self, root_schema: &RootSchema) -> Result<Self, convert::ConvertError> {
+        match self {
+            Self::Borsh { .. } => Ok(self.clone()),
+            Self::Json { args } => {
+                let args = args
+                    .iter()
+                    .map(|arg| {
+                        Ok(AbiBorshParameter::new(
+                            arg.name.clone(),
+                            convert::json_to_borsh(root_schema, &arg.type_schema)?,
+                        ))
+                    })
+                    .collect::<Result<_, convert::ConvertError>>()?;
+                Ok(Self::Borsh { args })
+            }
+        }
+    }
+}
+
+/// Error returned when a parameter is added to a list of parameters of another serialization
+/// type, see [`AbiParameters::push_json`] and [`AbiParameters::push_borsh`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MixedParametersError {
+    name: String,
+}
+
+impl MixedParametersError {
+    /// Name of the rejected parameter.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl std::error::Error for MixedParametersError {}
+impl fmt::Display for MixedParametersError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "parameter `{}` does not share the serialization type of the other parameters",
+            self.name
+        )
+    }
+}
+
+/// A callback of a function, i.e. the result of a promise received as an argument.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
+pub struct AbiCallback {
+    /// Callback parameter name (e.g. `result` in `#[callback_unwrap] result: u32`). Empty for
+    /// callbacks of ABIs predating named callbacks.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    /// Human-readable documentation of the callback.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+    /// Type identifier of the callback.
+    #[serde(flatten)]
+    pub ty: AbiType,
+}
+
+impl AbiCallback {
+    /// Creates an undocumented callback.
+    pub fn new(name: impl Into<String>, ty: AbiType) -> Self {
+        Self {
+            name: name.into(),
+            doc: None,
+            ty,
+        }
+    }
+}
+
+impl JsonSchema for AbiCallback {
+    fn schema_name() -> String {
+        "AbiCallback".to_string()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> Schema {
+        let mut schema = AbiType::json_schema(gen).into_object();
+        let variants = schema
+            .subschemas()
+            .one_of
+            .iter_mut()
+            .flatten()
+            .filter_map(|variant| match variant {
+                Schema::Object(object) => Some(object),
+                Schema::Bool(_) => None,
+            });
+        for variant in variants {
+            let object = variant.object();
+            object
+                .properties
+                .insert("name".to_string(), gen.subschema_for::<String>());
+            object
+                .properties
+                .insert("doc".to_string(), gen.subschema_for::<String>());
+        }
+        schema.metadata().description =
+            Some("Information about a single named callback of a function.".to_string());
+        schema.into()
+    }
+}
+
+/// Name given to the callback at `index` when it has none of its own.
+fn callback_name(index: usize) -> String {
+    format!("callback_{}", index)
+}
+
+/// The callbacks of a function, all sharing the same serialization type.
+///
+/// This is the consolidated counterpart of [`AbiFunction::callbacks`], which keeps the name and
+/// documentation of every callback along with its type. It is obtained with
+/// [`AbiFunction::consolidated_callbacks`] and applied with [`AbiFunction::set_callbacks`]; ABI
+/// documents keep the list of callbacks. Callbacks mixing serialization types are rejected when
+/// parsing and serializing functions.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AbiCallbacks {
+    Json { types: Vec<Schema> },
+    Borsh { types: Vec<BorshSchemaContainer> },
+}
+
+impl Default for AbiCallbacks {
+    fn default() -> Self {
+        AbiCallbacks::Json { types: Vec::new() }
+    }
+}
+
+impl AbiCallbacks {
+    /// Consolidates a list of callback types. Fails if their serialization types differ.
+    pub fn from_types(callbacks: Vec<AbiType>) -> Result<Self, MixedCallbacksError> {
+        let mut consolidated = match callbacks.first() {
+            Some(AbiType::Borsh { .. }) => Self::Borsh { types: Vec::new() },
+            _ => Self::default(),
+        };
+        for (index, callback) in callbacks.into_iter().enumerate() {
+            match (&mut consolidated, callback) {
+                (Self::Json { types }, AbiType::Json { type_schema }) => types.push(type_schema),
+                (Self::Borsh { types }, AbiType::Borsh { type_schema }) => types.push(type_schema),
+                _ => return Err(MixedCallbacksError { index }),
+            }
+        }
+        Ok(consolidated)
+    }
+
+    /// Expands the callbacks into a list of callback types, as stored in
+    /// [`AbiFunction::callbacks`].
+    pub fn to_types(&self) -> Vec<AbiType> {
+        match self {
+            Self::Json { types } => types
+                .iter()
+                .map(|type_schema| AbiType::Json {
+                    type_schema: type_schema.clone(),
+                })
+                .collect(),
+            Self::Borsh { types } => types
+                .iter()
+                .map(|type_schema| AbiType::Borsh {
+                    type_schema: type_schema.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Json { types } => types.len(),
+            Self::Borsh { types } => types.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Consolidated form of [`AbiFunction::callbacks`] accepted when parsing: like parameters, the
+/// callbacks of a function state their serialization type once.
+#[derive(Deserialize, JsonSchema)]
+#[serde(
+    tag = "serialization_type",
+    rename_all = "lowercase",
+    deny_unknown_fields
+)]
+#[schemars(rename = "AbiConsolidatedCallbacks")]
+enum CallbacksDef {
+    Json { args: Vec<JsonCallbackDef> },
+    Borsh { args: Vec<BorshCallbackDef> },
+}
+
+/// Information about a single named callback of a function, without its serialization type.
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "AbiJsonCallback")]
+struct JsonCallbackDef {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    doc: Option<String>,
+    type_schema: Schema,
+}
+
+/// Information about a single named callback of a function, without its serialization type.
+#[derive(Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "AbiBorshCallback")]
+struct BorshCallbackDef {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    doc: Option<String>,
+    #[serde(with = "BorshSchemaContainerDef")]
+    #[schemars(with = "serde_json::Value")]
+    type_schema: BorshSchemaContainer,
+}
+
+impl CallbacksDef {
+    fn into_callbacks(self) -> Vec<AbiCallback> {
+        match self {
+            Self::Json { args } => args
+                .into_iter()
+                .map(|arg| AbiCallback {
+                    name: arg.name,
+                    doc: arg.doc,
+                    ty: AbiType::Json {
+                        type_schema: arg.type_schema,
+                    },
+                })
+                .collect(),
+            Self::Borsh { args } => args
+                .into_iter()
+                .map(|arg| AbiCallback {
+                    name: arg.name,
+                    doc: arg.doc,
+                    ty: AbiType::Borsh {
+                        type_schema: arg.type_schema,
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+fn serialize_callbacks<S: serde::Serializer>(
+    callbacks: &[AbiCallback],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    check_callbacks(callbacks).map_err(serde::ser::Error::custom)?;
+    callbacks.serialize(serializer)
+}
+
+/// Checks that all `callbacks` share the serialization type of the first one.
+fn check_callbacks(callbacks: &[AbiCallback]) -> Result<(), MixedCallbacksError> {
+    let is_borsh = |callback: &AbiCallback| matches!(callback.ty, AbiType::Borsh { .. });
+    match callbacks.first() {
+        Some(first) => callbacks
+            .iter()
+            .position(|callback| is_borsh(callback) != is_borsh(first))
+            .map_or(Ok(()), |index| Err(MixedCallbacksError { index })),
+        None => Ok(()),
+    }
+}
+
+fn deserialize_callbacks<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<AbiCallback>, D::Error> {
+    struct CallbacksVisitor;
+
+    impl<'de> de::Visitor<'de> for CallbacksVisitor {
+        type Value = Vec<AbiCallback>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("consolidated callbacks or a list of callbacks")
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            let callbacks = CallbacksDef::deserialize(de::value::MapAccessDeserializer::new(map))?;
+            Ok(callbacks.into_callbacks())
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            let callbacks =
+                Vec::<AbiCallback>::deserialize(de::value::SeqAccessDeserializer::new(seq))?;
+            check_callbacks(&callbacks).map_err(de::Error::custom)?;
+            Ok(callbacks)
+        }
+    }
+
+    d.deserialize_any(CallbacksVisitor)
+}
+
+/// Schema of [`AbiFunction::callbacks`]: the list of callbacks, or their consolidated form.
+fn callbacks_schema(gen: &mut schemars::gen::SchemaGenerator) -> Schema {
+    schemars::schema::SchemaObject {
+        subschemas: Some(Box::new(schemars::schema::SubschemaValidation {
+            any_of: Some(vec![
+                gen.subschema_for::<CallbacksDef>(),
+                gen.subschema_for::<Vec<AbiCallback>>(),
+            ]),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Error returned when consolidating callbacks of different serialization types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MixedCallbacksError {
+    index: usize,
+}
+
+impl MixedCallbacksError {
+    /// Index of the first callback whose serialization type differs from the first callback.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl std::error::Error for MixedCallbacksError {}
+impl fmt::Display for MixedCallbacksError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "callback {} has a different serialization type than the preceding callbacks",
+            self.index
+        )
+    }
 }
 
 /// Information about a single named JSON function parameter.
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
-#[serde(deny_unknown_fields)]
 pub struct AbiJsonParameter {
     /// Parameter name (e.g. `p1` in `fn foo(p1: u32) {}`).
     pub name: String,
     /// JSON Subschema that represents this type (can be an inline primitive, a reference to the root schema and a few other corner-case things).
     pub type_schema: Schema,
+    /// Vendor extensions.
+    #[serde(flatten, skip_serializing_if = "AbiExtensions::is_empty")]
+    pub extensions: AbiExtensions,
+}
+
+impl AbiJsonParameter {
+    pub fn new(name: impl Into<String>, type_schema: Schema) -> Self {
+        Self {
+            name: name.into(),
+            type_schema,
+            extensions: AbiExtensions::default(),
+        }
+    }
 }
 
 /// Information about a single named Borsh function parameter.
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
-#[serde(deny_unknown_fields)]
 pub struct AbiBorshParameter {
     /// Parameter name (e.g. `p1` in `fn foo(p1: u32) {}`).
     pub name: String,
     /// Inline Borsh schema that represents this type.
     #[serde(with = "BorshSchemaContainerDef")]
     pub type_schema: BorshSchemaContainer,
+    /// Vendor extensions.
+    #[serde(flatten, skip_serializing_if = "AbiExtensions::is_empty")]
+    pub extensions: AbiExtensions,
+}
+
+impl AbiBorshParameter {
+    pub fn new(name: impl Into<String>, type_schema: BorshSchemaContainer) -> Self {
+        Self {
+            name: name.into(),
+            type_schema,
+            extensions: AbiExtensions::default(),
+        }
+    }
 }
 
 impl JsonSchema for AbiBorshParameter {
@@ -230,6 +1996,10 @@ impl JsonSchema for AbiBorshParameter {
             .insert("type_schema".to_string(), type_schema_object.into());
         object_validation.required.insert("name".to_string());
         object_validation.required.insert("type_schema".to_string());
+        object_validation.pattern_properties.insert(
+            AbiExtensions::KEY_PATTERN.to_string(),
+            schemars::schema::Schema::Bool(true),
+        );
         object_validation.additional_properties =
             Some(schemars::schema::Schema::Bool(false).into());
         schema_object.into()
@@ -253,6 +2023,168 @@ pub enum AbiType {
     },
 }
 
+impl AbiType {
+    /// JSON type of `T`. Definitions of the types `T` depends on are registered with `gen`, whose
+    /// root schema is meant to become the ABI root schema.
+    pub fn json_of<T: JsonSchema>(gen: &mut schemars::gen::SchemaGenerator) -> Self {
+        Self::Json {
+            type_schema: gen.subschema_for::<T>(),
+        }
+    }
+
+    /// Borsh type of `T`.
+    pub fn borsh_of<T: borsh::BorshSchema + ?Sized>() -> Self {
+        Self::Borsh {
+            type_schema: borsh::schema_container_of::<T>(),
+        }
+    }
+
+    /// Whether values of the type can be `null` once decoded into JSON.
+    ///
+    /// JSON types are nullable when their schema accepts `null`, resolving references against
+    /// `root_schema`, which is the case of `Option`s and the unit type. Borsh types are nullable
+    /// when they are an `Option` or the unit type.
+    pub fn is_nullable(&self, root_schema: &RootSchema) -> bool {
+        match self {
+            Self::Json { type_schema } => {
+                value::validate_json(root_schema, type_schema, &serde_json::Value::Null).is_ok()
+            }
+            Self::Borsh { type_schema } => {
+                match type_schema.get_definition(type_schema.declaration()) {
+                    Some(Definition::Enum { variants, .. }) => {
+                        variants.len() == 2
+                            && variants.iter().any(|(_, name, _)| name == "None")
+                            && variants.iter().any(|(_, name, _)| name == "Some")
+                    }
+                    Some(Definition::Struct {
+                        fields: Fields::Empty,
+                    }) => true,
+                    Some(Definition::Primitive(_)) | None => type_schema.declaration() == "()",
+                    Some(_) => false,
+                }
+            }
+        }
+    }
+
+    /// Short human-readable label of the type, e.g. `AccountId`, `Vec<TokenId>` or `u64`.
+    ///
+    /// JSON types are named after the referenced definition or their `title`, falling back to a
+    /// Rust-like rendering of the schema structure. Borsh types use their declaration.
+    pub fn display_name(&self) -> String {
+        match self {
+            Self::Json { type_schema } => json_display_name(type_schema),
+            Self::Borsh { type_schema } => type_schema.declaration().clone(),
+        }
+    }
+}
+
+fn json_display_name(schema: &Schema) -> String {
+    use schemars::schema::{InstanceType, ObjectValidation, SingleOrVec};
+
+    let object = match schema {
+        Schema::Bool(true) => return "Any".to_string(),
+        Schema::Bool(false) => return "Never".to_string(),
+        Schema::Object(object) => object,
+    };
+    if let Some(reference) = &object.reference {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+    if let Some(title) = object
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.title.as_ref())
+    {
+        return title.clone();
+    }
+    let is_null = |schema: &Schema| match schema {
+        Schema::Object(object) => {
+            object.instance_type == Some(SingleOrVec::Single(Box::new(InstanceType::Null)))
+        }
+        Schema::Bool(_) => false,
+    };
+    if let Some(subschemas) = &object.subschemas {
+        if let Some(variants) = subschemas.any_of.as_ref().or(subschemas.one_of.as_ref()) {
+            if let [first, second] = variants.as_slice() {
+                match (is_null(first), is_null(second)) {
+                    (false, true) => return format!("Option<{}>", json_display_name(first)),
+                    (true, false) => return format!("Option<{}>", json_display_name(second)),
+                    _ => {}
+                }
+            }
+        }
+        if let Some([schema]) = subschemas.all_of.as_deref() {
+            return json_display_name(schema);
+        }
+    }
+    let instance_type = match &object.instance_type {
+        Some(SingleOrVec::Single(instance_type)) => **instance_type,
+        Some(SingleOrVec::Vec(types)) => match types.as_slice() {
+            [instance_type, InstanceType::Null] | [InstanceType::Null, instance_type] => {
+                let mut some = object.clone();
+                some.instance_type = Some((*instance_type).into());
+                return format!("Option<{}>", json_display_name(&some.into()));
+            }
+            _ => return "Any".to_string(),
+        },
+        None if object.enum_values.is_some() || object.const_value.is_some() => {
+            return "Enum".to_string()
+        }
+        None => return "Any".to_string(),
+    };
+    let format = object.format.as_deref();
+    match instance_type {
+        InstanceType::Null => "()".to_string(),
+        InstanceType::Boolean => "bool".to_string(),
+        InstanceType::String => "String".to_string(),
+        InstanceType::Integer => match format {
+            Some("uint8") => "u8",
+            Some("uint16") => "u16",
+            Some("uint32") => "u32",
+            Some("uint64") | Some("uint") => "u64",
+            Some("uint128") => "u128",
+            Some("int8") => "i8",
+            Some("int16") => "i16",
+            Some("int32") => "i32",
+            Some("int64") | Some("int") => "i64",
+            Some("int128") => "i128",
+            _ => "Integer",
+        }
+        .to_string(),
+        InstanceType::Number => match format {
+            Some("float") => "f32",
+            Some("double") => "f64",
+            _ => "Number",
+        }
+        .to_string(),
+        InstanceType::Array => match object.array.as_ref().and_then(|array| array.items.as_ref()) {
+            Some(SingleOrVec::Single(items)) => format!("Vec<{}>", json_display_name(items)),
+            Some(SingleOrVec::Vec(items)) => format!(
+                "({})",
+                items
+                    .iter()
+                    .map(json_display_name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            None => "Vec<Any>".to_string(),
+        },
+        InstanceType::Object => match object.object.as_deref() {
+            Some(ObjectValidation {
+                properties,
+                additional_properties: Some(values),
+                ..
+            }) if properties.is_empty() && matches!(**values, Schema::Object(_)) => {
+                format!("HashMap<String, {}>", json_display_name(values))
+            }
+            _ => "Object".to_string(),
+        },
+    }
+}
+
 impl JsonSchema for AbiType {
     fn schema_name() -> String {
         "AbiType".to_string()
@@ -620,6 +2552,7 @@ mod tests {
     #[test]
     fn test_serde_abitype_borsh_struct_unnamed() {
         #[derive(BorshSchema)]
+        #[allow(dead_code)]
         struct Pair(u32, u32);
         let abi_type = AbiType::Borsh {
             type_schema: borsh::schema_container_of::<Pair>(),
@@ -691,10 +2624,7 @@ mod tests {
     fn test_serde_abiborshparameter_struct_empty() {
         #[derive(BorshSchema)]
         struct Unit;
-        let expected_param = AbiBorshParameter {
-            name: "foo".to_string(),
-            type_schema: borsh::schema_container_of::<Unit>(),
-        };
+        let expected_param = AbiBorshParameter::new("foo", borsh::schema_container_of::<Unit>());
 
         let expected_json_str = serde_json::to_string_pretty(&expected_param).unwrap();
         insta::assert_snapshot!(expected_json_str);
@@ -788,4 +2718,1006 @@ mod tests {
             .to_string()
             .contains("got 99.99.99: consider upgrading near-abi to a newer version"));
     }
+
+    #[test]
+    fn test_split_by_namespace() {
+        let json = format!(
+            r##"
+            {{
+                "schema_version": "{}",
+                "metadata": {{ "name": "platform" }},
+                "body": {{
+                    "functions": [
+                        {{
+                            "name": "get_config",
+                            "kind": "view",
+                            "result": {{
+                                "serialization_type": "json",
+                                "type_schema": {{ "$ref": "#/definitions/Config" }}
+                            }}
+                        }},
+                        {{
+                            "name": "set_owner",
+                            "namespace": "admin",
+                            "kind": "call",
+                            "params": {{
+                                "serialization_type": "json",
+                                "args": [
+                                    {{
+                                        "name": "owner",
+                                        "type_schema": {{ "$ref": "#/definitions/AccountId" }}
+                                    }}
+                                ]
+                            }}
+                        }}
+                    ],
+                    "root_schema": {{
+                        "definitions": {{
+                            "AccountId": {{ "type": "string" }},
+                            "Config": {{
+                                "type": "object",
+                                "properties": {{
+                                    "fee": {{ "$ref": "#/definitions/Fee" }}
+                                }}
+                            }},
+                            "Fee": {{ "type": "integer" }}
+                        }}
+                    }}
+                }}
+            }}
+            "##,
+            SCHEMA_VERSION
+        );
+        let abi_root = serde_json::from_str::<AbiRoot>(&json).unwrap();
+        let split = abi_root.split_by_namespace();
+        assert_eq!(split.len(), 2);
+
+        let public = &split[&None];
+        assert_eq!(public.metadata.name.as_deref(), Some("platform"));
+        assert_eq!(public.body.functions.len(), 1);
+        assert_eq!(
+            public
+                .body
+                .root_schema
+                .definitions
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["Config", "Fee"]
+        );
+
+        let admin = &split[&Some("admin".to_string())];
+        assert_eq!(admin.body.functions[0].name, "set_owner");
+        assert_eq!(
+            admin
+                .body
+                .root_schema
+                .definitions
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["AccountId"]
+        );
+
+        let body = &abi_root.body;
+        assert_eq!(body.functions[1].qualified_name(), "admin::set_owner");
+        assert_eq!(body.function("admin::set_owner"), Some(&body.functions[1]));
+        assert_eq!(body.function("set_owner"), Some(&body.functions[1]));
+        assert_eq!(body.function("public::set_owner"), None);
+        assert_eq!(
+            body.functions_in(None).map(|f| &f.name).collect::<Vec<_>>(),
+            ["get_config"]
+        );
+    }
+
+    #[test]
+    fn test_result_nullability() {
+        let mut root_schema = RootSchema::default();
+        root_schema.definitions.insert(
+            "MaybeToken".to_string(),
+            serde_json::from_value(serde_json::json!({ "type": ["string", "null"] })).unwrap(),
+        );
+        let json = |schema: serde_json::Value| AbiType::Json {
+            type_schema: serde_json::from_value(schema).unwrap(),
+        };
+        assert!(
+            json(serde_json::json!({ "$ref": "#/definitions/MaybeToken" }))
+                .is_nullable(&root_schema)
+        );
+        assert!(json(serde_json::json!({
+            "anyOf": [{ "type": "integer" }, { "type": "null" }]
+        }))
+        .is_nullable(&root_schema));
+        assert!(json(serde_json::json!({ "type": "null" })).is_nullable(&root_schema));
+        assert!(!json(serde_json::json!({ "type": "string" })).is_nullable(&root_schema));
+
+        assert!(AbiType::borsh_of::<Option<u64>>().is_nullable(&root_schema));
+        assert!(AbiType::borsh_of::<()>().is_nullable(&root_schema));
+        assert!(!AbiType::borsh_of::<Vec<u8>>().is_nullable(&root_schema));
+
+        let mut function = AbiFunction::new("get", AbiFunctionKind::View);
+        assert!(function.result_is_nullable(&root_schema));
+        function.result = Some(AbiType::borsh_of::<u64>());
+        assert!(!function.result_is_nullable(&root_schema));
+    }
+
+    #[test]
+    fn test_functions_by_interface() {
+        let abi_root = AbiRoot::from_value(serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    { "name": "ft_transfer", "interface": "FungibleTokenCore", "kind": "call" },
+                    { "name": "storage_deposit", "interface": "StorageManagement", "kind": "call" },
+                    { "name": "new", "kind": "call", "modifiers": ["init"] },
+                    { "name": "ft_total_supply", "interface": "FungibleTokenCore", "kind": "view" }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+        let groups = abi_root.functions_by_interface();
+        let names = |interface| {
+            groups[&interface]
+                .iter()
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            names(Some("FungibleTokenCore")),
+            vec!["ft_transfer", "ft_total_supply"]
+        );
+        assert_eq!(names(Some("StorageManagement")), vec!["storage_deposit"]);
+        assert_eq!(names(None), vec!["new"]);
+    }
+
+    #[test]
+    fn test_abiroot_value_roundtrip() {
+        let json = serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": { "name": "counter" },
+            "body": {
+                "functions": [
+                    {
+                        "name": "get",
+                        "kind": "view",
+                        "gas_estimate": { "min": 2000000000000u64, "median": 2400000000000u64, "max": 5000000000000u64 }
+                    }
+                ],
+                "root_schema": {}
+            }
+        });
+        let abi_root = AbiRoot::from_value(json.clone()).unwrap();
+        assert_eq!(abi_root.metadata.name.as_deref(), Some("counter"));
+        assert_eq!(abi_root.to_value(), json);
+    }
+
+    #[test]
+    fn test_validate_value() {
+        let mut value: serde_json::Value =
+            serde_json::from_str(include_str!("../corpus/0.4.0-adder.json")).unwrap();
+        assert_eq!(validate_value(&value), Ok(()));
+
+        value["metadata"]["authors"] = serde_json::json!("me");
+        value["body"]["functions"][0]["kind"] = serde_json::json!("pure");
+        value["body"]["functions"][0]["x-extra"] = serde_json::json!(1);
+        value["body"]["extra"] = serde_json::json!(1);
+        value["body"]["root_schema"] = serde_json::json!([]);
+        let errors = validate_value(&value)
+            .unwrap_err()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                "at `/body`: unknown property `extra`",
+                "at `/body/functions/0/kind`: \"pure\" is not one of the allowed values",
+                "at `/body/root_schema`: expected object, found array",
+                "at `/metadata/authors`: expected array, found string",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_ignore_state() {
+        let json = serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [{ "name": "reset", "kind": "call", "modifiers": ["init", "ignore_state"] }],
+                "root_schema": {}
+            }
+        });
+        assert_eq!(validate_value(&json), Ok(()));
+        let abi_root = AbiRoot::from_value(json.clone()).unwrap();
+        assert_eq!(abi_root.to_value(), json);
+    }
+
+    #[test]
+    fn test_from_value_error_older_version() {
+        let json = serde_json::json!({
+            "schema_version": "0.1.0",
+            "metadata": {},
+            "body": { "functions": "not even a list", "root_schema": {} }
+        });
+        let err = AbiRoot::from_value(json)
+            .expect_err("Expected deserialization to fail due to schema version mismatch");
+        assert!(err.to_string().contains(
+            "got 0.1.0: consider re-generating your ABI file with a newer version of SDK and cargo-near"
+        ));
+    }
+
+    #[test]
+    fn test_check_arity() {
+        let param = |name: &str| AbiJsonParameter::new(name.to_string(), Schema::Bool(true));
+        let function = AbiFunction {
+            params: AbiParameters::Json {
+                args: vec![param("receiver_id"), param("amount")],
+            },
+            ..AbiFunction::new("ft_transfer", AbiFunctionKind::Call)
+        };
+        let args = serde_json::json!({ "receiver_id": "bob.near", "amount": "1" });
+        assert_eq!(function.check_arity(&args), Ok(()));
+
+        let err = function
+            .check_arity(&serde_json::json!({ "receiver": "bob.near", "amount": "1" }))
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ArityError::Mismatch {
+                missing: vec!["receiver_id".to_string()],
+                unknown: vec!["receiver".to_string()],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "missing arguments: `receiver_id`; unknown arguments: `receiver`"
+        );
+        assert_eq!(
+            function.check_arity(&serde_json::json!([])),
+            Err(ArityError::NotAnObject)
+        );
+    }
+
+    #[test]
+    fn test_args_schema() {
+        let root_schema: RootSchema = serde_json::from_value(serde_json::json!({
+            "definitions": {
+                "AccountId": { "type": "string" },
+                "Memo": { "anyOf": [{ "$ref": "#/definitions/Text" }, { "type": "null" }] },
+                "Text": { "type": "string", "maxLength": 8 },
+                "Unused": { "type": "boolean" }
+            }
+        }))
+        .unwrap();
+        let param = |name: &str, schema: serde_json::Value| {
+            AbiJsonParameter::new(name, serde_json::from_value(schema).unwrap())
+        };
+        let function = AbiFunction {
+            params: AbiParameters::Json {
+                args: vec![
+                    param(
+                        "receiver_id",
+                        serde_json::json!({ "$ref": "#/definitions/AccountId" }),
+                    ),
+                    param("memo", serde_json::json!({ "$ref": "#/definitions/Memo" })),
+                ],
+            },
+            ..AbiFunction::new("ft_transfer", AbiFunctionKind::Call)
+        };
+
+        let schema = function.args_schema(&root_schema);
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            serde_json::json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": {
+                    "receiver_id": { "$ref": "#/definitions/AccountId" },
+                    "memo": { "$ref": "#/definitions/Memo" }
+                },
+                "required": ["receiver_id"],
+                "additionalProperties": false,
+                "definitions": {
+                    "AccountId": { "type": "string" },
+                    "Memo": { "anyOf": [{ "$ref": "#/definitions/Text" }, { "type": "null" }] },
+                    "Text": { "type": "string", "maxLength": 8 }
+                }
+            })
+        );
+        let validate = |args: serde_json::Value| {
+            value::validate_json(&schema, &Schema::Object(schema.schema.clone()), &args)
+        };
+        assert!(validate(serde_json::json!({ "receiver_id": "bob.near" })).is_ok());
+        assert!(validate(serde_json::json!({ "receiver_id": "bob.near", "memo": "hi" })).is_ok());
+        assert!(validate(serde_json::json!({ "memo": "hi" })).is_err());
+        assert!(
+            validate(serde_json::json!({ "receiver_id": "bob.near", "memo": "too long" })).is_ok()
+        );
+        assert!(
+            validate(serde_json::json!({ "receiver_id": "bob.near", "memo": "far too long" }))
+                .is_err()
+        );
+        assert!(validate(serde_json::json!({ "receiver_id": "bob.near", "amount": "1" })).is_err());
+
+        let function = AbiFunction {
+            params: AbiParameters::Borsh {
+                args: vec![AbiBorshParameter::new(
+                    "amount",
+                    borsh::schema_container_of::<u128>(),
+                )],
+            },
+            ..AbiFunction::new("burn", AbiFunctionKind::Call)
+        };
+        assert_eq!(
+            serde_json::to_value(function.args_schema(&root_schema)).unwrap(),
+            serde_json::json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "properties": { "amount": { "type": "string", "format": "uint128" } },
+                "required": ["amount"],
+                "additionalProperties": false
+            })
+        );
+    }
+
+    #[test]
+    fn test_abitype_display_name() {
+        let json = |type_schema: serde_json::Value| AbiType::Json {
+            type_schema: serde_json::from_value(type_schema).unwrap(),
+        };
+        let cases = [
+            (
+                json(serde_json::json!({ "$ref": "#/definitions/AccountId" })),
+                "AccountId",
+            ),
+            (
+                json(serde_json::json!({
+                    "type": "array",
+                    "items": { "$ref": "#/definitions/TokenId" }
+                })),
+                "Vec<TokenId>",
+            ),
+            (
+                json(serde_json::json!({ "title": "U128", "type": "string" })),
+                "U128",
+            ),
+            (
+                json(serde_json::json!({ "type": ["integer", "null"], "format": "uint64" })),
+                "Option<u64>",
+            ),
+            (
+                json(serde_json::json!({
+                    "anyOf": [{ "$ref": "#/definitions/Memo" }, { "type": "null" }]
+                })),
+                "Option<Memo>",
+            ),
+            (
+                AbiType::Borsh {
+                    type_schema: borsh::schema_container_of::<Vec<u32>>(),
+                },
+                "Vec<u32>",
+            ),
+        ];
+        for (abi_type, name) in cases {
+            assert_eq!(abi_type.display_name(), name);
+        }
+    }
+
+    #[test]
+    fn test_state_and_constants_keep_definitions_reachable() {
+        let json = serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [],
+                "root_schema": {
+                    "definitions": {
+                        "Contract": {
+                            "type": "object",
+                            "properties": { "owner": { "$ref": "#/definitions/AccountId" } }
+                        },
+                        "AccountId": { "type": "string" },
+                        "Unused": { "type": "string" }
+                    }
+                },
+                "state": {
+                    "root": {
+                        "serialization_type": "json",
+                        "type_schema": { "$ref": "#/definitions/Contract" }
+                    },
+                    "collections": [{ "field": "accounts", "prefix": [97] }]
+                },
+                "constants": [
+                    {
+                        "name": "DEFAULT_OWNER",
+                        "doc": " Owner assigned on initialization.",
+                        "type_schema": { "$ref": "#/definitions/AccountId" },
+                        "value": "owner.near"
+                    }
+                ]
+            }
+        });
+        let mut abi_root = AbiRoot::from_value(json.clone()).unwrap();
+        assert_eq!(abi_root.to_value(), json);
+        assert_eq!(
+            transform::prune_definitions(&mut abi_root.body),
+            vec!["Unused".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_external_contracts() {
+        let json = serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [],
+                "root_schema": {
+                    "definitions": {
+                        "U128": { "type": "string" },
+                        "Unused": { "type": "string" }
+                    }
+                },
+                "external_contracts": [
+                    {
+                        "name": "ext_ft_core",
+                        "doc": " Fungible token contract holding the deposits.",
+                        "functions": [
+                            {
+                                "name": "ft_balance_of",
+                                "kind": "view",
+                                "params": {
+                                    "serialization_type": "json",
+                                    "args": [{ "name": "account_id", "type_schema": { "type": "string" } }]
+                                },
+                                "result": {
+                                    "serialization_type": "json",
+                                    "type_schema": { "$ref": "#/definitions/U128" }
+                                }
+                            }
+                        ]
+                    }
+                ]
+            }
+        });
+        let mut abi_root = AbiRoot::from_value(json.clone()).unwrap();
+        assert_eq!(abi_root.to_value(), json);
+        assert_eq!(
+            abi_root.body.external_contracts[0].functions[0].name,
+            "ft_balance_of"
+        );
+        assert_eq!(
+            transform::prune_definitions(&mut abi_root.body),
+            vec!["Unused".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_standards() {
+        let standard: AbiStandard = "nep141@1.0.0".parse().unwrap();
+        assert_eq!(standard, AbiStandard::new("nep141", "1.0.0"));
+        assert_eq!(standard.nep(), Some(141));
+        assert_eq!(standard.to_string(), "nep141@1.0.0");
+        assert!("nep141".parse::<AbiStandard>().is_err());
+
+        let metadata: AbiMetadata = serde_json::from_value(serde_json::json!({
+            "name": "token",
+            "standards": [{ "standard": "nep141", "version": "1.0.0" }]
+        }))
+        .unwrap();
+        assert_eq!(metadata.standards, vec![standard]);
+        assert!(metadata.other.is_empty());
+        assert_eq!(metadata.structured_doc(), None);
+
+        let metadata: AbiMetadata = serde_json::from_value(serde_json::json!({
+            "standards": [{
+                "standard": "nep171",
+                "version": "1.2.0",
+                "interfaces": ["NonFungibleTokenCore", "NonFungibleTokenResolver"]
+            }]
+        }))
+        .unwrap();
+        let nft = metadata.standard("NEP171").unwrap();
+        assert!(nft.satisfies(&Version::new(1, 1, 0)));
+        assert!(!nft.satisfies(&Version::new(1, 3, 0)));
+        assert!(!nft.satisfies(&Version::new(0, 9, 0)));
+        assert!(nft.satisfies(&Version::new(1, 2, 0)));
+        assert!(!nft.satisfies(&Version::new(1, 2, 5)));
+        assert_eq!(
+            metadata.standard_of_interface("NonFungibleTokenResolver"),
+            Some(nft)
+        );
+        assert_eq!(metadata.standard_of_interface("StorageManagement"), None);
+    }
+
+    #[test]
+    fn test_abitype_of() {
+        #[derive(JsonSchema, BorshSchema)]
+        #[allow(dead_code)]
+        struct Pair(u32, u32);
+
+        let mut gen = schemars::gen::SchemaGenerator::default();
+        let abi_type = AbiType::json_of::<Vec<Pair>>(&mut gen);
+        assert_eq!(abi_type.display_name(), "Vec<Pair>");
+        assert!(gen.definitions().contains_key("Pair"));
+
+        let abi_type = AbiType::borsh_of::<Pair>();
+        assert_eq!(abi_type.display_name(), "Pair");
+    }
+
+    #[test]
+    fn test_definitions() {
+        let mut body: AbiBody = serde_json::from_value(serde_json::json!({
+            "functions": [{
+                "name": "total_supply",
+                "kind": "view",
+                "result": { "serialization_type": "json", "type_schema": { "$ref": "#/$defs/U128" } }
+            }],
+            "root_schema": {
+                "$defs": {
+                    "U128": { "type": "string" },
+                    "AccountId": { "type": "string" }
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            body.definitions().map(|(name, _)| name).collect::<Vec<_>>(),
+            vec!["AccountId", "U128"]
+        );
+        let string = serde_json::from_value(serde_json::json!({ "type": "string" })).unwrap();
+        assert_eq!(body.definition("U128"), Some(&string));
+        assert_eq!(body.definition("Missing"), None);
+        assert_eq!(body.resolve_reference("#/$defs/U128"), Some(&string));
+        assert_eq!(body.resolve_reference("#/definitions/U128"), Some(&string));
+        assert_eq!(body.resolve_reference("U128"), None);
+        assert_eq!(
+            transform::prune_definitions(&mut body),
+            vec!["AccountId".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_upgrade_entrypoints() {
+        let function: AbiFunction = serde_json::from_value(serde_json::json!({
+            "name": "migrate",
+            "kind": "call",
+            "modifiers": ["init", "private", "migrate"]
+        }))
+        .unwrap();
+        assert!(function.is_upgrade_entrypoint());
+        assert_eq!(
+            serde_json::to_value(&function.modifiers).unwrap(),
+            serde_json::json!(["init", "private", "migrate"])
+        );
+        assert!(!AbiFunction::new("get", AbiFunctionKind::View).is_upgrade_entrypoint());
+    }
+
+    #[test]
+    fn test_push_params() {
+        let mut params = AbiParameters::default();
+        params
+            .push_borsh(AbiBorshParameter::new(
+                "amount",
+                borsh::schema_container_of::<u128>(),
+            ))
+            .unwrap();
+        let err = params
+            .push_json(AbiJsonParameter::new("memo", Schema::Bool(true)))
+            .unwrap_err();
+        assert_eq!(err.name(), "memo");
+        assert_eq!(
+            params,
+            AbiParameters::Borsh {
+                args: vec![AbiBorshParameter::new(
+                    "amount",
+                    borsh::schema_container_of::<u128>()
+                )]
+            }
+        );
+    }
+
+    #[test]
+    fn test_build_provenance() {
+        let mut build: BuildInfo = serde_json::from_value(serde_json::json!({
+            "compiler": "rustc 1.84.0",
+            "builder": "cargo-near 0.13.2",
+            "image": "sourcescan/cargo-near:0.13.2-rust-1.84.0",
+            "build_command": ["cargo", "near", "build", "reproducible-wasm"],
+            "source_commit": "0a1b2c3d",
+            "rustc_version": "rustc 1.84.0 (9fc6b4312 2025-01-07)"
+        }))
+        .unwrap();
+        assert!(!build.is_reproducible());
+        build.cargo_lock_hash = Some("e3b0c442".to_string());
+        assert!(build.is_reproducible());
+    }
+
+    #[test]
+    fn test_effects() {
+        let mut function: AbiFunction = serde_json::from_value(serde_json::json!({
+            "name": "ft_transfer_call",
+            "kind": "call",
+            "effects": { "mutates_balances": true, "emits_events": true, "creates_promises": true }
+        }))
+        .unwrap();
+        assert!(function.requires_receipt_tracking());
+
+        function.effects = Some(AbiEffects {
+            emits_events: true,
+            ..Default::default()
+        });
+        assert!(!function.requires_receipt_tracking());
+        assert_eq!(
+            serde_json::to_value(&function.effects).unwrap(),
+            serde_json::json!({ "emits_events": true })
+        );
+
+        function.effects = None;
+        assert!(function.requires_receipt_tracking());
+        function.kind = AbiFunctionKind::View;
+        assert!(!function.requires_receipt_tracking());
+    }
+
+    #[test]
+    fn test_storage_usage() {
+        let function: AbiFunction = serde_json::from_value(serde_json::json!({
+            "name": "storage_deposit",
+            "kind": "call",
+            "modifiers": ["payable"],
+            "storage_usage": { "bytes_per_entry": 125 }
+        }))
+        .unwrap();
+        let storage_usage = function.storage_usage.unwrap();
+        assert_eq!(storage_usage.bytes_per_entry, 125);
+        assert_eq!(
+            storage_usage.required_deposit(1),
+            1_250_000_000_000_000_000_000
+        );
+        assert_eq!(storage_usage.required_deposit(0), 0);
+    }
+
+    #[test]
+    fn test_result_kind() {
+        let value = serde_json::json!({
+            "name": "ft_transfer_call",
+            "kind": "call",
+            "result": { "serialization_type": "json", "type_schema": { "$ref": "#/definitions/U128" } },
+            "result_kind": "promise_or_value"
+        });
+        let mut function: AbiFunction = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(function.result_kind, AbiResultKind::PromiseOrValue);
+        assert!(function.may_return_promise());
+        assert_eq!(serde_json::to_value(&function).unwrap(), value);
+
+        function.result_kind = AbiResultKind::Value;
+        assert!(!function.may_return_promise());
+        assert!(serde_json::to_value(&function)
+            .unwrap()
+            .get("result_kind")
+            .is_none());
+    }
+
+    #[test]
+    fn test_since_and_stability() {
+        let abi_root = AbiRoot::from_value(serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": { "version": "1.2.0" },
+            "body": {
+                "functions": [
+                    { "name": "ft_transfer", "kind": "call", "since": "1.0.0", "stability": "stable" },
+                    { "name": "ft_burn", "kind": "call", "since": "1.2.0", "stability": "unstable" },
+                    { "name": "ft_metadata", "kind": "view", "since": "1.2.0" }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            abi_root.body.functions[1].stability,
+            Some(AbiStability::Unstable)
+        );
+        assert_eq!(
+            abi_root
+                .introduced_in("1.2.0")
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>(),
+            ["ft_burn", "ft_metadata"]
+        );
+    }
+
+    #[test]
+    fn test_contract_doc() {
+        let metadata: AbiMetadata = serde_json::from_value(serde_json::json!({
+            "name": "guestbook",
+            "doc": " A guestbook storing messages.\n\n Premium messages require a deposit.",
+            "keywords": ["social", "guestbook"]
+        }))
+        .unwrap();
+        assert!(metadata.has_keyword("Social"));
+        assert!(!metadata.has_keyword("defi"));
+        let doc = metadata.structured_doc().unwrap();
+        assert_eq!(doc.summary, "A guestbook storing messages.");
+        assert_eq!(
+            doc.description.as_deref(),
+            Some("Premium messages require a deposit.")
+        );
+        assert!(metadata.other.is_empty());
+    }
+
+    #[test]
+    fn test_extensions() {
+        let value = serde_json::json!({
+            "name": "ft_transfer",
+            "kind": "call",
+            "params": {
+                "serialization_type": "json",
+                "args": [{ "name": "amount", "type_schema": { "type": "string" }, "x-unit": "yocto" }]
+            },
+            "x-indexer": { "priority": 1 }
+        });
+        let mut function: AbiFunction = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(
+            function.extensions.get("x-indexer"),
+            Some(&serde_json::json!({ "priority": 1 }))
+        );
+        assert_eq!(serde_json::to_value(&function).unwrap(), value);
+
+        assert!(function
+            .extensions
+            .insert("indexer", serde_json::Value::Null)
+            .is_err());
+        function
+            .extensions
+            .insert("x-audited", serde_json::Value::Bool(true))
+            .unwrap();
+        assert_eq!(function.extensions.iter().count(), 2);
+
+        let err = serde_json::from_value::<AbiFunction>(serde_json::json!({
+            "name": "ft_transfer",
+            "kind": "call",
+            "indexer": {}
+        }))
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown field `indexer`"));
+    }
+
+    #[test]
+    fn test_access() {
+        let function: AbiFunction = serde_json::from_value(serde_json::json!({
+            "name": "set_fee",
+            "kind": "call",
+            "access": { "type": "role", "role": "fee_manager" }
+        }))
+        .unwrap();
+        assert_eq!(
+            function.access,
+            Some(AbiAccess::Role {
+                role: "fee_manager".to_string()
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(AbiAccess::Accounts {
+                accounts: vec!["dao.near".to_string()]
+            })
+            .unwrap(),
+            serde_json::json!({ "type": "accounts", "accounts": ["dao.near"] })
+        );
+        assert_eq!(
+            serde_json::to_value(AbiAccess::Owner).unwrap(),
+            serde_json::json!({ "type": "owner" })
+        );
+    }
+
+    #[test]
+    fn test_named_callbacks() {
+        let function: AbiFunction = serde_json::from_value(serde_json::json!({
+            "name": "on_transfer",
+            "kind": "call",
+            "callbacks": [
+                {
+                    "name": "used_amount",
+                    "doc": " Amount used by the receiver.",
+                    "serialization_type": "json",
+                    "type_schema": { "type": "string" }
+                },
+                { "serialization_type": "json", "type_schema": { "type": "integer" } }
+            ]
+        }))
+        .unwrap();
+        assert_eq!(function.callbacks[0].name, "used_amount");
+        assert_eq!(
+            function.callbacks[0].doc.as_deref(),
+            Some(" Amount used by the receiver.")
+        );
+        // Callbacks of the legacy shape are left unnamed, and serialized as they were.
+        assert_eq!(function.callbacks[1].name, "");
+        assert_eq!(
+            function.callbacks[1].ty,
+            AbiType::Json {
+                type_schema: serde_json::from_value(serde_json::json!({ "type": "integer" }))
+                    .unwrap()
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(&function.callbacks[0]).unwrap(),
+            serde_json::json!({
+                "name": "used_amount",
+                "doc": " Amount used by the receiver.",
+                "serialization_type": "json",
+                "type_schema": { "type": "string" }
+            })
+        );
+        assert!(serde_json::to_value(&function.callbacks[1])
+            .unwrap()
+            .get("name")
+            .is_none());
+
+        assert!(serde_json::from_value::<AbiCallback>(serde_json::json!({
+            "name": "x",
+            "serialization_type": "json",
+            "type_schema": {},
+            "unknown": 1
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_consolidated_callbacks() {
+        let mut function = AbiFunction::new("on_transfer", AbiFunctionKind::Call);
+        let callbacks = AbiCallbacks::Borsh {
+            types: vec![
+                borsh::schema_container_of::<u64>(),
+                borsh::schema_container_of::<bool>(),
+            ],
+        };
+        function.set_callbacks(&callbacks);
+        assert_eq!(function.callbacks.len(), 2);
+        assert_eq!(function.callbacks[1].name, "callback_1");
+        assert_eq!(function.consolidated_callbacks(), Ok(callbacks));
+
+        function.callbacks.push(AbiCallback::new(
+            "other",
+            AbiType::Json {
+                type_schema: Schema::Bool(true),
+            },
+        ));
+        let err = function.consolidated_callbacks().unwrap_err();
+        assert_eq!(err.index(), 2);
+
+        function.callbacks.clear();
+        assert_eq!(
+            function.consolidated_callbacks(),
+            Ok(AbiCallbacks::default())
+        );
+    }
+
+    #[test]
+    fn test_serde_callbacks() {
+        let mut function = AbiFunction::new("on_transfer", AbiFunctionKind::Call);
+        function.callbacks = vec![
+            AbiCallback {
+                doc: Some(" Amount used.".to_string()),
+                ..AbiCallback::new("used_amount", AbiType::borsh_of::<u8>())
+            },
+            AbiCallback::new("", AbiType::borsh_of::<bool>()),
+        ];
+        let value = serde_json::to_value(&function).unwrap();
+        assert_eq!(
+            value["callbacks"],
+            serde_json::json!([
+                {
+                    "name": "used_amount",
+                    "doc": " Amount used.",
+                    "serialization_type": "borsh",
+                    "type_schema": { "declaration": "u8", "definitions": { "u8": { "Primitive": 1 } } }
+                },
+                {
+                    "serialization_type": "borsh",
+                    "type_schema": { "declaration": "bool", "definitions": { "bool": { "Primitive": 1 } } }
+                }
+            ])
+        );
+        assert_eq!(
+            serde_json::from_value::<AbiFunction>(value.clone()).unwrap(),
+            function
+        );
+
+        // The consolidated form is read too, and written back as a list.
+        let mut consolidated = value.clone();
+        consolidated["callbacks"] = serde_json::json!({
+            "serialization_type": "borsh",
+            "args": [
+                {
+                    "name": "used_amount",
+                    "doc": " Amount used.",
+                    "type_schema": { "declaration": "u8", "definitions": { "u8": { "Primitive": 1 } } }
+                },
+                { "type_schema": { "declaration": "bool", "definitions": { "bool": { "Primitive": 1 } } } }
+            ]
+        });
+        let parsed = serde_json::from_value::<AbiFunction>(consolidated).unwrap();
+        assert_eq!(parsed, function);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), value);
+
+        // Callbacks may not mix serialization types.
+        let legacy = |second: serde_json::Value| {
+            serde_json::from_value::<AbiFunction>(serde_json::json!({
+                "name": "on_transfer",
+                "kind": "call",
+                "callbacks": [
+                    { "serialization_type": "json", "type_schema": { "type": "string" } },
+                    second
+                ]
+            }))
+        };
+        let uniform = legacy(serde_json::json!({
+            "serialization_type": "json",
+            "type_schema": { "type": "integer" }
+        }))
+        .unwrap();
+        assert_eq!(uniform.callbacks.len(), 2);
+        let err = legacy(serde_json::json!({
+            "serialization_type": "borsh",
+            "type_schema": { "declaration": "u8", "definitions": { "u8": { "Primitive": 1 } } }
+        }))
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("callback 1 has a different serialization type"));
+
+        function.callbacks.push(AbiCallback::new(
+            "memo",
+            AbiType::Json {
+                type_schema: Schema::Bool(true),
+            },
+        ));
+        assert!(serde_json::to_value(&function).is_err());
+    }
+
+    #[test]
+    fn test_unknown_modifiers() {
+        let json = serde_json::json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [{ "name": "pay", "kind": "call", "modifiers": ["payable", "refundable"] }],
+                "root_schema": {}
+            }
+        });
+        let err = AbiRoot::from_value(json.clone()).unwrap_err();
+        assert!(err.to_string().contains("unknown variant `refundable`"));
+
+        let abi_root = AbiRoot::from_value_lenient(json.clone()).unwrap();
+        assert_eq!(
+            abi_root.body.functions[0].modifiers,
+            [
+                AbiFunctionModifier::Payable,
+                AbiFunctionModifier::Unknown("refundable".to_string())
+            ]
+        );
+        assert_eq!(abi_root.to_value(), json);
+        assert!(AbiRoot::from_value(json.clone()).is_err());
+
+        // Modifiers come from newer schema versions, which are accepted leniently as well.
+        let mut newer = json;
+        newer["schema_version"] = serde_json::json!("0.6.0");
+        assert!(AbiRoot::from_value(newer.clone())
+            .unwrap_err()
+            .to_string()
+            .contains("consider upgrading near-abi"));
+        assert_eq!(
+            AbiRoot::from_value_lenient(newer.clone())
+                .unwrap()
+                .to_value(),
+            newer
+        );
+        newer["schema_version"] = serde_json::json!("1.0.0");
+        assert!(AbiRoot::from_value_lenient(newer).is_err());
+        let mut older = abi_root.to_value();
+        older["schema_version"] = serde_json::json!("0.3.0");
+        assert!(AbiRoot::from_value_lenient(older).is_err());
+    }
 }
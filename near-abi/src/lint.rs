@@ -0,0 +1,279 @@
+//! Configurable quality checks of ABIs.
+//!
+//! Unlike [`validate`](crate::validate), which reports ABIs that are inconsistent, lints report
+//! ABIs that are valid but hard to consume: undocumented functions, meaningless parameter names
+//! or large schemas inlined instead of referenced. Every [`Lint`] has a [`Severity`] that can be
+//! changed through a [`LintConfig`], typically deserialized from the configuration of the build
+//! tool, and [`lint`] returns serializable [`Diagnostic`]s for it to surface.
+
+use crate::path::AbiPath;
+use crate::walk::{function_json_schemas, visit};
+use crate::{AbiParameters, AbiRoot};
+use schemars::schema::Schema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// How a lint is reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// The lint is not checked.
+    Allow,
+    Warning,
+    /// The lint should fail the build.
+    Error,
+}
+
+/// The quality checks of this crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Lint {
+    /// A function has no documentation.
+    MissingDocs,
+    /// A parameter has a positional name such as `arg0` or `_1`, as generated for patterns in
+    /// function signatures.
+    PositionalParamName,
+    /// A type schema of a function signature is a large inline schema instead of a `$ref` to a
+    /// root schema definition.
+    LargeInlineSchema,
+}
+
+impl Lint {
+    /// All lints, in the order they are checked.
+    pub const ALL: [Lint; 3] = [
+        Lint::MissingDocs,
+        Lint::PositionalParamName,
+        Lint::LargeInlineSchema,
+    ];
+
+    /// Name of the lint in configurations and diagnostics, e.g. `missing_docs`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::MissingDocs => "missing_docs",
+            Self::PositionalParamName => "positional_param_name",
+            Self::LargeInlineSchema => "large_inline_schema",
+        }
+    }
+
+    /// Severity of the lint when not configured.
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Self::MissingDocs => Severity::Allow,
+            Self::PositionalParamName | Self::LargeInlineSchema => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Configuration of the lints, e.g. `{ "levels": { "missing_docs": "error" } }`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintConfig {
+    /// Severities overriding the [default ones](Lint::default_severity).
+    pub levels: BTreeMap<Lint, Severity>,
+    /// Number of subschemas above which an inline schema is reported by
+    /// [`Lint::LargeInlineSchema`].
+    pub max_inline_schema_size: usize,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            levels: BTreeMap::new(),
+            max_inline_schema_size: 16,
+        }
+    }
+}
+
+impl LintConfig {
+    /// Configured severity of `lint`.
+    pub fn severity(&self, lint: Lint) -> Severity {
+        self.levels
+            .get(&lint)
+            .copied()
+            .unwrap_or_else(|| lint.default_severity())
+    }
+}
+
+/// A lint reported for an element of an ABI.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub lint: Lint,
+    pub severity: Severity,
+    pub location: AbiPath,
+    /// Human-readable description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Allow => "allowed",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(
+            f,
+            "{}[{}]: {}: {}",
+            severity, self.lint, self.location, self.message
+        )
+    }
+}
+
+/// Checks the lints that are not allowed by `config`, returning diagnostics in the order of the
+/// functions of the ABI.
+pub fn lint(abi: &AbiRoot, config: &LintConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut report = |lint: Lint, location: AbiPath, message: String| {
+        let severity = config.severity(lint);
+        if severity != Severity::Allow {
+            diagnostics.push(Diagnostic {
+                lint,
+                severity,
+                location,
+                message,
+            });
+        }
+    };
+    for function in &abi.body.functions {
+        let has_docs = function
+            .doc
+            .as_deref()
+            .map_or(false, |doc| !doc.trim().is_empty());
+        if !has_docs {
+            report(
+                Lint::MissingDocs,
+                AbiPath::Function(function.name.clone()),
+                "function has no documentation".to_string(),
+            );
+        }
+        let param_names: Vec<&str> = match &function.params {
+            AbiParameters::Json { args } => args.iter().map(|arg| arg.name.as_str()).collect(),
+            AbiParameters::Borsh { args } => args.iter().map(|arg| arg.name.as_str()).collect(),
+        };
+        for name in param_names {
+            if is_positional(name) {
+                report(
+                    Lint::PositionalParamName,
+                    AbiPath::Param {
+                        function: function.name.clone(),
+                        param: name.to_string(),
+                    },
+                    format!("parameter `{}` has a positional name", name),
+                );
+            }
+        }
+        for (site, schema) in function_json_schemas(function) {
+            let size = inline_size(schema);
+            if size > config.max_inline_schema_size {
+                report(
+                    Lint::LargeInlineSchema,
+                    AbiPath::from_site(function, &site),
+                    format!(
+                        "inline schema has {} subschemas, consider moving it to a definition",
+                        size
+                    ),
+                );
+            }
+        }
+    }
+    diagnostics
+}
+
+/// Whether a parameter name is made of a generic prefix and an index, e.g. `arg0` or `_1`.
+fn is_positional(name: &str) -> bool {
+    let index = name.trim_start_matches(|c: char| !c.is_ascii_digit());
+    let prefix = &name[..name.len() - index.len()];
+    !index.is_empty()
+        && index.chars().all(|c| c.is_ascii_digit())
+        && ["arg", "_", "p", "param"].contains(&prefix)
+}
+
+/// Number of schemas making up `schema`, references counting as one.
+fn inline_size(schema: &Schema) -> usize {
+    let mut size = 0;
+    visit(schema, &mut |_| size += 1);
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_lint() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    {
+                        "name": "set",
+                        "doc": "Sets the value.",
+                        "kind": "call",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [
+                                { "name": "arg0", "type_schema": { "type": "string" } },
+                                { "name": "arg", "type_schema": { "type": "string" } },
+                                {
+                                    "name": "value",
+                                    "type_schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "a": { "type": "string" },
+                                            "b": { "type": "array", "items": { "type": "integer" } }
+                                        }
+                                    }
+                                }
+                            ]
+                        }
+                    },
+                    { "name": "get", "kind": "view" }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+
+        let diagnostics = lint(&abi, &LintConfig::default());
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            ["warning[positional_param_name]: functions/set/params/arg0: parameter `arg0` has a positional name"]
+        );
+
+        let config: LintConfig = serde_json::from_value(json!({
+            "levels": { "missing_docs": "error", "positional_param_name": "allow" },
+            "max_inline_schema_size": 3
+        }))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(lint(&abi, &config)).unwrap(),
+            json!([
+                {
+                    "lint": "large_inline_schema",
+                    "severity": "warning",
+                    "location": "functions/set/params/value",
+                    "message": "inline schema has 4 subschemas, consider moving it to a definition"
+                },
+                {
+                    "lint": "missing_docs",
+                    "severity": "error",
+                    "location": "functions/get",
+                    "message": "function has no documentation"
+                }
+            ])
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! In-process mock of a contract driven purely by its ABI.
+//!
+//! [`MockContract`] accepts every function declared in the ABI, checks the supplied arguments
+//! against the declared parameter schemas and responds with the result of the matching
+//! [example](crate::AbiFunction::examples), if any, or else with the simplest value conforming to
+//! the declared result type (see [`default_json`] and [`default_borsh`]). This lets integration tests
+//! of contract callers run without building or deploying the real contract.
+//!
+//! Function modifiers are enforced as on chain: private functions only accept calls from the
+//! contract itself, only payable functions accept a deposit and init functions can initialize the
+//! contract only once, unless they ignore its state. The ABI does not tell whether the contract
+//! state has a default though, so the other functions can be invoked before any init function.
+
+use crate::value::{
+    default_borsh, default_json, validate_borsh_args, validate_json_args, ValueError,
+};
+use crate::{AbiFunction, AbiFunctionKind, AbiFunctionModifier, AbiParameters, AbiRoot, AbiType};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Mock contract responding to calls of the functions declared in an ABI.
+#[derive(Clone, Debug)]
+pub struct MockContract {
+    abi: AbiRoot,
+    responses: HashMap<String, Vec<u8>>,
+    initialized: bool,
+}
+
+/// Context of a call made with [`MockContract::call_with`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MockCall {
+    /// Whether the contract calls itself, e.g. in a callback. Required by private functions.
+    pub from_self: bool,
+    /// Deposit attached to the call, in yoctoNEAR. Only accepted by payable functions.
+    pub deposit: u128,
+}
+
+impl MockContract {
+    pub fn new(abi: AbiRoot) -> Self {
+        Self {
+            abi,
+            responses: HashMap::new(),
+            initialized: false,
+        }
+    }
+
+    /// ABI the mock was created from.
+    pub fn abi(&self) -> &AbiRoot {
+        &self.abi
+    }
+
+    /// Overrides the raw bytes returned by `function` instead of the schema-derived default.
+    pub fn set_response(&mut self, function: &str, response: Vec<u8>) {
+        self.responses.insert(function.to_string(), response);
+    }
+
+    /// Whether an init function was successfully called.
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    /// Invokes a view function, failing if the function requires a transaction.
+    pub fn view(&self, function: &str, args: &[u8]) -> Result<Vec<u8>, MockError> {
+        let abi_function = self.function(function)?;
+        if abi_function.kind != AbiFunctionKind::View {
+            return Err(MockError::NotAView(function.to_string()));
+        }
+        // There is no caller of a view function, so it can't be the contract itself.
+        if abi_function
+            .modifiers
+            .contains(&AbiFunctionModifier::Private)
+        {
+            return Err(MockError::Private(function.to_string()));
+        }
+        self.invoke(abi_function, args)
+    }
+
+    /// Invokes a function as part of a transaction made by another account, without a deposit.
+    /// Both view and call functions can be invoked this way, just as on chain.
+    pub fn call(&mut self, function: &str, args: &[u8]) -> Result<Vec<u8>, MockError> {
+        self.call_with(function, args, &MockCall::default())
+    }
+
+    /// Invokes a function as part of a transaction, in the given context.
+    pub fn call_with(
+        &mut self,
+        function: &str,
+        args: &[u8],
+        context: &MockCall,
+    ) -> Result<Vec<u8>, MockError> {
+        let abi_function = self.function(function)?;
+        let modifiers = &abi_function.modifiers;
+        if modifiers.contains(&AbiFunctionModifier::Private) && !context.from_self {
+            return Err(MockError::Private(function.to_string()));
+        }
+        if context.deposit > 0 && !modifiers.contains(&AbiFunctionModifier::Payable) {
+            return Err(MockError::NotPayable(function.to_string()));
+        }
+        let init = modifiers.contains(&AbiFunctionModifier::Init);
+        if init && self.initialized && !abi_function.init_ignores_state() {
+            return Err(MockError::AlreadyInitialized(function.to_string()));
+        }
+        let response = self.invoke(abi_function, args)?;
+        self.initialized |= init;
+        Ok(response)
+    }
+
+    fn function(&self, name: &str) -> Result<&AbiFunction, MockError> {
+        self.abi
+            .body
+            .functions
+            .iter()
+            .find(|function| function.name == name)
+            .ok_or_else(|| MockError::UnknownFunction(name.to_string()))
+    }
+
+    fn invoke(&self, function: &AbiFunction, args: &[u8]) -> Result<Vec<u8>, MockError> {
+        self.check_args(function, args)
+            .map_err(|error| MockError::InvalidArgs {
+                function: function.name.clone(),
+                error,
+            })?;
+        if let Some(response) = self.responses.get(&function.name) {
+            return Ok(response.clone());
+        }
+        if let Some(example) = function
+            .examples
+            .iter()
+            .find(|example| example.args.matches(args))
+        {
+            return match &example.result {
+                Some(result) => result.to_bytes().map_err(|err| MockError::InvalidExample {
+                    function: function.name.clone(),
+                    error: err.to_string(),
+                }),
+                None => Ok(Vec::new()),
+            };
+        }
+        let root_schema = &self.abi.body.root_schema;
+        match &function.result {
+            None => Ok(Vec::new()),
+            Some(AbiType::Json { type_schema }) => default_json(root_schema, type_schema)
+                .map(|value| serde_json::to_vec(&value).unwrap())
+                .map_err(|error| MockError::InvalidResultSchema {
+                    function: function.name.clone(),
+                    error,
+                }),
+            Some(AbiType::Borsh { type_schema }) => {
+                default_borsh(type_schema).map_err(|error| MockError::InvalidResultSchema {
+                    function: function.name.clone(),
+                    error,
+                })
+            }
+        }
+    }
+
+    fn check_args(&self, function: &AbiFunction, args: &[u8]) -> Result<(), ValueError> {
+        match &function.params {
+            AbiParameters::Json { args: params } => {
+                let value: Value = if args.is_empty() {
+                    Value::Object(Default::default())
+                } else {
+                    serde_json::from_slice(args).map_err(|err| {
+                        ValueError::new("", format!("arguments are not valid JSON: {}", err))
+                    })?
+                };
+                validate_json_args(&self.abi.body.root_schema, params, &value)
+            }
+            AbiParameters::Borsh { args: params } => validate_borsh_args(params, args),
+        }
+    }
+}
+
+/// Error returned by [`MockContract`] when a call does not conform to the ABI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MockError {
+    /// The function is not declared in the ABI.
+    UnknownFunction(String),
+    /// A call function was invoked as a view.
+    NotAView(String),
+    /// A private function was invoked by another account than the contract itself.
+    Private(String),
+    /// A deposit was attached to a call of a function that is not payable.
+    NotPayable(String),
+    /// An init function was invoked on an initialized contract.
+    AlreadyInitialized(String),
+    /// Arguments do not conform to the declared parameters.
+    InvalidArgs { function: String, error: ValueError },
+    /// No value can be produced for the declared result type.
+    InvalidResultSchema { function: String, error: ValueError },
+    /// The result of the matching example can not be decoded.
+    InvalidExample { function: String, error: String },
+}
+
+impl std::error::Error for MockError {}
+impl fmt::Display for MockError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownFunction(function) => {
+                write!(f, "function `{}` is not declared in the ABI", function)
+            }
+            Self::NotAView(function) => write!(f, "function `{}` is not a view function", function),
+            Self::Private(function) => write!(
+                f,
+                "function `{}` can only be called by the contract itself",
+                function
+            ),
+            Self::NotPayable(function) => {
+                write!(f, "function `{}` does not accept a deposit", function)
+            }
+            Self::AlreadyInitialized(function) => write!(
+                f,
+                "function `{}` can not be called on an initialized contract",
+                function
+            ),
+            Self::InvalidArgs { function, error } => {
+                write!(f, "invalid arguments for `{}`: {}", function, error)
+            }
+            Self::InvalidResultSchema { function, error } => {
+                write!(f, "invalid result schema of `{}`: {}", function, error)
+            }
+            Self::InvalidExample { function, error } => {
+                write!(f, "invalid example of `{}`: {}", function, error)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiBody, AbiJsonParameter, AbiMetadata, SCHEMA_VERSION};
+    use schemars::schema::RootSchema;
+    use serde_json::json;
+
+    fn abi() -> AbiRoot {
+        let function = |name: &str, kind, params, result| AbiFunction {
+            params,
+            result,
+            ..AbiFunction::new(name, kind)
+        };
+        AbiRoot {
+            schema_version: SCHEMA_VERSION.to_string(),
+            metadata: AbiMetadata::default(),
+            body: AbiBody {
+                functions: vec![
+                    function(
+                        "get_balance",
+                        AbiFunctionKind::View,
+                        AbiParameters::Json {
+                            args: vec![AbiJsonParameter::new(
+                                "account_id",
+                                serde_json::from_value(json!({ "type": "string" })).unwrap(),
+                            )],
+                        },
+                        Some(AbiType::Json {
+                            type_schema: serde_json::from_value(
+                                json!({ "type": "integer", "minimum": 0.0 }),
+                            )
+                            .unwrap(),
+                        }),
+                    ),
+                    function(
+                        "reset",
+                        AbiFunctionKind::Call,
+                        AbiParameters::default(),
+                        Some(AbiType::Borsh {
+                            type_schema: borsh::schema_container_of::<(u8, bool)>(),
+                        }),
+                    ),
+                ],
+                root_schema: RootSchema::default(),
+                state: None,
+                constants: Vec::new(),
+                external_contracts: Vec::new(),
+            },
+            test_vectors: Vec::new(),
+            extensions: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_mock_contract_responses() {
+        let mut mock = MockContract::new(abi());
+        assert_eq!(
+            mock.view("get_balance", br#"{"account_id":"alice.near"}"#)
+                .unwrap(),
+            b"0"
+        );
+        assert_eq!(mock.call("reset", b"").unwrap(), vec![0, 0]);
+        assert!(!mock.is_initialized());
+
+        let mut abi = abi();
+        abi.body.functions[0].examples = serde_json::from_value(json!([
+            { "args": { "json": { "account_id": "bob.near" } }, "result": { "json": 42 } }
+        ]))
+        .unwrap();
+        let with_examples = MockContract::new(abi);
+        assert_eq!(
+            with_examples
+                .view("get_balance", br#"{ "account_id": "bob.near" }"#)
+                .unwrap(),
+            b"42"
+        );
+        assert_eq!(
+            with_examples
+                .view("get_balance", br#"{"account_id":"alice.near"}"#)
+                .unwrap(),
+            b"0"
+        );
+
+        mock.set_response("get_balance", b"100".to_vec());
+        assert_eq!(
+            mock.view("get_balance", br#"{"account_id":"alice.near"}"#)
+                .unwrap(),
+            b"100"
+        );
+    }
+
+    #[test]
+    fn test_mock_contract_errors() {
+        let mock = MockContract::new(abi());
+        assert_eq!(
+            mock.view("reset", b""),
+            Err(MockError::NotAView("reset".to_string()))
+        );
+        assert_eq!(
+            mock.view("missing", b""),
+            Err(MockError::UnknownFunction("missing".to_string()))
+        );
+        let err = mock
+            .view("get_balance", br#"{"account_id":1}"#)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid arguments for `get_balance`: at `/account_id`: expected string, found number"
+        );
+        let err = mock
+            .view("get_balance", br#"{"account_id":"a","extra":1}"#)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid arguments for `get_balance`: unknown parameter `extra`"
+        );
+    }
+
+    #[test]
+    fn test_mock_contract_modifiers() {
+        let mut abi = abi();
+        let with_modifiers = |name: &str, modifiers: Vec<AbiFunctionModifier>| AbiFunction {
+            modifiers,
+            ..AbiFunction::new(name, AbiFunctionKind::Call)
+        };
+        abi.body.functions.extend([
+            with_modifiers("new", vec![AbiFunctionModifier::Init]),
+            with_modifiers(
+                "reinit",
+                vec![AbiFunctionModifier::Init, AbiFunctionModifier::IgnoreState],
+            ),
+            with_modifiers("on_transfer", vec![AbiFunctionModifier::Private]),
+            with_modifiers("deposit", vec![AbiFunctionModifier::Payable]),
+        ]);
+        abi.body.functions[0]
+            .modifiers
+            .push(AbiFunctionModifier::Private);
+        let mut mock = MockContract::new(abi);
+        let from_self = MockCall {
+            from_self: true,
+            ..MockCall::default()
+        };
+        let with_deposit = MockCall {
+            deposit: 1,
+            ..MockCall::default()
+        };
+
+        assert_eq!(
+            mock.call("on_transfer", b""),
+            Err(MockError::Private("on_transfer".to_string()))
+        );
+        assert_eq!(
+            mock.call_with("on_transfer", b"", &from_self),
+            Ok(Vec::new())
+        );
+        assert_eq!(
+            mock.view("get_balance", br#"{"account_id":"a"}"#),
+            Err(MockError::Private("get_balance".to_string()))
+        );
+
+        assert_eq!(
+            mock.call_with("reset", b"", &with_deposit),
+            Err(MockError::NotPayable("reset".to_string()))
+        );
+        assert_eq!(
+            mock.call_with("deposit", b"", &with_deposit),
+            Ok(Vec::new())
+        );
+
+        assert_eq!(mock.call("new", b""), Ok(Vec::new()));
+        assert!(mock.is_initialized());
+        let err = mock.call("new", b"").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "function `new` can not be called on an initialized contract"
+        );
+        assert_eq!(mock.call("reinit", b""), Ok(Vec::new()));
+    }
+}
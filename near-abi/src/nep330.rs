@@ -0,0 +1,169 @@
+//! Contract source metadata as defined by [NEP-330].
+//!
+//! Contracts expose [`ContractSourceMetadata`] through their `contract_source_metadata` view
+//! function so that the deployed code can be verified against its sources. The same information
+//! is part of [`AbiMetadata`], and the two representations convert into each other.
+//!
+//! [NEP-330]: https://github.com/near/NEPs/blob/master/neps/nep-0330.md
+
+use crate::{AbiLinks, AbiMetadata, AbiStandard, BuildInfo};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Version of NEP-330 the metadata conforms to.
+pub const NEP330_VERSION: &str = "1.2.0";
+
+/// Source metadata of a contract, in the format returned by `contract_source_metadata`.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub struct ContractSourceMetadata {
+    /// Version of the contract, e.g. a crate version or a commit hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Link to the source code of the contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    /// The standards implemented by the contract.
+    #[serde(default)]
+    pub standards: Vec<AbiStandard>,
+    /// How the contract can be reproducibly built.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_info: Option<SourceBuildInfo>,
+}
+
+/// Reproducible build information of a contract.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub struct SourceBuildInfo {
+    /// Docker image the contract was built in, pinned by digest.
+    pub build_environment: String,
+    /// Command that was run inside the build environment.
+    pub build_command: Vec<String>,
+    /// Path of the contract crate inside the source code snapshot.
+    pub contract_path: String,
+    /// Link to the snapshot of the source code the contract was built from.
+    pub source_code_snapshot: String,
+}
+
+impl TryFrom<&AbiMetadata> for ContractSourceMetadata {
+    type Error = Nep330Error;
+
+    /// The repository link of the ABI metadata becomes the NEP-330 source link. Build information
+    /// is only carried over when it is reproducible, i.e. records an image and a source code
+    /// snapshot, and is rejected otherwise.
+    fn try_from(metadata: &AbiMetadata) -> Result<Self, Self::Error> {
+        let build_info = match &metadata.build {
+            Some(build) => Some(SourceBuildInfo {
+                build_environment: build.image.clone().ok_or(Nep330Error::MissingImage)?,
+                build_command: build.build_command.clone(),
+                contract_path: build.contract_path.clone().unwrap_or_default(),
+                source_code_snapshot: build
+                    .source_code_snapshot
+                    .clone()
+                    .ok_or(Nep330Error::MissingSourceCodeSnapshot)?,
+            }),
+            None => None,
+        };
+        Ok(Self {
+            version: metadata.version.clone(),
+            link: metadata.links.repository.clone(),
+            // NEP-330 standards only carry a name and a version.
+            standards: metadata
+                .standards
+                .iter()
+                .map(|standard| AbiStandard::new(&standard.standard, &standard.version))
+                .collect(),
+            build_info,
+        })
+    }
+}
+
+impl From<ContractSourceMetadata> for AbiMetadata {
+    /// NEP-330 does not record the compiler of the contract, which is left empty, nor the build
+    /// tool, which is taken to be the first word of the build command.
+    fn from(metadata: ContractSourceMetadata) -> Self {
+        let build = metadata.build_info.map(|build| BuildInfo {
+            compiler: String::new(),
+            builder: build.build_command.first().cloned().unwrap_or_default(),
+            image: Some(build.build_environment),
+            source_code_snapshot: Some(build.source_code_snapshot),
+            contract_path: Some(build.contract_path),
+            build_command: build.build_command,
+            ..Default::default()
+        });
+        Self {
+            version: metadata.version,
+            build,
+            standards: metadata.standards,
+            links: AbiLinks {
+                repository: metadata.link,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// Error returned when ABI metadata can not be represented as NEP-330 source metadata.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Nep330Error {
+    /// The build information does not record the docker image the contract was built in.
+    MissingImage,
+    /// The build information does not record the source code the contract was built from.
+    MissingSourceCodeSnapshot,
+}
+
+impl std::error::Error for Nep330Error {}
+impl fmt::Display for Nep330Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingImage => write!(f, "the build environment image is not recorded"),
+            Self::MissingSourceCodeSnapshot => {
+                write!(f, "the source code snapshot is not recorded")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_nep330_conversions() {
+        let source: ContractSourceMetadata = serde_json::from_value(json!({
+            "version": "1.0.0",
+            "link": "https://github.com/near/token",
+            "standards": [{ "standard": "nep330", "version": NEP330_VERSION }],
+            "build_info": {
+                "build_environment": "sourcescan/cargo-near:0.13.2-rust-1.84.0@sha256:abc",
+                "build_command": ["cargo", "near", "build", "non-reproducible-wasm"],
+                "contract_path": "token",
+                "source_code_snapshot": "git+https://github.com/near/token?rev=0a1b2c"
+            }
+        }))
+        .unwrap();
+
+        let metadata = AbiMetadata::from(source.clone());
+        assert_eq!(
+            metadata.links.repository.as_deref(),
+            Some("https://github.com/near/token")
+        );
+        let build = metadata.build.as_ref().unwrap();
+        assert_eq!(build.builder, "cargo");
+        assert_eq!(build.contract_path.as_deref(), Some("token"));
+        assert_eq!(ContractSourceMetadata::try_from(&metadata), Ok(source));
+
+        let metadata = AbiMetadata {
+            build: Some(BuildInfo {
+                compiler: "rustc 1.84.0".to_string(),
+                builder: "cargo-near 0.13.2".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            ContractSourceMetadata::try_from(&metadata),
+            Err(Nep330Error::MissingImage)
+        );
+    }
+}
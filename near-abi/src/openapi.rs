@@ -0,0 +1,309 @@
+//! Export of the view functions of a contract as an OpenAPI document.
+//!
+//! API gateways proxying NEAR RPC `call_function` queries expose each view function as an HTTP
+//! endpoint taking the function arguments as request body. [`to_openapi`] describes such an API
+//! in OpenAPI 3.1, whose schema objects are JSON Schema 2020-12, so that gateway routes, request
+//! validation and clients can be generated from the ABI.
+
+use crate::draft::{convert_schema, SchemaDraft};
+use crate::walk::{function_json_schemas, reachable_definitions};
+use crate::{AbiFunction, AbiFunctionKind, AbiParameters, AbiRoot, AbiType};
+use schemars::schema::{InstanceType, Schema, SingleOrVec};
+use serde_json::{json, Map, Value};
+
+/// OpenAPI version of exported documents.
+pub const OPENAPI_VERSION: &str = "3.1.0";
+
+/// Prefix of the paths of view function endpoints, followed by the function name.
+pub const VIEW_PATH_PREFIX: &str = "/view/";
+
+/// Prefix of references to the schemas of the document components.
+const COMPONENTS_PREFIX: &str = "#/components/schemas/";
+
+/// Describes the view functions of `abi` as an OpenAPI 3.1 document.
+///
+/// Every view function is an operation `POST /view/{name}`, whose `operationId` is the function
+/// name:
+///
+/// * JSON parameters make up an `application/json` request body, an object with a property per
+///   parameter. Parameters are required unless their type is nullable;
+/// * Borsh parameters make up an `application/octet-stream` request body;
+/// * the `200` response holds the result of the function, as `application/json` or
+///   `application/octet-stream` depending on its serialization type.
+///
+/// Root schema definitions referenced by the view functions are exported as component schemas.
+/// Schemas are converted to JSON Schema 2020-12, as required by OpenAPI 3.1.
+pub fn to_openapi(abi: &AbiRoot) -> Value {
+    let views: Vec<&AbiFunction> = abi
+        .body
+        .functions
+        .iter()
+        .filter(|function| function.kind == AbiFunctionKind::View)
+        .collect();
+
+    let mut info = Map::new();
+    info.insert(
+        "title".to_string(),
+        abi.metadata.name.as_deref().unwrap_or("contract").into(),
+    );
+    info.insert(
+        "version".to_string(),
+        abi.metadata.version.as_deref().unwrap_or("0.0.0").into(),
+    );
+    if let Some(doc) = &abi.metadata.doc {
+        info.insert("description".to_string(), doc.trim().into());
+    }
+
+    let mut paths = Map::new();
+    for function in &views {
+        let path = format!("{}{}", VIEW_PATH_PREFIX, function.name);
+        paths.insert(path, json!({ "post": operation(function) }));
+    }
+
+    let root_schema = &abi.body.root_schema;
+    let reachable = reachable_definitions(
+        root_schema,
+        views
+            .iter()
+            .flat_map(|function| function_json_schemas(function))
+            .map(|(_, schema)| schema),
+    );
+    let mut schemas = Map::new();
+    for name in reachable {
+        if let Some(schema) = root_schema.definitions.get(&name) {
+            schemas.insert(name, schema_value(schema));
+        }
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": info,
+        "paths": paths,
+        "components": { "schemas": schemas },
+    })
+}
+
+/// Operation object of the endpoint of the view function `function`.
+fn operation(function: &AbiFunction) -> Value {
+    let mut operation = Map::new();
+    operation.insert("operationId".to_string(), function.name.clone().into());
+    if let Some(doc) = function.doc.as_deref().map(str::trim) {
+        if let Some(summary) = doc.lines().next().filter(|line| !line.is_empty()) {
+            operation.insert("summary".to_string(), summary.into());
+        }
+        operation.insert("description".to_string(), doc.into());
+    }
+    if function.deprecated.is_some() {
+        operation.insert("deprecated".to_string(), true.into());
+    }
+
+    let request_body = match &function.params {
+        AbiParameters::Json { args } if !args.is_empty() => {
+            let mut properties = Map::new();
+            let mut required = Vec::new();
+            for arg in args {
+                properties.insert(arg.name.clone(), schema_value(&arg.type_schema));
+                if !is_nullable(&arg.type_schema) {
+                    required.push(Value::from(arg.name.clone()));
+                }
+            }
+            let schema = json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+                "additionalProperties": false,
+            });
+            Some(json!({ "application/json": { "schema": schema } }))
+        }
+        AbiParameters::Borsh { args } if !args.is_empty() => {
+            Some(json!({ "application/octet-stream": {} }))
+        }
+        _ => None,
+    };
+    if let Some(content) = request_body {
+        operation.insert(
+            "requestBody".to_string(),
+            json!({ "required": true, "content": content }),
+        );
+    }
+
+    let mut response = Map::new();
+    response.insert(
+        "description".to_string(),
+        format!("Result of `{}`.", function.name).into(),
+    );
+    match &function.result {
+        Some(AbiType::Json { type_schema }) => {
+            response.insert(
+                "content".to_string(),
+                json!({ "application/json": { "schema": schema_value(type_schema) } }),
+            );
+        }
+        Some(AbiType::Borsh { .. }) => {
+            response.insert(
+                "content".to_string(),
+                json!({ "application/octet-stream": {} }),
+            );
+        }
+        None => {}
+    }
+    operation.insert("responses".to_string(), json!({ "200": response }));
+    Value::Object(operation)
+}
+
+/// Serializes `schema` into a JSON Schema 2020-12 schema object of the document, referencing
+/// root schema definitions as component schemas.
+fn schema_value(schema: &Schema) -> Value {
+    let mut value = serde_json::to_value(schema).expect("schema serialization can not fail");
+    convert_schema(&mut value, SchemaDraft::Draft2020_12);
+    reference_components(&mut value);
+    value
+}
+
+/// Rewrites references to root schema definitions in `value` into references to component
+/// schemas.
+fn reference_components(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (keyword, value) in map.iter_mut() {
+                match value {
+                    Value::String(reference) if keyword == "$ref" => {
+                        if let Some(name) = reference.strip_prefix("#/$defs/") {
+                            *reference = format!("{}{}", COMPONENTS_PREFIX, name);
+                        }
+                    }
+                    value => reference_components(value),
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(reference_components),
+        _ => {}
+    }
+}
+
+/// Whether the values of `schema` can be `null`, so that a parameter of that type can be
+/// omitted.
+fn is_nullable(schema: &Schema) -> bool {
+    let object = match schema {
+        Schema::Object(object) => object,
+        Schema::Bool(nullable) => return *nullable,
+    };
+    let null = InstanceType::Null;
+    match &object.instance_type {
+        Some(SingleOrVec::Single(ty)) if **ty == null => return true,
+        Some(SingleOrVec::Vec(types)) if types.contains(&null) => return true,
+        _ => {}
+    }
+    object.subschemas.as_deref().map_or(false, |subschemas| {
+        let variants = subschemas.any_of.iter().chain(&subschemas.one_of);
+        variants.flatten().any(is_nullable)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+
+    #[test]
+    fn test_to_openapi() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": { "name": "registry", "version": "1.2.0" },
+            "body": {
+                "functions": [
+                    {
+                        "name": "get_account",
+                        "kind": "view",
+                        "doc": " Looks up an account.\n\n Returns `null` if unknown.",
+                        "params": { "serialization_type": "json", "args": [
+                            { "name": "account_id", "type_schema": { "$ref": "#/definitions/AccountId" } },
+                            { "name": "at", "type_schema": { "type": ["integer", "null"] } }
+                        ] },
+                        "result": { "serialization_type": "json", "type_schema": {
+                            "anyOf": [{ "$ref": "#/definitions/Account" }, { "type": "null" }]
+                        } }
+                    },
+                    {
+                        "name": "get_raw",
+                        "kind": "view",
+                        "deprecated": {},
+                        "params": { "serialization_type": "borsh", "args": [
+                            { "name": "key", "type_schema": { "declaration": "u8", "definitions": {} } }
+                        ] }
+                    },
+                    {
+                        "name": "set_memo",
+                        "kind": "call",
+                        "params": { "serialization_type": "json", "args": [
+                            { "name": "memo", "type_schema": { "$ref": "#/definitions/Memo" } }
+                        ] }
+                    }
+                ],
+                "root_schema": { "definitions": {
+                    "AccountId": { "type": "string" },
+                    "Account": {
+                        "type": "object",
+                        "properties": {
+                            "id": { "$ref": "#/definitions/AccountId" },
+                            "keys": { "type": "array", "items": [{ "type": "string" }, { "type": "integer" }] }
+                        }
+                    },
+                    "Memo": { "type": "string" }
+                } }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            to_openapi(&abi),
+            json!({
+                "openapi": "3.1.0",
+                "info": { "title": "registry", "version": "1.2.0" },
+                "paths": {
+                    "/view/get_account": { "post": {
+                        "operationId": "get_account",
+                        "summary": "Looks up an account.",
+                        "description": "Looks up an account.\n\n Returns `null` if unknown.",
+                        "requestBody": { "required": true, "content": { "application/json": {
+                            "schema": {
+                                "type": "object",
+                                "properties": {
+                                    "account_id": { "$ref": "#/components/schemas/AccountId" },
+                                    "at": { "type": ["integer", "null"] }
+                                },
+                                "required": ["account_id"],
+                                "additionalProperties": false
+                            }
+                        } } },
+                        "responses": { "200": {
+                            "description": "Result of `get_account`.",
+                            "content": { "application/json": { "schema": {
+                                "anyOf": [{ "$ref": "#/components/schemas/Account" }, { "type": "null" }]
+                            } } }
+                        } }
+                    } },
+                    "/view/get_raw": { "post": {
+                        "operationId": "get_raw",
+                        "deprecated": true,
+                        "requestBody": {
+                            "required": true,
+                            "content": { "application/octet-stream": {} }
+                        },
+                        "responses": { "200": { "description": "Result of `get_raw`." } }
+                    } }
+                },
+                "components": { "schemas": {
+                    "Account": {
+                        "type": "object",
+                        "properties": {
+                            "id": { "$ref": "#/components/schemas/AccountId" },
+                            "keys": { "type": "array", "prefixItems": [{ "type": "string" }, { "type": "integer" }] }
+                        }
+                    },
+                    "AccountId": { "type": "string" }
+                } }
+            })
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Ordering guarantees of serialized ABIs.
+//!
+//! Some parts of an ABI are always serialized in a deterministic order, regardless of the policy:
+//!
+//! * object keys of the ABI models follow the declaration order of the model fields;
+//! * root schema definitions and properties of JSON schemas are sorted by name;
+//! * extra [`AbiMetadata::other`](crate::AbiMetadata::other) keys are sorted by name.
+//!
+//! The order of list items, on the other hand, is taken from the ABI value being serialized.
+//! [`Ordering`] governs whether those lists are normalized before serialization.
+
+use crate::AbiRoot;
+
+/// Policy for ordering the lists of an ABI on serialization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Ordering {
+    /// Functions are sorted by name and function modifiers are sorted in declaration order
+    /// with duplicates removed, so equivalent ABIs serialize identically.
+    Strict,
+    /// Lists keep their current order, producing minimal diffs against existing artifacts.
+    #[default]
+    PreserveInput,
+}
+
+impl Ordering {
+    /// Reorders the lists of `abi` according to the policy.
+    ///
+    /// Positional lists, like parameters and callbacks, are never reordered since their order
+    /// is part of the contract interface.
+    pub fn apply(&self, abi: &mut AbiRoot) {
+        match self {
+            Self::Strict => {
+                let functions = &mut abi.body.functions;
+                functions.sort_by(|x, y| x.name.cmp(&y.name));
+                for function in functions {
+                    function.modifiers.sort();
+                    function.modifiers.dedup();
+                }
+            }
+            Self::PreserveInput => {}
+        }
+    }
+
+    /// Whether the lists of `abi` are already ordered according to the policy, i.e. whether
+    /// [`Ordering::apply`] would leave it unchanged.
+    pub fn is_applied(&self, abi: &AbiRoot) -> bool {
+        match self {
+            Self::Strict => {
+                let functions = &abi.body.functions;
+                functions
+                    .windows(2)
+                    .all(|pair| pair[0].name <= pair[1].name)
+                    && functions
+                        .iter()
+                        .all(|function| function.modifiers.windows(2).all(|pair| pair[0] < pair[1]))
+            }
+            Self::PreserveInput => true,
+        }
+    }
+
+    /// Serializes `abi` into pretty-printed JSON with its lists ordered according to the policy.
+    pub fn to_string_pretty(&self, abi: &AbiRoot) -> String {
+        let mut abi = abi.clone();
+        self.apply(&mut abi);
+        serde_json::to_string_pretty(&abi).expect("ABI serialization can not fail")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiFunctionModifier, SCHEMA_VERSION};
+    use serde_json::json;
+
+    #[test]
+    fn test_ordering() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": { "zeta": "1", "alpha": "2", "beta": "3" },
+            "body": {
+                "functions": [
+                    { "name": "withdraw", "kind": "call", "modifiers": ["payable", "private", "payable"] },
+                    { "name": "deposit", "kind": "call" }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+
+        let preserved = Ordering::PreserveInput.to_string_pretty(&abi);
+        assert!(preserved.find("withdraw") < preserved.find("deposit"));
+        assert!(preserved.find("alpha") < preserved.find("beta"));
+        assert!(preserved.find("beta") < preserved.find("zeta"));
+
+        let strict = Ordering::Strict.to_string_pretty(&abi);
+        assert!(strict.find("deposit") < strict.find("withdraw"));
+        let mut abi = abi;
+        Ordering::Strict.apply(&mut abi);
+        assert_eq!(
+            abi.body.functions[1].modifiers,
+            [AbiFunctionModifier::Private, AbiFunctionModifier::Payable]
+        );
+    }
+}
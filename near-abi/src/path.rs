@@ -0,0 +1,285 @@
+//! Addresses of ABI elements.
+//!
+//! An [`AbiPath`] refers to a function, one of its parameters, callbacks or its result, a root
+//! schema definition or a constant by name rather than by position, so that addresses survive
+//! reordering. Paths are written as JSON Pointers relative to the ABI body, with `~` and `/`
+//! escaped in names, e.g. `functions/ft_transfer/params/amount`.
+
+use crate::walk::TypeSite;
+use crate::{
+    callback_name, AbiBorshParameter, AbiCallback, AbiConstant, AbiFunction, AbiJsonParameter,
+    AbiParameters, AbiRoot, AbiType,
+};
+use schemars::schema::Schema;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Address of an element of an ABI.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AbiPath {
+    /// `functions/<function>`
+    Function(String),
+    /// `functions/<function>/params/<param>`
+    Param { function: String, param: String },
+    /// `functions/<function>/callbacks/<callback>`, unnamed callbacks being named
+    /// `callback_<index>` after their position.
+    Callback { function: String, callback: String },
+    /// `functions/<function>/callbacks_vec`
+    CallbacksVec(String),
+    /// `functions/<function>/result`
+    Result(String),
+    /// `definitions/<name>`
+    Definition(String),
+    /// `constants/<name>`
+    Constant(String),
+}
+
+/// An element of an ABI, as resolved by [`AbiPath::resolve`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AbiElement<'a> {
+    Function(&'a AbiFunction),
+    JsonParam(&'a AbiJsonParameter),
+    BorshParam(&'a AbiBorshParameter),
+    Callback(&'a AbiCallback),
+    /// Type of the vararg callbacks or of the result of a function.
+    Type(&'a AbiType),
+    Definition(&'a Schema),
+    Constant(&'a AbiConstant),
+}
+
+impl AbiPath {
+    /// Name of the function the path points into, if any.
+    pub fn function(&self) -> Option<&str> {
+        match self {
+            Self::Function(function)
+            | Self::Param { function, .. }
+            | Self::Callback { function, .. }
+            | Self::CallbacksVec(function)
+            | Self::Result(function) => Some(function),
+            Self::Definition(_) | Self::Constant(_) => None,
+        }
+    }
+
+    /// Looks up the element the path points to.
+    pub fn resolve<'a>(&self, abi: &'a AbiRoot) -> Option<AbiElement<'a>> {
+        let body = &abi.body;
+        let function = |name: &str| body.functions.iter().find(|f| f.name == name);
+        match self {
+            Self::Function(name) => function(name).map(AbiElement::Function),
+            Self::Param {
+                function: name,
+                param,
+            } => match &function(name)?.params {
+                AbiParameters::Json { args } => args
+                    .iter()
+                    .find(|arg| arg.name == *param)
+                    .map(AbiElement::JsonParam),
+                AbiParameters::Borsh { args } => args
+                    .iter()
+                    .find(|arg| arg.name == *param)
+                    .map(AbiElement::BorshParam),
+            },
+            Self::Callback {
+                function: name,
+                callback,
+            } => function(name)?
+                .callbacks
+                .iter()
+                .enumerate()
+                .find(|(i, c)| match c.name.as_str() {
+                    "" => callback_name(*i) == *callback,
+                    name => name == callback,
+                })
+                .map(|(_, c)| AbiElement::Callback(c)),
+            Self::CallbacksVec(name) => {
+                function(name)?.callbacks_vec.as_ref().map(AbiElement::Type)
+            }
+            Self::Result(name) => function(name)?.result.as_ref().map(AbiElement::Type),
+            Self::Definition(name) => body.definition(name).map(AbiElement::Definition),
+            Self::Constant(name) => body
+                .constants
+                .iter()
+                .find(|constant| constant.name == *name)
+                .map(AbiElement::Constant),
+        }
+    }
+
+    /// Path of a type schema of the signature of `function`.
+    pub(crate) fn from_site(function: &AbiFunction, site: &TypeSite) -> Self {
+        let name = function.name.clone();
+        match site {
+            TypeSite::Param(param) => Self::Param {
+                function: name,
+                param: param.clone(),
+            },
+            TypeSite::Callback(i) => Self::Callback {
+                function: name,
+                callback: match function.callbacks[*i].name.as_str() {
+                    "" => callback_name(*i),
+                    name => name.to_string(),
+                },
+            },
+            TypeSite::CallbacksVec => Self::CallbacksVec(name),
+            TypeSite::Result => Self::Result(name),
+        }
+    }
+}
+
+fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+impl fmt::Display for AbiPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Function(function) => write!(f, "functions/{}", escape(function)),
+            Self::Param { function, param } => {
+                write!(f, "functions/{}/params/{}", escape(function), escape(param))
+            }
+            Self::Callback { function, callback } => write!(
+                f,
+                "functions/{}/callbacks/{}",
+                escape(function),
+                escape(callback)
+            ),
+            Self::CallbacksVec(function) => {
+                write!(f, "functions/{}/callbacks_vec", escape(function))
+            }
+            Self::Result(function) => write!(f, "functions/{}/result", escape(function)),
+            Self::Definition(name) => write!(f, "definitions/{}", escape(name)),
+            Self::Constant(name) => write!(f, "constants/{}", escape(name)),
+        }
+    }
+}
+
+impl FromStr for AbiPath {
+    type Err = ParsePathError;
+
+    /// Parses a path, with or without the leading `/` of JSON Pointers.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let segments: Vec<String> = s
+            .strip_prefix('/')
+            .unwrap_or(s)
+            .split('/')
+            .map(unescape)
+            .collect();
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        let path = match segments[..] {
+            ["functions", function] => Self::Function(function.to_string()),
+            ["functions", function, "params", param] => Self::Param {
+                function: function.to_string(),
+                param: param.to_string(),
+            },
+            ["functions", function, "callbacks", callback] => Self::Callback {
+                function: function.to_string(),
+                callback: callback.to_string(),
+            },
+            ["functions", function, "callbacks_vec"] => Self::CallbacksVec(function.to_string()),
+            ["functions", function, "result"] => Self::Result(function.to_string()),
+            ["definitions", name] => Self::Definition(name.to_string()),
+            ["constants", name] => Self::Constant(name.to_string()),
+            _ => return Err(ParsePathError(s.to_string())),
+        };
+        Ok(path)
+    }
+}
+
+impl Serialize for AbiPath {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AbiPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Error returned when a string is not a valid [`AbiPath`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsePathError(String);
+
+impl std::error::Error for ParsePathError {}
+impl fmt::Display for ParsePathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` is not the path of an ABI element", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_abi_path() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [{
+                    "name": "ft_transfer",
+                    "kind": "call",
+                    "params": {
+                        "serialization_type": "json",
+                        "args": [{ "name": "amount", "type_schema": { "$ref": "#/definitions/U128" } }]
+                    },
+                    "callbacks": [{ "serialization_type": "json", "type_schema": { "type": "string" } }]
+                }],
+                "root_schema": { "definitions": { "U128": { "type": "string" }, "a/b": true } }
+            }
+        }))
+        .unwrap();
+
+        let path: AbiPath = "functions/ft_transfer/params/amount".parse().unwrap();
+        assert_eq!(
+            path,
+            AbiPath::Param {
+                function: "ft_transfer".to_string(),
+                param: "amount".to_string()
+            }
+        );
+        assert!(matches!(
+            path.resolve(&abi),
+            Some(AbiElement::JsonParam(param)) if param.name == "amount"
+        ));
+        assert_eq!(path.function(), Some("ft_transfer"));
+
+        let path = AbiPath::Definition("a/b".to_string());
+        assert_eq!(
+            serde_json::to_value(&path).unwrap(),
+            json!("definitions/a~1b")
+        );
+        assert_eq!("/definitions/a~1b".parse(), Ok(path.clone()));
+        assert_eq!(
+            path.resolve(&abi),
+            Some(AbiElement::Definition(&Schema::Bool(true)))
+        );
+
+        let path: AbiPath = "functions/ft_transfer/callbacks/callback_0"
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            path.resolve(&abi),
+            Some(AbiElement::Callback(callback)) if callback.name.is_empty()
+        ));
+
+        assert_eq!(
+            AbiPath::Result("ft_transfer".to_string()).resolve(&abi),
+            None
+        );
+        assert_eq!(
+            "functions/ft_transfer/kind".parse::<AbiPath>(),
+            Err(ParsePathError("functions/ft_transfer/kind".to_string()))
+        );
+    }
+}
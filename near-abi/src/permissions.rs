@@ -0,0 +1,166 @@
+//! Permission matrices for security reviews.
+//!
+//! [`PermissionMatrix`] tabulates who can call every function of a contract and how, derived
+//! entirely from the ABI. It serializes to JSON and renders as CSV for spreadsheets.
+
+use crate::simulation::{AttachedDeposit, FunctionSimulation, Predecessor};
+use crate::{AbiAccess, AbiFunctionKind, AbiRoot};
+use serde::Serialize;
+
+/// Permissions of all functions of a contract, in declaration order.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PermissionMatrix {
+    pub rows: Vec<PermissionRow>,
+}
+
+/// Permissions of a single function.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct PermissionRow {
+    pub function: String,
+    pub kind: AbiFunctionKind,
+    pub payable: bool,
+    pub private: bool,
+    pub init: bool,
+    /// Whether the function upgrades the contract code or migrates its state.
+    pub upgrade: bool,
+    /// Restriction of the callers enforced by the contract logic.
+    pub access: Option<AbiAccess>,
+    pub attached_deposit: AttachedDeposit,
+    /// Measured maximum gas usage of the function.
+    pub max_gas: Option<u64>,
+}
+
+/// Columns of the CSV rendering.
+const CSV_HEADER: &str =
+    "function,kind,payable,private,init,upgrade,access,attached_deposit,max_gas";
+
+impl PermissionMatrix {
+    /// Builds the permission matrix of a contract from its ABI.
+    pub fn new(abi: &AbiRoot) -> Self {
+        let rows = abi
+            .body
+            .functions
+            .iter()
+            .map(|function| {
+                let simulation = FunctionSimulation::new(function);
+                PermissionRow {
+                    function: function.name.clone(),
+                    kind: function.kind.clone(),
+                    payable: simulation.attached_deposit == AttachedDeposit::Allowed,
+                    private: simulation.predecessor == Predecessor::CurrentAccount,
+                    init: simulation.init,
+                    upgrade: function.is_upgrade_entrypoint(),
+                    access: simulation.access,
+                    attached_deposit: simulation.attached_deposit,
+                    max_gas: function.gas_estimate.as_ref().map(|gas| gas.max),
+                }
+            })
+            .collect();
+        Self { rows }
+    }
+
+    /// Renders the matrix as CSV, with a header row. Access restrictions are written as `owner`,
+    /// `dao`, `accounts:<account>;<account>` or `role:<role>`.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", CSV_HEADER);
+        for row in &self.rows {
+            let kind = match row.kind {
+                AbiFunctionKind::View => "view",
+                AbiFunctionKind::Call => "call",
+            };
+            let access = match &row.access {
+                None => String::new(),
+                Some(AbiAccess::Owner) => "owner".to_string(),
+                Some(AbiAccess::Dao) => "dao".to_string(),
+                Some(AbiAccess::Accounts { accounts }) => {
+                    format!("accounts:{}", accounts.join(";"))
+                }
+                Some(AbiAccess::Role { role }) => format!("role:{}", role),
+            };
+            let attached_deposit = match row.attached_deposit {
+                AttachedDeposit::Allowed => "allowed",
+                AttachedDeposit::Forbidden => "forbidden",
+                AttachedDeposit::Unsupported => "unsupported",
+            };
+            let fields = [
+                csv_field(&row.function),
+                kind.to_string(),
+                row.payable.to_string(),
+                row.private.to_string(),
+                row.init.to_string(),
+                row.upgrade.to_string(),
+                csv_field(&access),
+                attached_deposit.to_string(),
+                row.max_gas.map(|gas| gas.to_string()).unwrap_or_default(),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, a quote or a line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_permission_matrix() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    { "name": "new", "kind": "call", "modifiers": ["init"] },
+                    { "name": "get", "kind": "view" },
+                    {
+                        "name": "mint",
+                        "kind": "call",
+                        "modifiers": ["payable"],
+                        "access": { "type": "accounts", "accounts": ["a.near", "b,c.near"] },
+                        "gas_estimate": { "min": 1, "median": 2, "max": 3 }
+                    },
+                    { "name": "upgrade", "kind": "call", "modifiers": ["private", "upgrade"] }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+
+        let matrix = PermissionMatrix::new(&abi);
+        assert_eq!(
+            matrix.to_csv(),
+            "function,kind,payable,private,init,upgrade,access,attached_deposit,max_gas
+new,call,false,false,true,false,,forbidden,
+get,view,false,false,false,false,,unsupported,
+mint,call,true,false,false,false,\"accounts:a.near;b,c.near\",allowed,3
+upgrade,call,false,true,false,true,,forbidden,
+"
+        );
+        assert_eq!(
+            serde_json::to_value(&matrix.rows[1]).unwrap(),
+            json!({
+                "function": "get",
+                "kind": "view",
+                "payable": false,
+                "private": false,
+                "init": false,
+                "upgrade": false,
+                "access": null,
+                "attached_deposit": "unsupported",
+                "max_gas": null
+            })
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use super::{
-    ensure_current_version, AbiBody, AbiFunction, AbiMetadata, AbiRoot, RootSchema, SCHEMA_VERSION,
+    ensure_current_version, AbiBody, AbiConstant, AbiExtensions, AbiExternalContract, AbiFunction,
+    AbiMetadata, AbiRoot, RootSchema, SCHEMA_VERSION,
 };
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -21,6 +22,9 @@ impl ChunkedAbiEntry {
             body: AbiBody {
                 functions,
                 root_schema,
+                state: None,
+                constants: Vec::new(),
+                external_contracts: Vec::new(),
             },
         }
     }
@@ -30,6 +34,9 @@ impl ChunkedAbiEntry {
     ) -> Result<ChunkedAbiEntry, AbiCombineError> {
         let mut schema_version = None;
         let mut functions = Vec::<AbiFunction>::new();
+        let mut state = None;
+        let mut constants = Vec::<AbiConstant>::new();
+        let mut external_contracts = Vec::<AbiExternalContract>::new();
 
         let mut gen = schemars::gen::SchemaGenerator::default();
         let definitions = gen.definitions_mut();
@@ -52,6 +59,60 @@ impl ChunkedAbiEntry {
 
             // Update resulting function list
             functions.extend(entry.body.functions);
+
+            // Only one entry is expected to describe the contract state, entries combined more
+            // than once describe it identically
+            match (&state, entry.body.state) {
+                (None, entry_state) => state = entry_state,
+                (Some(existing), Some(entry_state)) if *existing != entry_state => {
+                    return Err(AbiCombineError {
+                        kind: AbiCombineErrorKind::StateConflict {
+                            roots: vec![
+                                existing.root.display_name(),
+                                entry_state.root.display_name(),
+                            ],
+                        },
+                    });
+                }
+                _ => {}
+            }
+
+            // Identical constants are merged, the same name can not hold different values
+            for constant in entry.body.constants {
+                match constants
+                    .iter()
+                    .find(|existing| existing.name == constant.name)
+                {
+                    None => constants.push(constant),
+                    Some(existing) if *existing == constant => {}
+                    Some(existing) => {
+                        return Err(AbiCombineError {
+                            kind: AbiCombineErrorKind::ConstantConflict {
+                                name: constant.name,
+                                values: vec![existing.value.clone(), constant.value],
+                            },
+                        });
+                    }
+                }
+            }
+
+            // Interfaces called from several entries are merged when declared identically
+            for contract in entry.body.external_contracts {
+                match external_contracts
+                    .iter()
+                    .find(|existing| existing.name == contract.name)
+                {
+                    None => external_contracts.push(contract),
+                    Some(existing) if *existing == contract => {}
+                    Some(_) => {
+                        return Err(AbiCombineError {
+                            kind: AbiCombineErrorKind::ExternalContractConflict {
+                                name: contract.name,
+                            },
+                        });
+                    }
+                }
+            }
         }
 
         if !unexpected_versions.is_empty() {
@@ -65,12 +126,34 @@ impl ChunkedAbiEntry {
 
         // Sort the function list for readability
         functions.sort_by(|x, y| x.name.cmp(&y.name));
+        // Entries may be combined more than once, identical declarations are merged
+        functions.dedup();
+
+        // Namespaces are logical groups, method names have to be unique across all of them
+        for pair in functions.windows(2) {
+            if pair[0].name == pair[1].name {
+                let name = &pair[0].name;
+                return Err(AbiCombineError {
+                    kind: AbiCombineErrorKind::FunctionNameConflict {
+                        name: name.clone(),
+                        namespaces: functions
+                            .iter()
+                            .filter(|f| f.name == *name)
+                            .map(|f| f.namespace.clone())
+                            .collect(),
+                    },
+                });
+            }
+        }
 
         Ok(ChunkedAbiEntry {
             schema_version: schema_version.unwrap(),
             body: AbiBody {
                 functions,
                 root_schema: gen.into_root_schema_for::<String>(),
+                state,
+                constants,
+                external_contracts,
             },
         })
     }
@@ -80,6 +163,8 @@ impl ChunkedAbiEntry {
             schema_version: self.schema_version,
             metadata,
             body: self.body,
+            test_vectors: Vec::new(),
+            extensions: AbiExtensions::default(),
         }
     }
 }
@@ -110,6 +195,20 @@ pub enum AbiCombineErrorKind {
         expected: String,
         found: Vec<String>,
     },
+    FunctionNameConflict {
+        name: String,
+        namespaces: Vec<Option<String>>,
+    },
+    StateConflict {
+        roots: Vec<String>,
+    },
+    ConstantConflict {
+        name: String,
+        values: Vec<serde_json::Value>,
+    },
+    ExternalContractConflict {
+        name: String,
+    },
 }
 
 impl fmt::Display for AbiCombineErrorKind {
@@ -121,6 +220,148 @@ impl fmt::Display for AbiCombineErrorKind {
                 found.join(", ")
             )
             .fmt(f),
+            Self::FunctionNameConflict { name, namespaces } => format!(
+                "function `{}` is declared more than once, in namespaces {}",
+                name,
+                namespaces
+                    .iter()
+                    .map(|namespace| namespace.as_deref().unwrap_or("<none>"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .fmt(f),
+            Self::StateConflict { roots } => format!(
+                "contract state is declared differently by several entries, with root types {}",
+                roots.join(", ")
+            )
+            .fmt(f),
+            Self::ConstantConflict { name, values } => format!(
+                "constant `{}` is declared differently by several entries, with values {}",
+                name,
+                values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+            .fmt(f),
+            Self::ExternalContractConflict { name } => format!(
+                "external contract `{}` is declared differently by several entries",
+                name
+            )
+            .fmt(f),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiFunctionKind, AbiState, AbiType};
+    use schemars::schema::Schema;
+
+    #[test]
+    fn test_combine_function_name_conflict() {
+        let entry = |namespace: Option<&str>| {
+            let function = AbiFunction {
+                namespace: namespace.map(str::to_string),
+                ..AbiFunction::new("set_owner", AbiFunctionKind::Call)
+            };
+            ChunkedAbiEntry::new(vec![function], RootSchema::default())
+        };
+
+        let combined = ChunkedAbiEntry::combine([entry(None), entry(None)]).unwrap();
+        assert_eq!(combined.body.functions.len(), 1);
+
+        let err = ChunkedAbiEntry::combine([entry(Some("admin")), entry(None)]).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &AbiCombineErrorKind::FunctionNameConflict {
+                name: "set_owner".to_string(),
+                namespaces: vec![Some("admin".to_string()), None],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "function `set_owner` is declared more than once, in namespaces admin, <none>"
+        );
+    }
+
+    #[test]
+    fn test_combine_state_conflict() {
+        let entry = |state: Option<AbiType>| {
+            let mut entry = ChunkedAbiEntry::new(Vec::new(), RootSchema::default());
+            entry.body.state = state.map(|root| AbiState {
+                root,
+                collections: Vec::new(),
+            });
+            entry
+        };
+        let state = || Some(AbiType::borsh_of::<u64>());
+
+        let combined = ChunkedAbiEntry::combine([entry(state()), entry(None), entry(state())]);
+        assert_eq!(combined.unwrap().body.state, entry(state()).body.state);
+
+        let err =
+            ChunkedAbiEntry::combine([entry(state()), entry(Some(AbiType::borsh_of::<u8>()))])
+                .unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &AbiCombineErrorKind::StateConflict {
+                roots: vec!["u64".to_string(), "u8".to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn test_combine_constant_conflict() {
+        let entry = |decimals: u8| {
+            let mut entry = ChunkedAbiEntry::new(Vec::new(), RootSchema::default());
+            entry.body.constants.push(AbiConstant {
+                name: "DECIMALS".to_string(),
+                doc: None,
+                type_schema: Schema::Bool(true),
+                value: decimals.into(),
+            });
+            entry
+        };
+
+        let combined = ChunkedAbiEntry::combine([entry(24), entry(24)]).unwrap();
+        assert_eq!(combined.body.constants, entry(24).body.constants);
+
+        let err = ChunkedAbiEntry::combine([entry(24), entry(18)]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "constant `DECIMALS` is declared differently by several entries, with values 24, 18"
+        );
+    }
+
+    #[test]
+    fn test_combine_external_contract_conflict() {
+        let entry = |functions: &[&str]| {
+            let mut entry = ChunkedAbiEntry::new(Vec::new(), RootSchema::default());
+            entry.body.external_contracts.push(AbiExternalContract {
+                name: "ext_ft_core".to_string(),
+                doc: None,
+                functions: functions
+                    .iter()
+                    .map(|name| AbiFunction::new(*name, AbiFunctionKind::Call))
+                    .collect(),
+            });
+            entry
+        };
+
+        let combined =
+            ChunkedAbiEntry::combine([entry(&["ft_transfer"]), entry(&["ft_transfer"])]).unwrap();
+        assert_eq!(combined.body.external_contracts.len(), 1);
+
+        let err = ChunkedAbiEntry::combine([entry(&["ft_transfer"]), entry(&["ft_transfer_call"])])
+            .unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &AbiCombineErrorKind::ExternalContractConflict {
+                name: "ext_ft_core".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,376 @@
+//! Assembly of ABI bodies from the output of several schema generators.
+//!
+//! Crates of a workspace may generate their schemas with different `schemars` settings: with
+//! `inline_subschemas` on or off, or with definitions referenced under `#/$defs/` rather than
+//! `#/definitions/`. [`reconcile`] merges such bodies into a single body whose references all
+//! point into `#/definitions/`.
+
+use crate::transform::DEFINITIONS_PREFIX;
+use crate::walk::{function_json_schemas_mut, referenced_definitions, visit_mut};
+use crate::{AbiBody, AbiState, AbiType};
+use schemars::schema::{RootSchema, Schema};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Result of [`reconcile`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reconciliation {
+    pub body: AbiBody,
+    /// Definitions renamed because a previous body defined a different type under the same name.
+    pub renamed: Vec<RenamedDefinition>,
+}
+
+/// A definition renamed while reconciling bodies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenamedDefinition {
+    /// Position of the body the definition comes from.
+    pub body: usize,
+    pub old: String,
+    pub new: String,
+}
+
+/// Merges bodies generated independently into a single body.
+///
+/// References into definitions are rewritten to the `#/definitions/<name>` form, whatever path
+/// the generator used: a reference of the form `#/<path>/<name>` is rewritten when the body
+/// defines `<name>`. Inlined schemas are kept as is. Definitions shared by several bodies are
+/// merged when they are identical; a definition conflicting with a definition of a previous body
+/// is renamed with a numeric suffix, as are the definitions referencing it.
+///
+/// Functions, constants and external contracts are concatenated in order. The root schema object
+/// is taken from the first body. The state is taken from the first body declaring it; bodies
+/// declaring a different state are rejected.
+pub fn reconcile(
+    bodies: impl IntoIterator<Item = AbiBody>,
+) -> Result<Reconciliation, StateConflictError> {
+    let mut reconciled = AbiBody {
+        functions: Vec::new(),
+        root_schema: RootSchema::default(),
+        state: None,
+        constants: Vec::new(),
+        external_contracts: Vec::new(),
+    };
+    let mut renamed = Vec::new();
+    for (index, mut body) in bodies.into_iter().enumerate() {
+        let defined: BTreeSet<String> = body.root_schema.definitions.keys().cloned().collect();
+        rewrite_references(&mut body, |name| {
+            defined.contains(name).then(|| name.to_string())
+        });
+
+        // New names must be free in both the merged and the incoming definitions, or a renamed
+        // definition would replace another one.
+        let mut taken: BTreeSet<String> = reconciled
+            .root_schema
+            .definitions
+            .keys()
+            .chain(body.root_schema.definitions.keys())
+            .cloned()
+            .collect();
+        let renames = conflicting_definitions(&reconciled.root_schema, &body.root_schema)
+            .into_iter()
+            .map(|name| {
+                let new = (2..)
+                    .map(|i| format!("{}{}", name, i))
+                    .find(|new| !taken.contains(new))
+                    .unwrap();
+                taken.insert(new.clone());
+                (name, new)
+            })
+            .collect::<BTreeMap<_, _>>();
+        if !renames.is_empty() {
+            rewrite_references(&mut body, |name| renames.get(name).cloned());
+            let schemas: Vec<(String, Schema)> = renames
+                .iter()
+                .map(|(old, new)| {
+                    let schema = body.root_schema.definitions.remove(old).unwrap();
+                    (new.clone(), schema)
+                })
+                .collect();
+            body.root_schema.definitions.extend(schemas);
+            renamed.extend(renames.into_iter().map(|(old, new)| RenamedDefinition {
+                body: index,
+                old,
+                new,
+            }));
+        }
+
+        if index == 0 {
+            reconciled.root_schema.meta_schema = body.root_schema.meta_schema;
+            reconciled.root_schema.schema = body.root_schema.schema;
+        }
+        reconciled
+            .root_schema
+            .definitions
+            .extend(body.root_schema.definitions);
+        reconciled.functions.extend(body.functions);
+        match (&reconciled.state, body.state) {
+            (None, state) => reconciled.state = state,
+            (Some(state), Some(other)) if *state != other => {
+                return Err(StateConflictError { body: index })
+            }
+            _ => {}
+        }
+        reconciled.constants.extend(body.constants);
+        reconciled
+            .external_contracts
+            .extend(body.external_contracts);
+    }
+    Ok(Reconciliation {
+        body: reconciled,
+        renamed,
+    })
+}
+
+/// Error returned by [`reconcile`] when bodies declare different contract states.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateConflictError {
+    body: usize,
+}
+
+impl StateConflictError {
+    /// Position of the first body declaring a state different from a previous body.
+    pub fn body(&self) -> usize {
+        self.body
+    }
+}
+
+impl std::error::Error for StateConflictError {}
+impl fmt::Display for StateConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "body {} declares a contract state different from a previous body",
+            self.body
+        )
+    }
+}
+
+/// Definitions of `incoming` that can not be merged into `merged`: those defined differently in
+/// `merged`, and those referencing them.
+fn conflicting_definitions(merged: &RootSchema, incoming: &RootSchema) -> BTreeSet<String> {
+    let mut conflicting: BTreeSet<String> = incoming
+        .definitions
+        .iter()
+        .filter(|(name, schema)| {
+            merged
+                .definitions
+                .get(*name)
+                .map_or(false, |existing| existing != *schema)
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+    loop {
+        let dependents: Vec<String> = incoming
+            .definitions
+            .iter()
+            .filter(|(name, schema)| {
+                merged.definitions.contains_key(*name)
+                    && !conflicting.contains(*name)
+                    && !referenced_definitions(schema).is_disjoint(&conflicting)
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        if dependents.is_empty() {
+            return conflicting;
+        }
+        conflicting.extend(dependents);
+    }
+}
+
+/// Rewrites the references of all schemas of `body` pointing to a definition named `name` for
+/// which `rename` returns a new name into `#/definitions/<new name>`.
+fn rewrite_references(body: &mut AbiBody, rename: impl Fn(&str) -> Option<String>) {
+    let mut rewrite = |schema: &mut Schema| {
+        if let Schema::Object(object) = schema {
+            let new_name = object
+                .reference
+                .as_deref()
+                .and_then(|reference| reference.strip_prefix("#/"))
+                .map(|path| path.rsplit('/').next().unwrap_or(path))
+                .map(|name| name.replace("~1", "/").replace("~0", "~"))
+                .and_then(|name| rename(&name));
+            if let Some(new_name) = new_name {
+                object.reference = Some(format!("{}{}", DEFINITIONS_PREFIX, new_name));
+            }
+        }
+    };
+    let mut schemas: Vec<&mut Schema> = body.root_schema.definitions.values_mut().collect();
+    for function in body.functions.iter_mut().chain(
+        body.external_contracts
+            .iter_mut()
+            .flat_map(|c| &mut c.functions),
+    ) {
+        schemas.extend(
+            function_json_schemas_mut(function)
+                .into_iter()
+                .map(|(_, schema)| schema),
+        );
+    }
+    if let Some(AbiState {
+        root: AbiType::Json { type_schema },
+        ..
+    }) = &mut body.state
+    {
+        schemas.push(type_schema);
+    }
+    schemas.extend(body.constants.iter_mut().map(|c| &mut c.type_schema));
+    for schema in schemas {
+        visit_mut(schema, &mut rewrite);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_reconcile() {
+        let token: AbiBody = serde_json::from_value(json!({
+            "functions": [{
+                "name": "ft_transfer",
+                "kind": "call",
+                "params": {
+                    "serialization_type": "json",
+                    "args": [
+                        { "name": "amount", "type_schema": { "$ref": "#/definitions/U128" } },
+                        { "name": "config", "type_schema": { "$ref": "#/definitions/Config" } }
+                    ]
+                }
+            }],
+            "root_schema": {
+                "definitions": {
+                    "U128": { "type": "string" },
+                    "Config": { "type": "object", "properties": { "fee": { "$ref": "#/definitions/U128" } } }
+                }
+            }
+        }))
+        .unwrap();
+        // Generated with draft 2019-09 settings and a conflicting `Config`.
+        let staking: AbiBody = serde_json::from_value(json!({
+            "functions": [
+                {
+                    "name": "stake",
+                    "kind": "call",
+                    "params": {
+                        "serialization_type": "json",
+                        "args": [
+                            { "name": "amount", "type_schema": { "$ref": "#/$defs/U128" } },
+                            { "name": "pool", "type_schema": { "$ref": "#/$defs/Pool" } }
+                        ]
+                    }
+                },
+                {
+                    "name": "get_stake",
+                    "kind": "view",
+                    "result": {
+                        "serialization_type": "json",
+                        "type_schema": { "type": "object", "properties": { "amount": { "type": "string" } } }
+                    }
+                }
+            ],
+            "root_schema": {
+                "$defs": {
+                    "U128": { "type": "string" },
+                    "Config": { "type": "object", "properties": { "epochs": { "type": "integer" } } },
+                    "Pool": { "type": "object", "properties": { "config": { "$ref": "#/$defs/Config" } } }
+                }
+            }
+        }))
+        .unwrap();
+
+        let Reconciliation { body, renamed } = reconcile([token, staking]).unwrap();
+        assert_eq!(
+            renamed,
+            [RenamedDefinition {
+                body: 1,
+                old: "Config".to_string(),
+                new: "Config2".to_string(),
+            }]
+        );
+        assert_eq!(
+            body.root_schema.definitions.keys().collect::<Vec<_>>(),
+            ["Config", "Config2", "Pool", "U128"]
+        );
+        assert_eq!(
+            serde_json::to_value(body.definition("Pool")).unwrap(),
+            json!({ "type": "object", "properties": { "config": { "$ref": "#/definitions/Config2" } } })
+        );
+        let stake = serde_json::to_value(&body.functions[1].params).unwrap();
+        assert_eq!(
+            stake["args"][0]["type_schema"],
+            json!({ "$ref": "#/definitions/U128" })
+        );
+        assert_eq!(body.functions.len(), 3);
+    }
+
+    #[test]
+    fn test_reconcile_avoids_incoming_names() {
+        let body = |definitions: serde_json::Value| -> AbiBody {
+            let args: Vec<_> = definitions
+                .as_object()
+                .unwrap()
+                .keys()
+                .map(|name| json!({ "name": name, "type_schema": { "$ref": format!("#/definitions/{}", name) } }))
+                .collect();
+            serde_json::from_value(json!({
+                "functions": [{
+                    "name": "f",
+                    "kind": "call",
+                    "params": { "serialization_type": "json", "args": args }
+                }],
+                "root_schema": { "definitions": definitions }
+            }))
+            .unwrap()
+        };
+        let first = body(json!({ "Foo": { "type": "string" } }));
+        let second = body(json!({ "Foo": { "type": "integer" }, "Foo2": { "type": "boolean" } }));
+
+        let Reconciliation { body, renamed } = reconcile([first, second]).unwrap();
+        assert_eq!(
+            renamed,
+            [RenamedDefinition {
+                body: 1,
+                old: "Foo".to_string(),
+                new: "Foo3".to_string(),
+            }]
+        );
+        assert_eq!(
+            serde_json::to_value(&body.root_schema.definitions).unwrap(),
+            json!({
+                "Foo": { "type": "string" },
+                "Foo2": { "type": "boolean" },
+                "Foo3": { "type": "integer" }
+            })
+        );
+        let params = serde_json::to_value(&body.functions[1].params).unwrap();
+        assert_eq!(
+            params["args"],
+            json!([
+                { "name": "Foo", "type_schema": { "$ref": "#/definitions/Foo3" } },
+                { "name": "Foo2", "type_schema": { "$ref": "#/definitions/Foo2" } }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_reconcile_state_conflict() {
+        let body = |state: &str| -> AbiBody {
+            serde_json::from_value(json!({
+                "functions": [],
+                "root_schema": { "$defs": { "State": { "type": state } } },
+                "state": {
+                    "root": { "serialization_type": "json", "type_schema": { "$ref": "#/$defs/State" } }
+                }
+            }))
+            .unwrap()
+        };
+        let Reconciliation {
+            body: reconciled, ..
+        } = reconcile([body("object"), body("object")]).unwrap();
+        assert!(reconciled.state.is_some());
+
+        let err = reconcile([body("object"), body("object"), body("string")]).unwrap_err();
+        assert_eq!(err.body(), 2);
+    }
+}
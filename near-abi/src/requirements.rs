@@ -0,0 +1,127 @@
+//! Compatibility of contracts with networks.
+//!
+//! A contract relying on recent host functions (e.g. yield/resume) fails at runtime on networks
+//! running an older protocol version. [`AbiRequirements::check`] lets deployment tooling refuse
+//! such deployments upfront.
+
+use crate::AbiRequirements;
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// What a network offers to contracts, as known to the deployment tool.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Network {
+    /// Protocol version the network runs.
+    pub protocol_version: u32,
+    /// Host functions available on the network, if known.
+    pub host_functions: Option<BTreeSet<String>>,
+}
+
+impl Network {
+    pub fn new(protocol_version: u32) -> Self {
+        Self {
+            protocol_version,
+            host_functions: None,
+        }
+    }
+}
+
+pub(crate) fn check(
+    requirements: &AbiRequirements,
+    network: &Network,
+) -> Result<(), RequirementsError> {
+    if let Some(required) = requirements.min_protocol_version {
+        if network.protocol_version < required {
+            return Err(RequirementsError::ProtocolVersionTooOld {
+                required,
+                found: network.protocol_version,
+            });
+        }
+    }
+    if let Some(available) = &network.host_functions {
+        let missing: Vec<String> = requirements
+            .host_functions
+            .iter()
+            .filter(|function| !available.contains(*function))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            return Err(RequirementsError::MissingHostFunctions(missing));
+        }
+    }
+    Ok(())
+}
+
+/// Error returned when a network does not meet the requirements of a contract. The SDK version
+/// is not a property of the network and is never checked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RequirementsError {
+    /// The network runs an older protocol version than required.
+    ProtocolVersionTooOld { required: u32, found: u32 },
+    /// Host functions required by the contract that the network does not provide.
+    MissingHostFunctions(Vec<String>),
+}
+
+impl std::error::Error for RequirementsError {}
+impl fmt::Display for RequirementsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ProtocolVersionTooOld { required, found } => write!(
+                f,
+                "the contract requires protocol version {} or newer, but the network runs version {}",
+                required, found
+            ),
+            Self::MissingHostFunctions(functions) => write!(
+                f,
+                "the contract requires host functions the network does not provide: {}",
+                functions.join(", ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AbiMetadata;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_requirements() {
+        let metadata: AbiMetadata = serde_json::from_value(json!({
+            "requirements": {
+                "min_protocol_version": 67,
+                "min_sdk_version": "5.1.0",
+                "host_functions": ["promise_yield_create", "promise_yield_resume"]
+            }
+        }))
+        .unwrap();
+        let requirements = &metadata.requirements;
+
+        assert_eq!(requirements.check(&Network::new(73)), Ok(()));
+        let err = requirements.check(&Network::new(64)).unwrap_err();
+        assert_eq!(
+            err,
+            RequirementsError::ProtocolVersionTooOld {
+                required: 67,
+                found: 64
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "the contract requires protocol version 67 or newer, but the network runs version 64"
+        );
+
+        let network = Network {
+            host_functions: Some(["promise_yield_create".to_string()].into_iter().collect()),
+            ..Network::new(73)
+        };
+        assert_eq!(
+            requirements.check(&network),
+            Err(RequirementsError::MissingHostFunctions(vec![
+                "promise_yield_resume".to_string()
+            ]))
+        );
+        assert!(AbiMetadata::default().requirements.is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+//! Compact function identifiers.
+//!
+//! An [`AbiSelector`] is the first 4 bytes of the SHA-256 hash of the canonical signature of a
+//! function (see [`AbiFunction::canonical_signature`]), written in hex (e.g. `0x1a2b3c4d`). Tools
+//! use it to refer to functions compactly, e.g. in logs or in indexes spanning many contracts.
+
+use crate::wasm_hash::sha256;
+use crate::{AbiFunction, AbiParameters, AbiType};
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation};
+use schemars::JsonSchema;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Selector of a function.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AbiSelector([u8; 4]);
+
+impl AbiSelector {
+    /// Selector of a canonical signature.
+    pub fn of(canonical_signature: &str) -> Self {
+        let hash = sha256(canonical_signature.as_bytes());
+        Self([hash[0], hash[1], hash[2], hash[3]])
+    }
+
+    pub fn from_bytes(bytes: [u8; 4]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+}
+
+pub(crate) fn canonical_signature(function: &AbiFunction) -> String {
+    let params: Vec<String> = match &function.params {
+        AbiParameters::Json { args } => args
+            .iter()
+            .map(|arg| {
+                let ty = AbiType::Json {
+                    type_schema: arg.type_schema.clone(),
+                };
+                format!("{}:{}", arg.name, ty.display_name())
+            })
+            .collect(),
+        AbiParameters::Borsh { args } => args
+            .iter()
+            .map(|arg| format!("{}:{}", arg.name, arg.type_schema.declaration()))
+            .collect(),
+    };
+    format!("{}({})", function.qualified_name(), params.join(","))
+}
+
+impl fmt::Debug for AbiSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AbiSelector({})", self)
+    }
+}
+
+impl fmt::Display for AbiSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x")?;
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AbiSelector {
+    type Err = ParseSelectorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s
+            .strip_prefix("0x")
+            .filter(|digits| digits.len() == 8 && digits.is_ascii())
+            .ok_or_else(|| ParseSelectorError(s.to_string()))?;
+        let mut bytes = [0; 4];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16)
+                .map_err(|_| ParseSelectorError(s.to_string()))?;
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl Serialize for AbiSelector {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for AbiSelector {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl JsonSchema for AbiSelector {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "AbiSelector".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some(
+                    "First 4 bytes of the SHA-256 hash of the canonical signature, in hex."
+                        .to_string(),
+                ),
+                ..Default::default()
+            })),
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some("^0x[0-9a-fA-F]{8}$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+/// Error returned when a string is not a selector in the `0x<8 hex digits>` format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseSelectorError(String);
+
+impl std::error::Error for ParseSelectorError {}
+impl fmt::Display for ParseSelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "expected a selector in the `0x<8 hex digits>` format, got `{}`",
+            self.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiBody, AbiFunctionKind, AbiJsonParameter};
+    use serde_json::json;
+
+    #[test]
+    fn test_selectors() {
+        let mut function = AbiFunction::new("ft_transfer", AbiFunctionKind::Call);
+        function.params = AbiParameters::Json {
+            args: vec![
+                AbiJsonParameter::new(
+                    "receiver_id",
+                    serde_json::from_value(json!({ "$ref": "#/definitions/AccountId" })).unwrap(),
+                ),
+                AbiJsonParameter::new(
+                    "amount",
+                    serde_json::from_value(json!({ "$ref": "#/definitions/U128" })).unwrap(),
+                ),
+            ],
+        };
+        assert_eq!(
+            function.canonical_signature(),
+            "ft_transfer(receiver_id:AccountId,amount:U128)"
+        );
+        let selector = function.selector();
+        assert_eq!(selector, AbiSelector::of(&function.canonical_signature()));
+        assert_eq!(selector.to_string().parse(), Ok(selector));
+        assert_eq!(
+            AbiSelector::from_bytes([0x1a, 0x2b, 0x3c, 0x4d]).to_string(),
+            "0x1a2b3c4d"
+        );
+        assert!("0x1a2b3c".parse::<AbiSelector>().is_err());
+        assert!("1a2b3c4d".parse::<AbiSelector>().is_err());
+
+        let mut body = AbiBody {
+            functions: vec![
+                function,
+                AbiFunction::new("ft_total_supply", AbiFunctionKind::View),
+            ],
+            ..serde_json::from_value(json!({ "functions": [], "root_schema": {} })).unwrap()
+        };
+        assert_eq!(crate::transform::embed_selectors(&mut body), 2);
+        assert_eq!(body.functions[0].selector, Some(selector));
+        assert_eq!(
+            body.function_by_selector(&selector)
+                .map(|f| f.name.as_str()),
+            Some("ft_transfer")
+        );
+        let value = serde_json::to_value(&body.functions[0]).unwrap();
+        assert_eq!(value["selector"], json!(selector.to_string()));
+        assert_eq!(crate::transform::embed_selectors(&mut body), 0);
+    }
+}
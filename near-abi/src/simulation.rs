@@ -0,0 +1,187 @@
+//! Call environments for sandbox and simulation frameworks.
+//!
+//! [`SimulationManifest`] lists, for every function of a contract, the context a realistic call
+//! has to be set up with: who may call it, whether a deposit can be attached and which promise
+//! results it expects as callback inputs. The manifest serializes to JSON so that it can be
+//! consumed by tools written in other languages.
+
+use crate::{
+    AbiAccess, AbiCallback, AbiFunction, AbiFunctionKind, AbiFunctionModifier, AbiRoot, AbiType,
+};
+use serde::Serialize;
+
+/// Call environments of all functions of a contract, in declaration order.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SimulationManifest {
+    pub functions: Vec<FunctionSimulation>,
+}
+
+impl SimulationManifest {
+    /// Builds the manifest of a contract from its ABI.
+    pub fn new(abi: &AbiRoot) -> Self {
+        Self {
+            functions: abi
+                .body
+                .functions
+                .iter()
+                .map(FunctionSimulation::new)
+                .collect(),
+        }
+    }
+}
+
+/// Call environment of a single function.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct FunctionSimulation {
+    pub method_name: String,
+    /// View functions are simulated as queries, call functions as transactions.
+    pub kind: AbiFunctionKind,
+    /// Whether the function initializes the contract state and must be called on an
+    /// uninitialized contract.
+    pub init: bool,
+    /// Whether the init function may also be called on an initialized contract.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub ignore_state: bool,
+    pub predecessor: Predecessor,
+    /// Restriction of the callers enforced by the contract logic, on top of `predecessor`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access: Option<AbiAccess>,
+    pub attached_deposit: AttachedDeposit,
+    /// Promise results to provide to the call, in order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub callbacks: Vec<AbiCallback>,
+    /// Type of an arbitrary number of additional promise results to provide to the call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub callbacks_vec: Option<AbiType>,
+}
+
+impl FunctionSimulation {
+    /// Derives the call environment of `function` from its kind and modifiers.
+    pub fn new(function: &AbiFunction) -> Self {
+        let has = |modifier| function.modifiers.contains(&modifier);
+        let attached_deposit = match function.kind {
+            AbiFunctionKind::View => AttachedDeposit::Unsupported,
+            AbiFunctionKind::Call if has(AbiFunctionModifier::Payable) => AttachedDeposit::Allowed,
+            AbiFunctionKind::Call => AttachedDeposit::Forbidden,
+        };
+        Self {
+            method_name: function.name.clone(),
+            kind: function.kind.clone(),
+            init: has(AbiFunctionModifier::Init),
+            ignore_state: function.init_ignores_state(),
+            predecessor: if has(AbiFunctionModifier::Private) {
+                Predecessor::CurrentAccount
+            } else {
+                Predecessor::Any
+            },
+            access: function.access.clone(),
+            attached_deposit,
+            callbacks: function.callbacks.clone(),
+            callbacks_vec: function.callbacks_vec.clone(),
+        }
+    }
+}
+
+/// Account allowed to call a function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Predecessor {
+    /// Any account.
+    Any,
+    /// Only the contract account itself, e.g. from a callback of the contract.
+    CurrentAccount,
+}
+
+/// Whether a deposit can be attached to a call of a function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttachedDeposit {
+    /// The function is payable.
+    Allowed,
+    /// The call fails if a deposit is attached.
+    Forbidden,
+    /// View calls can not carry a deposit.
+    Unsupported,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_simulation_manifest() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    { "name": "new", "kind": "call", "modifiers": ["init", "ignore_state"] },
+                    { "name": "get", "kind": "view" },
+                    { "name": "deposit", "kind": "call", "modifiers": ["payable"], "access": { "type": "owner" } },
+                    {
+                        "name": "on_transfer",
+                        "kind": "call",
+                        "modifiers": ["private"],
+                        "callbacks": [
+                            {
+                                "name": "used_amount",
+                                "serialization_type": "json",
+                                "type_schema": { "type": "string" }
+                            }
+                        ]
+                    }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+
+        let manifest = serde_json::to_value(SimulationManifest::new(&abi)).unwrap();
+        assert_eq!(
+            manifest,
+            json!({
+                "functions": [
+                    {
+                        "method_name": "new",
+                        "kind": "call",
+                        "init": true,
+                        "ignore_state": true,
+                        "predecessor": "any",
+                        "attached_deposit": "forbidden"
+                    },
+                    {
+                        "method_name": "get",
+                        "kind": "view",
+                        "init": false,
+                        "predecessor": "any",
+                        "attached_deposit": "unsupported"
+                    },
+                    {
+                        "method_name": "deposit",
+                        "kind": "call",
+                        "init": false,
+                        "predecessor": "any",
+                        "access": { "type": "owner" },
+                        "attached_deposit": "allowed"
+                    },
+                    {
+                        "method_name": "on_transfer",
+                        "kind": "call",
+                        "init": false,
+                        "predecessor": "current_account",
+                        "attached_deposit": "forbidden",
+                        "callbacks": [
+                            {
+                                "name": "used_amount",
+                                "serialization_type": "json",
+                                "type_schema": { "type": "string" }
+                            }
+                        ]
+                    }
+                ]
+            })
+        );
+    }
+}
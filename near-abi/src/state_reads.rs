@@ -0,0 +1,110 @@
+//! Cache invalidation of view function results.
+//!
+//! [`AbiFunction::state_reads`] declares which parts of the contract state a function reads.
+//! Indexers and RPC caching layers observing a write to a storage key use
+//! [`functions_reading_key`] to find the cached view results it invalidates.
+
+use crate::{AbiBody, AbiFunction, AbiFunctionKind};
+
+/// Storage key of the top-level state struct.
+pub const STATE_KEY: &[u8] = b"STATE";
+
+/// View functions whose result may change when the storage entry under `key` is written, in
+/// declaration order.
+///
+/// [Pure](AbiFunction::is_pure) functions are never returned. Other functions without
+/// [declared reads](AbiFunction::state_reads) may read anything and are always
+/// returned. Collections are matched by the storage-key prefixes declared in the state of the
+/// contract; collections missing from it are assumed to be affected by any key.
+pub fn functions_reading_key<'a>(body: &'a AbiBody, key: &[u8]) -> Vec<&'a AbiFunction> {
+    body.functions
+        .iter()
+        .filter(|function| function.kind == AbiFunctionKind::View && !function.is_pure())
+        .filter(|function| match &function.state_reads {
+            None => true,
+            Some(reads) => {
+                (reads.root && key == STATE_KEY)
+                    || reads
+                        .collections
+                        .iter()
+                        .any(|field| match collection_prefix(body, field) {
+                            Some(prefix) => key.starts_with(prefix),
+                            None => true,
+                        })
+            }
+        })
+        .collect()
+}
+
+/// Storage-key prefixes read by `function`, the top-level state being read under [`STATE_KEY`].
+/// Empty for [pure](AbiFunction::is_pure) functions. `None` if the function does not declare its
+/// reads or reads a collection missing from the state of the contract.
+pub fn read_prefixes<'a>(body: &'a AbiBody, function: &AbiFunction) -> Option<Vec<&'a [u8]>> {
+    if function.is_pure() {
+        return Some(Vec::new());
+    }
+    let reads = function.state_reads.as_ref()?;
+    let mut prefixes = Vec::new();
+    if reads.root {
+        prefixes.push(STATE_KEY);
+    }
+    for field in &reads.collections {
+        prefixes.push(collection_prefix(body, field)?);
+    }
+    Some(prefixes)
+}
+
+fn collection_prefix<'a>(body: &'a AbiBody, field: &str) -> Option<&'a [u8]> {
+    body.state
+        .as_ref()?
+        .collections
+        .iter()
+        .find(|collection| collection.field == field)
+        .map(|collection| collection.prefix.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_functions_reading_key() {
+        let body: AbiBody = serde_json::from_value(json!({
+            "functions": [
+                { "name": "get_owner", "kind": "view", "state_reads": { "root": true } },
+                { "name": "ft_balance_of", "kind": "view", "state_reads": { "collections": ["accounts"] } },
+                { "name": "ft_metadata", "kind": "view", "state_reads": { "collections": ["metadata"] } },
+                { "name": "version", "kind": "view", "state_reads": {} },
+                { "name": "legacy_view", "kind": "view" },
+                { "name": "hash", "kind": "view", "modifiers": ["pure"] },
+                { "name": "ft_transfer", "kind": "call" }
+            ],
+            "root_schema": {},
+            "state": {
+                "root": { "serialization_type": "json", "type_schema": {} },
+                "collections": [{ "field": "accounts", "prefix": [97] }]
+            }
+        }))
+        .unwrap();
+        let names = |key: &[u8]| {
+            functions_reading_key(&body, key)
+                .into_iter()
+                .map(|f| f.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(b"STATE"), ["get_owner", "ft_metadata", "legacy_view"]);
+        assert_eq!(
+            names(b"abob.near"),
+            ["ft_balance_of", "ft_metadata", "legacy_view"]
+        );
+
+        assert_eq!(
+            read_prefixes(&body, &body.functions[1]),
+            Some(vec![&b"a"[..]])
+        );
+        assert_eq!(read_prefixes(&body, &body.functions[2]), None);
+        assert_eq!(read_prefixes(&body, &body.functions[3]), Some(vec![]));
+        assert_eq!(read_prefixes(&body, &body.functions[5]), Some(vec![]));
+    }
+}
@@ -0,0 +1,195 @@
+//! Strict deserialization profiles.
+//!
+//! Parsing already rejects unknown fields and incompatible schema versions. Registries that want
+//! to enforce the quality of the ABIs they ingest can additionally reject ABIs that are empty,
+//! undocumented, not canonically ordered or carry hashes that do not match their content, by
+//! parsing with [`DeserializeOptions`].
+
+use crate::ordering::Ordering;
+use crate::type_hash::{type_hashes, TYPE_HASH_KEY};
+use crate::validate::modifier_errors;
+use crate::AbiRoot;
+use schemars::schema::Schema;
+use serde::de::Error;
+
+/// Checks applied when parsing an ABI, on top of the checks of [`AbiRoot::from_value`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeserializeOptions {
+    /// Rejects ABIs without functions.
+    pub reject_empty: bool,
+    /// Rejects functions whose modifiers contradict their kind or each other (see
+    /// [`modifier_errors`]).
+    pub check_modifiers: bool,
+    /// Rejects ABIs whose functions and modifiers are not ordered as by [`Ordering::Strict`].
+    pub require_canonical_order: bool,
+    /// Rejects functions without documentation.
+    pub require_docs: bool,
+    /// Rejects a malformed `Cargo.lock` hash, and embedded selectors and type hashes that do not
+    /// match the function signatures and definitions they are embedded in.
+    pub verify_hashes: bool,
+}
+
+impl DeserializeOptions {
+    /// Every check except [`require_docs`](Self::require_docs).
+    pub fn strict() -> Self {
+        Self {
+            reject_empty: true,
+            check_modifiers: true,
+            require_canonical_order: true,
+            require_docs: false,
+            verify_hashes: true,
+        }
+    }
+
+    /// Parses an ABI from a JSON string and applies the enabled checks.
+    pub fn from_str(&self, s: &str) -> Result<AbiRoot, serde_json::Error> {
+        self.from_value(serde_json::from_str(s)?)
+    }
+
+    /// Parses an ABI from a JSON value and applies the enabled checks.
+    pub fn from_value(&self, value: serde_json::Value) -> Result<AbiRoot, serde_json::Error> {
+        let abi = AbiRoot::from_value(value)?;
+        self.check(&abi).map_err(serde_json::Error::custom)?;
+        Ok(abi)
+    }
+
+    /// Applies the enabled checks to an already parsed ABI, describing the first failing one.
+    pub fn check(&self, abi: &AbiRoot) -> Result<(), String> {
+        let functions = &abi.body.functions;
+        if self.reject_empty && functions.is_empty() {
+            return Err("the ABI declares no functions".to_string());
+        }
+        if self.check_modifiers {
+            for function in functions {
+                if let Some(error) = modifier_errors(function).into_iter().next() {
+                    return Err(format!(
+                        "invalid modifiers of `{}`: {}",
+                        function.name, error
+                    ));
+                }
+            }
+        }
+        if self.require_canonical_order && !Ordering::Strict.is_applied(abi) {
+            return Err("functions or modifiers are not in canonical order".to_string());
+        }
+        if self.require_docs {
+            let undocumented = functions.iter().find(|function| {
+                function
+                    .doc
+                    .as_deref()
+                    .map_or(true, |doc| doc.trim().is_empty())
+            });
+            if let Some(function) = undocumented {
+                return Err(format!("function `{}` has no documentation", function.name));
+            }
+        }
+        if self.verify_hashes {
+            verify_hashes(abi)?;
+        }
+        Ok(())
+    }
+}
+
+fn verify_hashes(abi: &AbiRoot) -> Result<(), String> {
+    let cargo_lock_hash = abi
+        .metadata
+        .build
+        .as_ref()
+        .and_then(|build| build.cargo_lock_hash.as_deref());
+    if let Some(hash) = cargo_lock_hash {
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!(
+                "`Cargo.lock` hash `{}` is not a hex-encoded SHA-256 hash",
+                hash
+            ));
+        }
+    }
+    for function in &abi.body.functions {
+        if let Some(selector) = function.selector {
+            if selector != function.selector() {
+                return Err(format!(
+                    "selector {} of `{}` does not match its signature",
+                    selector, function.name
+                ));
+            }
+        }
+    }
+    let hashes = type_hashes(&abi.body.root_schema);
+    for (name, schema) in abi.body.definitions() {
+        let embedded = match schema {
+            Schema::Object(object) => object.extensions.get(TYPE_HASH_KEY),
+            Schema::Bool(_) => None,
+        };
+        if let Some(embedded) = embedded {
+            if embedded.as_str() != hashes.get(name).map(ToString::to_string).as_deref() {
+                return Err(format!(
+                    "type hash of `{}` does not match its definition",
+                    name
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_strict_profile() {
+        let value = json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": { "build": { "compiler": "rustc", "builder": "cargo-near", "cargo_lock_hash": "e3b0c442" } },
+            "body": {
+                "functions": [
+                    { "name": "get", "kind": "view" },
+                    { "name": "add", "kind": "call", "doc": "Adds.", "modifiers": ["payable", "private"] }
+                ],
+                "root_schema": { "definitions": { "Id": { "type": "string" } } }
+            }
+        });
+        let strict = DeserializeOptions::strict();
+        let error =
+            |value: &serde_json::Value| strict.from_value(value.clone()).unwrap_err().to_string();
+        assert!(DeserializeOptions::default()
+            .from_value(value.clone())
+            .is_ok());
+        assert_eq!(
+            error(&value),
+            "functions or modifiers are not in canonical order"
+        );
+
+        let mut abi = AbiRoot::from_value(value).unwrap();
+        Ordering::Strict.apply(&mut abi);
+        assert_eq!(
+            error(&abi.to_value()),
+            "`Cargo.lock` hash `e3b0c442` is not a hex-encoded SHA-256 hash"
+        );
+        abi.metadata.build = None;
+        crate::type_hash::embed_type_hashes(&mut abi.body);
+        crate::transform::embed_selectors(&mut abi.body);
+        let mut value = abi.to_value();
+        assert!(AbiRoot::from_str_strict(&value.to_string()).is_ok());
+        assert_eq!(
+            DeserializeOptions {
+                require_docs: true,
+                ..DeserializeOptions::strict()
+            }
+            .from_value(value.clone())
+            .unwrap_err()
+            .to_string(),
+            "function `get` has no documentation"
+        );
+
+        value["body"]["root_schema"]["definitions"]["Id"]["type"] = json!("integer");
+        assert_eq!(
+            error(&value),
+            "type hash of `Id` does not match its definition"
+        );
+        value["body"]["functions"] = json!([]);
+        assert_eq!(error(&value), "the ABI declares no functions");
+    }
+}
@@ -0,0 +1,245 @@
+//! Conformance test vectors embedded in the ABI.
+//!
+//! A test vector pairs the encoded arguments of a function with their expected decoding, so that
+//! ABI implementations in other languages can check their codecs against this crate using data
+//! carried by the document itself. JSON arguments decode to the parsed object, Borsh arguments to
+//! an object keyed by parameter name with values represented as in
+//! [`decode_borsh`](crate::value::decode_borsh).
+
+use crate::value::{
+    decode_borsh_args, default_borsh_args, default_json, validate_json_args, ValueError,
+};
+use crate::{AbiParameters, AbiRoot};
+use base64::Engine;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// Encoded arguments of a function along with their expected decoding.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AbiTestVector {
+    /// Name of the function the arguments are passed to.
+    pub function: String,
+    /// Arguments as sent to the contract, encoded with standard base64.
+    pub input: String,
+    /// Expected decoding of the arguments.
+    pub expected: Value,
+}
+
+/// Produces a test vector with the simplest valid arguments of every function taking
+/// parameters, in declaration order.
+pub fn generate(abi: &AbiRoot) -> Result<Vec<AbiTestVector>, TestVectorError> {
+    let mut vectors = Vec::new();
+    for function in &abi.body.functions {
+        if function.params.is_empty() {
+            continue;
+        }
+        let input = match &function.params {
+            AbiParameters::Json { args } => {
+                let value = args
+                    .iter()
+                    .map(|param| {
+                        let value = default_json(&abi.body.root_schema, &param.type_schema)
+                            .map_err(|error| TestVectorError::Generate {
+                                function: function.name.clone(),
+                                error,
+                            })?;
+                        Ok((param.name.clone(), value))
+                    })
+                    .collect::<Result<serde_json::Map<_, _>, _>>()?;
+                serde_json::to_vec(&value).unwrap()
+            }
+            AbiParameters::Borsh { .. } => default_borsh_args(function)
+                .map_err(|error| TestVectorError::Generate {
+                    function: function.name.clone(),
+                    error,
+                })?
+                .unwrap_or_default(),
+        };
+        let expected =
+            decode(abi, &function.params, &input).map_err(|error| TestVectorError::Generate {
+                function: function.name.clone(),
+                error,
+            })?;
+        vectors.push(AbiTestVector {
+            function: function.name.clone(),
+            input: base64::engine::general_purpose::STANDARD.encode(input),
+            expected,
+        });
+    }
+    Ok(vectors)
+}
+
+/// Checks the test vectors of the ABI against the decoding of this crate, returning the vectors
+/// that do not hold.
+pub fn check(abi: &AbiRoot) -> Vec<TestVectorError> {
+    abi.test_vectors
+        .iter()
+        .enumerate()
+        .filter_map(|(index, vector)| check_vector(abi, index, vector).err())
+        .collect()
+}
+
+fn check_vector(
+    abi: &AbiRoot,
+    index: usize,
+    vector: &AbiTestVector,
+) -> Result<(), TestVectorError> {
+    let function = abi
+        .body
+        .functions
+        .iter()
+        .find(|function| function.name == vector.function)
+        .ok_or_else(|| TestVectorError::UnknownFunction {
+            index,
+            function: vector.function.clone(),
+        })?;
+    let input = base64::engine::general_purpose::STANDARD
+        .decode(&vector.input)
+        .map_err(|err| TestVectorError::InvalidInput {
+            index,
+            error: err.to_string(),
+        })?;
+    let found = decode(abi, &function.params, &input)
+        .map_err(|error| TestVectorError::Decode { index, error })?;
+    if found != vector.expected {
+        return Err(TestVectorError::Mismatch {
+            index,
+            expected: vector.expected.clone(),
+            found,
+        });
+    }
+    Ok(())
+}
+
+fn decode(abi: &AbiRoot, params: &AbiParameters, input: &[u8]) -> Result<Value, ValueError> {
+    match params {
+        AbiParameters::Json { args } => {
+            let value: Value = serde_json::from_slice(input).map_err(|err| {
+                ValueError::new("", format!("arguments are not valid JSON: {}", err))
+            })?;
+            validate_json_args(&abi.body.root_schema, args, &value)?;
+            Ok(value)
+        }
+        AbiParameters::Borsh { args } => decode_borsh_args(args, input),
+    }
+}
+
+/// A test vector that could not be produced or does not hold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestVectorError {
+    /// No valid arguments can be produced for the function.
+    Generate { function: String, error: ValueError },
+    /// The function of the test vector at `index` is not declared in the ABI.
+    UnknownFunction { index: usize, function: String },
+    /// The input of the test vector at `index` is not valid base64.
+    InvalidInput { index: usize, error: String },
+    /// The input of the test vector at `index` does not conform to the function parameters.
+    Decode { index: usize, error: ValueError },
+    /// The input of the test vector at `index` decodes to another value than expected.
+    Mismatch {
+        index: usize,
+        expected: Value,
+        found: Value,
+    },
+}
+
+impl std::error::Error for TestVectorError {}
+impl fmt::Display for TestVectorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Generate { function, error } => {
+                write!(f, "can not produce arguments for `{}`: {}", function, error)
+            }
+            Self::UnknownFunction { index, function } => write!(
+                f,
+                "test vector {}: function `{}` is not declared in the ABI",
+                index, function
+            ),
+            Self::InvalidInput { index, error } => {
+                write!(f, "test vector {}: invalid base64 input: {}", index, error)
+            }
+            Self::Decode { index, error } => write!(f, "test vector {}: {}", index, error),
+            Self::Mismatch {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "test vector {}: expected {}, decoded {}",
+                index, expected, found
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AbiBorshParameter, AbiFunction, AbiFunctionKind, SCHEMA_VERSION};
+    use serde_json::json;
+
+    #[test]
+    fn test_generate_and_check() {
+        let mut abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    {
+                        "name": "ft_transfer",
+                        "kind": "call",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [
+                                { "name": "receiver_id", "type_schema": { "type": "string" } },
+                                { "name": "memo", "type_schema": { "type": ["string", "null"] } }
+                            ]
+                        }
+                    },
+                    { "name": "ping", "kind": "call" }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+        let mut set = AbiFunction::new("set", AbiFunctionKind::Call);
+        set.params = AbiParameters::Borsh {
+            args: vec![
+                AbiBorshParameter::new("key", borsh::schema_container_of::<String>()),
+                AbiBorshParameter::new("value", borsh::schema_container_of::<u32>()),
+            ],
+        };
+        abi.body.functions.push(set);
+
+        abi.test_vectors = generate(&abi).unwrap();
+        assert_eq!(
+            serde_json::to_value(&abi.test_vectors).unwrap(),
+            json!([
+                {
+                    "function": "ft_transfer",
+                    "input": "eyJtZW1vIjpudWxsLCJyZWNlaXZlcl9pZCI6IiJ9",
+                    "expected": { "receiver_id": "", "memo": null }
+                },
+                { "function": "set", "input": "AAAAAAAAAAA=", "expected": { "key": "", "value": 0 } }
+            ])
+        );
+        assert_eq!(check(&abi), []);
+
+        abi.test_vectors[1].input = "AQAAAGsHAAAA".to_string();
+        abi.test_vectors[1].expected = json!({ "key": "k", "value": 7 });
+        abi.test_vectors.push(AbiTestVector {
+            function: "set".to_string(),
+            input: "AAAAAA==".to_string(),
+            expected: json!({}),
+        });
+        let errors = check(&abi);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].to_string(),
+            "test vector 2: at `4`: parameter `value`: unexpected end of input"
+        );
+    }
+}
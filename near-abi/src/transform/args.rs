@@ -0,0 +1,181 @@
+use super::{resolve_ref, to_pascal_case, unique_name, DEFINITIONS_PREFIX};
+use crate::{AbiFunction, AbiJsonParameter, AbiParameters};
+use schemars::schema::{InstanceType, ObjectValidation, RootSchema, Schema, SchemaObject};
+use std::fmt;
+
+/// Replaces a single struct-typed JSON parameter with one parameter per struct field.
+///
+/// The struct schema may be inline or a reference into `root_schema`. On success, returns the name
+/// of the parameter that was flattened (e.g. `args` for `fn foo(args: FooArgs)`), which callers
+/// need to wrap flat arguments back into the payload expected by the contract.
+pub fn flatten_args(
+    function: &mut AbiFunction,
+    root_schema: &RootSchema,
+) -> Result<String, ArgsTransformError> {
+    let args = match &mut function.params {
+        AbiParameters::Json { args } => args,
+        AbiParameters::Borsh { .. } => return Err(ArgsTransformError::BorshParameters),
+    };
+    if args.len() != 1 {
+        return Err(ArgsTransformError::NotSingleParameter(args.len()));
+    }
+    let schema = resolve_ref(root_schema, &args[0].type_schema)
+        .ok_or_else(|| ArgsTransformError::UnresolvedReference(args[0].name.clone()))?;
+    let properties = match schema {
+        Schema::Object(SchemaObject {
+            object: Some(object),
+            ..
+        }) if object.pattern_properties.is_empty() => &object.properties,
+        _ => return Err(ArgsTransformError::NotAStruct(args[0].name.clone())),
+    };
+
+    let flattened = properties
+        .iter()
+        .map(|(name, type_schema)| AbiJsonParameter::new(name.clone(), type_schema.clone()))
+        .collect();
+    let original = std::mem::replace(args, flattened);
+    Ok(original.into_iter().next().unwrap().name)
+}
+
+/// Wraps all JSON parameters of a function into a single struct-typed parameter named `param`.
+///
+/// The struct schema is added to `root_schema` as a new definition named after the function (e.g.
+/// `FooArgs` for `foo`) and the resulting parameter references it. Returns the name of the new
+/// definition.
+pub fn unflatten_args(
+    function: &mut AbiFunction,
+    root_schema: &mut RootSchema,
+    param: &str,
+) -> Result<String, ArgsTransformError> {
+    let args = match &mut function.params {
+        AbiParameters::Json { args } => args,
+        AbiParameters::Borsh { .. } => return Err(ArgsTransformError::BorshParameters),
+    };
+
+    let mut object = ObjectValidation::default();
+    for arg in args.iter() {
+        object
+            .properties
+            .insert(arg.name.clone(), arg.type_schema.clone());
+        object.required.insert(arg.name.clone());
+    }
+    let struct_schema = SchemaObject {
+        instance_type: Some(InstanceType::Object.into()),
+        object: Some(Box::new(object)),
+        ..Default::default()
+    };
+
+    let definition = unique_name(
+        &root_schema.definitions,
+        &format!("{}Args", to_pascal_case(&function.name)),
+    );
+    root_schema
+        .definitions
+        .insert(definition.clone(), struct_schema.into());
+    *args = vec![AbiJsonParameter::new(
+        param.to_string(),
+        Schema::new_ref(format!("{}{}", DEFINITIONS_PREFIX, definition)),
+    )];
+    Ok(definition)
+}
+
+/// Reason why function arguments could not be flattened or unflattened.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArgsTransformError {
+    /// Borsh parameters have no named fields to rearrange.
+    BorshParameters,
+    /// Only functions with exactly one parameter can be flattened.
+    NotSingleParameter(usize),
+    /// The parameter schema references a definition missing from the root schema.
+    UnresolvedReference(String),
+    /// The parameter schema does not describe an object with fixed properties.
+    NotAStruct(String),
+}
+
+impl std::error::Error for ArgsTransformError {}
+impl fmt::Display for ArgsTransformError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BorshParameters => write!(f, "Borsh parameters can not be restructured"),
+            Self::NotSingleParameter(count) => {
+                write!(f, "expected exactly one parameter, found {}", count)
+            }
+            Self::UnresolvedReference(param) => write!(
+                f,
+                "parameter `{}` references a type missing from the root schema",
+                param
+            ),
+            Self::NotAStruct(param) => {
+                write!(f, "parameter `{}` is not a struct with named fields", param)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AbiFunctionKind;
+
+    fn function(params: Vec<(&str, Schema)>) -> AbiFunction {
+        AbiFunction {
+            params: AbiParameters::Json {
+                args: params
+                    .into_iter()
+                    .map(|(name, type_schema)| AbiJsonParameter::new(name.to_string(), type_schema))
+                    .collect(),
+            },
+            ..AbiFunction::new("set_owner", AbiFunctionKind::Call)
+        }
+    }
+
+    fn string_schema() -> Schema {
+        SchemaObject {
+            instance_type: Some(InstanceType::String.into()),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_unflatten_then_flatten_roundtrip() {
+        let original = function(vec![("owner", string_schema()), ("memo", string_schema())]);
+        let mut root_schema = RootSchema::default();
+
+        let mut wrapped = original.clone();
+        let definition = unflatten_args(&mut wrapped, &mut root_schema, "args").unwrap();
+        assert_eq!(definition, "SetOwnerArgs");
+        assert_eq!(
+            wrapped.params,
+            AbiParameters::Json {
+                args: vec![AbiJsonParameter::new(
+                    "args",
+                    Schema::new_ref("#/definitions/SetOwnerArgs".to_string())
+                )]
+            }
+        );
+
+        let mut flattened = wrapped.clone();
+        assert_eq!(flatten_args(&mut flattened, &root_schema).unwrap(), "args");
+        // Properties are sorted by name in the struct schema.
+        let names: Vec<_> = match &flattened.params {
+            AbiParameters::Json { args } => args.iter().map(|arg| arg.name.as_str()).collect(),
+            _ => unreachable!(),
+        };
+        assert_eq!(names, vec!["memo", "owner"]);
+    }
+
+    #[test]
+    fn test_flatten_rejects_non_struct() {
+        let mut f = function(vec![("owner", string_schema())]);
+        assert_eq!(
+            flatten_args(&mut f, &RootSchema::default()),
+            Err(ArgsTransformError::NotAStruct("owner".to_string()))
+        );
+        let mut f = function(vec![]);
+        assert_eq!(
+            flatten_args(&mut f, &RootSchema::default()),
+            Err(ArgsTransformError::NotSingleParameter(0))
+        );
+    }
+}
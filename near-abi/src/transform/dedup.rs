@@ -0,0 +1,378 @@
+use super::{to_pascal_case, unique_name, TypeSite, DEFINITIONS_PREFIX};
+use crate::walk::{function_json_schemas_mut, subschemas, subschemas_mut, visit, visit_mut};
+use crate::{AbiBody, AbiState, AbiType};
+use schemars::schema::{Schema, SchemaObject};
+
+/// Hoists identical anonymous schemas used across function signatures, the contract state,
+/// constants and external contract signatures into named root schema definitions and replaces
+/// every occurrence with a `$ref`.
+///
+/// Schemas are compared at any depth, so that e.g. a struct inlined as a property of two
+/// different parameters is hoisted as well. An inline schema is hoisted when it is structurally
+/// complex (an object, array, enum or a schema composition) and either occurs more than once or
+/// is identical to an existing definition, in which case the existing definition is reused. New
+/// definitions are named after the schema `title` when available, and after the first
+/// parameter, function, constant or state they were found in otherwise. Existing definitions are
+/// left untouched.
+///
+/// Returns the number of inline schemas that were replaced with references.
+pub fn dedup_schemas(body: &mut AbiBody) -> usize {
+    let existing: Vec<(String, Schema)> = body
+        .root_schema
+        .definitions
+        .iter()
+        .map(|(name, schema)| (name.clone(), schema.clone()))
+        .collect();
+
+    // Outer schemas are hoisted first. Schemas nested in a hoisted definition are then counted
+    // once, however many copies of the definition were replaced.
+    let mut hoisted: Vec<(String, Schema)> = Vec::new();
+    let mut replaced = 0;
+    loop {
+        let mut candidates: Vec<(Schema, String, Option<String>)> = Vec::new();
+        for (schema, suggested, count) in schema_groups(body, &hoisted) {
+            let name = existing
+                .iter()
+                .chain(&hoisted)
+                .find(|(_, s)| s == &schema)
+                .map(|(name, _)| name.clone());
+            if name.is_some() || count > 1 {
+                candidates.push((schema, suggested, name));
+            }
+        }
+        if candidates.is_empty() {
+            return replaced;
+        }
+
+        // Names are only given to the candidates actually replaced: those nested in other
+        // candidates are not reached, and are counted again in the next round.
+        let mut definitions = body.root_schema.definitions.clone();
+        let mut new_definitions = Vec::new();
+        let mut replace = |schema: &mut Schema| {
+            if let Some((candidate, suggested, name)) =
+                candidates.iter_mut().find(|(s, _, _)| s == &*schema)
+            {
+                let name = name.get_or_insert_with(|| {
+                    let name = unique_name(&definitions, suggested);
+                    definitions.insert(name.clone(), candidate.clone());
+                    new_definitions.push((name.clone(), candidate.clone()));
+                    name
+                });
+                *schema = Schema::new_ref(format!("{}{}", DEFINITIONS_PREFIX, name));
+                replaced += 1;
+            }
+        };
+        for (_, schema) in type_schemas_mut(body) {
+            visit_mut(schema, &mut replace);
+        }
+        for (name, _) in &hoisted {
+            if let Some(Schema::Object(object)) = body.root_schema.definitions.get_mut(name) {
+                for schema in subschemas_mut(object) {
+                    visit_mut(schema, &mut replace);
+                }
+            }
+        }
+        for (name, schema) in new_definitions {
+            body.root_schema
+                .definitions
+                .insert(name.clone(), schema.clone());
+            hoisted.push((name, schema));
+        }
+    }
+}
+
+/// Groups the identical hoistable schemas of `body` and of the subschemas of its `hoisted`
+/// definitions, preserving the order of first occurrence, along with the name suggested for
+/// their definition and their number of occurrences.
+fn schema_groups(body: &mut AbiBody, hoisted: &[(String, Schema)]) -> Vec<(Schema, String, usize)> {
+    let mut groups: Vec<(Schema, String, usize)> = Vec::new();
+    let mut count = |fallback: &str, schema: &Schema| {
+        visit(schema, &mut |schema| {
+            if !is_hoistable(schema) {
+                return;
+            }
+            match groups.iter_mut().find(|(s, _, _)| s == schema) {
+                Some((_, _, count)) => *count += 1,
+                None => groups.push((schema.clone(), suggested_name(schema, fallback), 1)),
+            }
+        })
+    };
+    for (fallback, schema) in type_schemas_mut(body) {
+        count(&fallback, schema);
+    }
+    for (name, _) in hoisted {
+        if let Some(Schema::Object(object)) = body.root_schema.definitions.get(name) {
+            for schema in subschemas(object) {
+                count(name, schema);
+            }
+        }
+    }
+    groups
+}
+
+/// JSON type schemas of `body` outside of its root schema, along with the name of the
+/// definitions hoisted from them if untitled.
+fn type_schemas_mut(body: &mut AbiBody) -> Vec<(String, &mut Schema)> {
+    let mut schemas = Vec::new();
+    let functions = body.functions.iter_mut().chain(
+        body.external_contracts
+            .iter_mut()
+            .flat_map(|contract| &mut contract.functions),
+    );
+    for function in functions {
+        let name = function.name.clone();
+        for (site, schema) in function_json_schemas_mut(function) {
+            schemas.push((site_name(&name, &site), schema));
+        }
+    }
+    if let Some(AbiState {
+        root: AbiType::Json { type_schema },
+        ..
+    }) = &mut body.state
+    {
+        schemas.push(("State".to_string(), type_schema));
+    }
+    for constant in &mut body.constants {
+        let name = to_pascal_case(&constant.name.to_lowercase());
+        schemas.push((name, &mut constant.type_schema));
+    }
+    schemas
+}
+
+fn is_hoistable(schema: &Schema) -> bool {
+    match schema {
+        Schema::Bool(_) => false,
+        Schema::Object(SchemaObject {
+            reference: Some(_), ..
+        }) => false,
+        Schema::Object(object) => {
+            object.object.is_some()
+                || object.array.is_some()
+                || object.subschemas.is_some()
+                || object.enum_values.is_some()
+        }
+    }
+}
+
+fn suggested_name(schema: &Schema, fallback: &str) -> String {
+    if let Schema::Object(SchemaObject {
+        metadata: Some(metadata),
+        ..
+    }) = schema
+    {
+        if let Some(title) = metadata.title.as_deref().map(to_pascal_case) {
+            if !title.is_empty() {
+                return title;
+            }
+        }
+    }
+    fallback.to_string()
+}
+
+fn site_name(function: &str, site: &TypeSite) -> String {
+    match site {
+        TypeSite::Param(param) => to_pascal_case(param),
+        TypeSite::Callback(i) => format!("{}Callback{}", to_pascal_case(function), i),
+        TypeSite::CallbacksVec => format!("{}Callbacks", to_pascal_case(function)),
+        TypeSite::Result => format!("{}Result", to_pascal_case(function)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        AbiConstant, AbiExternalContract, AbiFunction, AbiFunctionKind, AbiJsonParameter,
+        AbiParameters,
+    };
+    use schemars::schema::RootSchema;
+    use serde_json::json;
+
+    fn function(name: &str, params: Vec<(&str, Schema)>, result: Option<Schema>) -> AbiFunction {
+        AbiFunction {
+            params: AbiParameters::Json {
+                args: params
+                    .into_iter()
+                    .map(|(name, type_schema)| AbiJsonParameter::new(name.to_string(), type_schema))
+                    .collect(),
+            },
+            result: result.map(|type_schema| AbiType::Json { type_schema }),
+            ..AbiFunction::new(name, AbiFunctionKind::View)
+        }
+    }
+
+    fn pair_schema() -> Schema {
+        serde_json::from_value(serde_json::json!({
+            "type": "array",
+            "items": [{ "type": "integer" }, { "type": "integer" }],
+            "maxItems": 2,
+            "minItems": 2
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dedup_hoists_repeated_schemas() {
+        let mut body = AbiBody {
+            functions: vec![
+                function("get_range", vec![("range", pair_schema())], None),
+                function(
+                    "set_range",
+                    vec![("range", pair_schema())],
+                    Some(pair_schema()),
+                ),
+                function(
+                    "get_amount",
+                    vec![],
+                    Some(serde_json::from_value(serde_json::json!({ "type": "string" })).unwrap()),
+                ),
+            ],
+            root_schema: RootSchema::default(),
+            state: None,
+            constants: Vec::new(),
+            external_contracts: Vec::new(),
+        };
+
+        assert_eq!(dedup_schemas(&mut body), 3);
+        assert_eq!(body.root_schema.definitions.len(), 1);
+        assert_eq!(body.root_schema.definitions["Range"], pair_schema());
+        let expected_ref = Schema::new_ref("#/definitions/Range".to_string());
+        assert_eq!(
+            body.functions[1].result,
+            Some(AbiType::Json {
+                type_schema: expected_ref
+            })
+        );
+        // Primitive schemas are left inline.
+        assert!(!body.functions[2]
+            .result
+            .as_ref()
+            .map(|r| matches!(r, AbiType::Json { type_schema } if type_schema.is_ref()))
+            .unwrap());
+    }
+
+    #[test]
+    fn test_dedup_reuses_existing_definitions() {
+        let mut root_schema = RootSchema::default();
+        root_schema
+            .definitions
+            .insert("Range".to_string(), pair_schema());
+        root_schema
+            .definitions
+            .insert("Other".to_string(), Schema::Bool(true));
+        let mut body = AbiBody {
+            functions: vec![function("get", vec![("bounds", pair_schema())], None)],
+            root_schema,
+            state: None,
+            constants: Vec::new(),
+            external_contracts: Vec::new(),
+        };
+
+        assert_eq!(dedup_schemas(&mut body), 1);
+        assert_eq!(body.root_schema.definitions.len(), 2);
+        assert_eq!(
+            body.functions[0].params,
+            AbiParameters::Json {
+                args: vec![AbiJsonParameter::new(
+                    "bounds",
+                    Schema::new_ref("#/definitions/Range".to_string())
+                )]
+            }
+        );
+    }
+
+    #[test]
+    fn test_dedup_nested_schemas() {
+        let range = || -> Schema {
+            let mut range = serde_json::to_value(pair_schema()).unwrap();
+            range["title"] = json!("Range");
+            serde_json::from_value(range).unwrap()
+        };
+        let wrapper = |property: &str| -> Schema {
+            serde_json::from_value(json!({ "type": "object", "properties": { property: range() } }))
+                .unwrap()
+        };
+        let mut body = AbiBody {
+            functions: vec![function(
+                "set_window",
+                vec![("window", wrapper("range"))],
+                None,
+            )],
+            root_schema: RootSchema::default(),
+            state: Some(AbiState {
+                root: AbiType::Json {
+                    type_schema: wrapper("current"),
+                },
+                collections: Vec::new(),
+            }),
+            constants: vec![AbiConstant {
+                name: "DEFAULT_WINDOW".to_string(),
+                doc: None,
+                type_schema: wrapper("range"),
+                value: json!({ "range": [0, 10] }),
+            }],
+            external_contracts: vec![AbiExternalContract {
+                name: "ext_oracle".to_string(),
+                doc: None,
+                functions: vec![function("get_range", vec![], Some(range()))],
+            }],
+        };
+
+        assert_eq!(dedup_schemas(&mut body), 5);
+        let reference = |name: &str| Schema::new_ref(format!("{}{}", DEFINITIONS_PREFIX, name));
+        let definitions = &body.root_schema.definitions;
+        assert_eq!(definitions.keys().collect::<Vec<_>>(), ["Range", "Window"]);
+        assert_eq!(definitions["Range"], range());
+        assert_eq!(
+            serde_json::to_value(&definitions["Window"]).unwrap(),
+            json!({ "type": "object", "properties": { "range": { "$ref": "#/definitions/Range" } } })
+        );
+        assert_eq!(body.constants[0].type_schema, reference("Window"));
+        assert_eq!(
+            body.state.unwrap().root,
+            AbiType::Json {
+                type_schema: serde_json::from_value(json!({
+                    "type": "object",
+                    "properties": { "current": { "$ref": "#/definitions/Range" } }
+                }))
+                .unwrap()
+            }
+        );
+        assert_eq!(
+            body.external_contracts[0].functions[0].result,
+            Some(AbiType::Json {
+                type_schema: reference("Range")
+            })
+        );
+    }
+
+    #[test]
+    fn test_dedup_counts_nested_schemas_once() {
+        let account = || -> Schema {
+            serde_json::from_value(json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string" },
+                    "limits": { "type": "object", "properties": { "max": { "type": "integer" } } }
+                }
+            }))
+            .unwrap()
+        };
+        let mut body = AbiBody {
+            functions: vec![
+                function("get_account", vec![], Some(account())),
+                function("set_account", vec![("account", account())], None),
+            ],
+            root_schema: RootSchema::default(),
+            state: None,
+            constants: Vec::new(),
+            external_contracts: Vec::new(),
+        };
+
+        // The limits are only used by the account, they stay inline in its definition.
+        assert_eq!(dedup_schemas(&mut body), 2);
+        let definitions = &body.root_schema.definitions;
+        assert_eq!(definitions.keys().collect::<Vec<_>>(), ["GetAccountResult"]);
+        assert_eq!(definitions["GetAccountResult"], account());
+    }
+}
@@ -0,0 +1,143 @@
+use super::definition_name;
+use crate::{AbiBody, AbiParameters};
+use schemars::schema::{Schema, SchemaObject};
+use std::collections::BTreeMap;
+
+/// Human-readable documentation of a root schema definition.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DefinitionDocs {
+    pub title: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Backfills the `title` and `description` of root schema definitions that lack them.
+///
+/// Documentation is taken from `docs`, keyed by definition name, first. Definitions still missing
+/// a description borrow the documentation of a JSON parameter referencing them directly, as
+/// listed in the function docs (see [`AbiDoc::params`](crate::doc::AbiDoc::params)).
+/// Definitions still missing a title are titled after their name. Existing titles and
+/// descriptions are never overwritten.
+///
+/// Returns the number of definitions that were modified.
+pub fn enrich_definitions(body: &mut AbiBody, docs: &BTreeMap<String, DefinitionDocs>) -> usize {
+    let mut param_descriptions = BTreeMap::<String, String>::new();
+    for function in &body.functions {
+        let (args, doc) = match (&function.params, function.structured_doc()) {
+            (AbiParameters::Json { args }, Some(doc)) => (args, doc),
+            _ => continue,
+        };
+        for arg in args {
+            let name = match &arg.type_schema {
+                Schema::Object(SchemaObject {
+                    reference: Some(reference),
+                    ..
+                }) => definition_name(reference),
+                _ => None,
+            };
+            let description = doc
+                .params
+                .iter()
+                .find(|(param, _)| param == &arg.name)
+                .map(|(_, description)| description);
+            if let (Some(name), Some(description)) = (name, description) {
+                param_descriptions
+                    .entry(name.to_string())
+                    .or_insert_with(|| description.clone());
+            }
+        }
+    }
+
+    let mut modified = 0;
+    for (name, schema) in body.root_schema.definitions.iter_mut() {
+        let object = match schema {
+            Schema::Object(object) => object,
+            Schema::Bool(_) => continue,
+        };
+        let before = object.metadata.clone();
+        let metadata = object.metadata();
+        let provided = docs.get(name);
+        if metadata.title.is_none() {
+            metadata.title = provided
+                .and_then(|docs| docs.title.clone())
+                .or_else(|| Some(name.clone()));
+        }
+        if metadata.description.is_none() {
+            metadata.description = provided
+                .and_then(|docs| docs.description.clone())
+                .or_else(|| param_descriptions.get(name).cloned());
+        }
+        if object.metadata != before {
+            modified += 1;
+        }
+    }
+    modified
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::DEFINITIONS_PREFIX;
+    use crate::{AbiFunction, AbiFunctionKind, AbiJsonParameter};
+    use schemars::schema::RootSchema;
+    use serde_json::json;
+
+    #[test]
+    fn test_enrich_definitions() {
+        let root_schema: RootSchema = serde_json::from_value(json!({
+            "definitions": {
+                "AccountId": { "type": "string" },
+                "U128": { "type": "string" },
+                "Memo": { "type": "string", "title": "Memo", "description": "Free-form note." }
+            }
+        }))
+        .unwrap();
+        let param = |name: &str, definition: &str| {
+            AbiJsonParameter::new(
+                name.to_string(),
+                Schema::new_ref(format!("{}{}", DEFINITIONS_PREFIX, definition)),
+            )
+        };
+        let mut body = AbiBody {
+            functions: vec![AbiFunction {
+                doc: Some(
+                    " Transfers tokens.\n\n # Arguments\n\n * `receiver_id` - Account receiving the tokens.\n * `amount` - Amount to transfer."
+                        .to_string(),
+                ),
+                params: AbiParameters::Json {
+                    args: vec![param("receiver_id", "AccountId"), param("amount", "U128")],
+                },
+                ..AbiFunction::new("ft_transfer", AbiFunctionKind::Call)
+            }],
+            root_schema,
+            state: None,
+            constants: Vec::new(),
+            external_contracts: Vec::new(),
+        };
+        let docs = BTreeMap::from([(
+            "U128".to_string(),
+            DefinitionDocs {
+                title: None,
+                description: Some("A 128-bit unsigned integer encoded as a string.".to_string()),
+            },
+        )]);
+
+        assert_eq!(enrich_definitions(&mut body, &docs), 2);
+        assert_eq!(
+            serde_json::to_value(&body.root_schema.definitions).unwrap(),
+            json!({
+                "AccountId": {
+                    "title": "AccountId",
+                    "description": "Account receiving the tokens.",
+                    "type": "string"
+                },
+                "U128": {
+                    "title": "U128",
+                    "description": "A 128-bit unsigned integer encoded as a string.",
+                    "type": "string"
+                },
+                "Memo": { "type": "string", "title": "Memo", "description": "Free-form note." }
+            })
+        );
+        assert_eq!(enrich_definitions(&mut body, &docs), 0);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::formats::{set_format, WELL_KNOWN_DEFINITIONS};
+use crate::AbiBody;
+use schemars::schema::{InstanceType, Schema, SingleOrVec};
+
+/// Annotates the root schema definitions of well-known `near-sdk` types (`AccountId`,
+/// `NearToken`, `Gas`, `Base64VecU8` and `Base58CryptoHash`) with their
+/// [`NearFormat`](crate::formats::NearFormat), so that consumers can recognize them without
+/// relying on definition names.
+///
+/// Definitions that are not plain strings or already have a format are left untouched.
+///
+/// Returns the number of definitions that were annotated.
+pub fn annotate_near_formats(body: &mut AbiBody) -> usize {
+    let mut annotated = 0;
+    for (name, format) in WELL_KNOWN_DEFINITIONS {
+        let schema = match body.definition_mut(name) {
+            Some(schema) => schema,
+            None => continue,
+        };
+        let is_untagged_string = match schema {
+            Schema::Object(object) => {
+                object.format.is_none()
+                    && matches!(
+                        &object.instance_type,
+                        Some(SingleOrVec::Single(ty)) if **ty == InstanceType::String
+                    )
+            }
+            Schema::Bool(_) => false,
+        };
+        if is_untagged_string {
+            set_format(schema, format);
+            annotated += 1;
+        }
+    }
+    annotated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::{format_of, NearFormat};
+    use serde_json::json;
+
+    #[test]
+    fn test_annotate_near_formats() {
+        let mut body: AbiBody = serde_json::from_value(json!({
+            "functions": [],
+            "root_schema": {
+                "definitions": {
+                    "AccountId": { "type": "string" },
+                    "NearToken": { "type": "string", "format": "custom" },
+                    "Gas": { "type": "integer" }
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(annotate_near_formats(&mut body), 1);
+        assert_eq!(
+            format_of(body.definition("AccountId").unwrap()),
+            Some(NearFormat::AccountId)
+        );
+        assert_eq!(annotate_near_formats(&mut body), 0);
+    }
+}
@@ -0,0 +1,64 @@
+use crate::analysis::STRING_WRAPPERS;
+use crate::AbiBody;
+use schemars::schema::{InstanceType, Schema, SingleOrVec};
+
+/// Annotates the root schema definitions of the `U64`, `U128`, `I64` and `I128` string
+/// wrappers of `near-sdk` with the `format` of the integer they encode (e.g. `uint128`), so that
+/// consumers can recognize string-encoded integers without relying on definition names.
+///
+/// Definitions that are not plain strings or already have a format are left untouched.
+///
+/// Returns the number of definitions that were annotated.
+pub fn annotate_integer_encodings(body: &mut AbiBody) -> usize {
+    let mut annotated = 0;
+    for (wrapper, _, format) in STRING_WRAPPERS {
+        let object = match body.definition_mut(wrapper) {
+            Some(Schema::Object(object)) => object,
+            _ => continue,
+        };
+        let is_string = matches!(
+            &object.instance_type,
+            Some(SingleOrVec::Single(ty)) if **ty == InstanceType::String
+        );
+        if is_string && object.format.is_none() {
+            object.format = Some(format.to_string());
+            annotated += 1;
+        }
+    }
+    annotated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{integer_encodings, IntegerEncoding};
+    use serde_json::json;
+
+    #[test]
+    fn test_annotate_integer_encodings() {
+        let mut body: AbiBody = serde_json::from_value(json!({
+            "functions": [],
+            "root_schema": {
+                "definitions": {
+                    "U128": { "type": "string" },
+                    "I64": { "type": "string", "format": "custom" },
+                    "Holder": {
+                        "type": "object",
+                        "properties": { "amount": { "$ref": "#/definitions/U128" } }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        assert_eq!(annotate_integer_encodings(&mut body), 1);
+        assert_eq!(
+            serde_json::to_value(body.definition("U128")).unwrap(),
+            json!({ "type": "string", "format": "uint128" })
+        );
+        assert_eq!(annotate_integer_encodings(&mut body), 0);
+
+        let sites = integer_encodings(&body);
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].encoding, IntegerEncoding::String);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::walk::{function_json_schemas_mut, json_type_schemas_mut};
+use crate::{AbiBody, AbiRoot, AbiState, AbiType};
+use schemars::schema::{Schema, SchemaObject, SingleOrVec};
+
+/// Strips the keywords of JSON schemas that do not affect validation: `title`, `description`,
+/// `examples` and `$comment`.
+///
+/// Every schema of the ABI is minified: the root schema and its definitions, function
+/// signatures, the contract state, constants and external contract signatures. Function and
+/// constant documentation is kept.
+///
+/// Returns the number of keywords that were removed.
+pub fn minify_schemas(body: &mut AbiBody) -> usize {
+    let mut removed = minify_object(&mut body.root_schema.schema);
+    for schema in body.root_schema.definitions.values_mut() {
+        removed += minify_schema(schema);
+    }
+    for (_, _, schema) in json_type_schemas_mut(body) {
+        removed += minify_schema(schema);
+    }
+    if let Some(AbiState {
+        root: AbiType::Json { type_schema },
+        ..
+    }) = &mut body.state
+    {
+        removed += minify_schema(type_schema);
+    }
+    for constant in &mut body.constants {
+        removed += minify_schema(&mut constant.type_schema);
+    }
+    for contract in &mut body.external_contracts {
+        for function in &mut contract.functions {
+            for (_, schema) in function_json_schemas_mut(function) {
+                removed += minify_schema(schema);
+            }
+        }
+    }
+    removed
+}
+
+/// Level of detail of the JSON schemas of a serialized ABI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SchemaDetail {
+    /// Schemas are serialized as is, for documentation purposes.
+    #[default]
+    Full,
+    /// Schemas are [minified](minify_schemas), for artifacts embedded into contracts.
+    Minified,
+}
+
+impl SchemaDetail {
+    /// Serializes `abi` into compact JSON with the given level of schema detail.
+    pub fn serialize(&self, abi: &AbiRoot) -> String {
+        match self {
+            Self::Full => serde_json::to_string(abi),
+            Self::Minified => {
+                let mut abi = abi.clone();
+                minify_schemas(&mut abi.body);
+                serde_json::to_string(&abi)
+            }
+        }
+        .expect("ABI serialization can not fail")
+    }
+}
+
+fn minify_schema(schema: &mut Schema) -> usize {
+    match schema {
+        Schema::Object(object) => minify_object(object),
+        Schema::Bool(_) => 0,
+    }
+}
+
+fn minify_object(object: &mut SchemaObject) -> usize {
+    let mut removed = 0;
+    if let Some(metadata) = &mut object.metadata {
+        removed += usize::from(metadata.title.take().is_some());
+        removed += usize::from(metadata.description.take().is_some());
+        if !metadata.examples.is_empty() {
+            metadata.examples.clear();
+            removed += 1;
+        }
+        if **metadata == Default::default() {
+            object.metadata = None;
+        }
+    }
+    removed += usize::from(object.extensions.remove("$comment").is_some());
+
+    if let Some(subschemas) = &mut object.subschemas {
+        for schemas in [
+            &mut subschemas.all_of,
+            &mut subschemas.any_of,
+            &mut subschemas.one_of,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            removed += schemas.iter_mut().map(minify_schema).sum::<usize>();
+        }
+        for schema in [
+            &mut subschemas.not,
+            &mut subschemas.if_schema,
+            &mut subschemas.then_schema,
+            &mut subschemas.else_schema,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            removed += minify_schema(schema);
+        }
+    }
+    if let Some(array) = &mut object.array {
+        match &mut array.items {
+            Some(SingleOrVec::Single(schema)) => removed += minify_schema(schema),
+            Some(SingleOrVec::Vec(schemas)) => {
+                removed += schemas.iter_mut().map(minify_schema).sum::<usize>()
+            }
+            None => {}
+        }
+        for schema in [&mut array.additional_items, &mut array.contains]
+            .into_iter()
+            .flatten()
+        {
+            removed += minify_schema(schema);
+        }
+    }
+    if let Some(validation) = &mut object.object {
+        for schema in validation
+            .properties
+            .values_mut()
+            .chain(validation.pattern_properties.values_mut())
+        {
+            removed += minify_schema(schema);
+        }
+        for schema in [
+            &mut validation.additional_properties,
+            &mut validation.property_names,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            removed += minify_schema(schema);
+        }
+    }
+    removed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_minify_schemas() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    {
+                        "name": "get_token",
+                        "doc": " Returns the token.",
+                        "kind": "view",
+                        "result": {
+                            "serialization_type": "json",
+                            "type_schema": {
+                                "description": "The token, if any.",
+                                "anyOf": [{ "$ref": "#/definitions/Token" }, { "type": "null" }]
+                            }
+                        }
+                    }
+                ],
+                "root_schema": {
+                    "$schema": "http://json-schema.org/draft-07/schema#",
+                    "title": "String",
+                    "type": "string",
+                    "definitions": {
+                        "Token": {
+                            "title": "Token",
+                            "description": "A non-fungible token.",
+                            "type": "object",
+                            "properties": {
+                                "title": {
+                                    "description": "Title of the token.",
+                                    "examples": ["Arlo #1"],
+                                    "default": "",
+                                    "type": "string"
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let mut minified = abi.clone();
+        assert_eq!(minify_schemas(&mut minified.body), 6);
+        assert_eq!(
+            serde_json::to_value(&minified.body.root_schema).unwrap(),
+            json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "string",
+                "definitions": {
+                    "Token": {
+                        "type": "object",
+                        "properties": { "title": { "default": "", "type": "string" } }
+                    }
+                }
+            })
+        );
+        assert_eq!(minified.body.functions[0].doc, abi.body.functions[0].doc);
+        assert_eq!(minify_schemas(&mut minified.body), 0);
+
+        assert_eq!(
+            SchemaDetail::Minified.serialize(&abi),
+            serde_json::to_string(&minified).unwrap()
+        );
+        assert_eq!(
+            SchemaDetail::Full.serialize(&abi),
+            serde_json::to_string(&abi).unwrap()
+        );
+    }
+}
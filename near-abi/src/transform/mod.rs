@@ -0,0 +1,85 @@
+//! Transformation passes over ABI bodies.
+//!
+//! Passes operate in place on an [`AbiBody`](crate::AbiBody) and keep it self-consistent: every `$ref` introduced
+//! by a pass points to a definition present in the resulting `root_schema`.
+//!
+//! A [`Pipeline`] runs a configurable sequence of passes over a whole ABI and reports their effect.
+
+use schemars::schema::{RootSchema, Schema};
+
+pub(crate) use crate::walk::{json_type_schemas, TypeSite};
+
+mod args;
+mod dedup;
+mod enrich;
+mod formats;
+mod integers;
+mod minify;
+mod normalize;
+mod pipeline;
+mod prune;
+mod selectors;
+
+pub use args::{flatten_args, unflatten_args, ArgsTransformError};
+pub use dedup::dedup_schemas;
+pub use enrich::{enrich_definitions, DefinitionDocs};
+pub use formats::annotate_near_formats;
+pub use integers::annotate_integer_encodings;
+pub use minify::{minify_schemas, SchemaDetail};
+pub use normalize::{canonicalize, normalize_docs, normalize_schemas};
+pub use pipeline::{AbiStats, AbiTransform, Pass, Pipeline, TransformReport};
+pub use prune::prune_definitions;
+pub use selectors::embed_selectors;
+
+/// Prefix of JSON Schema references pointing into the root schema definitions.
+pub(crate) const DEFINITIONS_PREFIX: &str = "#/definitions/";
+
+/// Name of the root schema definition a `$ref` points to, written either
+/// `#/definitions/<name>` or `#/$defs/<name>`.
+pub(crate) fn definition_name(reference: &str) -> Option<&str> {
+    reference
+        .strip_prefix(DEFINITIONS_PREFIX)
+        .or_else(|| reference.strip_prefix("#/$defs/"))
+}
+
+/// Converts a `snake_case` identifier into `PascalCase`.
+pub(crate) fn to_pascal_case(ident: &str) -> String {
+    ident
+        .split(|c: char| c == '_' || c == '-' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Follows a reference to the root schema definition it points to. Schemas
+/// that are not references are returned as is.
+pub(crate) fn resolve_ref<'a>(
+    root_schema: &'a RootSchema,
+    schema: &'a Schema,
+) -> Option<&'a Schema> {
+    match schema {
+        Schema::Object(object) => match &object.reference {
+            Some(reference) => crate::value::resolve_reference(root_schema, reference),
+            None => Some(schema),
+        },
+        Schema::Bool(_) => Some(schema),
+    }
+}
+
+/// Returns `suggested` if no definition with such name exists yet, otherwise appends the smallest
+/// numeric suffix that makes it unique.
+pub(crate) fn unique_name(definitions: &schemars::Map<String, Schema>, suggested: &str) -> String {
+    if !definitions.contains_key(suggested) {
+        return suggested.to_string();
+    }
+    (2..)
+        .map(|i| format!("{}{}", suggested, i))
+        .find(|name| !definitions.contains_key(name))
+        .unwrap()
+}
@@ -0,0 +1,297 @@
+use crate::ordering::Ordering;
+use crate::walk::{function_json_schemas_mut, json_type_schemas_mut, visit_mut};
+use crate::{AbiBody, AbiFunction, AbiRoot, AbiState, AbiType};
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+
+/// Rewrites equivalent spellings of JSON schemas into a single canonical one:
+///
+/// * `type` lists are sorted and deduplicated, and replaced with a single type when they hold
+///   only one;
+/// * `anyOf` unions of bare types, e.g. `[{ "type": "string" }, { "type": "null" }]`, are
+///   replaced with a `type` list, e.g. `["null", "string"]`;
+/// * `const` values are replaced with single-value `enum`s;
+/// * `allOf` wrappers around a single schema, without any other keyword, are replaced with the
+///   wrapped schema.
+///
+/// Every schema of the ABI is normalized, like in [`minify_schemas`](super::minify_schemas).
+///
+/// Returns the number of schemas that were rewritten.
+pub fn normalize_schemas(body: &mut AbiBody) -> usize {
+    let mut rewritten = 0;
+    for_each_json_schema_mut(body, &mut |schema| {
+        rewritten += usize::from(normalize_schema(schema));
+    });
+    rewritten
+}
+
+/// Strips insignificant whitespace from the documentation of the ABI: trailing whitespace of
+/// every line, leading and trailing blank lines, and repeated blank lines. Indentation is
+/// significant in rustdoc comments and is kept. Blank documentation is removed altogether.
+///
+/// The documentation of the contract, its functions, callbacks, constants and external
+/// contracts is normalized, as are the descriptions of JSON schemas.
+///
+/// Returns the number of documentation strings that were changed.
+pub fn normalize_docs(abi: &mut AbiRoot) -> usize {
+    let body = &mut abi.body;
+    let mut changed = usize::from(normalize_doc(&mut abi.metadata.doc));
+    for function in &mut body.functions {
+        changed += normalize_function_docs(function);
+    }
+    for constant in &mut body.constants {
+        changed += usize::from(normalize_doc(&mut constant.doc));
+    }
+    for contract in &mut body.external_contracts {
+        changed += usize::from(normalize_doc(&mut contract.doc));
+        for function in &mut contract.functions {
+            changed += normalize_function_docs(function);
+        }
+    }
+    for_each_json_schema_mut(body, &mut |schema| {
+        if let Schema::Object(SchemaObject {
+            metadata: Some(metadata),
+            ..
+        }) = schema
+        {
+            changed += usize::from(normalize_doc(&mut metadata.description));
+        }
+    });
+    changed
+}
+
+/// Brings `abi` into canonical form, so that equivalent ABIs serialize identically: schemas and
+/// documentation are normalized with [`normalize_schemas`] and [`normalize_docs`], and lists
+/// are sorted according to [`Ordering::Strict`]. Root schema definitions are always serialized
+/// sorted by name.
+///
+/// Returns the number of changes made: rewritten schemas, changed documentation strings and
+/// functions that changed position or modifiers.
+pub fn canonicalize(abi: &mut AbiRoot) -> usize {
+    let mut changes = normalize_schemas(&mut abi.body) + normalize_docs(abi);
+    let before = abi.body.functions.clone();
+    Ordering::Strict.apply(abi);
+    changes += before
+        .iter()
+        .zip(&abi.body.functions)
+        .filter(|(before, after)| before != after)
+        .count();
+    changes
+}
+
+fn normalize_function_docs(function: &mut AbiFunction) -> usize {
+    let mut changed = usize::from(normalize_doc(&mut function.doc));
+    for callback in &mut function.callbacks {
+        changed += usize::from(normalize_doc(&mut callback.doc));
+    }
+    changed
+}
+
+/// Calls `f` on every JSON schema of `body` and the schemas nested in them.
+fn for_each_json_schema_mut(body: &mut AbiBody, f: &mut impl FnMut(&mut Schema)) {
+    let root = &mut body.root_schema;
+    let mut root_schema = Schema::Object(std::mem::take(&mut root.schema));
+    visit_mut(&mut root_schema, f);
+    if let Schema::Object(object) = root_schema {
+        root.schema = object;
+    }
+    for schema in root.definitions.values_mut() {
+        visit_mut(schema, f);
+    }
+    for (_, _, schema) in json_type_schemas_mut(body) {
+        visit_mut(schema, f);
+    }
+    if let Some(AbiState {
+        root: AbiType::Json { type_schema },
+        ..
+    }) = &mut body.state
+    {
+        visit_mut(type_schema, f);
+    }
+    for constant in &mut body.constants {
+        visit_mut(&mut constant.type_schema, f);
+    }
+    for contract in &mut body.external_contracts {
+        for function in &mut contract.functions {
+            for (_, schema) in function_json_schemas_mut(function) {
+                visit_mut(schema, f);
+            }
+        }
+    }
+}
+
+fn normalize_doc(doc: &mut Option<String>) -> bool {
+    let text = match doc {
+        Some(text) => text,
+        None => return false,
+    };
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        let blank = line.is_empty();
+        if blank && lines.last().map_or(true, |last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    if lines.last() == Some(&"") {
+        lines.pop();
+    }
+    let normalized = lines.join("\n");
+    let normalized = if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized)
+    };
+    let changed = normalized != *doc;
+    *doc = normalized;
+    changed
+}
+
+fn normalize_schema(schema: &mut Schema) -> bool {
+    let object = match schema {
+        Schema::Object(object) => object,
+        Schema::Bool(_) => return false,
+    };
+    if let Some(wrapped) = single_all_of(object) {
+        *schema = wrapped;
+        normalize_schema(schema);
+        return true;
+    }
+    let mut changed = false;
+    if let Some(types) = bare_type_union(object) {
+        object.subschemas = None;
+        object.instance_type = Some(SingleOrVec::Vec(types));
+        changed = true;
+    }
+    if let Some(value) = object.const_value.take() {
+        object.enum_values = Some(vec![value]);
+        changed = true;
+    }
+    if let Some(types) = &mut object.instance_type {
+        let mut sorted = match types {
+            SingleOrVec::Single(ty) => vec![**ty],
+            SingleOrVec::Vec(types) => types.clone(),
+        };
+        sorted.sort();
+        sorted.dedup();
+        let normalized = match sorted.as_slice() {
+            [ty] => SingleOrVec::Single(Box::new(*ty)),
+            _ => SingleOrVec::Vec(sorted),
+        };
+        changed |= *types != normalized;
+        *types = normalized;
+    }
+    changed
+}
+
+/// The schema wrapped by `object` if it is a single-member `allOf` without other keywords.
+fn single_all_of(object: &SchemaObject) -> Option<Schema> {
+    let subschemas = object.subschemas.as_deref()?;
+    match subschemas.all_of.as_deref() {
+        Some([wrapped]) => {
+            let rest = SchemaObject {
+                subschemas: None,
+                ..object.clone()
+            };
+            let only_all_of = subschemas.any_of.is_none()
+                && subschemas.one_of.is_none()
+                && subschemas.not.is_none()
+                && subschemas.if_schema.is_none()
+                && subschemas.then_schema.is_none()
+                && subschemas.else_schema.is_none();
+            (only_all_of && rest == SchemaObject::default()).then(|| wrapped.clone())
+        }
+        _ => None,
+    }
+}
+
+/// The types of the variants of `object` if it is an `anyOf` union of schemas made of a `type`
+/// only, without other keywords.
+fn bare_type_union(object: &SchemaObject) -> Option<Vec<InstanceType>> {
+    let subschemas = object.subschemas.as_deref()?;
+    let variants = subschemas.any_of.as_ref()?;
+    let only_any_of = subschemas.all_of.is_none()
+        && subschemas.one_of.is_none()
+        && subschemas.not.is_none()
+        && subschemas.if_schema.is_none()
+        && subschemas.then_schema.is_none()
+        && subschemas.else_schema.is_none();
+    if !only_any_of || object.instance_type.is_some() || variants.is_empty() {
+        return None;
+    }
+    let mut types = Vec::new();
+    for variant in variants {
+        let variant = match variant {
+            Schema::Object(variant) => variant,
+            Schema::Bool(_) => return None,
+        };
+        let bare = SchemaObject {
+            instance_type: None,
+            ..variant.clone()
+        };
+        if bare != SchemaObject::default() {
+            return None;
+        }
+        match &variant.instance_type {
+            Some(SingleOrVec::Single(ty)) => types.push(**ty),
+            Some(SingleOrVec::Vec(variant_types)) => types.extend(variant_types),
+            None => return None,
+        }
+    }
+    Some(types)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonicalize() {
+        let abi = |functions: serde_json::Value, definitions: serde_json::Value| {
+            AbiRoot::from_value(json!({
+                "schema_version": SCHEMA_VERSION,
+                "metadata": { "doc": "\n Token contract.  \n\n\n More.\n" },
+                "body": {
+                    "functions": functions,
+                    "root_schema": { "definitions": definitions }
+                }
+            }))
+            .unwrap()
+        };
+        let result = |schema: serde_json::Value| json!({ "serialization_type": "json", "type_schema": schema });
+        let mut spelled = abi(
+            json!([
+                { "name": "set", "kind": "call", "doc": " Sets the memo.\n   ", "modifiers": ["private", "payable"] },
+                { "name": "get", "kind": "view", "result": result(json!({
+                    "anyOf": [{ "type": "string" }, { "type": "null" }]
+                })) }
+            ]),
+            json!({
+                "Memo": { "allOf": [{ "$ref": "#/definitions/Text" }] },
+                "Text": { "type": ["string", "string"] },
+                "Mode": { "const": "fast" }
+            }),
+        );
+        let mut canonical = abi(
+            json!([
+                { "name": "get", "kind": "view", "result": result(json!({ "type": ["null", "string"] })) },
+                { "name": "set", "kind": "call", "doc": " Sets the memo.", "modifiers": ["payable", "private"] }
+            ]),
+            json!({
+                "Memo": { "$ref": "#/definitions/Text" },
+                "Text": { "type": "string" },
+                "Mode": { "enum": ["fast"] }
+            }),
+        );
+
+        assert_eq!(canonicalize(&mut spelled), 8);
+        assert_eq!(
+            spelled.metadata.doc.as_deref(),
+            Some(" Token contract.\n\n More.")
+        );
+        canonical.normalize();
+        assert_eq!(spelled, canonical);
+        assert_eq!(canonicalize(&mut spelled), 0);
+    }
+}
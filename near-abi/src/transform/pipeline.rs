@@ -0,0 +1,224 @@
+use super::{
+    annotate_integer_encodings, annotate_near_formats, canonicalize, dedup_schemas, minify_schemas,
+    prune_definitions,
+};
+use crate::legacy::move_links;
+use crate::AbiRoot;
+use serde::{Deserialize, Serialize};
+
+/// A transformation of an ABI that can be run as part of a [`Pipeline`].
+pub trait AbiTransform {
+    /// Name of the transformation, used in reports.
+    fn name(&self) -> &str;
+
+    /// Transforms `abi` in place and returns the number of changes made, in units specific to
+    /// the transformation.
+    fn apply(&self, abi: &mut AbiRoot) -> usize;
+}
+
+/// The transformation passes of this crate, for declarative pipeline configurations (e.g.
+/// `["prune", "dedup", "minify"]`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pass {
+    /// [`prune_definitions`], counting removed definitions.
+    Prune,
+    /// [`dedup_schemas`], counting inline schemas replaced with references.
+    Dedup,
+    /// [`move_links`], [`annotate_integer_encodings`] and [`annotate_near_formats`], counting
+    /// moved links and annotated schemas.
+    Normalize,
+    /// [`minify_schemas`], counting removed keywords.
+    Minify,
+    /// [`canonicalize`], counting rewritten schemas, changed documentation and functions that
+    /// changed position or modifiers.
+    Canonicalize,
+}
+
+impl Pass {
+    /// All passes, in the order of [`Pipeline::default`].
+    pub const ALL: [Pass; 5] = [
+        Pass::Prune,
+        Pass::Dedup,
+        Pass::Normalize,
+        Pass::Minify,
+        Pass::Canonicalize,
+    ];
+}
+
+impl AbiTransform for Pass {
+    fn name(&self) -> &str {
+        match self {
+            Self::Prune => "prune",
+            Self::Dedup => "dedup",
+            Self::Normalize => "normalize",
+            Self::Minify => "minify",
+            Self::Canonicalize => "canonicalize",
+        }
+    }
+
+    fn apply(&self, abi: &mut AbiRoot) -> usize {
+        match self {
+            Self::Prune => prune_definitions(&mut abi.body).len(),
+            Self::Dedup => dedup_schemas(&mut abi.body),
+            Self::Normalize => {
+                move_links(&mut abi.metadata)
+                    + annotate_integer_encodings(&mut abi.body)
+                    + annotate_near_formats(&mut abi.body)
+            }
+            Self::Minify => minify_schemas(&mut abi.body),
+            Self::Canonicalize => canonicalize(abi),
+        }
+    }
+}
+
+/// An ordered list of transformations.
+pub struct Pipeline {
+    transforms: Vec<Box<dyn AbiTransform>>,
+}
+
+impl Default for Pipeline {
+    /// All passes of this crate: prune, dedup, normalize, minify and canonicalize.
+    fn default() -> Self {
+        Self::from_passes(&Pass::ALL)
+    }
+}
+
+impl Pipeline {
+    /// A pipeline without transformations.
+    pub fn new() -> Self {
+        Self {
+            transforms: Vec::new(),
+        }
+    }
+
+    /// A pipeline running `passes` in the given order.
+    pub fn from_passes(passes: &[Pass]) -> Self {
+        let mut pipeline = Self::new();
+        for pass in passes {
+            pipeline.push(*pass);
+        }
+        pipeline
+    }
+
+    /// Appends a transformation to the pipeline.
+    pub fn push(&mut self, transform: impl AbiTransform + 'static) {
+        self.transforms.push(Box::new(transform));
+    }
+
+    /// Names of the transformations, in order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.transforms.iter().map(|transform| transform.name())
+    }
+
+    /// Runs the transformations on `abi` in order, reporting the effect of each.
+    pub fn run(&self, abi: &mut AbiRoot) -> Vec<TransformReport> {
+        self.transforms
+            .iter()
+            .map(|transform| {
+                let before = AbiStats::new(abi);
+                let changes = transform.apply(abi);
+                TransformReport {
+                    transform: transform.name().to_string(),
+                    changes,
+                    before,
+                    after: AbiStats::new(abi),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Effect of a transformation on an ABI.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TransformReport {
+    pub transform: String,
+    /// Number of changes reported by the transformation.
+    pub changes: usize,
+    pub before: AbiStats,
+    pub after: AbiStats,
+}
+
+/// Size of an ABI.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct AbiStats {
+    pub functions: usize,
+    pub definitions: usize,
+    /// Length of the compact JSON serialization, in bytes.
+    pub bytes: usize,
+}
+
+impl AbiStats {
+    pub fn new(abi: &AbiRoot) -> Self {
+        Self {
+            functions: abi.body.functions.len(),
+            definitions: abi.body.root_schema.definitions.len(),
+            bytes: serde_json::to_vec(abi)
+                .expect("ABI serialization can not fail")
+                .len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    struct Rename;
+
+    impl AbiTransform for Rename {
+        fn name(&self) -> &str {
+            "rename"
+        }
+
+        fn apply(&self, abi: &mut AbiRoot) -> usize {
+            abi.metadata.name = Some("renamed".to_string());
+            1
+        }
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let mut abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    { "name": "set", "kind": "call", "modifiers": ["private", "payable"] },
+                    { "name": "get", "kind": "view", "result": { "serialization_type": "json", "type_schema": { "$ref": "#/definitions/Value", "description": "Stored value." } } }
+                ],
+                "root_schema": {
+                    "definitions": { "Value": { "type": "string", "title": "Value" }, "Unused": { "type": "integer" } }
+                }
+            }
+        }))
+        .unwrap();
+
+        let passes: Vec<Pass> =
+            serde_json::from_value(json!(["prune", "minify", "canonicalize"])).unwrap();
+        let mut pipeline = Pipeline::from_passes(&passes);
+        pipeline.push(Rename);
+        assert_eq!(
+            pipeline.names().collect::<Vec<_>>(),
+            ["prune", "minify", "canonicalize", "rename"]
+        );
+
+        let reports = pipeline.run(&mut abi);
+        assert_eq!(
+            reports.iter().map(|r| r.changes).collect::<Vec<_>>(),
+            [1, 2, 2, 1]
+        );
+        assert_eq!(reports[0].before.definitions, 2);
+        assert_eq!(reports[0].after.definitions, 1);
+        assert!(reports[1].after.bytes < reports[1].before.bytes);
+        assert_eq!(abi.body.functions[0].name, "get");
+        assert_eq!(abi.metadata.name.as_deref(), Some("renamed"));
+
+        assert_eq!(
+            Pipeline::default().names().collect::<Vec<_>>(),
+            ["prune", "dedup", "normalize", "minify", "canonicalize"]
+        );
+    }
+}
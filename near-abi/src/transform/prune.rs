@@ -0,0 +1,46 @@
+use super::json_type_schemas;
+use crate::walk::{function_json_schemas, reachable_definitions};
+use crate::{AbiBody, AbiState, AbiType};
+use schemars::schema::Schema;
+
+/// Removes root schema definitions that are not reachable from any function signature, from the
+/// contract state, from a constant or from a signature of an external contract function.
+///
+/// Returns the names of the removed definitions.
+pub fn prune_definitions(body: &mut AbiBody) -> Vec<String> {
+    let root = Schema::Object(body.root_schema.schema.clone());
+    let state = match &body.state {
+        Some(AbiState {
+            root: AbiType::Json { type_schema },
+            ..
+        }) => Some(type_schema),
+        _ => None,
+    };
+    let reachable = reachable_definitions(
+        &body.root_schema,
+        json_type_schemas(body)
+            .into_iter()
+            .map(|(_, _, schema)| schema)
+            .chain(std::iter::once(&root))
+            .chain(state)
+            .chain(body.constants.iter().map(|constant| &constant.type_schema))
+            .chain(
+                body.external_contracts
+                    .iter()
+                    .flat_map(|contract| &contract.functions)
+                    .flat_map(function_json_schemas)
+                    .map(|(_, schema)| schema),
+            ),
+    );
+    let unreachable: Vec<String> = body
+        .root_schema
+        .definitions
+        .keys()
+        .filter(|name| !reachable.contains(*name))
+        .cloned()
+        .collect();
+    for name in &unreachable {
+        body.root_schema.definitions.remove(name);
+    }
+    unreachable
+}
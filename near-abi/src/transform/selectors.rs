@@ -0,0 +1,17 @@
+use crate::AbiBody;
+
+/// Embeds the [selector](crate::AbiFunction::selector) of every function into its declaration,
+/// replacing selectors that do not match the current signature of the function.
+///
+/// Returns the number of functions whose selector was set or replaced.
+pub fn embed_selectors(body: &mut AbiBody) -> usize {
+    let mut embedded = 0;
+    for function in &mut body.functions {
+        let selector = function.selector();
+        if function.selector != Some(selector) {
+            function.selector = Some(selector);
+            embedded += 1;
+        }
+    }
+    embedded
+}
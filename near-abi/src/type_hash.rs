@@ -0,0 +1,189 @@
+//! Content-addressed identifiers of type definitions.
+//!
+//! The [`TypeHash`] of a root schema definition is the SHA-256 hash of its schema along with the
+//! schemas of all the definitions it references, so that two ABIs defining a type identically
+//! (e.g. `TokenMetadata` in two NEP-171 contracts) assign it the same hash, and tools can share
+//! the code generated for it.
+
+use crate::walk::reachable_definitions;
+use crate::wasm_hash::sha256;
+use crate::AbiBody;
+use schemars::schema::{RootSchema, Schema};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Extension key the hash of a definition is embedded under by [`embed_type_hashes`].
+pub const TYPE_HASH_KEY: &str = "x-type-hash";
+
+/// SHA-256 hash identifying the content of a type definition, written in hex.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TypeHash([u8; 32]);
+
+impl TypeHash {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Debug for TypeHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TypeHash({})", self)
+    }
+}
+
+impl fmt::Display for TypeHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hashes of all root schema definitions, by definition name.
+///
+/// A definition is hashed together with the definitions it transitively references, under their
+/// names, so renaming a referenced type changes the hash. Embedded hashes (see
+/// [`embed_type_hashes`]) are ignored.
+pub fn type_hashes(root_schema: &RootSchema) -> BTreeMap<String, TypeHash> {
+    root_schema
+        .definitions
+        .iter()
+        .map(|(name, schema)| {
+            let mut closure = BTreeMap::new();
+            closure.insert(name.as_str(), canonical_value(schema));
+            for dependency in reachable_definitions(root_schema, std::iter::once(schema)) {
+                if let Some((dependency, schema)) =
+                    root_schema.definitions.get_key_value(&dependency)
+                {
+                    closure.insert(dependency.as_str(), canonical_value(schema));
+                }
+            }
+            // The name of the definition itself is not part of its content.
+            let content = serde_json::json!({
+                "root": closure.remove(name.as_str()),
+                "definitions": closure,
+            });
+            let mut canonical = String::new();
+            write_canonical(&content, &mut canonical);
+            (name.clone(), TypeHash(sha256(canonical.as_bytes())))
+        })
+        .collect()
+}
+
+/// Embeds the hash of every root schema definition under the [`TYPE_HASH_KEY`] extension of the
+/// definition, replacing outdated hashes.
+///
+/// Returns the number of definitions whose hash was set or replaced.
+pub fn embed_type_hashes(body: &mut AbiBody) -> usize {
+    let mut embedded = 0;
+    for (name, hash) in type_hashes(&body.root_schema) {
+        if let Some(Schema::Object(object)) = body.definition_mut(&name) {
+            let hash = Value::String(hash.to_string());
+            if object.extensions.get(TYPE_HASH_KEY) != Some(&hash) {
+                object.extensions.insert(TYPE_HASH_KEY.to_string(), hash);
+                embedded += 1;
+            }
+        }
+    }
+    embedded
+}
+
+fn canonical_value(schema: &Schema) -> Value {
+    let mut value = serde_json::to_value(schema).expect("schema serialization can not fail");
+    if let Value::Object(map) = &mut value {
+        map.remove(TYPE_HASH_KEY);
+    }
+    value
+}
+
+/// Writes `value` as compact JSON with object keys sorted, regardless of how `serde_json` is
+/// configured to order maps.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn body(definitions: Value) -> AbiBody {
+        serde_json::from_value(json!({
+            "functions": [],
+            "root_schema": { "definitions": definitions }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_type_hashes() {
+        let metadata = json!({
+            "type": "object",
+            "properties": { "title": { "type": "string" }, "media": { "$ref": "#/definitions/Media" } }
+        });
+        let nft = body(json!({
+            "TokenMetadata": metadata,
+            "Media": { "type": "string" },
+            "Token": { "type": "object", "properties": { "metadata": { "$ref": "#/definitions/TokenMetadata" } } }
+        }));
+        let marketplace = body(json!({
+            "Metadata": metadata,
+            "Media": { "type": "string" }
+        }));
+        let changed = body(json!({
+            "TokenMetadata": metadata,
+            "Media": { "type": "string", "format": "uri" }
+        }));
+
+        let nft_hashes = type_hashes(&nft.root_schema);
+        assert_eq!(nft_hashes.len(), 3);
+        // Identical content under a different name.
+        assert_eq!(
+            nft_hashes["TokenMetadata"],
+            type_hashes(&marketplace.root_schema)["Metadata"]
+        );
+        // A referenced definition changed.
+        assert_ne!(
+            nft_hashes["TokenMetadata"],
+            type_hashes(&changed.root_schema)["TokenMetadata"]
+        );
+        assert_eq!(nft_hashes["Media"].to_string().len(), 64);
+
+        let mut embedded = nft.clone();
+        assert_eq!(embed_type_hashes(&mut embedded), 3);
+        assert_eq!(
+            serde_json::to_value(embedded.definition("Media")).unwrap()[TYPE_HASH_KEY],
+            json!(nft_hashes["Media"].to_string())
+        );
+        assert_eq!(type_hashes(&embedded.root_schema), nft_hashes);
+        assert_eq!(embed_type_hashes(&mut embedded), 0);
+    }
+}
@@ -0,0 +1,518 @@
+//! Internal consistency checks of ABIs.
+//!
+//! Deserialization only checks the shape of an ABI. [`validate`] additionally checks that its
+//! parts agree with each other, e.g. that every `$ref` resolves into the root schema (see
+//! [`dangling_references`]). A
+//! [`Validated`] ABI is known to pass these checks.
+
+use crate::analysis::{borsh_schema_errors, BorshSchemaError};
+use crate::path::AbiPath;
+use crate::walk::{function_borsh_schemas, function_json_schemas, visit};
+use crate::{
+    version, AbiBody, AbiFunction, AbiFunctionKind, AbiFunctionModifier, AbiRoot, SCHEMA_SEMVER,
+};
+use schemars::schema::Schema;
+use semver::Version;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::Deref;
+
+/// An inconsistency found by [`validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// Element of the ABI the violation was found in, `None` for the ABI as a whole.
+    pub location: Option<AbiPath>,
+    pub kind: ViolationKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// `schema_version` is not a semver version compatible with the structure of this crate.
+    UnsupportedSchemaVersion(String),
+    /// A function name is not a valid NEAR method name.
+    InvalidMethodName(MethodNameError),
+    /// Several functions share the same name.
+    DuplicateFunction,
+    /// A modifier of a function contradicts its kind or other modifiers.
+    InvalidModifier(ModifierError),
+    /// A function is declared as returning nothing but has a result type.
+    ResultOfFunctionReturningNothing,
+    /// A `$ref` does not resolve to a definition of the root schema.
+    UnresolvedReference(String),
+    /// A Borsh type schema can not describe actual values, see [`borsh_schema_errors`].
+    InvalidBorshSchema(BorshSchemaError),
+    /// A test vector refers to a function that is not declared.
+    TestVectorOfUnknownFunction(String),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(location) = &self.location {
+            write!(f, "{}: ", location)?;
+        }
+        match &self.kind {
+            ViolationKind::UnsupportedSchemaVersion(version) => write!(
+                f,
+                "schema version `{}` is not compatible with {}",
+                version, SCHEMA_SEMVER
+            ),
+            ViolationKind::InvalidMethodName(error) => write!(f, "{}", error),
+            ViolationKind::InvalidModifier(error) => write!(f, "{}", error),
+            ViolationKind::DuplicateFunction => write!(f, "function is declared more than once"),
+            ViolationKind::ResultOfFunctionReturningNothing => {
+                write!(f, "function returns nothing but declares a result")
+            }
+            ViolationKind::UnresolvedReference(reference) => {
+                write!(f, "reference `{}` does not resolve", reference)
+            }
+            ViolationKind::InvalidBorshSchema(error) => write!(f, "{}", error),
+            ViolationKind::TestVectorOfUnknownFunction(function) => write!(
+                f,
+                "test vector refers to undeclared function `{}`",
+                function
+            ),
+        }
+    }
+}
+
+/// Checks the internal consistency of an ABI, returning every violation found.
+pub fn validate(abi: &AbiRoot) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut violation = |location, kind| violations.push(Violation { location, kind });
+
+    let version_is_supported = Version::parse(&abi.schema_version).map_or(false, |version| {
+        version::is_compatible(&version, &SCHEMA_SEMVER)
+    });
+    if !version_is_supported {
+        violation(
+            None,
+            ViolationKind::UnsupportedSchemaVersion(abi.schema_version.clone()),
+        );
+    }
+
+    let body = &abi.body;
+    let mut names = BTreeSet::new();
+    for function in &body.functions {
+        let location = || Some(AbiPath::Function(function.name.clone()));
+        if let Err(error) = check_method_name(&function.name) {
+            violation(location(), ViolationKind::InvalidMethodName(error));
+        } else if !names.insert(function.name.as_str()) {
+            violation(location(), ViolationKind::DuplicateFunction);
+        }
+        for error in modifier_errors(function) {
+            violation(location(), ViolationKind::InvalidModifier(error));
+        }
+        if function.returns_nothing && function.result.is_some() {
+            violation(location(), ViolationKind::ResultOfFunctionReturningNothing);
+        }
+        for (site, container) in function_borsh_schemas(function) {
+            for error in borsh_schema_errors(container) {
+                violation(
+                    Some(AbiPath::from_site(function, &site)),
+                    ViolationKind::InvalidBorshSchema(error),
+                );
+            }
+        }
+    }
+    for dangling in dangling_references(body) {
+        violation(
+            Some(dangling.location),
+            ViolationKind::UnresolvedReference(dangling.reference),
+        );
+    }
+
+    for vector in &abi.test_vectors {
+        if !names.contains(vector.function.as_str()) {
+            violation(
+                None,
+                ViolationKind::TestVectorOfUnknownFunction(vector.function.clone()),
+            );
+        }
+    }
+    violations
+}
+
+/// Maximum length of a method name in bytes, as enforced by the NEAR runtime.
+pub const MAX_METHOD_NAME_LEN: usize = 256;
+
+/// Reason a function name is not a valid NEAR method name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MethodNameError {
+    Empty,
+    /// The name is longer than [`MAX_METHOD_NAME_LEN`] bytes.
+    TooLong(usize),
+    /// The name contains whitespace or control characters, which can not be exported.
+    InvalidChar(char),
+}
+
+impl std::error::Error for MethodNameError {}
+impl fmt::Display for MethodNameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "method name is empty"),
+            Self::TooLong(len) => write!(
+                f,
+                "method name is {} bytes long, at most {} are allowed",
+                len, MAX_METHOD_NAME_LEN
+            ),
+            Self::InvalidChar(c) => write!(f, "method name contains invalid character {:?}", c),
+        }
+    }
+}
+
+/// Checks that `name` can be exported from a contract and called as a NEAR method.
+///
+/// Deserialization rejects functions with invalid names, unless parsing leniently (see
+/// [`AbiRoot::from_value_lenient`]).
+pub fn check_method_name(name: &str) -> Result<(), MethodNameError> {
+    if name.is_empty() {
+        return Err(MethodNameError::Empty);
+    }
+    if name.len() > MAX_METHOD_NAME_LEN {
+        return Err(MethodNameError::TooLong(name.len()));
+    }
+    match name.chars().find(|c| c.is_whitespace() || c.is_control()) {
+        Some(c) => Err(MethodNameError::InvalidChar(c)),
+        None => Ok(()),
+    }
+}
+
+/// A modifier of a function that contradicts its kind or other modifiers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModifierError {
+    /// The modifier can not be used on functions of this kind, e.g. `payable` on a view function.
+    InvalidForKind {
+        modifier: AbiFunctionModifier,
+        kind: AbiFunctionKind,
+    },
+    /// The modifier can only be used together with another one, e.g. `ignore_state` with `init`.
+    Requires {
+        modifier: AbiFunctionModifier,
+        required: AbiFunctionModifier,
+    },
+    /// The modifier is listed more than once.
+    Duplicate(AbiFunctionModifier),
+}
+
+impl std::error::Error for ModifierError {}
+impl fmt::Display for ModifierError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidForKind { modifier, kind } => {
+                let kind = match kind {
+                    AbiFunctionKind::View => "view",
+                    AbiFunctionKind::Call => "call",
+                };
+                write!(
+                    f,
+                    "`{}` modifier can not be used on {} functions",
+                    modifier.as_str(),
+                    kind
+                )
+            }
+            Self::Requires { modifier, required } => write!(
+                f,
+                "`{}` modifier requires the `{}` modifier",
+                modifier.as_str(),
+                required.as_str()
+            ),
+            Self::Duplicate(modifier) => {
+                write!(
+                    f,
+                    "`{}` modifier is listed more than once",
+                    modifier.as_str()
+                )
+            }
+        }
+    }
+}
+
+/// Checks that the modifiers of `function` agree with its kind and with each other, in the order
+/// the modifiers are listed.
+///
+/// View functions are executed without a transaction, so they can not be `init`, `private`,
+/// `payable`, `upgrade` or `migrate` functions. Call functions can not be `pure`, and
+/// `ignore_state` only applies to `init` functions. Modifiers unknown to this crate are not
+/// checked.
+pub fn modifier_errors(function: &AbiFunction) -> Vec<ModifierError> {
+    let mut errors = Vec::new();
+    for (i, modifier) in function.modifiers.iter().enumerate() {
+        if function.modifiers[..i].contains(modifier) {
+            errors.push(ModifierError::Duplicate(modifier.clone()));
+            continue;
+        }
+        let valid_for_kind = match modifier {
+            AbiFunctionModifier::Init
+            | AbiFunctionModifier::IgnoreState
+            | AbiFunctionModifier::Private
+            | AbiFunctionModifier::Payable
+            | AbiFunctionModifier::Upgrade
+            | AbiFunctionModifier::Migrate => function.kind == AbiFunctionKind::Call,
+            AbiFunctionModifier::Pure => function.kind == AbiFunctionKind::View,
+            AbiFunctionModifier::Unknown(_) => true,
+        };
+        if !valid_for_kind {
+            errors.push(ModifierError::InvalidForKind {
+                modifier: modifier.clone(),
+                kind: function.kind.clone(),
+            });
+        }
+        if *modifier == AbiFunctionModifier::IgnoreState
+            && !function.modifiers.contains(&AbiFunctionModifier::Init)
+        {
+            errors.push(ModifierError::Requires {
+                modifier: modifier.clone(),
+                required: AbiFunctionModifier::Init,
+            });
+        }
+    }
+    errors
+}
+
+/// A `$ref` that does not resolve to a definition of the root schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DanglingReference {
+    /// Where the reference occurs: a parameter, callback or result of a function, a root schema
+    /// definition or a constant.
+    pub location: AbiPath,
+    pub reference: String,
+}
+
+/// Lists the references in function signatures, root schema definitions and constants that do
+/// not point to a definition of the root schema, in the order they appear in the ABI.
+///
+/// References of the form `#/definitions/<name>` and `#/$defs/<name>` resolve if the root schema
+/// has a definition `<name>`. Other references, e.g. to external documents, are reported as
+/// dangling since consumers of the ABI can not resolve them either.
+pub fn dangling_references(body: &AbiBody) -> Vec<DanglingReference> {
+    let mut schemas = Vec::new();
+    for function in &body.functions {
+        for (site, schema) in function_json_schemas(function) {
+            schemas.push((AbiPath::from_site(function, &site), schema));
+        }
+    }
+    for (name, schema) in body.definitions() {
+        schemas.push((AbiPath::Definition(name.to_string()), schema));
+    }
+    for constant in &body.constants {
+        schemas.push((
+            AbiPath::Constant(constant.name.clone()),
+            &constant.type_schema,
+        ));
+    }
+    let mut dangling = Vec::new();
+    for (location, schema) in schemas {
+        visit(schema, &mut |schema| {
+            if let Schema::Object(object) = schema {
+                if let Some(reference) = &object.reference {
+                    if body.resolve_reference(reference).is_none() {
+                        dangling.push(DanglingReference {
+                            location: location.clone(),
+                            reference: reference.clone(),
+                        });
+                    }
+                }
+            }
+        });
+    }
+    dangling
+}
+
+/// An ABI that passed [`validate`]. Deserializing it fails on violations.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Validated<T>(T);
+
+impl Validated<AbiRoot> {
+    /// Validates `abi`, returning the violations found if any.
+    pub fn new(abi: AbiRoot) -> Result<Self, Vec<Violation>> {
+        let violations = validate(&abi);
+        if violations.is_empty() {
+            Ok(Self(abi))
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+impl<T> Validated<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Validated<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> AsRef<T> for Validated<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<'de> Deserialize<'de> for Validated<AbiRoot> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let abi = AbiRoot::deserialize(deserializer)?;
+        Self::new(abi).map_err(|violations| {
+            let violations = violations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+            de::Error::custom(format!("invalid ABI: {}", violations.join("; ")))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SCHEMA_VERSION;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate() {
+        let mut value = json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    {
+                        "name": "get",
+                        "kind": "view",
+                        "params": {
+                            "serialization_type": "json",
+                            "args": [{ "name": "id", "type_schema": { "$ref": "#/definitions/Id" } }]
+                        }
+                    },
+                    { "name": "get", "kind": "view" },
+                    { "name": "", "kind": "call", "returns_nothing": true, "result": { "serialization_type": "json", "type_schema": {} } }
+                ],
+                "root_schema": {
+                    "definitions": {
+                        "Holder": { "properties": { "id": { "$ref": "#/$defs/Id" } } }
+                    }
+                }
+            },
+            "test_vectors": [{ "function": "set", "input": "", "expected": null }]
+        });
+        let mut abi = AbiRoot::from_value_lenient(value.clone()).unwrap();
+        abi.schema_version = "1.0.0".to_string();
+        assert_eq!(
+            validate(&abi)
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            [
+                format!(
+                    "schema version `1.0.0` is not compatible with {}",
+                    SCHEMA_VERSION
+                ),
+                "functions/get: function is declared more than once".to_string(),
+                "functions/: method name is empty".to_string(),
+                "functions/: function returns nothing but declares a result".to_string(),
+                "functions/get/params/id: reference `#/definitions/Id` does not resolve"
+                    .to_string(),
+                "definitions/Holder: reference `#/$defs/Id` does not resolve".to_string(),
+                "test vector refers to undeclared function `set`".to_string(),
+            ]
+        );
+        let err = AbiRoot::from_value(value.clone()).unwrap_err();
+        assert!(err.to_string().starts_with("method name is empty"));
+        value["body"]["functions"][2]["name"] = json!("set");
+        let err = serde_json::from_value::<Validated<AbiRoot>>(value).unwrap_err();
+        assert!(err.to_string().starts_with("invalid ABI: functions/get: "));
+
+        abi.schema_version = SCHEMA_VERSION.to_string();
+        abi.body.functions.truncate(1);
+        abi.test_vectors.clear();
+        assert_eq!(
+            dangling_references(&abi.body),
+            [
+                DanglingReference {
+                    location: AbiPath::Param {
+                        function: "get".to_string(),
+                        param: "id".to_string(),
+                    },
+                    reference: "#/definitions/Id".to_string(),
+                },
+                DanglingReference {
+                    location: AbiPath::Definition("Holder".to_string()),
+                    reference: "#/$defs/Id".to_string(),
+                }
+            ]
+        );
+        abi.body
+            .root_schema
+            .definitions
+            .insert("Id".to_string(), Schema::Bool(true));
+        assert_eq!(dangling_references(&abi.body), []);
+        let validated = Validated::new(abi.clone()).unwrap();
+        assert_eq!(validated.body.functions.len(), 1);
+        assert_eq!(validated.into_inner(), abi);
+    }
+
+    #[test]
+    fn test_check_method_name() {
+        assert_eq!(check_method_name("ft_transfer"), Ok(()));
+        assert_eq!(check_method_name("admin::set_fee"), Ok(()));
+        assert_eq!(check_method_name(""), Err(MethodNameError::Empty));
+        assert_eq!(
+            check_method_name("ft transfer"),
+            Err(MethodNameError::InvalidChar(' '))
+        );
+        assert_eq!(
+            check_method_name(&"a".repeat(257)),
+            Err(MethodNameError::TooLong(257))
+        );
+    }
+
+    #[test]
+    fn test_modifier_errors() {
+        let abi = AbiRoot::from_value(json!({
+            "schema_version": SCHEMA_VERSION,
+            "metadata": {},
+            "body": {
+                "functions": [
+                    { "name": "new", "kind": "call", "modifiers": ["init", "ignore_state"] },
+                    { "name": "get", "kind": "view", "modifiers": ["payable", "init", "pure"] },
+                    { "name": "reset", "kind": "call", "modifiers": ["ignore_state", "pure", "private", "private"] }
+                ],
+                "root_schema": {}
+            }
+        }))
+        .unwrap();
+        let errors = |i: usize| {
+            modifier_errors(&abi.body.functions[i])
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(errors(0), Vec::<String>::new());
+        assert_eq!(
+            errors(1),
+            [
+                "`payable` modifier can not be used on view functions",
+                "`init` modifier can not be used on view functions",
+            ]
+        );
+        assert_eq!(
+            errors(2),
+            [
+                "`ignore_state` modifier requires the `init` modifier",
+                "`pure` modifier can not be used on call functions",
+                "`private` modifier is listed more than once",
+            ]
+        );
+        let err = AbiRoot::from_value_strict(serde_json::to_value(&abi).unwrap()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid modifiers of `get`: `payable` modifier can not be used on view functions"
+        );
+    }
+}
@@ -0,0 +1,1398 @@
+//! Checking and generating argument and result values against ABI type schemas.
+//!
+//! JSON values are checked against the subset of JSON Schema emitted by `schemars` (references
+//! into the root schema, types, enums, object/array/number/string validation and schema
+//! composition). Borsh payloads are checked by decoding them with the inline Borsh schema.
+
+use crate::analysis::borsh_width_errors;
+use crate::{AbiBorshParameter, AbiFunction, AbiJsonParameter, AbiParameters};
+use borsh::schema::{BorshSchemaContainer, Declaration, Definition, Fields};
+use schemars::schema::{
+    ArrayValidation, InstanceType, ObjectValidation, RootSchema, Schema, SchemaObject, SingleOrVec,
+    SubschemaValidation,
+};
+use serde_json::Value;
+use std::fmt;
+
+/// Recursion limit protecting against self-referencing schemas.
+const MAX_DEPTH: usize = 64;
+
+/// Size limit of default values, the limit of NEAR on the arguments of a function call. It also
+/// bounds the number of schemas visited, see [`spend`].
+const MAX_DEFAULT_LEN: usize = 4 * 1024 * 1024;
+
+/// A value that does not conform to its type schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValueError {
+    path: String,
+    message: String,
+}
+
+impl ValueError {
+    pub(crate) fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// JSON Pointer (for JSON values) or byte offset (for Borsh payloads) of the offending value.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Human-readable description of the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::error::Error for ValueError {}
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            self.message.fmt(f)
+        } else {
+            write!(f, "at `{}`: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Checks that `value` conforms to `schema`, resolving references against `root_schema`.
+pub fn validate_json(
+    root_schema: &RootSchema,
+    schema: &Schema,
+    value: &Value,
+) -> Result<(), ValueError> {
+    JsonValidator { root_schema }.check(schema, value, "", 0)
+}
+
+/// Checks that `value` conforms to `schema` like [`validate_json`], but reports every
+/// nonconforming part of the value instead of stopping at the first one.
+///
+/// Objects and arrays are checked property by property and item by item, so that for example
+/// each malformed function of an ABI is reported. Values matched against schema compositions
+/// (`anyOf`, `oneOf`, ...) are reported as a whole.
+pub fn json_errors(root_schema: &RootSchema, schema: &Schema, value: &Value) -> Vec<ValueError> {
+    let mut errors = Vec::new();
+    JsonValidator { root_schema }.collect(schema, value, "", 0, &mut errors);
+    errors
+}
+
+struct JsonValidator<'a> {
+    root_schema: &'a RootSchema,
+}
+
+impl JsonValidator<'_> {
+    fn collect(
+        &self,
+        schema: &Schema,
+        value: &Value,
+        path: &str,
+        depth: usize,
+        errors: &mut Vec<ValueError>,
+    ) {
+        let object = match schema {
+            Schema::Object(object) if depth <= MAX_DEPTH => object,
+            _ => return errors.extend(self.check(schema, value, path, depth).err()),
+        };
+        if let Some(target) = self.wrapped_schema(object) {
+            return self.collect(target, value, path, depth + 1, errors);
+        }
+        let decomposable = object.reference.is_none()
+            && object.subschemas.is_none()
+            && object.enum_values.is_none()
+            && object.const_value.is_none();
+        let type_only = Schema::Object(SchemaObject {
+            instance_type: object.instance_type.clone(),
+            ..Default::default()
+        });
+        match (value, &object.object, &object.array) {
+            (Value::Object(map), Some(validation), _)
+                if decomposable
+                    && validation.min_properties.is_none()
+                    && validation.max_properties.is_none() =>
+            {
+                if let Err(err) = self.check(&type_only, value, path, depth) {
+                    return errors.push(err);
+                }
+                for required in &validation.required {
+                    if !map.contains_key(required) {
+                        errors.push(ValueError::new(
+                            path,
+                            format!("missing required property `{}`", required),
+                        ));
+                    }
+                }
+                for (key, item) in map {
+                    let item_path = format!("{}/{}", path, escape_pointer(key));
+                    if let Some(property) = validation.properties.get(key) {
+                        self.collect(property, item, &item_path, depth + 1, errors);
+                    } else if is_additional(validation, key) {
+                        match validation.additional_properties.as_deref() {
+                            Some(Schema::Bool(false)) => errors
+                                .push(ValueError::new(path, format!("unknown property `{}`", key))),
+                            Some(additional) => {
+                                self.collect(additional, item, &item_path, depth + 1, errors)
+                            }
+                            None => {}
+                        }
+                    }
+                }
+            }
+            (Value::Array(items), _, Some(array))
+                if decomposable
+                    && array.min_items.is_none()
+                    && array.max_items.is_none()
+                    && array.unique_items.is_none() =>
+            {
+                let item_schema = match &array.items {
+                    Some(SingleOrVec::Single(item_schema)) => item_schema,
+                    _ => return errors.extend(self.check(schema, value, path, depth).err()),
+                };
+                if let Err(err) = self.check(&type_only, value, path, depth) {
+                    return errors.push(err);
+                }
+                for (i, item) in items.iter().enumerate() {
+                    let item_path = format!("{}/{}", path, i);
+                    self.collect(item_schema, item, &item_path, depth + 1, errors);
+                }
+            }
+            _ => errors.extend(self.check(schema, value, path, depth).err()),
+        }
+    }
+
+    /// The schema `object` consists of apart from annotations: the target of a `$ref`, or the
+    /// single element of an `allOf` that `schemars` wraps references with.
+    fn wrapped_schema<'a>(&'a self, object: &'a SchemaObject) -> Option<&'a Schema> {
+        let only_annotated = object.instance_type.is_none()
+            && object.format.is_none()
+            && object.enum_values.is_none()
+            && object.const_value.is_none()
+            && object.number.is_none()
+            && object.string.is_none()
+            && object.array.is_none()
+            && object.object.is_none();
+        if !only_annotated {
+            return None;
+        }
+        match (&object.reference, &object.subschemas) {
+            (Some(reference), None) => resolve_reference(self.root_schema, reference),
+            (None, Some(subschemas)) => match &**subschemas {
+                SubschemaValidation {
+                    all_of: Some(all_of),
+                    any_of: None,
+                    one_of: None,
+                    not: None,
+                    if_schema: None,
+                    then_schema: None,
+                    else_schema: None,
+                } if all_of.len() == 1 => all_of.first(),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn check(
+        &self,
+        schema: &Schema,
+        value: &Value,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), ValueError> {
+        if depth > MAX_DEPTH {
+            return Err(ValueError::new(path, "schema nesting is too deep"));
+        }
+        let object = match schema {
+            Schema::Bool(true) => return Ok(()),
+            Schema::Bool(false) => return Err(ValueError::new(path, "no value is allowed here")),
+            Schema::Object(object) => object,
+        };
+        if let Some(reference) = &object.reference {
+            let target = resolve_reference(self.root_schema, reference).ok_or_else(|| {
+                ValueError::new(path, format!("unresolved reference `{}`", reference))
+            })?;
+            self.check(target, value, path, depth + 1)?;
+        }
+        if let Some(instance_type) = &object.instance_type {
+            let matches = match instance_type {
+                SingleOrVec::Single(ty) => has_type(value, ty),
+                SingleOrVec::Vec(types) => types.iter().any(|ty| has_type(value, ty)),
+            };
+            if !matches {
+                return Err(ValueError::new(
+                    path,
+                    format!(
+                        "expected {}, found {}",
+                        describe_types(instance_type),
+                        describe(value)
+                    ),
+                ));
+            }
+        }
+        if let Some(enum_values) = &object.enum_values {
+            if !enum_values.contains(value) {
+                return Err(ValueError::new(
+                    path,
+                    format!("{} is not one of the allowed values", value),
+                ));
+            }
+        }
+        if let Some(const_value) = &object.const_value {
+            if const_value != value {
+                return Err(ValueError::new(
+                    path,
+                    format!("expected constant {}", const_value),
+                ));
+            }
+        }
+        if let (Some(number), Some(n)) = (&object.number, value.as_f64()) {
+            let out_of_range = number.minimum.map_or(false, |min| n < min)
+                || number.maximum.map_or(false, |max| n > max)
+                || number.exclusive_minimum.map_or(false, |min| n <= min)
+                || number.exclusive_maximum.map_or(false, |max| n >= max);
+            if out_of_range {
+                return Err(ValueError::new(
+                    path,
+                    format!("{} is out of the allowed range", n),
+                ));
+            }
+            if let Some(multiple_of) = number.multiple_of {
+                if (n / multiple_of).fract() != 0.0 {
+                    return Err(ValueError::new(
+                        path,
+                        format!("{} is not a multiple of {}", n, multiple_of),
+                    ));
+                }
+            }
+        }
+        if let (Some(string), Value::String(s)) = (&object.string, value) {
+            let len = s.chars().count() as u32;
+            if string.min_length.map_or(false, |min| len < min)
+                || string.max_length.map_or(false, |max| len > max)
+            {
+                return Err(ValueError::new(
+                    path,
+                    "string length is out of the allowed range",
+                ));
+            }
+        }
+        if let (Some(array), Value::Array(items)) = (&object.array, value) {
+            self.check_array(array, items, path, depth)?;
+        }
+        if let (Some(validation), Value::Object(map)) = (&object.object, value) {
+            self.check_object(validation, map, path, depth)?;
+        }
+        if let Some(subschemas) = &object.subschemas {
+            self.check_subschemas(subschemas, value, path, depth)?;
+        }
+        Ok(())
+    }
+
+    fn check_array(
+        &self,
+        array: &ArrayValidation,
+        items: &[Value],
+        path: &str,
+        depth: usize,
+    ) -> Result<(), ValueError> {
+        let len = items.len() as u32;
+        if array.min_items.map_or(false, |min| len < min)
+            || array.max_items.map_or(false, |max| len > max)
+        {
+            return Err(ValueError::new(
+                path,
+                format!("unexpected number of items: {}", len),
+            ));
+        }
+        for (i, item) in items.iter().enumerate() {
+            let item_schema = match &array.items {
+                Some(SingleOrVec::Single(schema)) => Some(&**schema),
+                Some(SingleOrVec::Vec(schemas)) => {
+                    schemas.get(i).or(array.additional_items.as_deref())
+                }
+                None => None,
+            };
+            if let Some(item_schema) = item_schema {
+                self.check(item_schema, item, &format!("{}/{}", path, i), depth + 1)?;
+            }
+        }
+        if array.unique_items == Some(true) {
+            for (i, item) in items.iter().enumerate() {
+                if items[..i].contains(item) {
+                    return Err(ValueError::new(path, "items are not unique"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_object(
+        &self,
+        validation: &ObjectValidation,
+        map: &serde_json::Map<String, Value>,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), ValueError> {
+        for required in &validation.required {
+            if !map.contains_key(required) {
+                return Err(ValueError::new(
+                    path,
+                    format!("missing required property `{}`", required),
+                ));
+            }
+        }
+        let len = map.len() as u32;
+        if validation.min_properties.map_or(false, |min| len < min)
+            || validation.max_properties.map_or(false, |max| len > max)
+        {
+            return Err(ValueError::new(
+                path,
+                format!("unexpected number of properties: {}", len),
+            ));
+        }
+        for (key, item) in map {
+            let item_path = format!("{}/{}", path, escape_pointer(key));
+            if let Some(property) = validation.properties.get(key) {
+                self.check(property, item, &item_path, depth + 1)?;
+            } else if is_additional(validation, key) {
+                if let Some(additional) = &validation.additional_properties {
+                    if **additional == Schema::Bool(false) {
+                        return Err(ValueError::new(path, format!("unknown property `{}`", key)));
+                    }
+                    self.check(additional, item, &item_path, depth + 1)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_subschemas(
+        &self,
+        subschemas: &SubschemaValidation,
+        value: &Value,
+        path: &str,
+        depth: usize,
+    ) -> Result<(), ValueError> {
+        if let Some(all_of) = &subschemas.all_of {
+            for schema in all_of {
+                self.check(schema, value, path, depth + 1)?;
+            }
+        }
+        if let Some(any_of) = &subschemas.any_of {
+            if !any_of
+                .iter()
+                .any(|schema| self.check(schema, value, path, depth + 1).is_ok())
+            {
+                return Err(ValueError::new(
+                    path,
+                    "value does not match any of the allowed schemas",
+                ));
+            }
+        }
+        if let Some(one_of) = &subschemas.one_of {
+            let matching = one_of
+                .iter()
+                .filter(|schema| self.check(schema, value, path, depth + 1).is_ok())
+                .count();
+            if matching != 1 {
+                return Err(ValueError::new(
+                    path,
+                    format!(
+                        "value must match exactly one schema, but matches {}",
+                        matching
+                    ),
+                ));
+            }
+        }
+        if let Some(not) = &subschemas.not {
+            if self.check(not, value, path, depth + 1).is_ok() {
+                return Err(ValueError::new(path, "value matches a forbidden schema"));
+            }
+        }
+        if let Some(if_schema) = &subschemas.if_schema {
+            let branch = if self.check(if_schema, value, path, depth + 1).is_ok() {
+                &subschemas.then_schema
+            } else {
+                &subschemas.else_schema
+            };
+            if let Some(branch) = branch {
+                self.check(branch, value, path, depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Produces the simplest value conforming to `schema`, resolving references against
+/// `root_schema`. Schema `default`s, constants and the first enum variant are preferred when
+/// present; optional object properties are omitted and nullable types default to `null`.
+///
+/// Fails if the value would exceed the size limit of function call arguments, e.g. because of a
+/// large `minLength` or `minItems`.
+pub fn default_json(root_schema: &RootSchema, schema: &Schema) -> Result<Value, ValueError> {
+    let mut budget = MAX_DEFAULT_LEN;
+    default_json_impl(root_schema, schema, 0, &mut budget)
+        .map_err(|message| ValueError::new("", message))
+}
+
+fn default_json_impl(
+    root_schema: &RootSchema,
+    schema: &Schema,
+    depth: usize,
+    budget: &mut usize,
+) -> Result<Value, String> {
+    spend(budget, 1)?;
+    let object = match schema {
+        Schema::Object(object) if depth <= MAX_DEPTH => object,
+        _ => return Ok(Value::Null),
+    };
+    if let Some(default) = object.metadata.as_ref().and_then(|m| m.default.clone()) {
+        return Ok(default);
+    }
+    if let Some(const_value) = &object.const_value {
+        return Ok(const_value.clone());
+    }
+    if let Some(value) = object
+        .enum_values
+        .as_ref()
+        .and_then(|values| values.first())
+    {
+        return Ok(value.clone());
+    }
+    if let Some(reference) = &object.reference {
+        return match resolve_reference(root_schema, reference) {
+            Some(target) => default_json_impl(root_schema, target, depth + 1, budget),
+            None => Ok(Value::Null),
+        };
+    }
+    if let Some(subschemas) = &object.subschemas {
+        let first = [&subschemas.all_of, &subschemas.any_of, &subschemas.one_of]
+            .iter()
+            .find_map(|schemas| schemas.as_ref().and_then(|schemas| schemas.first()));
+        if let Some(first) = first {
+            if object.instance_type.is_none() {
+                return default_json_impl(root_schema, first, depth + 1, budget);
+            }
+        }
+    }
+    let instance_type = match &object.instance_type {
+        Some(SingleOrVec::Single(ty)) => **ty,
+        Some(SingleOrVec::Vec(types)) if types.contains(&InstanceType::Null) => InstanceType::Null,
+        Some(SingleOrVec::Vec(types)) => types.first().copied().unwrap_or(InstanceType::Null),
+        None if object.object.is_some() => InstanceType::Object,
+        None if object.array.is_some() => InstanceType::Array,
+        None => InstanceType::Null,
+    };
+    let value = match instance_type {
+        InstanceType::Null => Value::Null,
+        InstanceType::Boolean => Value::Bool(false),
+        InstanceType::Integer | InstanceType::Number => {
+            let minimum = object
+                .number
+                .as_ref()
+                .and_then(|n| {
+                    n.minimum
+                        .or(n.exclusive_minimum.map(|min| min.floor() + 1.0))
+                })
+                .unwrap_or(0.0)
+                .max(0.0);
+            if instance_type == InstanceType::Integer || minimum.fract() == 0.0 {
+                Value::from(minimum as u64)
+            } else {
+                Value::from(minimum)
+            }
+        }
+        InstanceType::String => {
+            let min_length = object
+                .string
+                .as_ref()
+                .and_then(|s| s.min_length)
+                .unwrap_or(0);
+            spend(budget, min_length as usize)?;
+            Value::String("a".repeat(min_length as usize))
+        }
+        InstanceType::Array => {
+            let items = match object.array.as_deref() {
+                Some(ArrayValidation {
+                    items: Some(SingleOrVec::Vec(items)),
+                    ..
+                }) => items
+                    .iter()
+                    .map(|item| default_json_impl(root_schema, item, depth + 1, budget))
+                    .collect::<Result<_, _>>()?,
+                Some(ArrayValidation {
+                    items: Some(SingleOrVec::Single(item)),
+                    min_items: Some(min_items),
+                    ..
+                }) => {
+                    spend(budget, *min_items as usize)?;
+                    (0..*min_items)
+                        .map(|_| default_json_impl(root_schema, item, depth + 1, budget))
+                        .collect::<Result<_, _>>()?
+                }
+                _ => Vec::new(),
+            };
+            Value::Array(items)
+        }
+        InstanceType::Object => {
+            let mut map = serde_json::Map::new();
+            if let Some(validation) = &object.object {
+                for required in &validation.required {
+                    let value = match validation.properties.get(required) {
+                        Some(property) => {
+                            default_json_impl(root_schema, property, depth + 1, budget)?
+                        }
+                        None => Value::Null,
+                    };
+                    map.insert(required.clone(), value);
+                }
+            }
+            Value::Object(map)
+        }
+    };
+    Ok(value)
+}
+
+/// Takes `cost` from the `budget` of a default value, failing once it is exhausted. Every schema
+/// visited costs one unit, so that schemas fanning out into many empty values are bounded too.
+fn spend(budget: &mut usize, cost: usize) -> Result<(), String> {
+    *budget = budget.checked_sub(cost).ok_or_else(|| {
+        format!(
+            "the default value exceeds the limit of {} bytes",
+            MAX_DEFAULT_LEN
+        )
+    })?;
+    Ok(())
+}
+
+/// Checks that `bytes` is a complete Borsh serialization of the type described by `schema`.
+pub fn validate_borsh(schema: &BorshSchemaContainer, bytes: &[u8]) -> Result<(), ValueError> {
+    let end = read_borsh(schema, bytes, 0)?;
+    if end != bytes.len() {
+        return Err(ValueError::new(
+            &end.to_string(),
+            format!("{} unexpected trailing bytes", bytes.len() - end),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks a JSON arguments object against the declared parameters of a function.
+///
+/// Arguments that are not declared parameters are rejected, while missing arguments are checked
+/// as `null`, so optional parameters can be omitted.
+pub fn validate_json_args(
+    root_schema: &RootSchema,
+    params: &[AbiJsonParameter],
+    args: &Value,
+) -> Result<(), ValueError> {
+    let map = args
+        .as_object()
+        .ok_or_else(|| ValueError::new("", "arguments must be a JSON object"))?;
+    if let Some(unknown) = map
+        .keys()
+        .find(|key| !params.iter().any(|param| &param.name == *key))
+    {
+        return Err(ValueError::new(
+            "",
+            format!("unknown parameter `{}`", unknown),
+        ));
+    }
+    for param in params {
+        let value = map.get(&param.name).unwrap_or(&Value::Null);
+        validate_json(root_schema, &param.type_schema, value).map_err(|err| {
+            ValueError::new(&format!("/{}{}", param.name, err.path()), err.message())
+        })?;
+    }
+    Ok(())
+}
+
+/// Checks a Borsh arguments payload against the declared parameters of a function, which are
+/// serialized one after another.
+pub fn validate_borsh_args(params: &[AbiBorshParameter], bytes: &[u8]) -> Result<(), ValueError> {
+    let mut offset = 0;
+    for param in params {
+        offset = read_borsh(&param.type_schema, bytes, offset).map_err(|err| {
+            ValueError::new(
+                err.path(),
+                format!("parameter `{}`: {}", param.name, err.message()),
+            )
+        })?;
+    }
+    if offset != bytes.len() {
+        return Err(ValueError::new(
+            &offset.to_string(),
+            format!("{} unexpected trailing bytes", bytes.len() - offset),
+        ));
+    }
+    Ok(())
+}
+
+/// Decodes a Borsh arguments payload into a JSON object keyed by parameter name, with values
+/// represented as in [`decode_borsh`].
+pub fn decode_borsh_args(params: &[AbiBorshParameter], bytes: &[u8]) -> Result<Value, ValueError> {
+    let mut args = serde_json::Map::new();
+    let mut offset = 0;
+    for param in params {
+        let in_param = |err: ValueError| {
+            ValueError::new(
+                err.path(),
+                format!("parameter `{}`: {}", param.name, err.message()),
+            )
+        };
+        let mut reader = BorshReader::new(&param.type_schema, bytes, offset).map_err(in_param)?;
+        let value = reader
+            .read(param.type_schema.declaration(), 0)
+            .map_err(in_param)?;
+        offset = reader.offset;
+        args.insert(param.name.clone(), value);
+    }
+    if offset != bytes.len() {
+        return Err(ValueError::new(
+            &offset.to_string(),
+            format!("{} unexpected trailing bytes", bytes.len() - offset),
+        ));
+    }
+    Ok(Value::Object(args))
+}
+
+/// Decodes a single value described by `schema` starting at `offset` and returns the offset right
+/// after it.
+pub(crate) fn read_borsh(
+    schema: &BorshSchemaContainer,
+    bytes: &[u8],
+    offset: usize,
+) -> Result<usize, ValueError> {
+    let mut reader = BorshReader::new(schema, bytes, offset)?;
+    reader.read(schema.declaration(), 0)?;
+    Ok(reader.offset)
+}
+
+/// Decodes a Borsh payload into its JSON representation.
+///
+/// Integers wider than 64 bits are represented as decimal strings, `String`s as JSON strings,
+/// structs with named fields as objects, other structs and tuples as arrays, `Option`s as the
+/// contained value or `null` and other enums as single-key objects keyed by the variant name.
+pub fn decode_borsh(schema: &BorshSchemaContainer, bytes: &[u8]) -> Result<Value, ValueError> {
+    let mut reader = BorshReader::new(schema, bytes, 0)?;
+    let value = reader.read(schema.declaration(), 0)?;
+    if reader.offset != bytes.len() {
+        return Err(ValueError::new(
+            &reader.offset.to_string(),
+            format!("{} unexpected trailing bytes", bytes.len() - reader.offset),
+        ));
+    }
+    Ok(value)
+}
+
+struct BorshReader<'a> {
+    schema: &'a BorshSchemaContainer,
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BorshReader<'a> {
+    /// Reader of the value described by `schema` at `offset`, failing if the schema can not be
+    /// decoded (see [`check_borsh_schema`]).
+    fn new(
+        schema: &'a BorshSchemaContainer,
+        bytes: &'a [u8],
+        offset: usize,
+    ) -> Result<Self, ValueError> {
+        check_borsh_schema(schema).map_err(|message| ValueError::new("", message))?;
+        Ok(Self {
+            schema,
+            bytes,
+            offset,
+        })
+    }
+
+    fn take(&mut self, len: usize) -> Result<&[u8], ValueError> {
+        if self.bytes.len() - self.offset < len {
+            return Err(ValueError::new(
+                &self.offset.to_string(),
+                "unexpected end of input",
+            ));
+        }
+        let slice = &self.bytes[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(slice)
+    }
+
+    fn read_uint(&mut self, width: u8) -> Result<u64, ValueError> {
+        let mut buf = [0u8; 8];
+        buf[..width as usize].copy_from_slice(self.take(width as usize)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn read(&mut self, declaration: &Declaration, depth: usize) -> Result<Value, ValueError> {
+        let definition = borsh_definition(self.schema, declaration, depth)
+            .map_err(|message| ValueError::new(&self.offset.to_string(), message))?;
+        let value = match definition {
+            Definition::Primitive(size) => {
+                let offset = self.offset;
+                let bytes = self.take(*size as usize)?;
+                if declaration == "bool" && bytes[0] > 1 {
+                    return Err(ValueError::new(
+                        &offset.to_string(),
+                        "invalid boolean value",
+                    ));
+                }
+                primitive_value(declaration, bytes)
+            }
+            Definition::Sequence {
+                length_width,
+                length_range,
+                elements,
+            } => {
+                let offset = self.offset;
+                let len = if *length_width == 0 {
+                    *length_range.start()
+                } else {
+                    self.read_uint(*length_width)?
+                };
+                if !length_range.contains(&len) {
+                    return Err(ValueError::new(
+                        &offset.to_string(),
+                        format!("sequence length {} is out of the allowed range", len),
+                    ));
+                }
+                if declaration == "String" && elements == "u8" {
+                    let start = self.offset;
+                    let bytes = self.take(len as usize)?;
+                    let string = std::str::from_utf8(bytes).map_err(|_| {
+                        ValueError::new(&start.to_string(), "string is not valid UTF-8")
+                    })?;
+                    return Ok(Value::String(string.to_string()));
+                }
+                let mut values = Vec::new();
+                for _ in 0..len {
+                    let start = self.offset;
+                    values.push(self.read(elements, depth + 1)?);
+                    // Lengths are only bounded by the input for elements taking up bytes.
+                    if self.offset == start {
+                        return Err(ValueError::new(
+                            &offset.to_string(),
+                            "sequence of zero-size elements",
+                        ));
+                    }
+                }
+                Value::Array(values)
+            }
+            Definition::Tuple { elements } => {
+                let mut values = Vec::new();
+                for element in elements {
+                    values.push(self.read(element, depth + 1)?);
+                }
+                Value::Array(values)
+            }
+            Definition::Enum {
+                tag_width,
+                variants,
+            } => {
+                let offset = self.offset;
+                let tag = self.read_uint(*tag_width)?;
+                let (_, name, variant) = variants
+                    .iter()
+                    .find(|(discriminant, _, _)| tag_matches(*discriminant, *tag_width, tag))
+                    .ok_or_else(|| {
+                        ValueError::new(&offset.to_string(), format!("unknown enum tag {}", tag))
+                    })?;
+                let value = self.read(variant, depth + 1)?;
+                if declaration.starts_with("Option<") {
+                    match (name.as_str(), value) {
+                        ("None", _) => Value::Null,
+                        // The `Some` variant wraps the value in a single-field tuple struct.
+                        ("Some", Value::Array(mut fields)) if fields.len() == 1 => fields.remove(0),
+                        (_, value) => value,
+                    }
+                } else {
+                    let mut map = serde_json::Map::new();
+                    map.insert(name.clone(), value);
+                    Value::Object(map)
+                }
+            }
+            Definition::Struct { fields } => match fields {
+                Fields::NamedFields(fields) => {
+                    let mut map = serde_json::Map::new();
+                    for (name, field) in fields {
+                        map.insert(name.clone(), self.read(field, depth + 1)?);
+                    }
+                    Value::Object(map)
+                }
+                Fields::UnnamedFields(fields) => {
+                    let mut values = Vec::new();
+                    for field in fields {
+                        values.push(self.read(field, depth + 1)?);
+                    }
+                    Value::Array(values)
+                }
+                Fields::Empty => Value::Null,
+            },
+        };
+        Ok(value)
+    }
+}
+
+fn primitive_value(declaration: &str, bytes: &[u8]) -> Value {
+    let mut buf = [0u8; 16];
+    buf[..bytes.len()].copy_from_slice(bytes);
+    let unsigned = u128::from_le_bytes(buf);
+    let signed = {
+        let shift = 128 - 8 * bytes.len() as u32;
+        if shift == 128 {
+            0
+        } else {
+            ((unsigned << shift) as i128) >> shift
+        }
+    };
+    match declaration {
+        "bool" => Value::Bool(bytes[0] == 1),
+        "u8" | "u16" | "u32" | "u64" => Value::from(unsigned as u64),
+        "i8" | "i16" | "i32" | "i64" => Value::from(signed as i64),
+        "u128" => Value::String(unsigned.to_string()),
+        "i128" => Value::String(signed.to_string()),
+        "f32" => Value::from(f32::from_le_bytes(buf[..4].try_into().unwrap())),
+        "f64" => Value::from(f64::from_le_bytes(buf[..8].try_into().unwrap())),
+        "()" => Value::Null,
+        _ => Value::Array(bytes.iter().map(|byte| Value::from(*byte)).collect()),
+    }
+}
+
+/// Produces the simplest Borsh serialization of the type described by `schema`: zeroed
+/// primitives, sequences of minimal allowed length and the first variant of every enum.
+pub fn default_borsh(schema: &BorshSchemaContainer) -> Result<Vec<u8>, ValueError> {
+    let mut bytes = Vec::new();
+    let mut budget = MAX_DEFAULT_LEN;
+    check_borsh_schema(schema)
+        .and_then(|()| {
+            write_default_borsh(schema, schema.declaration(), &mut bytes, 0, &mut budget)
+        })
+        .map_err(|message| ValueError::new("", message))?;
+    Ok(bytes)
+}
+
+/// Produces a minimal valid argument payload for a function taking Borsh parameters: the
+/// [`default_borsh`] value of every parameter, in declaration order. Functions taking JSON
+/// parameters yield `None`.
+pub fn default_borsh_args(function: &AbiFunction) -> Result<Option<Vec<u8>>, ValueError> {
+    let params = match &function.params {
+        AbiParameters::Borsh { args } => args,
+        AbiParameters::Json { .. } => return Ok(None),
+    };
+    let mut bytes = Vec::new();
+    let mut budget = MAX_DEFAULT_LEN;
+    for param in params {
+        check_borsh_schema(&param.type_schema)
+            .and_then(|()| {
+                write_default_borsh(
+                    &param.type_schema,
+                    param.type_schema.declaration(),
+                    &mut bytes,
+                    0,
+                    &mut budget,
+                )
+            })
+            .map_err(|message| {
+                ValueError::new("", format!("parameter `{}`: {}", param.name, message))
+            })?;
+    }
+    Ok(Some(bytes))
+}
+
+fn write_default_borsh(
+    schema: &BorshSchemaContainer,
+    declaration: &Declaration,
+    out: &mut Vec<u8>,
+    depth: usize,
+    budget: &mut usize,
+) -> Result<(), String> {
+    spend(budget, 1)?;
+    match borsh_definition(schema, declaration, depth)? {
+        Definition::Primitive(size) => {
+            spend(budget, *size as usize)?;
+            out.extend(std::iter::repeat(0).take(*size as usize))
+        }
+        Definition::Sequence {
+            length_width,
+            length_range,
+            elements,
+        } => {
+            let len = *length_range.start();
+            spend(budget, *length_width as usize)?;
+            out.extend_from_slice(&len.to_le_bytes()[..*length_width as usize]);
+            for _ in 0..len {
+                let start = out.len();
+                write_default_borsh(schema, elements, out, depth + 1, budget)?;
+                if out.len() == start {
+                    return Err(format!(
+                        "`{}` is a sequence of zero-size elements",
+                        declaration
+                    ));
+                }
+            }
+        }
+        Definition::Tuple { elements } => {
+            for element in elements {
+                write_default_borsh(schema, element, out, depth + 1, budget)?;
+            }
+        }
+        Definition::Enum {
+            tag_width,
+            variants,
+        } => {
+            let (discriminant, _, variant) = variants
+                .first()
+                .ok_or_else(|| format!("enum `{}` has no variants", declaration))?;
+            spend(budget, *tag_width as usize)?;
+            out.extend_from_slice(&discriminant.to_le_bytes()[..*tag_width as usize]);
+            write_default_borsh(schema, variant, out, depth + 1, budget)?;
+        }
+        Definition::Struct { fields } => {
+            for field in fields_declarations(fields) {
+                write_default_borsh(schema, field, out, depth + 1, budget)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks the definitions of `schema` for widths and sizes the decoding and default helpers can
+/// not handle: the errors of [`borsh_width_errors`], primitives wider than 16 bytes and `bool`s
+/// that are not 1 byte wide.
+fn check_borsh_schema(schema: &BorshSchemaContainer) -> Result<(), String> {
+    if let Some(error) = borsh_width_errors(schema).first() {
+        return Err(format!("invalid Borsh schema: {}", error));
+    }
+    for (declaration, definition) in schema.definitions() {
+        match definition {
+            Definition::Primitive(size) if *size > 16 => {
+                return Err(format!(
+                    "invalid Borsh schema: `{}`: primitive of {} bytes, at most 16 are supported",
+                    declaration, size
+                ));
+            }
+            Definition::Primitive(size) if declaration == "bool" && *size != 1 => {
+                return Err(format!(
+                    "invalid Borsh schema: `bool`: primitive of {} bytes instead of 1",
+                    size
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn borsh_definition<'a>(
+    schema: &'a BorshSchemaContainer,
+    declaration: &Declaration,
+    depth: usize,
+) -> Result<&'a Definition, String> {
+    if depth > MAX_DEPTH {
+        return Err("schema nesting is too deep".to_string());
+    }
+    schema
+        .get_definition(declaration)
+        .ok_or_else(|| format!("missing definition for `{}`", declaration))
+}
+
+fn fields_declarations(fields: &Fields) -> Vec<&Declaration> {
+    match fields {
+        Fields::NamedFields(fields) => fields.iter().map(|(_, declaration)| declaration).collect(),
+        Fields::UnnamedFields(fields) => fields.iter().collect(),
+        Fields::Empty => Vec::new(),
+    }
+}
+
+fn tag_matches(discriminant: i64, tag_width: u8, tag: u64) -> bool {
+    let mut expected = [0u8; 8];
+    expected[..tag_width as usize]
+        .copy_from_slice(&discriminant.to_le_bytes()[..tag_width as usize]);
+    u64::from_le_bytes(expected) == tag
+}
+
+pub(crate) fn resolve_reference<'a>(
+    root_schema: &'a RootSchema,
+    reference: &str,
+) -> Option<&'a Schema> {
+    crate::transform::definition_name(reference).and_then(|name| root_schema.definitions.get(name))
+}
+
+/// Whether `key` is an additional property, i.e. is matched by neither a property nor a pattern
+/// property of `validation`.
+///
+/// Only patterns matching a literal prefix, such as `^x-`, are evaluated. Keys are assumed to
+/// match other patterns, so that they are never treated as additional by mistake.
+fn is_additional(validation: &ObjectValidation, key: &str) -> bool {
+    !validation.properties.contains_key(key)
+        && validation.pattern_properties.keys().all(|pattern| {
+            let prefix = pattern.strip_prefix('^').filter(|prefix| {
+                prefix
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            });
+            matches!(prefix, Some(prefix) if !key.starts_with(prefix))
+        })
+}
+
+fn has_type(value: &Value, ty: &InstanceType) -> bool {
+    match ty {
+        InstanceType::Null => value.is_null(),
+        InstanceType::Boolean => value.is_boolean(),
+        InstanceType::Object => value.is_object(),
+        InstanceType::Array => value.is_array(),
+        InstanceType::Number => value.is_number(),
+        InstanceType::String => value.is_string(),
+        InstanceType::Integer => {
+            value.is_i64() || value.is_u64() || value.as_f64().map_or(false, |n| n.fract() == 0.0)
+        }
+    }
+}
+
+fn describe_types(types: &SingleOrVec<InstanceType>) -> String {
+    let name = |ty: &InstanceType| {
+        serde_json::to_value(ty)
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+    match types {
+        SingleOrVec::Single(ty) => name(ty),
+        SingleOrVec::Vec(types) => types.iter().map(name).collect::<Vec<_>>().join(" or "),
+    }
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn escape_pointer(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSchema;
+    use serde_json::json;
+
+    fn schema(value: Value) -> Schema {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_validate_json() {
+        let mut root_schema = RootSchema::default();
+        root_schema.definitions.insert(
+            "Pair".to_string(),
+            schema(json!({
+                "type": "object",
+                "properties": {
+                    "first": { "type": "integer", "format": "uint32", "minimum": 0.0 },
+                    "second": { "type": ["string", "null"] }
+                },
+                "required": ["first"],
+                "additionalProperties": false
+            })),
+        );
+        let pair_ref = schema(json!({ "$ref": "#/definitions/Pair" }));
+
+        validate_json(&root_schema, &pair_ref, &json!({ "first": 1 })).unwrap();
+        validate_json(
+            &root_schema,
+            &pair_ref,
+            &json!({ "first": 1, "second": "x" }),
+        )
+        .unwrap();
+
+        let err = validate_json(&root_schema, &pair_ref, &json!({ "first": -1 })).unwrap_err();
+        assert_eq!(err.path(), "/first");
+        let err = validate_json(&root_schema, &pair_ref, &json!({ "second": null })).unwrap_err();
+        assert_eq!(err.to_string(), "missing required property `first`");
+        let err =
+            validate_json(&root_schema, &pair_ref, &json!({ "first": 1, "third": 3 })).unwrap_err();
+        assert_eq!(err.message(), "unknown property `third`");
+    }
+
+    #[test]
+    fn test_validate_borsh() {
+        let container = borsh::schema_container_of::<(bool, [u8; 3], Vec<Option<u32>>)>();
+        let bytes = borsh::to_vec(&(true, [1u8, 2, 3], vec![Some(7u32), None])).unwrap();
+        validate_borsh(&container, &bytes).unwrap();
+
+        let mut truncated = bytes.clone();
+        truncated.pop();
+        assert!(validate_borsh(&container, &truncated).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            validate_borsh(&container, &trailing)
+                .unwrap_err()
+                .to_string(),
+            format!("at `{}`: 1 unexpected trailing bytes", bytes.len())
+        );
+        let mut invalid_bool = bytes;
+        invalid_bool[0] = 2;
+        assert_eq!(
+            validate_borsh(&container, &invalid_bool)
+                .unwrap_err()
+                .to_string(),
+            "at `0`: invalid boolean value"
+        );
+    }
+
+    #[test]
+    fn test_default_json_is_valid() {
+        let root_schema = RootSchema::default();
+        let option_vec = schema(json!({
+            "type": "object",
+            "properties": {
+                "items": { "type": "array", "items": { "type": "string" } },
+                "limit": { "type": ["integer", "null"] },
+                "kind": { "enum": ["a", "b"] }
+            },
+            "required": ["items", "kind"]
+        }));
+        let value = default_json(&root_schema, &option_vec).unwrap();
+        assert_eq!(value, json!({ "items": [], "kind": "a" }));
+        validate_json(&root_schema, &option_vec, &value).unwrap();
+    }
+
+    #[test]
+    fn test_default_json_limits() {
+        let root_schema = RootSchema::default();
+        let default_err = |value| {
+            default_json(&root_schema, &schema(value))
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            default_err(json!({ "type": "string", "minLength": u32::MAX })),
+            "the default value exceeds the limit of 4194304 bytes"
+        );
+        assert_eq!(
+            default_err(json!({ "type": "array", "items": {}, "minItems": u32::MAX })),
+            "the default value exceeds the limit of 4194304 bytes"
+        );
+
+        // Each level doubles the number of schemas visited, without growing the items.
+        let mut definitions = serde_json::Map::new();
+        definitions.insert("Level0".to_string(), json!({ "type": "null" }));
+        for level in 1..=30 {
+            let inner = json!({ "$ref": format!("#/definitions/Level{}", level - 1) });
+            definitions.insert(
+                format!("Level{}", level),
+                json!({
+                    "type": "object",
+                    "properties": { "a": inner, "b": inner },
+                    "required": ["a", "b"]
+                }),
+            );
+        }
+        let root_schema: RootSchema =
+            serde_json::from_value(json!({ "definitions": definitions })).unwrap();
+        assert_eq!(
+            default_json(
+                &root_schema,
+                &schema(json!({ "$ref": "#/definitions/Level30" }))
+            )
+            .unwrap_err()
+            .to_string(),
+            "the default value exceeds the limit of 4194304 bytes"
+        );
+    }
+
+    #[test]
+    fn test_default_borsh_roundtrip() {
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        enum Action {
+            Transfer { amount: u128, memo: Option<String> },
+            Stop,
+        }
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        struct Args {
+            flag: bool,
+            fixed: [u8; 3],
+            actions: Vec<Action>,
+            action: Action,
+        }
+        let container = borsh::schema_container_of::<Args>();
+        let bytes = default_borsh(&container).unwrap();
+        // bool + [u8; 3] + empty Vec length + Transfer tag + u128 + None tag
+        assert_eq!(bytes.len(), 1 + 3 + 4 + 1 + 16 + 1);
+        validate_borsh(&container, &bytes).unwrap();
+
+        let mut truncated = bytes.clone();
+        truncated.pop();
+        assert!(validate_borsh(&container, &truncated).is_err());
+        let mut invalid_bool = bytes;
+        invalid_bool[0] = 2;
+        assert_eq!(
+            validate_borsh(&container, &invalid_bool)
+                .unwrap_err()
+                .to_string(),
+            "at `0`: invalid boolean value"
+        );
+    }
+
+    #[test]
+    fn test_decode_borsh() {
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        enum Action {
+            Transfer { amount: u128, memo: Option<String> },
+            Stop,
+        }
+        #[derive(BorshSchema)]
+        #[allow(dead_code)]
+        struct Args {
+            flag: bool,
+            pair: (u8, i32),
+            actions: Vec<Action>,
+        }
+        let container = borsh::schema_container_of::<Args>();
+        let mut bytes = vec![1, 7];
+        bytes.extend((-2i32).to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+        bytes.push(0);
+        bytes.extend(5u128.to_le_bytes());
+        bytes.push(1);
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(b"hi");
+        bytes.push(1);
+        assert_eq!(
+            decode_borsh(&container, &bytes).unwrap(),
+            json!({
+                "flag": true,
+                "pair": [7, -2],
+                "actions": [
+                    { "Transfer": { "amount": "5", "memo": "hi" } },
+                    { "Stop": null }
+                ]
+            })
+        );
+
+        bytes.push(0);
+        assert_eq!(
+            decode_borsh(&container, &bytes).unwrap_err().to_string(),
+            format!("at `{}`: 1 unexpected trailing bytes", bytes.len() - 1)
+        );
+    }
+
+    #[test]
+    fn test_default_borsh_args() {
+        let mut function = AbiFunction {
+            params: AbiParameters::Borsh {
+                args: vec![
+                    crate::AbiBorshParameter::new("amount", borsh::schema_container_of::<u64>()),
+                    crate::AbiBorshParameter::new(
+                        "memo",
+                        borsh::schema_container_of::<Option<String>>(),
+                    ),
+                ],
+            },
+            ..AbiFunction::new("transfer", crate::AbiFunctionKind::Call)
+        };
+        assert_eq!(default_borsh_args(&function).unwrap(), Some(vec![0; 9]));
+
+        function.params = AbiParameters::default();
+        assert_eq!(default_borsh_args(&function).unwrap(), None);
+    }
+
+    #[test]
+    fn test_unsupported_borsh_schemas() {
+        let container = |definition: Definition| {
+            let definitions = [
+                ("Value".to_string(), definition),
+                ("()".to_string(), Definition::Primitive(0)),
+                ("u8".to_string(), Definition::Primitive(1)),
+            ];
+            BorshSchemaContainer::new("Value".to_string(), definitions.into_iter().collect())
+        };
+        let sequence = |length_width: u8, length: u64, elements: &str| {
+            container(Definition::Sequence {
+                length_width,
+                length_range: length..=length,
+                elements: elements.to_string(),
+            })
+        };
+        let decode_err = |container: &BorshSchemaContainer, bytes: &[u8]| {
+            decode_borsh(container, bytes).unwrap_err().to_string()
+        };
+        let default_err =
+            |container: &BorshSchemaContainer| default_borsh(container).unwrap_err().to_string();
+
+        let wide_prefix = sequence(16, 1, "u8");
+        assert_eq!(
+            decode_err(&wide_prefix, &[1; 17]),
+            "invalid Borsh schema: `Value`: length prefix width of 16 bytes is invalid"
+        );
+        assert_eq!(default_err(&wide_prefix), decode_err(&wide_prefix, &[]));
+        let wide_tag = container(Definition::Enum {
+            tag_width: 9,
+            variants: vec![(0, "A".to_string(), "()".to_string())],
+        });
+        assert!(decode_err(&wide_tag, &[0; 9]).starts_with("invalid Borsh schema: `Value`"));
+        assert!(default_err(&wide_tag).starts_with("invalid Borsh schema: `Value`"));
+        assert_eq!(
+            decode_err(&container(Definition::Primitive(17)), &[0; 17]),
+            "invalid Borsh schema: `Value`: primitive of 17 bytes, at most 16 are supported"
+        );
+        let mut empty_bool = BorshSchemaContainer::for_type::<bool>();
+        empty_bool.insert_definition("bool".to_string(), Definition::Primitive(0));
+        assert_eq!(
+            decode_err(&empty_bool, &[]),
+            "invalid Borsh schema: `bool`: primitive of 0 bytes instead of 1"
+        );
+
+        let units = sequence(0, u64::MAX, "()");
+        assert_eq!(
+            decode_err(&units, &[]),
+            "at `0`: sequence of zero-size elements"
+        );
+        assert_eq!(
+            default_err(&units),
+            "`Value` is a sequence of zero-size elements"
+        );
+        let huge = sequence(0, 1 << 40, "u8");
+        assert_eq!(
+            decode_err(&huge, &[0; 8]),
+            "at `8`: unexpected end of input"
+        );
+        assert_eq!(
+            default_err(&huge),
+            "the default value exceeds the limit of 4194304 bytes"
+        );
+
+        // Nested pairs of unit structs are empty, but still take exponential work to produce.
+        let mut definitions = vec![("Level0".to_string(), Definition::Primitive(0))];
+        for level in 1..=30 {
+            let inner = format!("Level{}", level - 1);
+            definitions.push((
+                format!("Level{}", level),
+                Definition::Tuple {
+                    elements: vec![inner.clone(), inner],
+                },
+            ));
+        }
+        let fan_out =
+            BorshSchemaContainer::new("Level30".to_string(), definitions.into_iter().collect());
+        assert_eq!(
+            default_err(&fan_out),
+            "the default value exceeds the limit of 4194304 bytes"
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! Compatibility rules between ABI schema versions.
+//!
+//! ABI schema versions follow semver with the usual caveat for `0.x` releases: while the major
+//! version is `0`, only versions sharing the same minor version are compatible with each other.
+
+use semver::{Comparator, Op, Version, VersionReq};
+
+/// Whether ABI documents of schema version `a` can be read by consumers of schema version `b`
+/// and vice versa.
+pub fn is_compatible(a: &Version, b: &Version) -> bool {
+    a.major == b.major && (a.major != 0 || a.minor == b.minor)
+}
+
+/// Requirement matching every schema version compatible with `version`.
+pub fn compatible_requirement(version: &Version) -> VersionReq {
+    let op = if version.major == 0 {
+        Op::Tilde
+    } else {
+        Op::Caret
+    };
+    VersionReq {
+        comparators: vec![Comparator {
+            op,
+            major: version.major,
+            minor: Some(version.minor),
+            patch: None,
+            pre: semver::Prerelease::EMPTY,
+        }],
+    }
+}
+
+/// Requirement matching every schema version compatible with the current
+/// [`SCHEMA_VERSION`](crate::SCHEMA_VERSION).
+pub fn current_requirement() -> VersionReq {
+    compatible_requirement(&crate::SCHEMA_SEMVER)
+}
+
+/// Outcome of matching an ABI artifact version against the versions supported by a client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NegotiationResult {
+    /// The artifact satisfies the contained requirement, the first matching one in the client's
+    /// list.
+    Compatible(VersionReq),
+    /// The artifact is older than any version the client supports; it should be re-generated
+    /// with a newer SDK.
+    ArtifactTooOld,
+    /// The artifact is newer than any version the client supports; the client should be
+    /// upgraded.
+    ClientTooOld,
+}
+
+impl NegotiationResult {
+    pub fn is_compatible(&self) -> bool {
+        matches!(self, Self::Compatible(_))
+    }
+}
+
+/// Matches an ABI artifact version against the schema versions supported by a client.
+///
+/// Requirements are tried in order using standard semver matching; [`compatible_requirement`]
+/// builds requirements that follow the ABI compatibility rules. When nothing matches, the
+/// artifact is reported as too old if it precedes the lowest version accepted by any
+/// requirement, and the client as too old otherwise.
+pub fn negotiate_version(
+    supported: &[VersionReq],
+    artifact_version: &Version,
+) -> NegotiationResult {
+    if let Some(requirement) = supported
+        .iter()
+        .find(|requirement| requirement.matches(artifact_version))
+    {
+        return NegotiationResult::Compatible(requirement.clone());
+    }
+
+    let lowest = supported
+        .iter()
+        .flat_map(|requirement| requirement.comparators.iter())
+        .filter_map(lower_bound)
+        .min();
+    match lowest {
+        Some(lowest) if artifact_version < &lowest => NegotiationResult::ArtifactTooOld,
+        _ => NegotiationResult::ClientTooOld,
+    }
+}
+
+fn lower_bound(comparator: &Comparator) -> Option<Version> {
+    match comparator.op {
+        Op::Less | Op::LessEq => None,
+        _ => Some(Version {
+            major: comparator.major,
+            minor: comparator.minor.unwrap_or(0),
+            patch: comparator.patch.unwrap_or(0),
+            pre: comparator.pre.clone(),
+            build: semver::BuildMetadata::EMPTY,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(version: &str) -> Version {
+        Version::parse(version).unwrap()
+    }
+
+    fn req(requirement: &str) -> VersionReq {
+        VersionReq::parse(requirement).unwrap()
+    }
+
+    #[test]
+    fn test_is_compatible() {
+        assert!(is_compatible(&v("0.4.0"), &v("0.4.7")));
+        assert!(!is_compatible(&v("0.4.0"), &v("0.5.0")));
+        assert!(is_compatible(&v("1.2.0"), &v("1.9.1")));
+        assert!(!is_compatible(&v("1.2.0"), &v("2.0.0")));
+    }
+
+    #[test]
+    fn test_compatible_requirement() {
+        assert!(compatible_requirement(&v("0.4.3")).matches(&v("0.4.0")));
+        assert!(!compatible_requirement(&v("0.4.3")).matches(&v("0.5.0")));
+        assert!(compatible_requirement(&v("1.2.0")).matches(&v("1.3.0")));
+        assert!(current_requirement().matches(&v(crate::SCHEMA_VERSION)));
+    }
+
+    #[test]
+    fn test_negotiate_version() {
+        let supported = [req("~0.3"), req("^0.4")];
+        assert_eq!(
+            negotiate_version(&supported, &v("0.4.2")),
+            NegotiationResult::Compatible(req("^0.4"))
+        );
+        assert_eq!(
+            negotiate_version(&supported, &v("0.2.0")),
+            NegotiationResult::ArtifactTooOld
+        );
+        assert_eq!(
+            negotiate_version(&supported, &v("0.5.0")),
+            NegotiationResult::ClientTooOld
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Traversal helpers over JSON schemas.
+
+use crate::{AbiBody, AbiFunction, AbiParameters, AbiType};
+use borsh::schema::BorshSchemaContainer;
+use schemars::schema::{RootSchema, Schema, SchemaObject, SingleOrVec};
+use std::collections::BTreeSet;
+
+/// Location of a JSON type schema inside a function signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum TypeSite {
+    Param(String),
+    Callback(usize),
+    CallbacksVec,
+    Result,
+}
+
+/// Collects all JSON type schemas used in function signatures, in the order they appear in the
+/// ABI.
+pub(crate) fn json_type_schemas(body: &AbiBody) -> Vec<(&str, TypeSite, &Schema)> {
+    body.functions
+        .iter()
+        .flat_map(|function| {
+            function_json_schemas(function)
+                .into_iter()
+                .map(move |(site, schema)| (function.name.as_str(), site, schema))
+        })
+        .collect()
+}
+
+/// Collects all JSON type schemas used in the signature of `function`.
+pub(crate) fn function_json_schemas(function: &AbiFunction) -> Vec<(TypeSite, &Schema)> {
+    let mut schemas = Vec::new();
+    if let AbiParameters::Json { args } = &function.params {
+        for arg in args {
+            schemas.push((TypeSite::Param(arg.name.clone()), &arg.type_schema));
+        }
+    }
+    for (i, callback) in function.callbacks.iter().enumerate() {
+        if let AbiType::Json { type_schema } = &callback.ty {
+            schemas.push((TypeSite::Callback(i), type_schema));
+        }
+    }
+    if let Some(AbiType::Json { type_schema }) = &function.callbacks_vec {
+        schemas.push((TypeSite::CallbacksVec, type_schema));
+    }
+    if let Some(AbiType::Json { type_schema }) = &function.result {
+        schemas.push((TypeSite::Result, type_schema));
+    }
+    schemas
+}
+
+/// Collects mutable references to all JSON type schemas used in function signatures, in the
+/// order they appear in the ABI.
+pub(crate) fn json_type_schemas_mut(body: &mut AbiBody) -> Vec<(String, TypeSite, &mut Schema)> {
+    let mut schemas = Vec::new();
+    for function in body.functions.iter_mut() {
+        let name = function.name.clone();
+        schemas.extend(
+            function_json_schemas_mut(function)
+                .into_iter()
+                .map(|(site, schema)| (name.clone(), site, schema)),
+        );
+    }
+    schemas
+}
+
+/// Collects mutable references to all JSON type schemas used in the signature of `function`.
+pub(crate) fn function_json_schemas_mut(
+    function: &mut AbiFunction,
+) -> Vec<(TypeSite, &mut Schema)> {
+    let mut schemas = Vec::new();
+    if let AbiParameters::Json { args } = &mut function.params {
+        for arg in args.iter_mut() {
+            schemas.push((TypeSite::Param(arg.name.clone()), &mut arg.type_schema));
+        }
+    }
+    for (i, callback) in function.callbacks.iter_mut().enumerate() {
+        if let AbiType::Json { type_schema } = &mut callback.ty {
+            schemas.push((TypeSite::Callback(i), type_schema));
+        }
+    }
+    if let Some(AbiType::Json { type_schema }) = &mut function.callbacks_vec {
+        schemas.push((TypeSite::CallbacksVec, type_schema));
+    }
+    if let Some(AbiType::Json { type_schema }) = &mut function.result {
+        schemas.push((TypeSite::Result, type_schema));
+    }
+    schemas
+}
+
+/// Collects all Borsh type schemas used in function signatures, in the order they appear in the
+/// ABI.
+pub(crate) fn borsh_type_schemas(body: &AbiBody) -> Vec<(&str, TypeSite, &BorshSchemaContainer)> {
+    body.functions
+        .iter()
+        .flat_map(|function| {
+            function_borsh_schemas(function)
+                .into_iter()
+                .map(move |(site, schema)| (function.name.as_str(), site, schema))
+        })
+        .collect()
+}
+
+/// Collects all Borsh type schemas used in the signature of `function`.
+pub(crate) fn function_borsh_schemas(
+    function: &AbiFunction,
+) -> Vec<(TypeSite, &BorshSchemaContainer)> {
+    let mut schemas = Vec::new();
+    if let AbiParameters::Borsh { args } = &function.params {
+        for arg in args {
+            schemas.push((TypeSite::Param(arg.name.clone()), &arg.type_schema));
+        }
+    }
+    for (i, callback) in function.callbacks.iter().enumerate() {
+        if let AbiType::Borsh { type_schema } = &callback.ty {
+            schemas.push((TypeSite::Callback(i), type_schema));
+        }
+    }
+    if let Some(AbiType::Borsh { type_schema }) = &function.callbacks_vec {
+        schemas.push((TypeSite::CallbacksVec, type_schema));
+    }
+    if let Some(AbiType::Borsh { type_schema }) = &function.result {
+        schemas.push((TypeSite::Result, type_schema));
+    }
+    schemas
+}
+
+/// Direct subschemas of a schema object.
+pub(crate) fn subschemas(object: &SchemaObject) -> Vec<&Schema> {
+    let mut children = Vec::new();
+    if let Some(subschemas) = &object.subschemas {
+        for schemas in [&subschemas.all_of, &subschemas.any_of, &subschemas.one_of]
+            .into_iter()
+            .flatten()
+        {
+            children.extend(schemas.iter());
+        }
+        for schema in [
+            &subschemas.not,
+            &subschemas.if_schema,
+            &subschemas.then_schema,
+            &subschemas.else_schema,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            children.push(&**schema);
+        }
+    }
+    if let Some(array) = &object.array {
+        match &array.items {
+            Some(SingleOrVec::Single(schema)) => children.push(&**schema),
+            Some(SingleOrVec::Vec(schemas)) => children.extend(schemas.iter()),
+            None => {}
+        }
+        for schema in [&array.additional_items, &array.contains]
+            .into_iter()
+            .flatten()
+        {
+            children.push(&**schema);
+        }
+    }
+    if let Some(validation) = &object.object {
+        children.extend(validation.properties.values());
+        children.extend(validation.pattern_properties.values());
+        for schema in [
+            &validation.additional_properties,
+            &validation.property_names,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            children.push(&**schema);
+        }
+    }
+    children
+}
+
+/// Mutable references to the direct subschemas of a schema object.
+pub(crate) fn subschemas_mut(object: &mut SchemaObject) -> Vec<&mut Schema> {
+    let mut children = Vec::new();
+    if let Some(subschemas) = &mut object.subschemas {
+        let subschemas = &mut **subschemas;
+        for schemas in [
+            &mut subschemas.all_of,
+            &mut subschemas.any_of,
+            &mut subschemas.one_of,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            children.extend(schemas.iter_mut());
+        }
+        for schema in [
+            &mut subschemas.not,
+            &mut subschemas.if_schema,
+            &mut subschemas.then_schema,
+            &mut subschemas.else_schema,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            children.push(&mut **schema);
+        }
+    }
+    if let Some(array) = &mut object.array {
+        let array = &mut **array;
+        match &mut array.items {
+            Some(SingleOrVec::Single(schema)) => children.push(&mut **schema),
+            Some(SingleOrVec::Vec(schemas)) => children.extend(schemas.iter_mut()),
+            None => {}
+        }
+        for schema in [&mut array.additional_items, &mut array.contains]
+            .into_iter()
+            .flatten()
+        {
+            children.push(&mut **schema);
+        }
+    }
+    if let Some(validation) = &mut object.object {
+        let validation = &mut **validation;
+        children.extend(validation.properties.values_mut());
+        children.extend(validation.pattern_properties.values_mut());
+        for schema in [
+            &mut validation.additional_properties,
+            &mut validation.property_names,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            children.push(&mut **schema);
+        }
+    }
+    children
+}
+
+/// Calls `f` on `schema` and every schema nested in it, parents first.
+pub(crate) fn visit<'a>(schema: &'a Schema, f: &mut impl FnMut(&'a Schema)) {
+    f(schema);
+    if let Schema::Object(object) = schema {
+        for child in subschemas(object) {
+            visit(child, f);
+        }
+    }
+}
+
+/// Calls `f` on `schema` and every schema nested in it, parents first, allowing modifications.
+pub(crate) fn visit_mut(schema: &mut Schema, f: &mut impl FnMut(&mut Schema)) {
+    f(schema);
+    if let Schema::Object(object) = schema {
+        for child in subschemas_mut(object) {
+            visit_mut(child, f);
+        }
+    }
+}
+
+/// Names of the root schema definitions referenced directly from `schema` or its subschemas.
+pub(crate) fn referenced_definitions(schema: &Schema) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    visit(schema, &mut |schema| {
+        if let Schema::Object(SchemaObject {
+            reference: Some(reference),
+            ..
+        }) = schema
+        {
+            if let Some(name) = crate::transform::definition_name(reference) {
+                names.insert(name.to_string());
+            }
+        }
+    });
+    names
+}
+
+/// Names of the root schema definitions transitively reachable from `schemas`.
+pub(crate) fn reachable_definitions<'a>(
+    root_schema: &RootSchema,
+    schemas: impl IntoIterator<Item = &'a Schema>,
+) -> BTreeSet<String> {
+    let mut reachable = BTreeSet::new();
+    let mut pending: Vec<String> = schemas
+        .into_iter()
+        .flat_map(referenced_definitions)
+        .collect();
+    while let Some(name) = pending.pop() {
+        if !reachable.insert(name.clone()) {
+            continue;
+        }
+        if let Some(definition) = root_schema.definitions.get(&name) {
+            pending.extend(referenced_definitions(definition));
+        }
+    }
+    reachable
+}
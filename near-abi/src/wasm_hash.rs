@@ -0,0 +1,179 @@
+//! Hashes of contract code.
+//!
+//! NEAR identifies contract code by the SHA-256 hash of the WASM binary, written in Base58 (e.g.
+//! in `near view-account` output). [`WasmHash`] parses and validates that representation and
+//! checks it against an actual binary.
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Metadata, Schema, SchemaObject, StringValidation};
+use schemars::JsonSchema;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// SHA-256 hash of a contract WASM binary.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WasmHash([u8; 32]);
+
+impl WasmHash {
+    /// Hashes a WASM binary.
+    pub fn of(wasm: &[u8]) -> Self {
+        Self(sha256(wasm))
+    }
+
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Raw bytes of the hash.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Whether `wasm` is the binary this is the hash of.
+    pub fn matches(&self, wasm: &[u8]) -> bool {
+        *self == Self::of(wasm)
+    }
+}
+
+impl fmt::Debug for WasmHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WasmHash({})", self)
+    }
+}
+
+impl fmt::Display for WasmHash {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&bs58::encode(self.0).into_string())
+    }
+}
+
+impl FromStr for WasmHash {
+    type Err = ParseWasmHashError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s).into_vec().map_err(|err| match err {
+            bs58::decode::Error::InvalidCharacter { character, .. } => {
+                ParseWasmHashError::InvalidCharacter(character)
+            }
+            bs58::decode::Error::NonAsciiCharacter { index } => {
+                ParseWasmHashError::InvalidCharacter(s[index..].chars().next().unwrap_or('\0'))
+            }
+            _ => ParseWasmHashError::InvalidLength(0),
+        })?;
+        let len = bytes.len();
+        bytes
+            .try_into()
+            .map(Self)
+            .map_err(|_| ParseWasmHashError::InvalidLength(len))
+    }
+}
+
+impl Serialize for WasmHash {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for WasmHash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl JsonSchema for WasmHash {
+    fn is_referenceable() -> bool {
+        false
+    }
+
+    fn schema_name() -> String {
+        "WasmHash".to_string()
+    }
+
+    fn json_schema(_gen: &mut SchemaGenerator) -> Schema {
+        SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                description: Some("SHA-256 hash in Base58 format.".to_string()),
+                ..Default::default()
+            })),
+            instance_type: Some(InstanceType::String.into()),
+            string: Some(Box::new(StringValidation {
+                pattern: Some("^[1-9A-HJ-NP-Za-km-z]{32,44}$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+/// Error returned when a string is not a Base58 SHA-256 hash.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseWasmHashError {
+    /// The character is not a Base58 digit.
+    InvalidCharacter(char),
+    /// The string decodes to the given number of bytes instead of 32.
+    InvalidLength(usize),
+}
+
+impl std::error::Error for ParseWasmHashError {}
+impl fmt::Display for ParseWasmHashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidCharacter(c) => write!(f, "`{}` is not a Base58 digit", c),
+            Self::InvalidLength(len) => {
+                write!(f, "a SHA-256 hash has 32 bytes, found {}", len)
+            }
+        }
+    }
+}
+
+/// Computes the SHA-256 digest of `data`.
+pub(crate) fn sha256(data: &[u8]) -> [u8; 32] {
+    Sha256::digest(data).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_hash() {
+        // SHA-256 of the empty input.
+        let hash: WasmHash = "GKot5hBsd81kMupNCXHaqbhv3huEbxAFMLnpcX2hniwn"
+            .parse()
+            .unwrap();
+        assert_eq!(hash.as_bytes()[..4], [0xe3, 0xb0, 0xc4, 0x42],);
+        assert!(hash.matches(b""));
+        assert!(!hash.matches(b"\0asm"));
+        assert_eq!(
+            hash.to_string(),
+            "GKot5hBsd81kMupNCXHaqbhv3huEbxAFMLnpcX2hniwn"
+        );
+        assert_eq!(
+            WasmHash::of(b"abc").as_bytes()[28..],
+            [0xf2, 0x00, 0x15, 0xad]
+        );
+
+        let zero = WasmHash::from_bytes([0; 32]);
+        assert_eq!(zero.to_string(), "1".repeat(32));
+        assert_eq!(zero.to_string().parse(), Ok(zero));
+
+        assert_eq!(
+            "0".parse::<WasmHash>(),
+            Err(ParseWasmHashError::InvalidCharacter('0'))
+        );
+        assert_eq!(
+            "abc".parse::<WasmHash>(),
+            Err(ParseWasmHashError::InvalidLength(3))
+        );
+        assert_eq!(
+            "é".parse::<WasmHash>(),
+            Err(ParseWasmHashError::InvalidCharacter('é'))
+        );
+        assert!(serde_json::from_value::<WasmHash>(serde_json::json!("not a hash")).is_err());
+    }
+}